@@ -13,7 +13,7 @@ use rustc_hash::FxHashSet;
 use span::{Span, SyntaxContextId};
 use syntax::{
     ast::{self, HasAttrs},
-    AstNode, Parse, SyntaxError, SyntaxNode, SyntaxToken, T,
+    AstNode, Parse, SyntaxError, SyntaxNode, SyntaxToken, TextSize, T,
 };
 use triomphe::Arc;
 
@@ -117,6 +117,20 @@ impl DeclarativeMacroExpander {
             None => self.mac.expand(&tt, |_| (), new_meta_vars, call_site).map_err(Into::into),
         }
     }
+
+    /// Computes what the rules of this macro expect at `offset` in the (possibly incomplete)
+    /// call input `tt`, `offset` being the position of an identifier in it.
+    pub fn expected_tokens_at(
+        &self,
+        tt: &ast::TokenTree,
+        offset: TextSize,
+    ) -> Vec<mbe::ExpectedToken> {
+        let span_map = RealSpanMap::absolute(FileId::BOGUS);
+        let call_site = span_map.span_for_range(tt.syntax().text_range());
+        let tt =
+            syntax_node_to_token_tree(tt.syntax(), SpanMapRef::RealSpanMap(&span_map), call_site);
+        self.mac.expected_tokens_at(&tt, |ident| ident.span.range.contains_inclusive(offset))
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
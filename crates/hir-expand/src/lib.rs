@@ -52,7 +52,7 @@ use crate::{
 pub use crate::ast_id_map::{AstId, ErasedAstId, ErasedFileAstId};
 pub use crate::files::{InFile, InMacroFile, InRealFile};
 
-pub use mbe::ValueResult;
+pub use mbe::{ExpectedToken, MetaVarKind, ValueResult};
 pub use span::{HirFileId, MacroCallId, MacroFileId};
 
 pub type DeclarativeMacro = ::mbe::DeclarativeMacro<tt::Span>;
@@ -68,7 +68,6 @@ use hir_ty::{
     method_resolution::{self, TyFingerprint},
     mir::interpret_mir,
     primitive::UintTy,
-    traits::FnTrait,
     AliasTy, CallableDefId, CallableSig, Canonical, CanonicalVarKinds, Cast, ClosureId, GenericArg,
     GenericArgData, Interner, ParamKind, QuantifiedWhereClause, Scalar, Substitution,
     TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind, ValueTyDefId,
@@ -81,7 +80,7 @@ use rustc_hash::FxHashSet;
 use stdx::{impl_from, never};
 use syntax::{
     ast::{self, HasAttrs as _, HasName},
-    AstNode, AstPtr, SmolStr, SyntaxNode, SyntaxNodePtr, TextRange, T,
+    AstNode, AstPtr, SmolStr, SyntaxNode, SyntaxNodePtr, TextRange, TextSize, T,
 };
 use triomphe::Arc;
 
@@ -128,12 +127,13 @@ pub use {
         hygiene::{marks_rev, SyntaxContextExt},
         name::{known, Name},
         proc_macro::ProcMacros,
-        tt, ExpandResult, HirFileId, HirFileIdExt, InFile, InMacroFile, InRealFile, MacroFileId,
-        MacroFileIdExt,
+        tt, ExpandResult, ExpectedToken, HirFileId, HirFileIdExt, InFile, InMacroFile, InRealFile,
+        MacroFileId, MacroFileIdExt, MetaVarKind,
     },
     hir_ty::{
         display::{ClosureStyle, HirDisplay, HirDisplayError, HirWrite},
         layout::LayoutError,
+        traits::FnTrait,
         PointerCast, Safety,
     },
     // FIXME: Properly encapsulate mir
@@ -2703,6 +2703,22 @@ impl Macro {
     pub fn is_derive(&self, db: &dyn HirDatabase) -> bool {
         matches!(self.kind(db), MacroKind::Derive)
     }
+
+    /// For declarative macros, computes what the macro's rules expect at `offset` in the
+    /// (possibly incomplete) input `tt` of a call, `offset` being the position of an identifier.
+    pub fn expected_tokens_at(
+        self,
+        db: &dyn HirDatabase,
+        tt: &ast::TokenTree,
+        offset: TextSize,
+    ) -> Vec<ExpectedToken> {
+        match db.macro_expander(db.macro_def(self.id)) {
+            hir_expand::db::TokenExpander::DeclarativeMacro(expander) => {
+                expander.expected_tokens_at(tt, offset)
+            }
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl HasVisibility for Macro {
@@ -4694,6 +4710,28 @@ impl Callable {
     pub fn return_type(&self) -> Type {
         self.ty.derived(self.sig.ret().clone())
     }
+    /// The types the explicit generic type parameters of the called function, excluding the ones
+    /// of its impl or trait, are instantiated with. `None` if the callee isn't a function or has
+    /// const parameters.
+    pub fn type_arguments(&self, db: &dyn HirDatabase) -> Option<Vec<Type>> {
+        let Callee::Def(CallableDefId::FunctionId(func)) = self.callee else { return None };
+        let TyKind::FnDef(_, substs) = self.ty.ty.kind(Interner) else { return None };
+        let params = db.generic_params(func.into());
+        let mut args = Vec::new();
+        for (idx, (_, data)) in params.type_or_consts.iter().enumerate() {
+            match data {
+                TypeOrConstParamData::TypeParamData(it)
+                    if it.provenance == TypeParamProvenance::TypeParamList =>
+                {
+                    let ty = substs.as_slice(Interner).get(idx)?.ty(Interner)?;
+                    args.push(self.ty.derived(ty.clone()));
+                }
+                TypeOrConstParamData::TypeParamData(_) => (),
+                TypeOrConstParamData::ConstParamData(_) => return None,
+            }
+        }
+        Some(args)
+    }
     pub fn sig(&self) -> &CallableSig {
         &self.sig
     }
@@ -0,0 +1,1193 @@
+use either::Either;
+use hir::{AsAssocItem, AssocItem, HasCrate, HasSource, HirDisplay};
+use ide_db::{
+    base_db::FileId, defs::Definition, helpers::is_editable_crate, FxHashMap, FxHashSet, SnippetCap,
+};
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, make, HasArgList, HasName},
+    ted, AstNode, Direction, SmolStr, SyntaxKind, SyntaxNode, TextRange,
+};
+
+use crate::{
+    assist_context::SourceChangeBuilder, utils::suggest_name, AssistContext, AssistId, AssistKind,
+    Assists,
+};
+
+// Assist: move_param_left
+//
+// Swaps a function parameter with the one before it, updating all call sites.
+//
+// ```
+// fn div(divisor: i32, $0dividend: i32) -> i32 { dividend / divisor }
+//
+// fn main() {
+//     div(2, 10);
+// }
+// ```
+// ->
+// ```
+// fn div(dividend: i32, divisor: i32) -> i32 { dividend / divisor }
+//
+// fn main() {
+//     div(10, 2);
+// }
+// ```
+pub(crate) fn move_param_left(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    move_param(acc, ctx, Direction::Prev)
+}
+
+// Assist: move_param_right
+//
+// Swaps a function parameter with the one after it, updating all call sites.
+//
+// ```
+// fn div($0divisor: i32, dividend: i32) -> i32 { dividend / divisor }
+//
+// fn main() {
+//     div(2, 10);
+// }
+// ```
+// ->
+// ```
+// fn div(dividend: i32, divisor: i32) -> i32 { dividend / divisor }
+//
+// fn main() {
+//     div(10, 2);
+// }
+// ```
+pub(crate) fn move_param_right(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    move_param(acc, ctx, Direction::Next)
+}
+
+fn move_param(acc: &mut Assists, ctx: &AssistContext<'_>, direction: Direction) -> Option<()> {
+    let param: ast::Param = ctx.find_node_at_offset()?;
+    let param_list = ast::ParamList::cast(param.syntax().parent()?)?;
+    let fn_ = ast::Fn::cast(param_list.syntax().parent()?)?;
+    let count = param_list.params().count();
+    let idx = param_list.params().position(|it| it == param)?;
+    let (other, id, label) = match direction {
+        Direction::Prev => (idx.checked_sub(1)?, "move_param_left", "Move parameter left"),
+        Direction::Next => {
+            ((idx + 1 < count).then_some(idx + 1)?, "move_param_right", "Move parameter right")
+        }
+    };
+    let funcs = related_functions(ctx, ctx.sema.to_def(&fn_)?)?;
+    let sites = call_sites(ctx, &funcs);
+    // Arguments are evaluated from left to right, swapping two of them that may have side effects
+    // changes what the call does.
+    let reordered = sites
+        .values()
+        .flatten()
+        .filter(|site| match site {
+            Site::Args { arg_list, has_self_arg } => {
+                let args: Vec<_> = arg_list.args().skip(usize::from(*has_self_arg)).collect();
+                match (args.get(idx), args.get(other)) {
+                    (Some(a), Some(b)) => !is_pure(a) && !is_pure(b),
+                    _ => false,
+                }
+            }
+            _ => false,
+        })
+        .count();
+    let label = match reordered {
+        0 => label.to_owned(),
+        1 => format!("{label} (changes evaluation order at 1 call site)"),
+        n => format!("{label} (changes evaluation order at {n} call sites)"),
+    };
+
+    let mut new_params: Vec<_> = (0..count).map(NewParam::Existing).collect();
+    new_params.swap(idx, other);
+    acc.add(
+        AssistId(id, AssistKind::RefactorRewrite),
+        label,
+        param.syntax().text_range(),
+        |builder| {
+            SignatureChange { new_params, replacements: Vec::new(), placeholders: None }
+                .apply(builder, sites)
+        },
+    )
+}
+
+/// Whether evaluating the expression can't have side effects, so that moving it relative to other
+/// arguments doesn't change the behavior of the call.
+fn is_pure(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Literal(_) | ast::Expr::PathExpr(_) | ast::Expr::ClosureExpr(_) => true,
+        ast::Expr::RefExpr(it) => it.expr().map_or(false, |it| is_pure(&it)),
+        ast::Expr::FieldExpr(it) => it.expr().map_or(false, |it| is_pure(&it)),
+        ast::Expr::ParenExpr(it) => it.expr().map_or(false, |it| is_pure(&it)),
+        ast::Expr::TupleExpr(it) => it.fields().all(|it| is_pure(&it)),
+        _ => false,
+    }
+}
+
+// Assist: add_param
+//
+// Adds a new parameter after the one under the cursor, with placeholders for its type and for the
+// argument passed for it at all call sites.
+//
+// ```
+// fn greet($0name: &str) {}
+//
+// fn main() {
+//     greet("world");
+// }
+// ```
+// ->
+// ```
+// fn greet(name: &str, new_param: ${1:_}) {}
+//
+// fn main() {
+//     greet("world", ${0:todo!()});
+// }
+// ```
+pub(crate) fn add_param(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let param_list: ast::ParamList = ctx.find_node_at_offset()?;
+    let fn_ = ast::Fn::cast(param_list.syntax().parent()?)?;
+    let params: Vec<_> = param_list.params().collect();
+    let idx = match ctx.find_node_at_offset::<ast::Param>() {
+        Some(param) => params.iter().position(|it| *it == param)? + 1,
+        None => params.len(),
+    };
+    let funcs = related_functions(ctx, ctx.sema.to_def(&fn_)?)?;
+
+    acc.add(
+        AssistId("add_param", AssistKind::RefactorRewrite),
+        "Add parameter",
+        param_list.syntax().text_range(),
+        |builder| {
+            // The name has to be free in every implementation, not just this one.
+            let taken = funcs
+                .iter()
+                .filter_map(|&it| ctx.sema.source(it)?.value.param_list())
+                .flat_map(|it| it.params())
+                .filter_map(|it| match it.pat()? {
+                    ast::Pat::IdentPat(pat) => Some(pat.name()?.text().into()),
+                    _ => None,
+                })
+                .collect();
+            let scope = fn_.body().map_or_else(|| fn_.syntax().clone(), |it| it.syntax().clone());
+            let name = unused_name(ctx, &scope, "new_param".to_owned(), taken);
+            // Without snippets there is nothing to fill in, so the new parameter is a unit.
+            let (ty, default_arg) = match ctx.config.snippet_cap {
+                Some(_) => (make::ty_placeholder(), make::ext::expr_todo()),
+                None => (make::ty_unit(), make::expr_unit()),
+            };
+            let param = make::param(make::ident_pat(false, false, make::name(&name)).into(), ty);
+            let mut new_params: Vec<_> = (0..params.len()).map(NewParam::Existing).collect();
+            new_params.insert(idx, NewParam::Added { param, default_arg });
+            let placeholders = ctx.config.snippet_cap;
+            SignatureChange { new_params, replacements: Vec::new(), placeholders }
+                .apply(builder, call_sites(ctx, &funcs))
+        },
+    )
+}
+
+// Assist: remove_param
+//
+// Removes a parameter of a trait method that no implementation uses, from the trait, all of its
+// implementations and all call sites.
+//
+// ```
+// trait Shape {
+//     fn area(&self, $0scale: f32) -> f32;
+// }
+//
+// struct Square;
+// impl Shape for Square {
+//     fn area(&self, _: f32) -> f32 { 1.0 }
+// }
+//
+// fn main() {
+//     Square.area(2.0);
+// }
+// ```
+// ->
+// ```
+// trait Shape {
+//     fn area(&self) -> f32;
+// }
+//
+// struct Square;
+// impl Shape for Square {
+//     fn area(&self) -> f32 { 1.0 }
+// }
+//
+// fn main() {
+//     Square.area();
+// }
+// ```
+pub(crate) fn remove_param(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let param: ast::Param = ctx.find_node_at_offset()?;
+    let param_list = ast::ParamList::cast(param.syntax().parent()?)?;
+    let fn_ = ast::Fn::cast(param_list.syntax().parent()?)?;
+    let count = param_list.params().count();
+    let idx = param_list.params().position(|it| it == param)?;
+    let func = ctx.sema.to_def(&fn_)?;
+    // Other functions are handled by `remove_unused_param`.
+    func.as_assoc_item(ctx.db())?.containing_trait_or_trait_impl(ctx.db())?;
+    let funcs = related_functions(ctx, func)?;
+    for &func in &funcs {
+        let fn_ = ctx.sema.source(func)?.value;
+        if fn_.body().is_none() {
+            continue;
+        }
+        match fn_.param_list()?.params().nth(idx)?.pat()? {
+            ast::Pat::WildcardPat(_) => (),
+            ast::Pat::IdentPat(pat) if pat.pat().is_none() => {
+                let local = ctx.sema.to_def(&pat)?;
+                if Definition::Local(local).usages(&ctx.sema).at_least_one() {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    acc.add(
+        AssistId("remove_param", AssistKind::RefactorRewrite),
+        "Remove parameter from all implementations",
+        param.syntax().text_range(),
+        |builder| {
+            let new_params = (0..count).filter(|&it| it != idx).map(NewParam::Existing).collect();
+            SignatureChange { new_params, replacements: Vec::new(), placeholders: None }
+                .apply(builder, call_sites(ctx, &funcs))
+        },
+    )
+}
+
+// Assist: rename_param
+//
+// Gives the parameter of a trait method the same name in the trait and all of its
+// implementations.
+//
+// ```
+// trait Shape {
+//     fn scale(&self, f: f32);
+// }
+//
+// struct Square;
+// impl Shape for Square {
+//     fn scale(&self, $0factor: f32) {
+//         let _ = factor;
+//     }
+// }
+// ```
+// ->
+// ```
+// trait Shape {
+//     fn scale(&self, factor: f32);
+// }
+//
+// struct Square;
+// impl Shape for Square {
+//     fn scale(&self, factor: f32) {
+//         let _ = factor;
+//     }
+// }
+// ```
+pub(crate) fn rename_param(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let name: ast::Name = ctx.find_node_at_offset()?;
+    let pat = ast::IdentPat::cast(name.syntax().parent()?)?;
+    let param = ast::Param::cast(pat.syntax().parent()?)?;
+    let param_list = ast::ParamList::cast(param.syntax().parent()?)?;
+    let fn_ = ast::Fn::cast(param_list.syntax().parent()?)?;
+    let idx = param_list.params().position(|it| it == param)?;
+    let func = ctx.sema.to_def(&fn_)?;
+    func.as_assoc_item(ctx.db())?.containing_trait_or_trait_impl(ctx.db())?;
+    let new_name = name.text().to_string();
+
+    let mut renames = Vec::new();
+    for func in related_functions(ctx, func)? {
+        let src = ctx.sema.source(func)?;
+        let Some(ast::Pat::IdentPat(pat)) = src.value.param_list()?.params().nth(idx)?.pat() else {
+            continue;
+        };
+        let Some(name) = pat.name() else { continue };
+        // Parameters that are explicitly unused stay that way.
+        if name.text() == new_name.as_str() || name.text().starts_with('_') {
+            continue;
+        }
+        renames.push((src.file_id.file_id()?, src.value.body().is_some(), pat, name));
+    }
+    if renames.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("rename_param", AssistKind::RefactorRewrite),
+        format!("Rename parameter to `{new_name}` in all implementations"),
+        name.syntax().text_range(),
+        |builder| {
+            for (file_id, has_body, pat, name) in renames {
+                let local = has_body.then(|| ctx.sema.to_def(&pat)).flatten();
+                let Some(local) = local else {
+                    builder.edit_file(file_id);
+                    builder.replace(name.syntax().text_range(), &new_name);
+                    continue;
+                };
+                let Ok(change) = Definition::Local(local).rename(&ctx.sema, &new_name) else {
+                    continue;
+                };
+                for (file_id, (edit, _)) in change.source_file_edits {
+                    builder.edit_file(file_id);
+                    for indel in edit {
+                        builder.replace(indel.delete, indel.insert);
+                    }
+                }
+            }
+        },
+    )
+}
+
+// Assist: introduce_param
+//
+// Replaces an expression in a function's body with a new parameter, passing the expression
+// as the argument for it at all call sites.
+//
+// ```
+// fn circle(radius: u32) {}
+//
+// fn draw() {
+//     circle($010$0);
+// }
+//
+// fn main() {
+//     draw();
+// }
+// ```
+// ->
+// ```
+// fn circle(radius: u32) {}
+//
+// fn draw(radius: u32) {
+//     circle(radius);
+// }
+//
+// fn main() {
+//     draw(10);
+// }
+// ```
+pub(crate) fn introduce_param(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let expr: ast::Expr = ctx.find_node_at_range()?;
+    if !ctx.has_empty_selection() && expr.syntax().text_range() != ctx.selection_trimmed() {
+        return None;
+    }
+    // The expression is evaluated at the call sites instead, so it must not refer to anything
+    // that's only in scope inside of the function.
+    if expr
+        .syntax()
+        .descendants()
+        .any(|it| matches!(it.kind(), SyntaxKind::PATH | SyntaxKind::MACRO_CALL))
+    {
+        return None;
+    }
+    let fn_ = expr.syntax().ancestors().find_map(ast::Fn::cast)?;
+    if !fn_.body()?.syntax().text_range().contains_range(expr.syntax().text_range()) {
+        return None;
+    }
+    let func = ctx.sema.to_def(&fn_)?;
+    // A parameter that only one implementation of a trait method uses isn't worth it.
+    if func
+        .as_assoc_item(ctx.db())
+        .and_then(|it| it.containing_trait_or_trait_impl(ctx.db()))
+        .is_some()
+    {
+        return None;
+    }
+    let ty = ctx.sema.type_of_expr(&expr)?.original;
+    if ty.is_unknown() || ty.is_unit() {
+        return None;
+    }
+    let module = ctx.sema.scope(expr.syntax())?.module();
+    let ty = ty.display_source_code(ctx.db(), module.into(), true).ok()?;
+    let funcs = related_functions(ctx, func)?;
+    let count = fn_.param_list()?.params().count();
+
+    acc.add(
+        AssistId("introduce_param", AssistKind::RefactorRewrite),
+        "Introduce parameter",
+        expr.syntax().text_range(),
+        |builder| {
+            let name = unused_name(
+                ctx,
+                expr.syntax(),
+                suggest_name::for_variable(&expr, &ctx.sema),
+                FxHashSet::default(),
+            );
+            let param =
+                make::param(make::ident_pat(false, false, make::name(&name)).into(), make::ty(&ty));
+            let mut new_params: Vec<_> = (0..count).map(NewParam::Existing).collect();
+            new_params.push(NewParam::Added { param, default_arg: expr.clone() });
+            let replacement = make::expr_path(make::ext::ident_path(&name));
+            SignatureChange {
+                new_params,
+                replacements: vec![(expr.syntax().clone(), replacement.syntax().clone())],
+                placeholders: None,
+            }
+            .apply(builder, call_sites(ctx, &funcs))
+        },
+    )
+}
+
+/// Returns `base`, or `base` with a number appended, so that it is neither in `names` nor
+/// visible at `node`.
+fn unused_name(
+    ctx: &AssistContext<'_>,
+    node: &SyntaxNode,
+    base: String,
+    mut names: FxHashSet<SmolStr>,
+) -> String {
+    if let Some(scope) = ctx.sema.scope(node) {
+        scope.process_all_names(&mut |name, _| {
+            names.insert(name.to_smol_str());
+        });
+    }
+    let mut name = base.clone();
+    let mut counter = 1;
+    while names.contains(name.as_str()) {
+        name = format!("{base}{counter}");
+        counter += 1;
+    }
+    name
+}
+
+/// The parameter list of a function after a [`SignatureChange`], parameters that don't occur in
+/// it are removed.
+enum NewParam {
+    /// The current parameter at this index, not counting the `self` parameter.
+    Existing(usize),
+    /// A new parameter, with the argument to pass for it at the existing call sites.
+    Added { param: ast::Param, default_arg: ast::Expr },
+}
+
+/// Changes the parameters of a function, together with the other implementations of a trait
+/// method, and updates all of their uses: calls, method calls and the function being used as a
+/// value, which becomes a closure calling it.
+struct SignatureChange {
+    new_params: Vec<NewParam>,
+    /// Further node replacements in the current file, like uses of a new parameter.
+    replacements: Vec<(SyntaxNode, SyntaxNode)>,
+    /// Whether the types and default arguments of the new parameters become snippet placeholders.
+    placeholders: Option<SnippetCap>,
+}
+
+/// Returns the function along with all functions that need to keep the same signature as it,
+/// which are the declaration and all implementations of a trait method. Returns `None` if some
+/// of them can't be edited.
+fn related_functions(ctx: &AssistContext<'_>, func: hir::Function) -> Option<Vec<hir::Function>> {
+    let db = ctx.db();
+    let funcs = match func.as_assoc_item(db).and_then(|it| it.containing_trait_or_trait_impl(db)) {
+        Some(trait_) => {
+            let name = func.name(db);
+            let find = |items: Vec<AssocItem>| {
+                items.into_iter().find_map(|it| match it {
+                    AssocItem::Function(it) if it.name(db) == name => Some(it),
+                    _ => None,
+                })
+            };
+            std::iter::once(trait_.items(db))
+                .chain(hir::Impl::all_for_trait(db, trait_).into_iter().map(|it| it.items(db)))
+                .filter_map(find)
+                .collect()
+        }
+        None => vec![func],
+    };
+    let param_count = func.assoc_fn_params(db).len();
+    let editable = funcs.iter().all(|it| {
+        is_editable_crate(it.krate(db), db)
+            && it.assoc_fn_params(db).len() == param_count
+            && it.source(db).map_or(false, |src| src.file_id.file_id().is_some())
+    });
+    editable.then_some(funcs)
+}
+
+enum Site {
+    Params(ast::ParamList),
+    Args { arg_list: ast::ArgList, has_self_arg: bool },
+    Value { path_expr: ast::PathExpr, closure_params: Vec<String>, has_self: bool },
+    Replace(SyntaxNode, SyntaxNode),
+}
+
+impl Site {
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Site::Params(it) => it.syntax(),
+            Site::Args { arg_list, .. } => arg_list.syntax(),
+            Site::Value { path_expr, .. } => path_expr.syntax(),
+            Site::Replace(it, _) => it,
+        }
+    }
+
+    fn make_mut(self, builder: &mut SourceChangeBuilder) -> Site {
+        match self {
+            Site::Params(it) => Site::Params(builder.make_mut(it)),
+            Site::Args { arg_list, has_self_arg } => {
+                Site::Args { arg_list: builder.make_mut(arg_list), has_self_arg }
+            }
+            Site::Value { path_expr, closure_params, has_self } => {
+                Site::Value { path_expr: builder.make_mut(path_expr), closure_params, has_self }
+            }
+            Site::Replace(node, with) => Site::Replace(builder.make_syntax_mut(node), with),
+        }
+    }
+}
+
+/// Collects the parameter lists of the functions and all of their uses.
+fn call_sites(ctx: &AssistContext<'_>, funcs: &[hir::Function]) -> FxHashMap<FileId, Vec<Site>> {
+    let db = ctx.db();
+    let mut sites: FxHashMap<FileId, Vec<Site>> = FxHashMap::default();
+    let mut seen = FxHashSet::default();
+    for &func in funcs {
+        let Some(src) = func.source(db) else { continue };
+        let Some(file_id) = src.file_id.file_id() else { continue };
+        if let Some(param_list) = src.value.param_list() {
+            sites.entry(file_id).or_default().push(Site::Params(param_list));
+        }
+        let closure_params = closure_params(&src.value);
+        let has_self = func.has_self_param(db);
+        for (file_id, references) in Definition::Function(func).usages(&ctx.sema).all() {
+            let source_file = ctx.sema.parse(file_id);
+            for reference in references {
+                if !seen.insert((file_id, reference.range)) {
+                    continue;
+                }
+                let Some(name_ref) =
+                    find_node_at_range::<ast::NameRef>(source_file.syntax(), reference.range)
+                else {
+                    continue;
+                };
+                if let Some(site) = usage_site(name_ref, &closure_params, has_self) {
+                    sites.entry(file_id).or_default().push(site);
+                }
+            }
+        }
+    }
+    sites
+}
+
+impl SignatureChange {
+    fn apply(self, builder: &mut SourceChangeBuilder, mut sites: FxHashMap<FileId, Vec<Site>>) {
+        if !self.replacements.is_empty() {
+            sites.entry(builder.file_id).or_default().extend(
+                self.replacements.iter().map(|(it, with)| Site::Replace(it.clone(), with.clone())),
+            );
+        }
+
+        for (file_id, sites) in sites {
+            builder.edit_file(file_id);
+            let mut sites: Vec<(TextRange, Site)> =
+                sites.into_iter().map(|it| (it.syntax().text_range(), it)).collect();
+            // Nested calls are rewritten inside out, so that the outer ones pick up the changes.
+            sites.sort_by_key(|(range, _)| range.len());
+            let sites: Vec<Site> = sites.into_iter().map(|(_, it)| it.make_mut(builder)).collect();
+            let mut added: Vec<_> =
+                sites.into_iter().flat_map(|it| self.apply_to_site(it)).collect();
+            if let Some(cap) = self.placeholders {
+                added.sort_by_key(|it| it.syntax().text_range().start());
+                for it in added {
+                    builder.add_placeholder_snippet(cap, it);
+                }
+            }
+        }
+    }
+
+    /// Rewrites `site`, returning the types and arguments it got for the new parameters.
+    fn apply_to_site(&self, site: Site) -> Vec<Either<ast::Type, ast::Expr>> {
+        let added_idxs = || {
+            self.new_params
+                .iter()
+                .enumerate()
+                .filter(|(_, it)| matches!(it, NewParam::Added { .. }))
+                .map(|(idx, _)| idx)
+        };
+        match site {
+            Site::Params(param_list) => {
+                let params: Vec<_> = param_list.params().collect();
+                let Some(new_params) = self.reorder(&params, |it| it.param.clone()) else {
+                    return Vec::new();
+                };
+                let new_list =
+                    make::param_list(param_list.self_param(), new_params).clone_for_update();
+                ted::replace(param_list.syntax(), new_list.syntax());
+                let params: Vec<_> = new_list.params().collect();
+                added_idxs().filter_map(|idx| params.get(idx)?.ty()).map(Either::Left).collect()
+            }
+            Site::Args { arg_list, has_self_arg } => {
+                let mut args: Vec<_> = arg_list.args().collect();
+                let self_args = usize::from(has_self_arg).min(args.len());
+                let rest = args.split_off(self_args);
+                let Some(new_args) = self.reorder(&rest, |it| it.default_arg.clone()) else {
+                    return Vec::new();
+                };
+                args.extend(new_args);
+                let new_list = make::arg_list(args).clone_for_update();
+                ted::replace(arg_list.syntax(), new_list.syntax());
+                let args: Vec<_> = new_list.args().collect();
+                added_idxs()
+                    .filter_map(|idx| args.get(self_args + idx).cloned())
+                    .map(Either::Right)
+                    .collect()
+            }
+            Site::Value { path_expr, closure_params, has_self } => {
+                let names: Vec<ast::Expr> = closure_params
+                    .iter()
+                    .map(|it| make::expr_path(make::ext::ident_path(it)))
+                    .collect();
+                let self_args = usize::from(has_self).min(names.len());
+                let (self_arg, rest) = names.split_at(self_args);
+                let Some(new_args) = self.reorder(rest, |it| it.default_arg.clone()) else {
+                    return Vec::new();
+                };
+                let args = self_arg.iter().cloned().chain(new_args);
+                let call =
+                    make::expr_call(ast::Expr::PathExpr(path_expr.clone()), make::arg_list(args));
+                let pats = closure_params.iter().map(|it| {
+                    make::untyped_param(make::ident_pat(false, false, make::name(it)).into())
+                });
+                let mut closure = make::expr_closure(pats, call);
+                if path_expr.syntax().parent().map_or(false, |it| ast::Expr::can_cast(it.kind())) {
+                    closure = make::expr_paren(closure);
+                }
+                let closure = closure.clone_for_update();
+                ted::replace(path_expr.syntax(), closure.syntax());
+                let Some(args) = closure.syntax().descendants().find_map(ast::ArgList::cast) else {
+                    return Vec::new();
+                };
+                let args: Vec<_> = args.args().collect();
+                added_idxs()
+                    .filter_map(|idx| args.get(self_args + idx).cloned())
+                    .map(Either::Right)
+                    .collect()
+            }
+            Site::Replace(node, with) => {
+                ted::replace(node, with.clone_for_update());
+                Vec::new()
+            }
+        }
+    }
+
+    /// Rearranges the current parameters or arguments according to the new parameter list,
+    /// returning `None` if their number doesn't fit.
+    fn reorder<T: Clone>(&self, old: &[T], added: impl Fn(&AddedParam<'_>) -> T) -> Option<Vec<T>> {
+        if self
+            .new_params
+            .iter()
+            .any(|it| matches!(it, NewParam::Existing(idx) if *idx >= old.len()))
+        {
+            return None;
+        }
+        let res = self
+            .new_params
+            .iter()
+            .map(|it| match it {
+                NewParam::Existing(idx) => old[*idx].clone(),
+                NewParam::Added { param, default_arg } => added(&AddedParam { param, default_arg }),
+            })
+            .collect();
+        Some(res)
+    }
+}
+
+struct AddedParam<'a> {
+    param: &'a ast::Param,
+    default_arg: &'a ast::Expr,
+}
+
+fn usage_site(name_ref: ast::NameRef, closure_params: &[String], has_self: bool) -> Option<Site> {
+    let parent = name_ref.syntax().parent()?;
+    if let Some(method_call) = ast::MethodCallExpr::cast(parent.clone()) {
+        return Some(Site::Args { arg_list: method_call.arg_list()?, has_self_arg: false });
+    }
+    let segment = ast::PathSegment::cast(parent)?;
+    let path = segment.parent_path();
+    if path.syntax().parent().and_then(ast::Path::cast).is_some() {
+        return None;
+    }
+    let path_expr = ast::PathExpr::cast(path.syntax().parent()?)?;
+    match path_expr.syntax().parent().and_then(ast::CallExpr::cast) {
+        Some(call) if call.expr()?.syntax() == path_expr.syntax() => {
+            Some(Site::Args { arg_list: call.arg_list()?, has_self_arg: has_self })
+        }
+        _ => Some(Site::Value { path_expr, closure_params: closure_params.to_vec(), has_self }),
+    }
+}
+
+/// Names for the parameters of a closure that forwards to the function.
+fn closure_params(fn_: &ast::Fn) -> Vec<String> {
+    let Some(param_list) = fn_.param_list() else { return Vec::new() };
+    let self_param = param_list.self_param().map(|_| "this".to_owned());
+    let params = param_list.params().enumerate().map(|(idx, it)| match it.pat() {
+        Some(ast::Pat::IdentPat(pat)) if pat.ref_token().is_none() && pat.pat().is_none() => {
+            pat.name().map_or_else(|| format!("arg{idx}"), |it| it.text().to_string())
+        }
+        _ => format!("arg{idx}"),
+    });
+    self_param.into_iter().chain(params).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        check_assist, check_assist_by_label, check_assist_no_snippet_cap,
+        check_assist_not_applicable,
+    };
+
+    use super::*;
+
+    #[test]
+    fn moves_param_in_calls() {
+        check_assist(
+            move_param_left,
+            r#"
+fn f(a: i32, b: u8, $0c: bool) {}
+fn main() {
+    f(1, 2, true);
+    f(f(1, 2, false), 3, true);
+}
+"#,
+            r#"
+fn f(a: i32, c: bool, b: u8) {}
+fn main() {
+    f(1, true, 2);
+    f(f(1, false, 2), true, 3);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_param_in_method_calls() {
+        check_assist(
+            move_param_right,
+            r#"
+struct S;
+impl S {
+    fn m(&self, $0a: i32, b: u8) {}
+}
+fn main() {
+    S.m(1, 2);
+    S::m(&S, 1, 2);
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn m(&self, b: u8, a: i32) {}
+}
+fn main() {
+    S.m(2, 1);
+    S::m(&S, 2, 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_param_in_trait_impls() {
+        check_assist(
+            move_param_left,
+            r#"
+trait Trait {
+    fn m(&self, a: i32, b: u8);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, x: i32, $0y: u8) {}
+}
+struct T;
+impl Trait for T {
+    fn m(&self, _: i32, _: u8) {}
+}
+fn f(t: &impl Trait) {
+    t.m(1, 2);
+    Trait::m(t, 1, 2);
+    <S as Trait>::m(&S, 1, 2);
+}
+"#,
+            r#"
+trait Trait {
+    fn m(&self, b: u8, a: i32);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, y: u8, x: i32) {}
+}
+struct T;
+impl Trait for T {
+    fn m(&self, _: u8, _: i32) {}
+}
+fn f(t: &impl Trait) {
+    t.m(2, 1);
+    Trait::m(t, 2, 1);
+    <S as Trait>::m(&S, 2, 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_function_values_in_closures() {
+        check_assist(
+            move_param_right,
+            r#"
+fn sub($0a: i32, b: i32) -> i32 { a - b }
+fn apply(f: impl Fn(i32, i32) -> i32) {}
+fn main() {
+    apply(sub);
+    let f = sub as fn(i32, i32) -> i32;
+}
+"#,
+            r#"
+fn sub(b: i32, a: i32) -> i32 { a - b }
+fn apply(f: impl Fn(i32, i32) -> i32) {}
+fn main() {
+    apply(|a, b| sub(b, a));
+    let f = (|a, b| sub(b, a)) as fn(i32, i32) -> i32;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_param_across_files() {
+        check_assist(
+            move_param_left,
+            r#"
+//- /main.rs
+mod foo;
+pub fn f(a: i32, $0b: u8) {}
+//- /foo.rs
+fn g() {
+    crate::f(1, 2);
+}
+"#,
+            r#"
+//- /main.rs
+mod foo;
+pub fn f(b: u8, a: i32) {}
+//- /foo.rs
+fn g() {
+    crate::f(2, 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn move_param_not_applicable_at_ends() {
+        check_assist_not_applicable(move_param_left, "fn f($0a: i32, b: i32) {}");
+        check_assist_not_applicable(move_param_right, "fn f(a: i32, $0b: i32) {}");
+        check_assist_not_applicable(move_param_left, "fn f(a: fn(i32, $0i32)) {}");
+    }
+
+    #[test]
+    fn introduces_param() {
+        check_assist(
+            introduce_param,
+            r#"
+struct S;
+impl S {
+    fn scale(&self, x: u32) -> u32 {
+        x * $03$0
+    }
+}
+fn main() {
+    S.scale(1);
+    S::scale(&S, 2);
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn scale(&self, x: u32, var_name: u32) -> u32 {
+        x * var_name
+    }
+}
+fn main() {
+    S.scale(1, 3);
+    S::scale(&S, 2, 3);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn introduce_param_avoids_name_clashes() {
+        check_assist(
+            introduce_param,
+            r#"
+fn circle(radius: u32) {}
+fn draw() {
+    let radius = 1;
+    circle($010$0);
+}
+fn main() {
+    draw();
+}
+"#,
+            r#"
+fn circle(radius: u32) {}
+fn draw(radius1: u32) {
+    let radius = 1;
+    circle(radius1);
+}
+fn main() {
+    draw(10);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn introduce_param_not_applicable_to_local_expressions() {
+        check_assist_not_applicable(
+            introduce_param,
+            r#"
+fn f(x: u32) -> u32 {
+    $0x + 1$0
+}
+"#,
+        );
+        check_assist_not_applicable(
+            introduce_param,
+            r#"
+trait Trait {
+    fn f() -> u32 {
+        $01$0
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn move_param_warns_about_evaluation_order() {
+        check_assist_by_label(
+            move_param_left,
+            r#"
+fn g() -> i32 { 0 }
+fn f(a: i32, $0b: i32) {}
+fn main() {
+    f(g(), g());
+    f(1, g());
+}
+"#,
+            r#"
+fn g() -> i32 { 0 }
+fn f(b: i32, a: i32) {}
+fn main() {
+    f(g(), g());
+    f(g(), 1);
+}
+"#,
+            "Move parameter left (changes evaluation order at 1 call site)",
+        );
+    }
+
+    #[test]
+    fn adds_param() {
+        check_assist(
+            add_param,
+            r#"
+trait Trait {
+    fn m(&self, a: i32);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, $0a: i32) {
+        let new_param = a;
+    }
+}
+struct T;
+impl Trait for T {
+    fn m(&self, new_param: i32) {}
+}
+fn f(s: S) {
+    s.m(1);
+    Trait::m(&s, 2);
+}
+"#,
+            r#"
+trait Trait {
+    fn m(&self, a: i32, new_param1: ${1:_});
+}
+struct S;
+impl Trait for S {
+    fn m(&self, a: i32, new_param1: ${2:_}) {
+        let new_param = a;
+    }
+}
+struct T;
+impl Trait for T {
+    fn m(&self, new_param: i32, new_param1: ${3:_}) {}
+}
+fn f(s: S) {
+    s.m(1, ${4:todo!()});
+    Trait::m(&s, 2, ${0:todo!()});
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_param_to_empty_list() {
+        check_assist(
+            add_param,
+            r#"
+fn f($0) {}
+fn main() {
+    f();
+}
+"#,
+            r#"
+fn f(new_param: ${1:_}) {}
+fn main() {
+    f(${0:todo!()});
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_unit_param_without_snippets() {
+        check_assist_no_snippet_cap(
+            add_param,
+            r#"
+fn f(a: i32$0) {}
+fn main() {
+    f(1);
+}
+"#,
+            r#"
+fn f(a: i32, new_param: ()) {}
+fn main() {
+    f(1, ());
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_param_from_trait_impls() {
+        check_assist(
+            remove_param,
+            r#"
+trait Trait {
+    fn m(&self, $0a: i32, b: u8);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, _: i32, b: u8) {}
+}
+struct T;
+impl Trait for T {
+    fn m(&self, a: i32, b: u8) {}
+}
+fn f(t: &impl Trait) {
+    t.m(1, 2);
+    Trait::m(t, 1, 2);
+}
+"#,
+            r#"
+trait Trait {
+    fn m(&self, b: u8);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, b: u8) {}
+}
+struct T;
+impl Trait for T {
+    fn m(&self, b: u8) {}
+}
+fn f(t: &impl Trait) {
+    t.m(2);
+    Trait::m(t, 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_param_not_applicable_if_used() {
+        check_assist_not_applicable(
+            remove_param,
+            r#"
+trait Trait {
+    fn m(&self, $0a: i32);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, a: i32) {
+        let _ = a;
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(remove_param, "fn f($0a: i32) {}");
+    }
+
+    #[test]
+    fn renames_param_in_trait_impls() {
+        check_assist(
+            rename_param,
+            r#"
+trait Trait {
+    fn m(&self, a: i32, b: u8);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, $0x: i32, y: u8) {}
+}
+struct T;
+impl Trait for T {
+    fn m(&self, a: i32, _b: u8) {
+        let _ = a + 1;
+    }
+}
+struct U;
+impl Trait for U {
+    fn m(&self, _a: i32, _: u8) {}
+}
+"#,
+            r#"
+trait Trait {
+    fn m(&self, x: i32, b: u8);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, x: i32, y: u8) {}
+}
+struct T;
+impl Trait for T {
+    fn m(&self, x: i32, _b: u8) {
+        let _ = x + 1;
+    }
+}
+struct U;
+impl Trait for U {
+    fn m(&self, _a: i32, _: u8) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rename_param_not_applicable_if_names_match() {
+        check_assist_not_applicable(
+            rename_param,
+            r#"
+trait Trait {
+    fn m(&self, a: i32);
+}
+struct S;
+impl Trait for S {
+    fn m(&self, $0a: i32) {}
+}
+"#,
+        );
+        check_assist_not_applicable(rename_param, "fn f($0a: i32) {}");
+    }
+}
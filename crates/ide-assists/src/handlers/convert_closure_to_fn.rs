@@ -0,0 +1,604 @@
+use hir::{db::HirDatabase, CaptureKind, FnTrait, HirDisplay, PathResolution};
+use ide_db::{
+    assists::{AssistId, AssistKind},
+    base_db::FileRange,
+    defs::Definition,
+    search::{FileReferenceNode, SearchScope},
+};
+use syntax::{
+    ast::{self, edit::IndentLevel, edit_in_place::Indent, make, HasArgList, HasName},
+    ted, AstNode, SyntaxKind, T,
+};
+
+use crate::assist_context::{AssistContext, Assists};
+
+// Assist: convert_closure_to_fn
+//
+// Converts a closure into a function after the enclosing item. Captured variables become
+// parameters, taken by value, `&` or `&mut` depending on how the closure captures them, and are
+// passed at every call. A closure bound to a local gives the function its name, and uses of the
+// local other than calls get a closure forwarding to the function.
+//
+// ```
+// fn main() {
+//     let mut total = 0;
+//     let add = $0|n: u32| total += n;
+//     add(1);
+//     add(2);
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let mut total = 0;
+//     add(1, &mut total);
+//     add(2, &mut total);
+// }
+//
+// fn add(n: u32, total: &mut u32) {
+//     *total += n
+// }
+// ```
+pub(crate) fn convert_closure_to_fn(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let closure = ctx.find_node_at_offset::<ast::ClosureExpr>()?;
+    let body = closure.body()?;
+    if body.syntax().text_range().contains_inclusive(ctx.offset())
+        || closure.async_token().is_some()
+        || closure.for_token().is_some()
+    {
+        return None;
+    }
+    // The function goes after the item at module level the closure is in.
+    let item = closure.syntax().ancestors().filter_map(ast::Item::cast).find(|it| {
+        it.syntax().parent().map_or(false, |it| {
+            ast::SourceFile::can_cast(it.kind()) || ast::ItemList::can_cast(it.kind())
+        })
+    })?;
+
+    let db = ctx.db();
+    let scope = ctx.sema.scope(closure.syntax())?;
+    let module = scope.module().nearest_non_block_module(db);
+    let mut names_in_scope = Vec::new();
+    scope.process_all_names(&mut |name, _| names_in_scope.push(name.to_smol_str()));
+    // A closure bound to a local becomes a function of the same name, used in place of the local.
+    let binding = ast::LetStmt::cast(closure.syntax().parent()?)
+        .filter(|it| it.ty().is_none() && it.let_else().is_none())
+        .and_then(|let_stmt| match let_stmt.pat()? {
+            ast::Pat::IdentPat(it) if it.ref_token().is_none() && it.pat().is_none() => {
+                Some((let_stmt, it))
+            }
+            _ => None,
+        });
+    let (fn_name, closure_local) = match &binding {
+        Some((_, ident_pat)) => {
+            let name = ident_pat.name()?;
+            // Without the local, its uses would refer to whatever it shadowed.
+            if names_in_scope.iter().any(|it| *it == name.text().as_str()) {
+                return None;
+            }
+            (name, Some(ctx.sema.to_def(ident_pat)?))
+        }
+        None => {
+            let mut name = "fun_name".to_owned();
+            let mut counter = 0;
+            while names_in_scope.iter().any(|it| *it == name.as_str()) {
+                counter += 1;
+                name = format!("fun_name{counter}");
+            }
+            (make::name(&name), None)
+        }
+    };
+
+    // The function can't use the generic parameters of the enclosing item, nor items local to it.
+    if body.syntax().descendants_with_tokens().any(|it| it.kind() == SyntaxKind::SELF_TYPE_KW) {
+        return None;
+    }
+    let is_local_item = |def: hir::ModuleDef| {
+        def.module(db).map_or(false, |it| it.nearest_non_block_module(db) != it)
+    };
+    for path in body.syntax().descendants().filter_map(ast::Path::cast) {
+        if let Some(PathResolution::Def(def)) = ctx.sema.resolve_path(&path) {
+            if is_local_item(def) {
+                return None;
+            }
+        }
+    }
+    let render_ty = |ty: &hir::Type| {
+        if !ty.generic_params(db).is_empty()
+            || ty.as_adt().map_or(false, |it| is_local_item(it.into()))
+        {
+            return None;
+        }
+        ty.display_source_code(db, module.into(), false).ok().map(|it| make::ty(&it))
+    };
+
+    let closure_ty = ctx.sema.type_of_expr(&ast::Expr::ClosureExpr(closure.clone()))?.original;
+    let hir_closure = closure_ty.as_closure()?;
+
+    let mut params = Vec::new();
+    // The names the closure's parameters are forwarded under, where it's used other than called.
+    let mut forwarded = Vec::new();
+    for (idx, param) in closure.param_list()?.params().enumerate() {
+        let pat = param.pat()?;
+        let ty = match param.ty() {
+            Some(ty) => ty,
+            None => render_ty(&ctx.sema.type_of_pat(&pat)?.original)?,
+        };
+        forwarded.push(match &pat {
+            ast::Pat::IdentPat(it) if it.pat().is_none() => it.name()?.text().to_string(),
+            _ => format!("arg{idx}"),
+        });
+        params.push(make::param(pat, ty));
+    }
+    let ret_type = match closure.ret_type() {
+        Some(ret_type) => Some(ret_type),
+        None => {
+            let ty = ctx.sema.type_of_expr(&body)?.adjusted();
+            if ty.is_unit() {
+                None
+            } else {
+                Some(make::ret_type(render_ty(&ty)?))
+            }
+        }
+    };
+
+    // The calls of the local, and its other uses, which are replaced by a closure calling the
+    // function. Without a local, the closure itself is such a use.
+    let mut calls = Vec::new();
+    let mut uses = Vec::new();
+    match closure_local {
+        Some(closure_local) => {
+            let usages = Definition::Local(closure_local).usages(&ctx.sema).all();
+            for reference in usages.iter().flat_map(|(_, refs)| refs) {
+                let name_ref = reference.name.as_name_ref()?;
+                let path_expr = name_ref.syntax().ancestors().find_map(ast::PathExpr::cast)?;
+                match path_expr.syntax().parent().and_then(ast::CallExpr::cast) {
+                    Some(call) if call.expr()?.syntax() == path_expr.syntax() => calls.push(call),
+                    _ => uses.push(ast::Expr::from(path_expr)),
+                }
+            }
+        }
+        None => uses.push(ast::Expr::ClosureExpr(closure.clone())),
+    }
+
+    let captures = captured_locals(db, &hir_closure, calls.len() + uses.len() > 1)?;
+    let mut capture_args = Vec::new();
+    for &(local, kind) in &captures {
+        let name = make::name(&local.name(db).to_smol_str());
+        let ty = render_ty(&local.ty(db))?;
+        let (ty, arg) = match kind {
+            Capture::Value => (ty, make::expr_path(make::ext::ident_path(&name.text()))),
+            Capture::Ref(mutable) => (
+                make::ty_ref(ty, mutable),
+                make::expr_ref(make::expr_path(make::ext::ident_path(&name.text())), mutable),
+            ),
+        };
+        params.push(make::param(make::ident_pat(false, false, name).into(), ty));
+        capture_args.push(arg);
+    }
+    // Without captures the function itself can be used in place of the closure.
+    let fn_path = make::expr_path(make::ext::ident_path(&fn_name.text()));
+    let replacement = if captures.is_empty() {
+        fn_path
+    } else {
+        let args = forwarded
+            .iter()
+            .map(|it| make::expr_path(make::ext::ident_path(it)))
+            .chain(capture_args.iter().cloned());
+        make::expr_closure(
+            forwarded
+                .iter()
+                .map(|it| make::untyped_param(make::ext::simple_ident_pat(make::name(it)).into())),
+            make::expr_call(fn_path, make::arg_list(args)),
+        )
+    };
+
+    // Captures taken by reference have to be dereferenced where they're used by value.
+    let body_range = body.syntax().text_range();
+    let mut derefs = Vec::new();
+    for &(local, kind) in &captures {
+        if kind == Capture::Value {
+            continue;
+        }
+        let usages = Definition::Local(local)
+            .usages(&ctx.sema)
+            .in_scope(&SearchScope::file_range(FileRange {
+                file_id: ctx.file_id(),
+                range: body_range,
+            }))
+            .all();
+        for reference in usages.references.get(&ctx.file_id()).into_iter().flatten() {
+            let FileReferenceNode::NameRef(name_ref) = &reference.name else { continue };
+            // Uses in macro calls don't have a path to dereference.
+            let Some(path_expr) = name_ref.syntax().ancestors().find_map(ast::PathExpr::cast)
+            else {
+                continue;
+            };
+            if !auto_derefs(&path_expr) {
+                derefs.push(path_expr.syntax().text_range() - body_range.start());
+            }
+        }
+    }
+
+    acc.add(
+        AssistId("convert_closure_to_fn", AssistKind::RefactorRewrite),
+        "Convert closure to function",
+        closure.syntax().text_range(),
+        |builder| {
+            let body = body.clone_subtree().clone_for_update();
+            // Replace back to front, so that the ranges of the remaining uses stay valid.
+            for range in derefs.into_iter().rev() {
+                let Some(path_expr) =
+                    body.syntax().covering_element(range).ancestors().find_map(ast::PathExpr::cast)
+                else {
+                    continue;
+                };
+                let deref = make::expr_prefix(T![*], path_expr.clone().into()).clone_for_update();
+                match path_expr
+                    .path()
+                    .and_then(|it| it.as_single_name_ref())
+                    .and_then(|it| ast::RecordExprField::for_name_ref(&it))
+                {
+                    Some(field) => field.replace_expr(deref),
+                    None => ted::replace(path_expr.syntax(), deref.syntax()),
+                }
+            }
+            let body = match body {
+                ast::Expr::BlockExpr(block) => {
+                    block.dedent(IndentLevel::from_node(closure.syntax()));
+                    block
+                }
+                expr => make::block_expr(None, Some(expr)).clone_for_update(),
+            };
+
+            let fn_ = make::fn_(
+                None,
+                fn_name,
+                None,
+                None,
+                make::param_list(None, params),
+                body,
+                ret_type,
+                false,
+                false,
+                false,
+            )
+            .clone_for_update();
+            let indent = IndentLevel::from_node(item.syntax());
+            fn_.reindent_to(indent);
+
+            let calls: Vec<_> = calls
+                .into_iter()
+                .filter_map(|call| call.arg_list())
+                .map(|arg_list| builder.make_mut(arg_list))
+                .collect();
+            let uses: Vec<_> = uses.into_iter().map(|it| builder.make_mut(it)).collect();
+            let let_stmt = binding.map(|(let_stmt, _)| builder.make_mut(let_stmt));
+            let item = builder.make_mut(item);
+            for arg_list in calls {
+                let args = arg_list.args().chain(capture_args.iter().cloned());
+                ted::replace(arg_list.syntax(), make::arg_list(args).clone_for_update().syntax());
+            }
+            for it in uses {
+                ted::replace(it.syntax(), replacement.clone_for_update().syntax());
+            }
+            if let Some(let_stmt) = let_stmt {
+                if let Some(ws) = let_stmt
+                    .syntax()
+                    .prev_sibling_or_token()
+                    .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+                {
+                    ted::remove(ws);
+                }
+                ted::remove(let_stmt.syntax());
+            }
+            ted::insert_all_raw(
+                ted::Position::after(item.syntax()),
+                vec![
+                    make::tokens::whitespace(&format!("\n\n{indent}")).into(),
+                    fn_.syntax().clone().into(),
+                ],
+            );
+        },
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Capture {
+    Value,
+    Ref(bool),
+}
+
+/// The locals the closure captures, with how the function has to take them. Captures of several
+/// places within the same local are merged into one capture of the whole local.
+fn captured_locals(
+    db: &dyn HirDatabase,
+    closure: &hir::Closure,
+    called_repeatedly: bool,
+) -> Option<Vec<(hir::Local, Capture)>> {
+    let fn_trait = closure.fn_trait(db);
+    let mut captures: Vec<(hir::Local, Capture)> = Vec::new();
+    for (capture, place_ty) in closure.captured_items(db).into_iter().zip(closure.capture_types(db))
+    {
+        let local = capture.local();
+        if local.is_self(db) {
+            return None;
+        }
+        let whole_local = capture.display_place(db) == local.name(db).display(db).to_string();
+        let kind = match capture.kind() {
+            CaptureKind::SharedRef => Capture::Ref(false),
+            CaptureKind::MutableRef => Capture::Ref(true),
+            // A unique borrow is of a `&mut` local that isn't declared `mut` itself, which is
+            // reborrowed when it's passed on.
+            CaptureKind::UniqueSharedRef => Capture::Value,
+            CaptureKind::Move if place_ty.is_copy(db) && whole_local => Capture::Value,
+            // Copying a part of the local out only needs a borrow of the whole local, and so does
+            // a `move` closure that is called more than once, as it can't consume its captures.
+            CaptureKind::Move if place_ty.is_copy(db) || called_repeatedly => {
+                Capture::Ref(fn_trait == FnTrait::FnMut)
+            }
+            CaptureKind::Move => Capture::Value,
+        };
+        match captures.iter_mut().find(|(it, _)| *it == local) {
+            Some((_, existing)) => *existing = merge_captures(*existing, kind),
+            None => captures.push((local, kind)),
+        }
+    }
+    Some(captures)
+}
+
+fn merge_captures(a: Capture, b: Capture) -> Capture {
+    match (a, b) {
+        (Capture::Value, _) | (_, Capture::Value) => Capture::Value,
+        (Capture::Ref(a), Capture::Ref(b)) => Capture::Ref(a || b),
+    }
+}
+
+/// Whether a reference can be used in place of the value at `path_expr`, because the expression
+/// it's part of dereferences it automatically.
+fn auto_derefs(path_expr: &ast::PathExpr) -> bool {
+    let Some(parent) = path_expr.syntax().parent() else { return false };
+    let expr = path_expr.syntax();
+    if let Some(call) = ast::MethodCallExpr::cast(parent.clone()) {
+        return call.receiver().map_or(false, |it| it.syntax() == expr);
+    }
+    if let Some(call) = ast::CallExpr::cast(parent.clone()) {
+        return call.expr().map_or(false, |it| it.syntax() == expr);
+    }
+    if let Some(index) = ast::IndexExpr::cast(parent.clone()) {
+        return index.base().map_or(false, |it| it.syntax() == expr);
+    }
+    ast::FieldExpr::can_cast(parent.kind())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn closure_without_captures() {
+        check_assist(
+            convert_closure_to_fn,
+            r#"
+fn apply(f: impl Fn(u32) -> [u32; 2]) -> [u32; 2] { f(1) }
+fn main() {
+    let double = $0|x| [x, x];
+    let a = double(3u32);
+    let b = apply(double);
+}
+"#,
+            r#"
+fn apply(f: impl Fn(u32) -> [u32; 2]) -> [u32; 2] { f(1) }
+fn main() {
+    let a = double(3u32);
+    let b = apply(double);
+}
+
+fn double(x: u32) -> [u32; 2] {
+    [x, x]
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_with_captures() {
+        check_assist(
+            convert_closure_to_fn,
+            r#"
+//- minicore: copy, add
+struct Counter { count: u32 }
+fn main() {
+    let step = 2;
+    let mut counter = Counter { count: 0 };
+    let name = Counter { count: 5 };
+    let mut bump = $0|by: u32| {
+        counter.count += by + step;
+        let other = Counter { count: name.count };
+        other.count
+    };
+    bump(1);
+}
+"#,
+            r#"
+struct Counter { count: u32 }
+fn main() {
+    let step = 2;
+    let mut counter = Counter { count: 0 };
+    let name = Counter { count: 5 };
+    bump(1, &step, &mut counter, &name);
+}
+
+fn bump(by: u32, step: &u32, counter: &mut Counter, name: &Counter) -> u32 {
+    counter.count += by + *step;
+    let other = Counter { count: name.count };
+    other.count
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn derefs_captures_used_by_value() {
+        check_assist(
+            convert_closure_to_fn,
+            r#"
+//- minicore: copy, add
+struct S { total: u32 }
+fn main() {
+    let mut total = 0u32;
+    let mut add = |$0n: u32| {
+        total = total + n;
+        S { total }
+    };
+    add(1);
+}
+"#,
+            r#"
+struct S { total: u32 }
+fn main() {
+    let mut total = 0u32;
+    add(1, &mut total);
+}
+
+fn add(n: u32, total: &mut u32) -> S {
+    *total = *total + n;
+    S { total: *total }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn move_closure_called_repeatedly_borrows() {
+        check_assist(
+            convert_closure_to_fn,
+            r#"
+struct Name(u32);
+fn main() {
+    let name = Name(1);
+    let get = move $0|| name.0;
+    get();
+    get();
+}
+"#,
+            r#"
+struct Name(u32);
+fn main() {
+    let name = Name(1);
+    get(&name);
+    get(&name);
+}
+
+fn get(name: &Name) -> u32 {
+    name.0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn capturing_closure_passed_on() {
+        check_assist(
+            convert_closure_to_fn,
+            r#"
+fn apply(f: impl Fn(u32) -> u32) -> u32 { f(1) }
+fn main() {
+    let x = 1;
+    let add = $0|n: u32| n + x;
+    add(2);
+    apply(add);
+}
+"#,
+            r#"
+fn apply(f: impl Fn(u32) -> u32) -> u32 { f(1) }
+fn main() {
+    let x = 1;
+    add(2, &x);
+    apply(|n| add(n, &x));
+}
+
+fn add(n: u32, x: &u32) -> u32 {
+    n + *x
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_not_bound_to_local() {
+        check_assist(
+            convert_closure_to_fn,
+            r#"
+//- minicore: fn
+fn apply(f: impl Fn(u32) -> u32) -> u32 { f(1) }
+mod m {
+    struct S;
+    impl S {
+        fn f(&self, offset: u32) -> u32 {
+            super::apply($0|n| {
+                n + offset
+            })
+        }
+    }
+}
+"#,
+            r#"
+fn apply(f: impl Fn(u32) -> u32) -> u32 { f(1) }
+mod m {
+    struct S;
+    impl S {
+        fn f(&self, offset: u32) -> u32 {
+            super::apply(|n| fun_name(n, offset))
+        }
+    }
+
+    fn fun_name(n: u32, offset: u32) -> u32 {
+        n + offset
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_local_items() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+fn main() {
+    struct Local(u32);
+    let get = $0|| Local(1);
+    get();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_body() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+fn main() {
+    let double = |x: u32| x $0* 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_generic_types() {
+        check_assist_not_applicable(
+            convert_closure_to_fn,
+            r#"
+fn f<T: Copy>(t: T) {
+    let get = $0|| t;
+    get();
+}
+"#,
+        );
+    }
+}
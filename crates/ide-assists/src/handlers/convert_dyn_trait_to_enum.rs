@@ -0,0 +1,856 @@
+use hir::{HasSource, ModuleDef, PathResolution, Semantics};
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    famous_defs::FamousDefs,
+    helpers::mod_path_to_ast,
+    imports::insert_use::{insert_use, ImportScope},
+    search::{FileReferenceNode, SearchScope},
+    syntax_helpers::node_ext::full_path_of_name_ref,
+    FxHashMap, RootDatabase,
+};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{
+        self, edit::IndentLevel, make, HasArgList, HasGenericParams, HasModuleItem, HasName,
+        HasTypeBounds, HasVisibility,
+    },
+    ted, AstNode, SyntaxKind, SyntaxNode, TextRange, TextSize,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: generate_enum_dispatch
+//
+// Generates an enum with a variant for every implementor of a trait, and an impl of the trait
+// for the enum that forwards each method to the variants.
+//
+// ```
+// trait Shape$0 {
+//     fn area(&self) -> f64;
+// }
+//
+// struct Square(f64);
+// impl Shape for Square {
+//     fn area(&self) -> f64 { self.0 * self.0 }
+// }
+// ```
+// ->
+// ```
+// trait Shape {
+//     fn area(&self) -> f64;
+// }
+//
+// enum AnyShape {
+//     Square(Square),
+// }
+//
+// impl Shape for AnyShape {
+//     fn area(&self) -> f64 {
+//         match self {
+//             AnyShape::Square(it) => Shape::area(it),
+//         }
+//     }
+// }
+//
+// struct Square(f64);
+// impl Shape for Square {
+//     fn area(&self) -> f64 { self.0 * self.0 }
+// }
+// ```
+pub(crate) fn generate_enum_dispatch(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let dispatch = Dispatch::new(ctx)?;
+
+    acc.add(
+        AssistId("generate_enum_dispatch", AssistKind::Generate),
+        format!("Generate enum dispatch for `{}`", dispatch.trait_name),
+        dispatch.target,
+        |builder| {
+            builder.edit_file(dispatch.file_id);
+            builder.insert(dispatch.offset, dispatch.text);
+        },
+    )
+}
+
+// Assist: convert_dyn_trait_to_enum
+//
+// Generates an enum dispatching to the implementors of a trait, and replaces `Box<dyn Trait>`
+// types and the boxes created for them with the enum.
+//
+// ```
+// # //- minicore: coerce_unsized
+// # //- /main.rs crate:main deps:alloc
+// # use alloc::boxed::Box;
+// trait Shape {
+//     fn area(&self) -> f64;
+// }
+//
+// struct Square(f64);
+// impl Shape for Square {
+//     fn area(&self) -> f64 { self.0 * self.0 }
+// }
+//
+// fn unit() -> Box<dyn $0Shape> {
+//     Box::new(Square(1.0))
+// }
+// # //- /alloc.rs crate:alloc
+// # pub mod boxed {
+// #     use core::marker::Unsize;
+// #     use core::ops::CoerceUnsized;
+// #     pub struct Box<T: ?Sized>(*mut T);
+// #     impl<T> Box<T> {
+// #         pub fn new(value: T) -> Box<T> { loop {} }
+// #     }
+// #     impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+// # }
+// ```
+// ->
+// ```
+// # use alloc::boxed::Box;
+// trait Shape {
+//     fn area(&self) -> f64;
+// }
+//
+// enum AnyShape {
+//     Square(Square),
+// }
+//
+// impl Shape for AnyShape {
+//     fn area(&self) -> f64 {
+//         match self {
+//             AnyShape::Square(it) => Shape::area(it),
+//         }
+//     }
+// }
+//
+// struct Square(f64);
+// impl Shape for Square {
+//     fn area(&self) -> f64 { self.0 * self.0 }
+// }
+//
+// fn unit() -> AnyShape {
+//     AnyShape::Square(Square(1.0))
+// }
+// ```
+pub(crate) fn convert_dyn_trait_to_enum(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let dispatch = Dispatch::new(ctx)?;
+    let rewrites = Rewrites::find(ctx, &dispatch)?;
+    if rewrites.edits.is_empty() {
+        return None;
+    }
+
+    let mut label = format!("Replace `Box<dyn {}>` with enum dispatch", dispatch.trait_name);
+    if rewrites.skipped > 0 {
+        let s = if rewrites.skipped == 1 { "" } else { "s" };
+        format_to!(label, " ({} reference{s} left unchanged)", rewrites.skipped);
+    }
+
+    acc.add(
+        AssistId("convert_dyn_trait_to_enum", AssistKind::RefactorRewrite),
+        label,
+        dispatch.target,
+        |builder| {
+            let Rewrites { mut edits, imports, .. } = rewrites;
+            // The enum goes after the trait, even where nothing else changes in its file.
+            edits.entry(dispatch.file_id).or_default();
+            for (file_id, edits) in edits {
+                builder.edit_file(file_id);
+                let edits: Vec<_> = edits
+                    .into_iter()
+                    .map(|(it, with)| (builder.make_syntax_mut(it), with))
+                    .collect();
+                let trait_ = ctx
+                    .sema
+                    .source(dispatch.trait_)
+                    .filter(|_| file_id == dispatch.file_id)
+                    .map(|it| builder.make_mut(it.value));
+                let import_nodes: Vec<_> = imports
+                    .iter()
+                    .filter(|(_, (it, _))| *it == file_id)
+                    .map(|(module, (_, node))| (*module, builder.make_syntax_mut(node.clone())))
+                    .collect();
+                // The imports go first, while the nodes in the modules are still in the tree.
+                for (module, node) in import_nodes {
+                    let path = module.find_use_path_prefixed(
+                        ctx.sema.db,
+                        ModuleDef::Module(dispatch.module),
+                        ctx.config.insert_use.prefix_kind,
+                        ctx.config.prefer_no_std,
+                        ctx.config.prefer_prelude,
+                    );
+                    let scope = ImportScope::find_insert_use_container(&node, &ctx.sema);
+                    if let Some((scope, path)) = scope.zip(path) {
+                        let path = make::path_concat(
+                            mod_path_to_ast(&path),
+                            make::path_from_text(&dispatch.enum_name),
+                        );
+                        insert_use(&scope, path, &ctx.config.insert_use);
+                    }
+                }
+                for (node, with) in edits {
+                    ted::replace(node, with.clone_for_update());
+                }
+                if let Some(trait_) = trait_ {
+                    let indent = IndentLevel::from_node(trait_.syntax());
+                    let items = ast::SourceFile::parse(&dispatch.text).tree().clone_for_update();
+                    let mut elements = Vec::new();
+                    for item in items.items().collect::<Vec<_>>() {
+                        elements.push(make::tokens::whitespace(&format!("\n\n{indent}")).into());
+                        item.syntax().detach();
+                        elements.push(item.syntax().clone().into());
+                    }
+                    ted::insert_all_raw(ted::Position::after(trait_.syntax()), elements);
+                }
+            }
+        },
+    )
+}
+
+/// The enum for a trait and the impl forwarding the trait to its variants.
+struct Dispatch {
+    trait_: hir::Trait,
+    trait_name: String,
+    module: hir::Module,
+    krate: hir::Crate,
+    enum_name: String,
+    variants: Vec<(hir::Adt, String)>,
+    target: TextRange,
+    /// Where the enum goes, right after the trait.
+    file_id: FileId,
+    offset: TextSize,
+    text: String,
+}
+
+impl Dispatch {
+    fn new(ctx: &AssistContext<'_>) -> Option<Dispatch> {
+        let db = ctx.db();
+        let (trait_, target) = target_trait(ctx)?;
+        let module = trait_.module(db);
+        let krate = module.krate();
+        if ctx.sema.to_module_def(ctx.file_id())?.krate() != krate {
+            return None;
+        }
+        let source = trait_.source(db)?;
+        let file_id = source.file_id.file_id()?;
+        let trait_ast = source.value;
+        if trait_ast.generic_param_list().is_some()
+            || trait_ast.where_clause().is_some()
+            || trait_ast.unsafe_token().is_some()
+            || trait_ast.auto_token().is_some()
+        {
+            return None;
+        }
+        // Other supertraits would need impls for the enum as well.
+        if let Some(bounds) = trait_ast.type_bound_list() {
+            if !bounds.bounds().all(|it| is_auto_or_lifetime_bound(&ctx.sema, &it)) {
+                return None;
+            }
+        }
+        let methods = trait_ast
+            .assoc_item_list()?
+            .assoc_items()
+            .map(|item| match item {
+                ast::AssocItem::Fn(it) => Some(it),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut variants = Vec::new();
+        for impl_ in hir::Impl::all_for_trait(db, trait_) {
+            if impl_.module(db).krate() != krate || impl_.is_negative(db) {
+                return None;
+            }
+            if impl_.source(db)?.value.generic_param_list().is_some() {
+                return None;
+            }
+            let self_ty = impl_.self_ty(db);
+            if self_ty.type_arguments().next().is_some() {
+                return None;
+            }
+            let adt = self_ty.as_adt()?;
+            let path = module.find_use_path(
+                db,
+                ModuleDef::Adt(adt),
+                ctx.config.prefer_no_std,
+                ctx.config.prefer_prelude,
+            )?;
+            variants.push((adt, adt.name(db).display(db).to_string(), mod_path_to_ast(&path)));
+        }
+        if variants.is_empty() || !variants.iter().map(|(_, name, _)| name).all_unique() {
+            return None;
+        }
+
+        let trait_name = trait_.name(db).display(db).to_string();
+        let enum_name = format!("Any{trait_name}");
+        if module.scope(db, None).iter().any(|(name, _)| name.to_smol_str() == enum_name) {
+            return None;
+        }
+
+        let indent = IndentLevel::from_node(trait_ast.syntax());
+        let vis = trait_ast.visibility().map_or(String::new(), |it| format!("{it} "));
+        let mut text = format!("\n\n{indent}{vis}enum {enum_name} {{\n");
+        for (_, name, path) in &variants {
+            format_to!(text, "{}{name}({path}),\n", indent + 1);
+        }
+        format_to!(text, "{indent}}}\n\n{indent}impl {trait_name} for {enum_name} {{");
+        let variant_names: Vec<_> = variants.iter().map(|(_, name, _)| name.as_str()).collect();
+        for (idx, method) in methods.iter().enumerate() {
+            let method =
+                forwarding_method(method, &trait_name, &enum_name, &variant_names, indent + 1)?;
+            let sep = if idx == 0 { "" } else { "\n" };
+            format_to!(text, "{sep}\n{method}");
+        }
+        format_to!(text, "\n{indent}}}");
+
+        Some(Dispatch {
+            trait_,
+            trait_name,
+            module,
+            krate,
+            enum_name,
+            variants: variants.into_iter().map(|(adt, name, _)| (adt, name)).collect(),
+            target,
+            file_id,
+            offset: trait_ast.syntax().text_range().end(),
+            text,
+        })
+    }
+}
+
+/// The trait whose header or `dyn` type the cursor is on.
+fn target_trait(ctx: &AssistContext<'_>) -> Option<(hir::Trait, TextRange)> {
+    if let Some(dyn_ty) = ctx.find_node_at_offset::<ast::DynTraitType>() {
+        let trait_ = dyn_ty.type_bound_list()?.bounds().find_map(|bound| {
+            let ast::Type::PathType(ty) = bound.ty()? else { return None };
+            match ctx.sema.resolve_path(&ty.path()?)? {
+                PathResolution::Def(ModuleDef::Trait(it)) if !it.is_auto(ctx.db()) => Some(it),
+                _ => None,
+            }
+        })?;
+        return Some((trait_, dyn_ty.syntax().text_range()));
+    }
+    let trait_ast: ast::Trait = ctx.find_node_at_offset()?;
+    if ctx.offset() >= trait_ast.assoc_item_list()?.syntax().text_range().start() {
+        return None;
+    }
+    Some((ctx.sema.to_def(&trait_ast)?, trait_ast.syntax().text_range()))
+}
+
+fn is_auto_or_lifetime_bound(sema: &Semantics<'_, RootDatabase>, bound: &ast::TypeBound) -> bool {
+    if bound.lifetime().is_some() {
+        return true;
+    }
+    let Some(ast::Type::PathType(ty)) = bound.ty() else { return false };
+    matches!(
+        ty.path().and_then(|it| sema.resolve_path(&it)),
+        Some(PathResolution::Def(ModuleDef::Trait(it))) if it.is_auto(sema.db)
+    )
+}
+
+/// An impl of the trait method `method` matching on the enum and calling the method of the
+/// variant's value.
+fn forwarding_method(
+    method: &ast::Fn,
+    trait_name: &str,
+    enum_name: &str,
+    variants: &[&str],
+    indent: IndentLevel,
+) -> Option<String> {
+    let name = method.name()?;
+    let param_list = method.param_list()?;
+    let self_param = param_list.self_param()?;
+    // `self: Box<Self>` and other typed receivers can't be matched on.
+    if self_param.colon_token().is_some() {
+        return None;
+    }
+    // The forwarded call would return or take the variant's type instead of the enum.
+    let mentions_self = |node: &SyntaxNode| {
+        node.descendants_with_tokens().any(|it| it.kind() == SyntaxKind::SELF_TYPE_KW)
+    };
+    if param_list.params().any(|it| mentions_self(it.syntax()))
+        || method.ret_type().map_or(false, |it| mentions_self(it.syntax()))
+    {
+        return None;
+    }
+
+    let mut params = vec![match self_param.kind() {
+        ast::SelfParamKind::Owned => "self".to_owned(),
+        _ => self_param.syntax().text().to_string(),
+    }];
+    let mut args = vec!["it".to_owned()];
+    let mut has_impl_trait_arg = false;
+    for (idx, param) in param_list.params().enumerate() {
+        let ty = param.ty()?;
+        has_impl_trait_arg |=
+            ty.syntax().descendants().any(|it| ast::ImplTraitType::can_cast(it.kind()));
+        let arg = match param.pat() {
+            Some(ast::Pat::IdentPat(pat)) if pat.ref_token().is_none() && pat.pat().is_none() => {
+                pat.name()?.to_string()
+            }
+            _ => format!("arg{idx}"),
+        };
+        params.push(format!("{arg}: {ty}"));
+        args.push(arg);
+    }
+
+    // Type parameters only used in the return type have to be passed explicitly.
+    let mut turbofish = String::new();
+    if let Some(generic_params) = method.generic_param_list() {
+        let generic_args = generic_params
+            .generic_params()
+            .filter_map(|it| match it {
+                ast::GenericParam::TypeParam(it) => it.name(),
+                ast::GenericParam::ConstParam(it) => it.name(),
+                ast::GenericParam::LifetimeParam(_) => None,
+            })
+            .join(", ");
+        if !generic_args.is_empty() && !has_impl_trait_arg {
+            turbofish = format!("::<{generic_args}>");
+        }
+    }
+
+    let mut buf = format!("{indent}");
+    if method.async_token().is_some() {
+        buf.push_str("async ");
+    }
+    if method.unsafe_token().is_some() {
+        buf.push_str("unsafe ");
+    }
+    if let Some(abi) = method.abi() {
+        format_to!(buf, "{abi} ");
+    }
+    format_to!(buf, "fn {name}");
+    if let Some(generic_params) = method.generic_param_list() {
+        format_to!(buf, "{generic_params}");
+    }
+    format_to!(buf, "({})", params.join(", "));
+    if let Some(ret_type) = method.ret_type() {
+        format_to!(buf, " {ret_type}");
+    }
+    if let Some(where_clause) = method.where_clause() {
+        format_to!(buf, " {where_clause}");
+    }
+    let await_ = if method.async_token().is_some() { ".await" } else { "" };
+    let call = format!("{trait_name}::{name}{turbofish}({}){await_}", args.join(", "));
+    format_to!(buf, " {{\n{}match self {{\n", indent + 1);
+    for variant in variants {
+        format_to!(buf, "{}{enum_name}::{variant}(it) => {call},\n", indent + 2);
+    }
+    format_to!(buf, "{}}}\n{indent}}}", indent + 1);
+    Some(buf)
+}
+
+/// The edits replacing `Box<dyn Trait>` with the enum.
+struct Rewrites {
+    /// The nodes to replace, with their replacements.
+    edits: FxHashMap<FileId, Vec<(SyntaxNode, SyntaxNode)>>,
+    /// Modules referring to the enum from outside of the trait's module, with a node in them.
+    imports: FxHashMap<hir::Module, (FileId, SyntaxNode)>,
+    skipped: usize,
+}
+
+impl Rewrites {
+    fn find(ctx: &AssistContext<'_>, dispatch: &Dispatch) -> Option<Rewrites> {
+        let sema = &ctx.sema;
+        let box_ = FamousDefs(sema, dispatch.krate).alloc_boxed_Box()?;
+        let mut rewrites =
+            Rewrites { edits: FxHashMap::default(), imports: FxHashMap::default(), skipped: 0 };
+
+        // `Box::new(value)` coerced to `Box<dyn Trait>`.
+        let scope = SearchScope::module_and_children(sema.db, dispatch.krate.root_module());
+        let box_refs = Definition::Adt(hir::Adt::Struct(box_)).usages(sema).in_scope(&scope).all();
+        for (file_id, references) in box_refs {
+            for reference in references {
+                let FileReferenceNode::NameRef(name_ref) = reference.name else { continue };
+                let Some((expr, value)) = boxed_value(sema, &name_ref, box_, dispatch.trait_)
+                else {
+                    continue;
+                };
+                if sema.hir_file_for(name_ref.syntax()).is_macro() {
+                    rewrites.skipped += 1;
+                    continue;
+                }
+                let variant = sema.type_of_expr(&value).and_then(|it| {
+                    let adt = it.original.as_adt()?;
+                    dispatch.variants.iter().find(|(it, _)| *it == adt)
+                });
+                let Some((_, variant)) = variant else {
+                    rewrites.skipped += 1;
+                    continue;
+                };
+                let enum_ref = rewrites.enum_ref(sema, dispatch, file_id, expr.syntax());
+                let variant =
+                    make::expr_path(make::path_from_text(&format!("{enum_ref}::{variant}")));
+                let new_expr = make::expr_call(variant, make::arg_list([value]));
+                rewrites
+                    .edits
+                    .entry(file_id)
+                    .or_default()
+                    .push((expr.syntax().clone(), new_expr.syntax().clone()));
+            }
+        }
+
+        // The `Box<dyn Trait>` types.
+        for (file_id, references) in Definition::Trait(dispatch.trait_).usages(sema).all() {
+            for reference in references {
+                let FileReferenceNode::NameRef(name_ref) = reference.name else { continue };
+                let Some(box_ty) = boxed_dyn_type(sema, &name_ref, box_) else { continue };
+                if sema.hir_file_for(name_ref.syntax()).is_macro() {
+                    rewrites.skipped += 1;
+                    continue;
+                }
+                let range = box_ty.syntax().text_range();
+                // Casts to the type are replaced as a whole.
+                let edits = rewrites.edits.entry(file_id).or_default();
+                if edits.iter().any(|(it, _)| it.text_range().contains_range(range)) {
+                    continue;
+                }
+                let enum_ref = rewrites.enum_ref(sema, dispatch, file_id, box_ty.syntax());
+                let new_ty = make::ty(&enum_ref);
+                rewrites
+                    .edits
+                    .entry(file_id)
+                    .or_default()
+                    .push((box_ty.syntax().clone(), new_ty.syntax().clone()));
+            }
+        }
+        rewrites.edits.retain(|_, it| !it.is_empty());
+        Some(rewrites)
+    }
+
+    /// How `node` refers to the enum, importing it if it's in another module.
+    fn enum_ref(
+        &mut self,
+        sema: &Semantics<'_, RootDatabase>,
+        dispatch: &Dispatch,
+        file_id: FileId,
+        node: &SyntaxNode,
+    ) -> String {
+        if let Some(module) = sema.scope(node).map(|it| it.module()) {
+            if module != dispatch.module {
+                self.imports.entry(module).or_insert_with(|| (file_id, node.clone()));
+            }
+        }
+        dispatch.enum_name.clone()
+    }
+}
+
+/// The expression creating a `Box<dyn Trait>` from `Box::new(value)`, where `name_ref` is the
+/// `Box`, and the boxed value.
+fn boxed_value(
+    sema: &Semantics<'_, RootDatabase>,
+    name_ref: &ast::NameRef,
+    box_: hir::Struct,
+    trait_: hir::Trait,
+) -> Option<(ast::Expr, ast::Expr)> {
+    let path = full_path_of_name_ref(name_ref)?;
+    if path.qualifier()?.segment()?.name_ref()? != *name_ref
+        || path.segment()?.name_ref()?.text() != "new"
+    {
+        return None;
+    }
+    let path_expr = ast::PathExpr::cast(path.syntax().parent()?)?;
+    let call = ast::CallExpr::cast(path_expr.syntax().parent()?)?;
+    let value = call.arg_list()?.args().exactly_one().ok()?;
+    let is_boxed_dyn = |expr: &ast::Expr| {
+        sema.type_of_expr(expr).map_or(false, |it| {
+            let ty = it.adjusted();
+            ty.as_adt() == Some(hir::Adt::Struct(box_))
+                && ty.type_arguments().next().and_then(|it| it.as_dyn_trait()) == Some(trait_)
+        })
+    };
+    if let Some(cast) = call.syntax().parent().and_then(ast::CastExpr::cast) {
+        let cast = ast::Expr::CastExpr(cast);
+        return is_boxed_dyn(&cast).then_some((cast, value));
+    }
+    let call = ast::Expr::CallExpr(call);
+    is_boxed_dyn(&call).then_some((call, value))
+}
+
+/// The `Box<dyn Trait>` type `name_ref` is the trait in.
+fn boxed_dyn_type(
+    sema: &Semantics<'_, RootDatabase>,
+    name_ref: &ast::NameRef,
+    box_: hir::Struct,
+) -> Option<ast::PathType> {
+    let path = full_path_of_name_ref(name_ref)?;
+    if path.segment()?.name_ref()? != *name_ref {
+        return None;
+    }
+    let bound = ast::TypeBound::cast(path.syntax().parent()?.parent()?)?;
+    let bounds = ast::TypeBoundList::cast(bound.syntax().parent()?)?;
+    let dyn_ty = ast::DynTraitType::cast(bounds.syntax().parent()?)?;
+    // The enum is `Send`, `'static` and so on if all implementors are.
+    if !bounds.bounds().all(|it| it == bound || is_auto_or_lifetime_bound(sema, &it)) {
+        return None;
+    }
+    let arg_list = ast::GenericArgList::cast(dyn_ty.syntax().parent()?.parent()?)?;
+    if arg_list.generic_args().count() != 1 || arg_list.coloncolon_token().is_some() {
+        return None;
+    }
+    let box_ty = ast::PathType::cast(arg_list.syntax().parent()?.parent()?.parent()?)?;
+    match sema.resolve_path(&box_ty.path()?)? {
+        PathResolution::Def(ModuleDef::Adt(hir::Adt::Struct(it))) if it == box_ => Some(box_ty),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn forwards_every_method() {
+        check_assist(
+            generate_enum_dispatch,
+            r#"
+//- minicore: future, send
+pub trait Handler$0: Send + 'static {
+    fn name(&self) -> &str;
+    fn handle(&mut self, (id, retries): (u32, u8), _: bool) -> bool;
+    fn into_id(self) -> u32;
+    fn parse<T: Default>(&self, input: &str) -> T;
+    async fn flush(&self);
+}
+
+mod handlers {
+    pub struct Log;
+    impl super::Handler for Log {
+        fn name(&self) -> &str { "log" }
+        fn handle(&mut self, _: (u32, u8), _: bool) -> bool { true }
+        fn into_id(self) -> u32 { 0 }
+        fn parse<T: Default>(&self, input: &str) -> T { T::default() }
+        async fn flush(&self) {}
+    }
+}
+"#,
+            r#"
+pub trait Handler: Send + 'static {
+    fn name(&self) -> &str;
+    fn handle(&mut self, (id, retries): (u32, u8), _: bool) -> bool;
+    fn into_id(self) -> u32;
+    fn parse<T: Default>(&self, input: &str) -> T;
+    async fn flush(&self);
+}
+
+pub enum AnyHandler {
+    Log(handlers::Log),
+}
+
+impl Handler for AnyHandler {
+    fn name(&self) -> &str {
+        match self {
+            AnyHandler::Log(it) => Handler::name(it),
+        }
+    }
+
+    fn handle(&mut self, arg0: (u32, u8), arg1: bool) -> bool {
+        match self {
+            AnyHandler::Log(it) => Handler::handle(it, arg0, arg1),
+        }
+    }
+
+    fn into_id(self) -> u32 {
+        match self {
+            AnyHandler::Log(it) => Handler::into_id(it),
+        }
+    }
+
+    fn parse<T: Default>(&self, input: &str) -> T {
+        match self {
+            AnyHandler::Log(it) => Handler::parse::<T>(it, input),
+        }
+    }
+
+    async fn flush(&self) {
+        match self {
+            AnyHandler::Log(it) => Handler::flush(it).await,
+        }
+    }
+}
+
+mod handlers {
+    pub struct Log;
+    impl super::Handler for Log {
+        fn name(&self) -> &str { "log" }
+        fn handle(&mut self, _: (u32, u8), _: bool) -> bool { true }
+        fn into_id(self) -> u32 { 0 }
+        fn parse<T: Default>(&self, input: &str) -> T { T::default() }
+        async fn flush(&self) {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_closed_set_of_implementors() {
+        check_assist_not_applicable(
+            generate_enum_dispatch,
+            r#"
+trait Handler$0 {
+    fn handle(&self);
+}
+"#,
+        );
+        check_assist_not_applicable(
+            generate_enum_dispatch,
+            r#"
+trait Handler$0 {
+    fn handle(&self);
+}
+struct Log;
+impl Handler for Log {
+    fn handle(&self) {}
+}
+impl<T> Handler for [T] {
+    fn handle(&self) {}
+}
+"#,
+        );
+        check_assist_not_applicable(
+            generate_enum_dispatch,
+            r#"
+trait Handler$0 {
+    type Output;
+    fn handle(&self) -> Self::Output;
+}
+struct Log;
+impl Handler for Log {
+    type Output = ();
+    fn handle(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replaces_boxes() {
+        check_assist(
+            convert_dyn_trait_to_enum,
+            r#"
+//- minicore: coerce_unsized, send
+//- /main.rs crate:main deps:alloc
+mod server;
+use alloc::boxed::Box;
+
+trait Handler {
+    fn handle(&self) -> bool;
+}
+
+struct Log;
+impl Handler for Log {
+    fn handle(&self) -> bool { true }
+}
+struct Quiet;
+impl Handler for Quiet {
+    fn handle(&self) -> bool { false }
+}
+
+fn pick(log: bool) -> Box<dyn Handler$0 + Send> {
+    if log {
+        Box::new(Log)
+    } else {
+        Box::new(Quiet) as Box<dyn Handler + Send>
+    }
+}
+//- /server.rs
+use alloc::boxed::Box;
+use crate::Handler;
+
+struct Server {
+    handler: Box<dyn Handler>,
+    other: Box<u32>,
+}
+
+fn server() -> Server {
+    let handler: Box<dyn Handler> = Box::new(crate::Log);
+    Server { handler, other: Box::new(0) }
+}
+//- /alloc.rs crate:alloc
+pub mod boxed {
+    use core::marker::Unsize;
+    use core::ops::CoerceUnsized;
+    pub struct Box<T: ?Sized>(*mut T);
+    impl<T> Box<T> {
+        pub fn new(value: T) -> Box<T> { loop {} }
+    }
+    impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+}
+"#,
+            r#"
+//- /main.rs
+mod server;
+use alloc::boxed::Box;
+
+trait Handler {
+    fn handle(&self) -> bool;
+}
+
+enum AnyHandler {
+    Log(Log),
+    Quiet(Quiet),
+}
+
+impl Handler for AnyHandler {
+    fn handle(&self) -> bool {
+        match self {
+            AnyHandler::Log(it) => Handler::handle(it),
+            AnyHandler::Quiet(it) => Handler::handle(it),
+        }
+    }
+}
+
+struct Log;
+impl Handler for Log {
+    fn handle(&self) -> bool { true }
+}
+struct Quiet;
+impl Handler for Quiet {
+    fn handle(&self) -> bool { false }
+}
+
+fn pick(log: bool) -> AnyHandler {
+    if log {
+        AnyHandler::Log(Log)
+    } else {
+        AnyHandler::Quiet(Quiet)
+    }
+}
+//- /server.rs
+use alloc::boxed::Box;
+use crate::{Handler, AnyHandler};
+
+struct Server {
+    handler: AnyHandler,
+    other: Box<u32>,
+}
+
+fn server() -> Server {
+    let handler: AnyHandler = AnyHandler::Log(crate::Log);
+    Server { handler, other: Box::new(0) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn conversion_not_offered_without_boxes() {
+        check_assist_not_applicable(
+            convert_dyn_trait_to_enum,
+            r#"
+trait Handler$0 {
+    fn handle(&self);
+}
+struct Log;
+impl Handler for Log {
+    fn handle(&self) {}
+}
+fn run(handler: &dyn Handler) {}
+"#,
+        );
+    }
+}
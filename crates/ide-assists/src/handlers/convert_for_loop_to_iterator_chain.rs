@@ -0,0 +1,1203 @@
+use hir::{HirDisplay, Semantics};
+use ide_db::{defs::Definition, famous_defs::FamousDefs, search::ReferenceCategory, RootDatabase};
+use stdx::format_to;
+use syntax::{
+    ast::{
+        self, edit::IndentLevel, edit_in_place::Indent, HasArgList, HasLoopBody, HasName,
+        LiteralKind,
+    },
+    ted, AstNode, SyntaxKind, SyntaxNode, TextSize,
+};
+
+use crate::{
+    handlers::convert_iter_for_each_to_for::{impls_core_iter, iterable_to_iterator},
+    utils::invert_boolean_expression,
+    AssistContext, AssistId, AssistKind, Assists,
+};
+
+// Assist: convert_for_loop_to_iterator_chain
+//
+// Converts a for loop that fills an accumulator into an iterator chain computing it.
+//
+// ```
+// # //- minicore: iterators, copy
+// fn main() {
+//     let mut total = 0;
+//     for$0 x in [1, 2, 3] {
+//         if x > 1 {
+//             total += x;
+//         }
+//     }
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let total: i32 = [1, 2, 3].into_iter().filter(|&x| x > 1).sum();
+// }
+// ```
+pub(crate) fn convert_for_loop_to_iterator_chain(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let for_loop = ctx.find_node_at_offset::<ast::ForExpr>()?;
+    let body = for_loop.loop_body()?;
+    if body.syntax().text_range().start() < ctx.offset() || for_loop.label().is_some() {
+        return None;
+    }
+    let pat = for_loop.pat()?;
+    let iterable = for_loop.iterable()?;
+
+    // The accumulator has to be declared right before the loop.
+    let loop_stmt = match for_loop.syntax().parent()? {
+        parent if ast::ExprStmt::can_cast(parent.kind()) => parent,
+        parent if ast::StmtList::can_cast(parent.kind()) => for_loop.syntax().clone(),
+        _ => return None,
+    };
+    let let_stmt = ast::LetStmt::cast(loop_stmt.prev_sibling()?)?;
+    if let_stmt.let_else().is_some() {
+        return None;
+    }
+    let ast::Pat::IdentPat(acc_pat) = let_stmt.pat()? else { return None };
+    if acc_pat.mut_token().is_none() || acc_pat.ref_token().is_some() || acc_pat.pat().is_some() {
+        return None;
+    }
+    let acc_name = acc_pat.name()?;
+    let acc_local = ctx.sema.to_def(&acc_pat)?;
+    let acc_ty = ctx.sema.type_of_pat(&let_stmt.pat()?)?.original;
+    let init = Init::new(ctx, &let_stmt.initializer()?, &acc_ty)?;
+
+    let (cond, effect, breaks) = loop_body_parts(&body)?;
+    let effect = Effect::new(&ctx.sema, &effect, acc_local)?;
+    let terminal = match (init, effect, &cond, breaks) {
+        (Init::EmptyVec, Effect::Push(item), _, false) => Terminal::Collect(item),
+        (Init::Zero, Effect::AddAssign(item), _, false) => {
+            if acc_ty.is_usize() && is_literal(&item, "1") {
+                Terminal::Count
+            } else {
+                Terminal::Sum(item)
+            }
+        }
+        (Init::Bool(false), Effect::Assign(value), Some(_), _) if is_literal(&value, "true") => {
+            Terminal::Any
+        }
+        (Init::Bool(true), Effect::Assign(value), Some(_), _) if is_literal(&value, "false") => {
+            Terminal::All
+        }
+        (Init::None, Effect::Assign(value), Some(_), true) => Terminal::Find(some_arg(&value)?),
+        _ => return None,
+    };
+    let exprs = cond.iter().chain(terminal.item());
+    if !exprs.into_iter().all(|it| can_move_into_closure(&ctx.sema, it, acc_local)) {
+        return None;
+    }
+
+    let item_ty = ctx.sema.type_of_pat(&pat)?.original;
+    let ref_param = match &cond {
+        Some(cond) if !matches!(terminal, Terminal::Any | Terminal::All) => {
+            Some(ref_closure_param(&ctx.sema, &pat, &item_ty, cond)?)
+        }
+        _ => None,
+    };
+
+    let module = ctx.sema.scope(let_stmt.syntax())?.module();
+    let ty = match (&terminal, let_stmt.ty()) {
+        (_, Some(ty)) => Some(ty.to_string()),
+        (Terminal::Collect(_), None) => Some("Vec<_>".to_string()),
+        (Terminal::Sum(_), None) => {
+            Some(acc_ty.display_source_code(ctx.db(), module.into(), false).ok()?)
+        }
+        _ => None,
+    };
+
+    let range = let_stmt.syntax().text_range().cover(loop_stmt.text_range());
+    acc.add(
+        AssistId("convert_for_loop_to_iterator_chain", AssistKind::RefactorRewrite),
+        "Convert for loop into iterator chain",
+        range,
+        |builder| {
+            let mut chain = iterable_to_iterator(&ctx.sema, &iterable);
+            match (&terminal, &cond) {
+                (Terminal::Any, Some(cond)) => format_to!(chain, ".any(|{pat}| {cond})"),
+                (Terminal::All, Some(cond)) => {
+                    format_to!(chain, ".all(|{pat}| {})", invert_boolean_expression(cond.clone()))
+                }
+                (Terminal::Find(item), Some(cond)) if is_pat_binding(&ctx.sema, &pat, item) => {
+                    format_to!(chain, ".find(|{}| {cond})", ref_param.as_deref().unwrap_or("_"))
+                }
+                _ => {
+                    if let (Some(cond), Some(param)) = (&cond, &ref_param) {
+                        format_to!(chain, ".filter(|{param}| {cond})");
+                    }
+                    if let Some(item) = terminal.item() {
+                        if !is_pat_binding(&ctx.sema, &pat, item) {
+                            format_to!(chain, ".map(|{pat}| {item})");
+                        }
+                    }
+                    chain.push_str(match terminal {
+                        Terminal::Collect(_) => ".collect()",
+                        Terminal::Sum(_) => ".sum()",
+                        Terminal::Count => ".count()",
+                        _ => ".next()",
+                    });
+                }
+            }
+
+            let mut_kw =
+                if is_mutated_after(&ctx.sema, acc_local, range.end()) { "mut " } else { "" };
+            let ty = ty.map_or(String::new(), |ty| format!(": {ty}"));
+            builder.replace(range, format!("let {mut_kw}{acc_name}{ty} = {chain};"));
+        },
+    )
+}
+
+// Assist: convert_iterator_chain_to_for_loop
+//
+// Expands an iterator chain computing a value into a for loop filling an accumulator.
+//
+// ```
+// # //- minicore: iterators
+// fn main() {
+//     let found = [1, 2, 3].into_iter().any$0(|x| x > 2);
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let mut found = false;
+//     for x in [1, 2, 3] {
+//         if x > 2 {
+//             found = true;
+//             break;
+//         }
+//     }
+// }
+// ```
+pub(crate) fn convert_iterator_chain_to_for_loop(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let call = ctx.find_node_at_offset::<ast::MethodCallExpr>()?;
+    let name_ref = call.name_ref()?;
+    if !name_ref.syntax().text_range().contains_range(ctx.selection_trimmed()) {
+        return None;
+    }
+    let let_stmt = ast::LetStmt::cast(call.syntax().parent()?)?;
+    if let_stmt.initializer()?.syntax() != call.syntax() || let_stmt.let_else().is_some() {
+        return None;
+    }
+    let ast::Pat::IdentPat(binding) = let_stmt.pat()? else { return None };
+    if binding.ref_token().is_some() || binding.pat().is_some() {
+        return None;
+    }
+    let acc_name = binding.name()?;
+
+    let has_args = call.arg_list()?.args().next().is_some();
+    let terminal = match name_ref.text().as_str() {
+        "collect" if !has_args => ChainTerminal::Collect,
+        "sum" if !has_args => ChainTerminal::Sum,
+        "count" if !has_args => ChainTerminal::Count,
+        "any" => ChainTerminal::Any(closure_arg(&call)?),
+        "all" => ChainTerminal::All(closure_arg(&call)?),
+        "find" => ChainTerminal::Find(closure_arg(&call)?),
+        _ => return None,
+    };
+
+    let mut stages = Vec::new();
+    let mut receiver = call.receiver()?;
+    while let ast::Expr::MethodCallExpr(adapter) = &receiver {
+        let stage = match adapter.name_ref()?.text().as_str() {
+            "filter" => Stage::Filter(closure_arg(adapter)?),
+            "map" => Stage::Map(closure_arg(adapter)?),
+            _ => break,
+        };
+        stages.push(stage);
+        receiver = adapter.receiver()?;
+    }
+    stages.reverse();
+    if !impls_core_iter(&ctx.sema, &receiver) {
+        return None;
+    }
+
+    // The accumulator is initialized with the value the chain computes for an empty iterator.
+    let turbofish = call.generic_arg_list().and_then(|it| match it.generic_args().next()? {
+        ast::GenericArg::TypeArg(arg) => arg.ty(),
+        _ => None,
+    });
+    let declared_ty = let_stmt.ty().or(turbofish);
+    let resolved_ty = declared_ty.as_ref().and_then(|ty| ctx.sema.resolve_type(ty));
+    let (ty, init) = match &terminal {
+        ChainTerminal::Collect => {
+            if !is_vec(ctx, call.syntax(), &resolved_ty?) {
+                return None;
+            }
+            (declared_ty.map(|it| it.to_string()), "Vec::new()")
+        }
+        ChainTerminal::Sum => {
+            let ty =
+                resolved_ty.or_else(|| Some(ctx.sema.type_of_pat(&let_stmt.pat()?)?.original))?;
+            let init = if ty.is_float() {
+                "0.0"
+            } else if ty.is_int_or_uint() {
+                "0"
+            } else {
+                return None;
+            };
+            let module = ctx.sema.scope(let_stmt.syntax())?.module();
+            (Some(ty.display_source_code(ctx.db(), module.into(), false).ok()?), init)
+        }
+        ChainTerminal::Count => {
+            (Some(let_stmt.ty().map_or("usize".to_string(), |it| it.to_string())), "0")
+        }
+        ChainTerminal::Any(_) => (let_stmt.ty().map(|it| it.to_string()), "false"),
+        ChainTerminal::All(_) => (let_stmt.ty().map(|it| it.to_string()), "true"),
+        ChainTerminal::Find(_) => (let_stmt.ty().map(|it| it.to_string()), "None"),
+    };
+
+    let indent = let_stmt.indent_level();
+    let mut loop_ = LoopBuilder {
+        sema: &ctx.sema,
+        indent,
+        depth: 1,
+        item: None,
+        first_item: None,
+        body: String::new(),
+    };
+    for (idx, stage) in stages.iter().enumerate() {
+        match stage {
+            Stage::Filter(closure) => {
+                let (pat, cond) = loop_.by_value(closure)?;
+                loop_.bind(pat)?;
+                loop_.open(&format!("if {cond} {{"));
+            }
+            Stage::Map(closure) => {
+                loop_.bind(closure.param.clone())?;
+                let next = match stages.get(idx + 1) {
+                    Some(Stage::Filter(next)) => loop_.by_value(next)?.0,
+                    Some(Stage::Map(next)) => next.param.clone(),
+                    None => match &terminal {
+                        ChainTerminal::Any(next) | ChainTerminal::All(next) => next.param.clone(),
+                        ChainTerminal::Find(next) => loop_.by_value(next)?.0,
+                        ChainTerminal::Count => make_pat("_"),
+                        ChainTerminal::Collect | ChainTerminal::Sum => make_pat("item"),
+                    },
+                };
+                let value = loop_.render(&closure.body, &[]);
+                loop_.line(&format!("let {next} = {value};"));
+                loop_.item = Some(next);
+            }
+        }
+    }
+    match &terminal {
+        ChainTerminal::Collect | ChainTerminal::Sum => {
+            if loop_.item.is_none() {
+                loop_.bind(make_pat("item"))?;
+            }
+            let item = loop_.item_name()?;
+            if let ChainTerminal::Collect = terminal {
+                loop_.line(&format!("{acc_name}.push({item});"));
+            } else {
+                loop_.line(&format!("{acc_name} += {item};"));
+            }
+        }
+        ChainTerminal::Count => {
+            if loop_.item.is_none() {
+                loop_.bind(make_pat("_"))?;
+            }
+            loop_.line(&format!("{acc_name} += 1;"));
+        }
+        ChainTerminal::Any(closure) | ChainTerminal::All(closure) => {
+            loop_.bind(closure.param.clone())?;
+            let cond = loop_.render(&closure.body, &[]);
+            let (cond, value) = match terminal {
+                ChainTerminal::Any(_) => (cond, "true"),
+                _ => (invert_boolean_expression(parse_expr(&cond)?).to_string(), "false"),
+            };
+            loop_.open(&format!("if {cond} {{"));
+            loop_.line(&format!("{acc_name} = {value};"));
+            loop_.line("break;");
+        }
+        ChainTerminal::Find(closure) => {
+            let (pat, cond) = loop_.by_value(closure)?;
+            loop_.bind(pat)?;
+            let item = loop_.item_name()?;
+            loop_.open(&format!("if {cond} {{"));
+            loop_.line(&format!("{acc_name} = Some({item});"));
+            loop_.line("break;");
+        }
+    }
+    while loop_.depth > 1 {
+        loop_.close();
+    }
+    let loop_pat = loop_.first_item?;
+    let iterable = loop_iterable(&receiver);
+
+    acc.add(
+        AssistId("convert_iterator_chain_to_for_loop", AssistKind::RefactorRewrite),
+        "Convert iterator chain into for loop",
+        let_stmt.syntax().text_range(),
+        |builder| {
+            let ty = ty.map_or(String::new(), |ty| format!(": {ty}"));
+            let text = format!(
+                "let mut {acc_name}{ty} = {init};\n{indent}for {loop_pat} in {iterable} {{\n{}{indent}}}",
+                loop_.body
+            );
+            builder.replace(let_stmt.syntax().text_range(), text);
+        },
+    )
+}
+
+/// How the accumulator of a loop starts out.
+enum Init {
+    EmptyVec,
+    Zero,
+    Bool(bool),
+    None,
+}
+
+impl Init {
+    fn new(ctx: &AssistContext<'_>, init: &ast::Expr, ty: &hir::Type) -> Option<Init> {
+        let init = match init {
+            ast::Expr::CallExpr(call) => {
+                let ast::Expr::PathExpr(callee) = call.expr()? else { return None };
+                let segment = callee.path()?.segment()?;
+                if segment.name_ref()?.text() != "new" || call.arg_list()?.args().next().is_some() {
+                    return None;
+                }
+                if !is_vec(ctx, call.syntax(), ty) {
+                    return None;
+                }
+                Init::EmptyVec
+            }
+            ast::Expr::MacroExpr(mac) => {
+                let mac = mac.macro_call()?;
+                if mac.path()?.as_single_name_ref()?.text() != "vec"
+                    || mac.token_tree()?.token_trees_and_tokens().count() != 2
+                {
+                    return None;
+                }
+                Init::EmptyVec
+            }
+            ast::Expr::Literal(lit) => match lit.kind() {
+                LiteralKind::IntNumber(num) if num.value()? == 0 => Init::Zero,
+                LiteralKind::FloatNumber(num) if num.value()? == 0.0 => Init::Zero,
+                LiteralKind::Bool(value) if ty.is_bool() => Init::Bool(value),
+                _ => return None,
+            },
+            ast::Expr::PathExpr(path) => {
+                let famous_defs = FamousDefs(&ctx.sema, ctx.sema.scope(path.syntax())?.krate());
+                let option = famous_defs.core_option_Option()?;
+                if path.path()?.as_single_name_ref()?.text() != "None"
+                    || ty.as_adt() != Some(hir::Adt::Enum(option))
+                {
+                    return None;
+                }
+                Init::None
+            }
+            _ => return None,
+        };
+        if matches!(init, Init::Zero) && !(ty.is_int_or_uint() || ty.is_float()) {
+            return None;
+        }
+        Some(init)
+    }
+}
+
+/// What the loop does with the accumulator.
+enum Effect {
+    Push(ast::Expr),
+    AddAssign(ast::Expr),
+    Assign(ast::Expr),
+}
+
+impl Effect {
+    fn new(
+        sema: &Semantics<'_, RootDatabase>,
+        expr: &ast::Expr,
+        acc: hir::Local,
+    ) -> Option<Effect> {
+        match expr {
+            ast::Expr::MethodCallExpr(call) => {
+                if !is_local(sema, &call.receiver()?, acc) || call.name_ref()?.text() != "push" {
+                    return None;
+                }
+                let mut args = call.arg_list()?.args();
+                match (args.next(), args.next()) {
+                    (Some(item), None) => Some(Effect::Push(item)),
+                    _ => None,
+                }
+            }
+            ast::Expr::BinExpr(bin) => {
+                if !is_local(sema, &bin.lhs()?, acc) {
+                    return None;
+                }
+                match bin.op_kind()? {
+                    ast::BinaryOp::Assignment { op: None } => Some(Effect::Assign(bin.rhs()?)),
+                    ast::BinaryOp::Assignment { op: Some(ast::ArithOp::Add) } => {
+                        Some(Effect::AddAssign(bin.rhs()?))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The iterator method computing the value of the accumulator.
+enum Terminal {
+    Collect(ast::Expr),
+    Sum(ast::Expr),
+    Count,
+    Any,
+    All,
+    Find(ast::Expr),
+}
+
+impl Terminal {
+    /// The value each matching item gets mapped to.
+    fn item(&self) -> Option<&ast::Expr> {
+        match self {
+            Terminal::Collect(item) | Terminal::Sum(item) | Terminal::Find(item) => Some(item),
+            Terminal::Count | Terminal::Any | Terminal::All => None,
+        }
+    }
+}
+
+/// Splits the body of the loop into an optional condition, the statement updating the accumulator
+/// and whether the loop is left once the condition holds.
+fn loop_body_parts(body: &ast::BlockExpr) -> Option<(Option<ast::Expr>, ast::Expr, bool)> {
+    match block_exprs(body)?.as_slice() {
+        [ast::Expr::IfExpr(if_expr)] => {
+            if if_expr.else_branch().is_some() {
+                return None;
+            }
+            let cond = if_expr.condition()?;
+            if cond.syntax().descendants().any(|it| it.kind() == SyntaxKind::LET_EXPR) {
+                return None;
+            }
+            match block_exprs(&if_expr.then_branch()?)?.as_slice() {
+                [effect] => Some((Some(cond), effect.clone(), false)),
+                [effect, ast::Expr::BreakExpr(break_)]
+                    if break_.lifetime().is_none() && break_.expr().is_none() =>
+                {
+                    Some((Some(cond), effect.clone(), true))
+                }
+                _ => None,
+            }
+        }
+        [effect] => Some((None, effect.clone(), false)),
+        _ => None,
+    }
+}
+
+/// Whether `ty` is the `Vec` of the standard library.
+fn is_vec(ctx: &AssistContext<'_>, node: &SyntaxNode, ty: &hir::Type) -> bool {
+    let Some(scope) = ctx.sema.scope(node) else { return false };
+    let vec = FamousDefs(&ctx.sema, scope.krate()).alloc_vec_Vec();
+    vec.is_some() && ty.as_adt() == vec.map(hir::Adt::Struct)
+}
+
+fn block_exprs(block: &ast::BlockExpr) -> Option<Vec<ast::Expr>> {
+    if block.modifier().is_some() {
+        return None;
+    }
+    let stmt_list = block.stmt_list()?;
+    let mut exprs = stmt_list
+        .statements()
+        .map(|stmt| match stmt {
+            ast::Stmt::ExprStmt(stmt) => stmt.expr(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    exprs.extend(stmt_list.tail_expr());
+    Some(exprs)
+}
+
+fn is_literal(expr: &ast::Expr, text: &str) -> bool {
+    matches!(expr, ast::Expr::Literal(lit) if lit.syntax().text() == text)
+}
+
+/// `x` for `Some(x)`.
+fn some_arg(expr: &ast::Expr) -> Option<ast::Expr> {
+    let ast::Expr::CallExpr(call) = expr else { return None };
+    let ast::Expr::PathExpr(callee) = call.expr()? else { return None };
+    if callee.path()?.as_single_name_ref()?.text() != "Some" {
+        return None;
+    }
+    let mut args = call.arg_list()?.args();
+    match (args.next(), args.next()) {
+        (Some(arg), None) => Some(arg),
+        _ => None,
+    }
+}
+
+fn is_local(sema: &Semantics<'_, RootDatabase>, expr: &ast::Expr, local: hir::Local) -> bool {
+    let ast::Expr::PathExpr(path_expr) = expr else { return false };
+    matches!(
+        path_expr.path().and_then(|path| sema.resolve_path(&path)),
+        Some(hir::PathResolution::Local(it)) if it == local
+    )
+}
+
+fn local_uses<'a>(
+    sema: &'a Semantics<'_, RootDatabase>,
+    expr: &ast::Expr,
+    local: hir::Local,
+) -> impl Iterator<Item = ast::Expr> + 'a {
+    expr.syntax()
+        .descendants()
+        .filter_map(ast::Expr::cast)
+        .filter(move |it| matches!(it, ast::Expr::PathExpr(_)) && is_local(sema, it, local))
+}
+
+/// Whether `expr` is the value bound by the (identifier) pattern `pat`.
+fn is_pat_binding(sema: &Semantics<'_, RootDatabase>, pat: &ast::Pat, expr: &ast::Expr) -> bool {
+    let ast::Pat::IdentPat(ident) = pat else { return false };
+    sema.to_def(ident).map_or(false, |local| is_local(sema, expr, local))
+}
+
+/// Control flow can't leave a closure and the accumulator can't be used while it is computed.
+fn can_move_into_closure(
+    sema: &Semantics<'_, RootDatabase>,
+    expr: &ast::Expr,
+    acc: hir::Local,
+) -> bool {
+    let leaves = expr.syntax().descendants().any(|it| {
+        matches!(
+            it.kind(),
+            SyntaxKind::RETURN_EXPR
+                | SyntaxKind::BREAK_EXPR
+                | SyntaxKind::CONTINUE_EXPR
+                | SyntaxKind::TRY_EXPR
+                | SyntaxKind::AWAIT_EXPR
+                | SyntaxKind::YIELD_EXPR
+        )
+    });
+    !leaves && local_uses(sema, expr, acc).next().is_none()
+}
+
+/// The parameter of a `filter` or `find` closure, which gets a reference to the item the loop
+/// pattern binds.
+fn ref_closure_param(
+    sema: &Semantics<'_, RootDatabase>,
+    pat: &ast::Pat,
+    item_ty: &hir::Type,
+    cond: &ast::Expr,
+) -> Option<String> {
+    let binds_by_value = pat
+        .syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .all(|it| it.mut_token().is_none() && it.ref_token().is_none());
+    if binds_by_value && item_ty.is_copy(sema.db) {
+        return Some(format!("&{pat}"));
+    }
+    // Otherwise the condition has to work with a reference as well.
+    let ast::Pat::IdentPat(ident) = pat else { return None };
+    let local = sema.to_def(ident)?;
+    local_uses(sema, cond, local).all(|it| is_auto_deref(sema, &it)).then(|| pat.to_string())
+}
+
+/// Whether `expr` is used in a way that works the same for a shared reference to it.
+fn is_auto_deref(sema: &Semantics<'_, RootDatabase>, expr: &ast::Expr) -> bool {
+    match expr.syntax().parent().and_then(ast::Expr::cast) {
+        Some(ast::Expr::FieldExpr(field)) => field.expr().as_ref() == Some(expr),
+        Some(ast::Expr::MethodCallExpr(call)) => {
+            call.receiver().as_ref() == Some(expr)
+                && sema
+                    .resolve_method_call(&call)
+                    .and_then(|func| func.self_param(sema.db))
+                    .map_or(false, |param| param.access(sema.db) == hir::Access::Shared)
+        }
+        _ => false,
+    }
+}
+
+/// Whether the accumulator is still mutated after it has been computed.
+fn is_mutated_after(sema: &Semantics<'_, RootDatabase>, acc: hir::Local, offset: TextSize) -> bool {
+    let usages = Definition::Local(acc).usages(sema).all();
+    let mutated =
+        usages.iter().flat_map(|(_, refs)| refs).filter(|it| it.range.start() >= offset).any(
+            |it| {
+                if it.category == Some(ReferenceCategory::Write) {
+                    return true;
+                }
+                let Some(expr) = it.name.as_name_ref().and_then(|name_ref| {
+                    name_ref.syntax().ancestors().find_map(ast::PathExpr::cast)
+                }) else {
+                    return false;
+                };
+                let expr = ast::Expr::PathExpr(expr);
+                match expr.syntax().parent().and_then(ast::Expr::cast) {
+                    Some(ast::Expr::RefExpr(ref_expr)) => ref_expr.mut_token().is_some(),
+                    Some(ast::Expr::MethodCallExpr(call)) if call.receiver() == Some(expr) => sema
+                        .resolve_method_call(&call)
+                        .and_then(|func| func.self_param(sema.db))
+                        .map_or(true, |param| param.access(sema.db) == hir::Access::Exclusive),
+                    _ => false,
+                }
+            },
+        );
+    mutated
+}
+
+enum Stage {
+    Filter(Closure),
+    Map(Closure),
+}
+
+enum ChainTerminal {
+    Collect,
+    Sum,
+    Count,
+    Any(Closure),
+    All(Closure),
+    Find(Closure),
+}
+
+struct Closure {
+    param: ast::Pat,
+    body: ast::Expr,
+}
+
+fn closure_arg(call: &ast::MethodCallExpr) -> Option<Closure> {
+    let mut args = call.arg_list()?.args();
+    let (Some(ast::Expr::ClosureExpr(closure)), None) = (args.next(), args.next()) else {
+        return None;
+    };
+    if closure.async_token().is_some() {
+        return None;
+    }
+    let mut params = closure.param_list()?.params();
+    let (Some(param), None) = (params.next(), params.next()) else { return None };
+    let body = closure.body()?;
+    // `return` and `?` in the body would leave the enclosing function inside of the loop.
+    if body
+        .syntax()
+        .descendants()
+        .any(|it| matches!(it.kind(), SyntaxKind::RETURN_EXPR | SyntaxKind::TRY_EXPR))
+    {
+        return None;
+    }
+    Some(Closure { param: param.pat()?, body })
+}
+
+/// The expression to loop over for the head of an iterator chain.
+fn loop_iterable(receiver: &ast::Expr) -> String {
+    if let ast::Expr::MethodCallExpr(call) = receiver {
+        let no_args = call.arg_list().map_or(false, |it| it.args().next().is_none());
+        if let (true, None, Some(name), Some(inner)) =
+            (no_args, call.generic_arg_list(), call.name_ref(), call.receiver())
+        {
+            let is_place = matches!(
+                inner,
+                ast::Expr::PathExpr(_) | ast::Expr::FieldExpr(_) | ast::Expr::IndexExpr(_)
+            );
+            match name.text().as_str() {
+                "iter" if is_place => return format!("&{inner}"),
+                "iter_mut" if is_place => return format!("&mut {inner}"),
+                "into_iter" => {
+                    return match inner {
+                        ast::Expr::ParenExpr(paren) => {
+                            paren.expr().map_or_else(|| paren.to_string(), |it| it.to_string())
+                        }
+                        _ => inner.to_string(),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    receiver.to_string()
+}
+
+fn make_pat(text: &str) -> ast::Pat {
+    let file = ast::SourceFile::parse(&format!("fn f({text}: ()) {{}}")).tree();
+    file.syntax().descendants().find_map(ast::Pat::cast).unwrap()
+}
+
+fn parse_expr(text: &str) -> Option<ast::Expr> {
+    let file = ast::SourceFile::parse(&format!("const _: () = {text};")).tree();
+    file.syntax().descendants().find_map(ast::Expr::cast)
+}
+
+/// Writes the body of the loop an iterator chain expands to.
+struct LoopBuilder<'a, 'db> {
+    sema: &'a Semantics<'db, RootDatabase>,
+    indent: IndentLevel,
+    depth: u8,
+    /// The pattern binding the current item.
+    item: Option<ast::Pat>,
+    first_item: Option<ast::Pat>,
+    body: String,
+}
+
+impl LoopBuilder<'_, '_> {
+    fn line(&mut self, text: &str) {
+        let indent = self.indent + self.depth;
+        format_to!(self.body, "{indent}{text}\n");
+    }
+
+    fn open(&mut self, text: &str) {
+        self.line(text);
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    /// Binds the current item to `pat`.
+    fn bind(&mut self, pat: ast::Pat) -> Option<()> {
+        match &self.item {
+            None => self.first_item = Some(pat.clone()),
+            Some(item) if item.syntax().text() == pat.syntax().text() => return Some(()),
+            Some(_) => {
+                let item = self.item_name()?;
+                self.line(&format!("let {pat} = {item};"));
+            }
+        }
+        self.item = Some(pat);
+        Some(())
+    }
+
+    fn item_name(&self) -> Option<String> {
+        match self.item.as_ref()? {
+            ast::Pat::IdentPat(ident) if ident.pat().is_none() && ident.ref_token().is_none() => {
+                Some(ident.name()?.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// The pattern binding the item by value and the body of a closure taking a reference to the
+    /// item, like the ones of `filter` and `find`.
+    fn by_value(&self, closure: &Closure) -> Option<(ast::Pat, String)> {
+        match &closure.param {
+            ast::Pat::RefPat(ref_pat) if ref_pat.mut_token().is_none() => {
+                Some((ref_pat.pat()?, self.render(&closure.body, &[])))
+            }
+            ast::Pat::IdentPat(ident)
+                if ident.pat().is_none()
+                    && ident.ref_token().is_none()
+                    && ident.mut_token().is_none() =>
+            {
+                // Explicit dereferences of the parameter are dropped, other uses have to work with
+                // the item itself as well.
+                let local = self.sema.to_def(ident)?;
+                let mut derefs = Vec::new();
+                for use_ in local_uses(self.sema, &closure.body, local) {
+                    match use_.syntax().parent().and_then(ast::PrefixExpr::cast) {
+                        Some(prefix) if prefix.op_kind() == Some(ast::UnaryOp::Deref) => {
+                            derefs.push(prefix)
+                        }
+                        _ if is_auto_deref(self.sema, &use_) => {}
+                        _ => return None,
+                    }
+                }
+                Some((closure.param.clone(), self.render(&closure.body, &derefs)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders a closure body at the current depth, with `derefs` replaced by their operands.
+    fn render(&self, body: &ast::Expr, derefs: &[ast::PrefixExpr]) -> String {
+        if let Some(deref) = derefs.iter().find(|it| it.syntax() == body.syntax()) {
+            return deref.expr().map_or_else(String::new, |it| self.render(&it, derefs));
+        }
+        let start = body.syntax().text_range().start();
+        let body_indent = IndentLevel::from_node(body.syntax());
+        let body = ast::Expr::cast(body.syntax().clone_subtree().clone_for_update()).unwrap();
+        let targets: Vec<_> = derefs
+            .iter()
+            .filter_map(|deref| {
+                let range = deref.syntax().text_range().checked_sub(start)?;
+                let node = body.syntax().descendants().find(|it| it.text_range() == range)?;
+                Some((ast::PrefixExpr::cast(node)?, deref.expr()?))
+            })
+            .collect();
+        for (target, operand) in targets {
+            let operand = operand.syntax().clone_subtree().clone_for_update();
+            ted::replace(target.syntax(), operand);
+        }
+        body.dedent(body_indent);
+        body.indent(self.indent + self.depth);
+        body.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn collect_with_filter_and_map() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators, copy
+//- /main.rs crate:main deps:alloc
+use alloc::vec::Vec;
+fn is_even(x: u32) -> bool { true }
+fn double(x: u32) -> u32 { x }
+
+fn f() {
+    let mut evens = Vec::new();
+    for$0 x in [1, 2, 3] {
+        if is_even(x) {
+            evens.push(double(x));
+        }
+    }
+    drop(evens);
+}
+//- /alloc.rs crate:alloc
+pub mod vec {
+    pub struct Vec<T>(T);
+    impl<T> Vec<T> {
+        pub fn new() -> Self { loop {} }
+        pub fn push(&mut self, value: T) {}
+    }
+}
+"#,
+            r#"
+use alloc::vec::Vec;
+fn is_even(x: u32) -> bool { true }
+fn double(x: u32) -> u32 { x }
+
+fn f() {
+    let evens: Vec<_> = [1, 2, 3].into_iter().filter(|&x| is_even(x)).map(|x| double(x)).collect();
+    drop(evens);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_mut_if_mutated_later() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators
+//- /main.rs crate:main deps:alloc
+use alloc::vec::Vec;
+
+fn f(items: [u32; 3]) {
+    let mut all = Vec::new();
+    for$0 x in items {
+        all.push(x);
+    }
+    all.push(0);
+}
+//- /alloc.rs crate:alloc
+pub mod vec {
+    pub struct Vec<T>(T);
+    impl<T> Vec<T> {
+        pub fn new() -> Self { loop {} }
+        pub fn push(&mut self, value: T) {}
+    }
+}
+"#,
+            r#"
+use alloc::vec::Vec;
+
+fn f(items: [u32; 3]) {
+    let mut all: Vec<_> = items.into_iter().collect();
+    all.push(0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn count_and_find() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators, copy
+fn f(items: [u32; 3]) {
+    let mut n: usize = 0;
+    for$0 x in items {
+        if x > 1 {
+            n += 1;
+        }
+    }
+}
+"#,
+            r#"
+fn f(items: [u32; 3]) {
+    let n: usize = items.into_iter().filter(|&x| x > 1).count();
+}
+"#,
+        );
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators, copy, option
+fn f(items: [u32; 3]) {
+    let mut found = None;
+    for$0 x in items {
+        if x > 1 {
+            found = Some(x);
+            break;
+        }
+    }
+}
+"#,
+            r#"
+fn f(items: [u32; 3]) {
+    let found = items.into_iter().find(|&x| x > 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn all_with_reference_param() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators
+struct S;
+impl S {
+    fn is_valid(&self) -> bool { true }
+}
+
+fn f(items: [S; 3]) {
+    let mut valid = true;
+    for$0 s in items {
+        if !s.is_valid() {
+            valid = false;
+        }
+    }
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn is_valid(&self) -> bool { true }
+}
+
+fn f(items: [S; 3]) {
+    let valid = items.into_iter().all(|s| s.is_valid());
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_loops_with_other_effects() {
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators
+fn f(items: [u32; 3]) {
+    let mut total = 0;
+    for$0 x in items {
+        if x > 1 {
+            total += x;
+            break;
+        }
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators
+fn f(items: [u32; 3]) -> u32 {
+    let mut total = 0;
+    for$0 x in items {
+        total += if x > 1 { x } else { return 0 };
+    }
+    total
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_other_vec_types() {
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators
+struct Vec<T>(T);
+impl<T> Vec<T> {
+    fn new() -> Self { loop {} }
+    fn push(&mut self, value: T) {}
+}
+
+fn f(items: [u32; 3]) {
+    let mut all = Vec::new();
+    for$0 x in items {
+        all.push(x);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_if_filter_needs_owned_item() {
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r#"
+//- minicore: iterators, option
+struct S;
+fn check(s: S) -> bool { true }
+
+fn f(items: [S; 3]) {
+    let mut found = None;
+    for$0 s in items {
+        if check(s) {
+            found = Some(1);
+            break;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn expand_filter_map_collect() {
+        check_assist(
+            convert_iterator_chain_to_for_loop,
+            r#"
+//- minicore: iterators
+//- /main.rs crate:main deps:alloc
+use alloc::vec::Vec;
+
+fn f(items: [u32; 3]) {
+    let doubled: Vec<u32> = items.into_iter().filter(|x| *x > 1).map(|y| y + y).collect$0();
+}
+//- /alloc.rs crate:alloc
+pub mod vec {
+    pub struct Vec<T>(T);
+    impl<T> Vec<T> {
+        pub fn new() -> Self { loop {} }
+        pub fn push(&mut self, value: T) {}
+    }
+}
+"#,
+            r#"
+use alloc::vec::Vec;
+
+fn f(items: [u32; 3]) {
+    let mut doubled: Vec<u32> = Vec::new();
+    for x in items {
+        if x > 1 {
+            let y = x;
+            let item = y + y;
+            doubled.push(item);
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn expand_sum_and_all() {
+        check_assist(
+            convert_iterator_chain_to_for_loop,
+            r#"
+//- minicore: iterators
+fn f(items: [u32; 3]) {
+    let total = items.into_iter().map(|x| x + 1).sum$0::<u64>();
+}
+"#,
+            r#"
+fn f(items: [u32; 3]) {
+    let mut total: u64 = 0;
+    for x in items {
+        let item = x + 1;
+        total += item;
+    }
+}
+"#,
+        );
+        check_assist(
+            convert_iterator_chain_to_for_loop,
+            r#"
+//- minicore: iterators
+fn f(items: [u32; 3]) {
+    let small = items.into_iter().all$0(|x| x < 10);
+}
+"#,
+            r#"
+fn f(items: [u32; 3]) {
+    let mut small = true;
+    for x in items {
+        if x >= 10 {
+            small = false;
+            break;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn expand_find_with_ref_pattern() {
+        check_assist(
+            convert_iterator_chain_to_for_loop,
+            r#"
+//- minicore: iterators, option
+fn f(items: [u32; 3]) {
+    let first = items.into_iter().find$0(|&x| x > 1);
+}
+"#,
+            r#"
+fn f(items: [u32; 3]) {
+    let mut first = None;
+    for x in items {
+        if x > 1 {
+            first = Some(x);
+            break;
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_non_iterators() {
+        check_assist_not_applicable(
+            convert_iterator_chain_to_for_loop,
+            r#"
+struct S;
+impl S {
+    fn any(&self, f: impl Fn(u32) -> bool) -> bool { true }
+}
+
+fn f(s: S) {
+    let found = s.any$0(|x| x > 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_collecting_into_other_types() {
+        check_assist_not_applicable(
+            convert_iterator_chain_to_for_loop,
+            r#"
+//- minicore: iterators
+struct String;
+
+fn f(items: [char; 3]) {
+    let s: String = items.into_iter().collect$0();
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use hir::DescendPreference;
+use ide_db::syntax_helpers::{
+    format_string::is_format_string,
+    format_string_exprs::{parse_placeholders, PlaceholderArg},
+};
+use syntax::{
+    ast::{self, IsString},
+    hacks::parse_expr_from_str,
+    AstNode, AstToken,
+};
+
+use crate::{utils::format_string_args, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_format_to_to_string
+//
+// Replaces a `format!` of a single value with a `to_string` call.
+//
+// ```
+// # //- minicore: fmt
+// # //- /main.rs crate:main deps:alloc
+// # use alloc::{format, string::ToString};
+// fn main() {
+//     let n = 92;
+//     let s = format!$0("{}", n + 1);
+// }
+// # //- /alloc.rs crate:alloc
+// # pub mod string {
+// #     pub struct String;
+// #     pub trait ToString {
+// #         fn to_string(&self) -> String;
+// #     }
+// #     impl<T: ?Sized> ToString for T {
+// #         fn to_string(&self) -> String { String }
+// #     }
+// # }
+// # pub mod fmt {
+// #     pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+// # }
+// # #[macro_export]
+// # macro_rules! format {
+// #     ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+// # }
+// ```
+// ->
+// ```
+// # use alloc::{format, string::ToString};
+// fn main() {
+//     let n = 92;
+//     let s = (n + 1).to_string();
+// }
+// ```
+pub(crate) fn convert_format_to_to_string(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let macro_call = ctx.find_node_at_offset::<ast::MacroCall>()?;
+    if macro_call.path()?.segment()?.name_ref()?.text() != "format" {
+        return None;
+    }
+    let tt = macro_call.token_tree()?;
+    let fmt_string = tt
+        .token_trees_and_tokens()
+        .filter(|it| it.as_token().map_or(true, |it| !it.kind().is_trivia()))
+        // The first one is the opening delimiter.
+        .nth(1)?
+        .into_token()
+        .and_then(ast::String::cast)?;
+
+    let expanded_t = ast::String::cast(
+        ctx.sema
+            .descend_into_macros_single(DescendPreference::SameKind, fmt_string.syntax().clone()),
+    )?;
+    if !is_format_string(&expanded_t) {
+        return None;
+    }
+
+    // Only `"{}"` or `"{name}"` without format spec.
+    let text = fmt_string.text_without_quotes();
+    let placeholders = parse_placeholders(text).ok()?;
+    let [placeholder] = &placeholders[..] else { return None };
+    if placeholder.range.len() != text.len().try_into().ok()?
+        || placeholder.spec.as_ref().map_or(false, |it| !it.trim().is_empty())
+    {
+        return None;
+    }
+    let (args, _) = format_string_args(&tt, &fmt_string)?;
+    let value = match (&placeholder.arg, &args[..]) {
+        (PlaceholderArg::Next | PlaceholderArg::Index(0), [arg]) => arg.clone(),
+        (PlaceholderArg::Name(name), []) => name.clone(),
+        _ => return None,
+    };
+    let needs_parens = !matches!(
+        parse_expr_from_str(&value)?,
+        ast::Expr::PathExpr(_)
+            | ast::Expr::CallExpr(_)
+            | ast::Expr::MethodCallExpr(_)
+            | ast::Expr::FieldExpr(_)
+            | ast::Expr::IndexExpr(_)
+            | ast::Expr::Literal(_)
+            | ast::Expr::ParenExpr(_)
+            | ast::Expr::TupleExpr(_)
+            | ast::Expr::ArrayExpr(_)
+            | ast::Expr::MacroExpr(_)
+    );
+
+    acc.add(
+        AssistId("convert_format_to_to_string", AssistKind::RefactorRewrite),
+        "Replace `format!` with `to_string`",
+        macro_call.syntax().text_range(),
+        |edit| {
+            let receiver = if needs_parens { format!("({value})") } else { value };
+            edit.replace(macro_call.syntax().text_range(), format!("{receiver}.to_string()"));
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    const ALLOC: &str = r#"//- /alloc.rs crate:alloc
+pub mod string {
+    pub struct String;
+    pub trait ToString {
+        fn to_string(&self) -> String;
+    }
+    impl<T: ?Sized> ToString for T {
+        fn to_string(&self) -> String { String }
+    }
+}
+pub mod fmt {
+    pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+}
+#[macro_export]
+macro_rules! format {
+    ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+}
+"#;
+
+    #[test]
+    fn format_of_captured_name() {
+        check_assist(
+            convert_format_to_to_string,
+            &format!(
+                r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::ToString}};
+fn main() {{
+    let name = "x";
+    let s = format!("{{name}}"$0);
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::{format, string::ToString};
+fn main() {
+    let name = "x";
+    let s = name.to_string();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn format_of_method_call() {
+        check_assist(
+            convert_format_to_to_string,
+            &format!(
+                r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::ToString}};
+struct S;
+impl S {{ fn len(&self) -> usize {{ 0 }} }}
+fn main() {{
+    let s = S;
+    let s = format!$0("{{}}", s.len(),);
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::{format, string::ToString};
+struct S;
+impl S { fn len(&self) -> usize { 0 } }
+fn main() {
+    let s = S;
+    let s = s.len().to_string();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_format_spec_or_text() {
+        for fmt in [r#""{:?}", 1"#, r#""n: {}", 1"#, r#""{} {}", 1, 2"#] {
+            check_assist_not_applicable(
+                convert_format_to_to_string,
+                &format!(
+                    r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::format;
+fn main() {{
+    let s = format!$0({fmt});
+}}
+{ALLOC}"#
+                ),
+            );
+        }
+    }
+}
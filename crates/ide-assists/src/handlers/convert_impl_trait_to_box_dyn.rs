@@ -0,0 +1,461 @@
+use std::iter;
+
+use hir::ModuleDef;
+use ide_db::{
+    famous_defs::FamousDefs,
+    helpers::mod_path_to_ast,
+    syntax_helpers::node_ext::{for_each_tail_expr, walk_expr},
+};
+use syntax::{
+    ast::{self, make, Expr, HasArgList},
+    ted, AstNode,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_impl_trait_to_box_dyn
+//
+// Converts an `impl Trait` return type to `Box<dyn Trait>`, boxing every returned value.
+//
+// ```
+// # //- minicore: coerce_unsized
+// # //- /main.rs crate:main deps:alloc
+// # use alloc::boxed::Box;
+// trait Shape {}
+// struct Square;
+// impl Shape for Square {}
+//
+// fn unit() -> impl Shape$0 {
+//     Square
+// }
+// # //- /alloc.rs crate:alloc
+// # pub mod boxed {
+// #     use core::marker::Unsize;
+// #     use core::ops::CoerceUnsized;
+// #     pub struct Box<T: ?Sized>(*mut T);
+// #     impl<T> Box<T> {
+// #         pub fn new(value: T) -> Box<T> { loop {} }
+// #     }
+// #     impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+// # }
+// ```
+// ->
+// ```
+// # use alloc::boxed::Box;
+// trait Shape {}
+// struct Square;
+// impl Shape for Square {}
+//
+// fn unit() -> Box<dyn Shape> {
+//     Box::new(Square)
+// }
+// ```
+pub(crate) fn convert_impl_trait_to_box_dyn(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let (ret_type, body) = fn_ret_type_at_offset(ctx)?;
+
+    let type_ref = ret_type.ty()?;
+    let ast::Type::ImplTraitType(impl_ty) = &type_ref else { return None };
+    let bounds = impl_ty.type_bound_list()?;
+    // `impl Iterator<Item = impl Display>` has no `dyn` counterpart.
+    if bounds.syntax().descendants().any(|it| ast::ImplTraitType::can_cast(it.kind())) {
+        return None;
+    }
+
+    let scope = ctx.sema.scope(type_ref.syntax())?;
+    let box_ = FamousDefs(&ctx.sema, scope.krate()).alloc_boxed_Box()?;
+    let box_path = scope.module().find_use_path(
+        ctx.db(),
+        ModuleDef::Adt(hir::Adt::Struct(box_)),
+        ctx.config.prefer_no_std,
+        ctx.config.prefer_prelude,
+    )?;
+    let box_path = mod_path_to_ast(&box_path);
+
+    let exprs_to_wrap = returned_exprs(ctx, &body);
+
+    acc.add(
+        AssistId("convert_impl_trait_to_box_dyn", AssistKind::RefactorRewrite),
+        "Convert `impl Trait` to `Box<dyn Trait>`",
+        type_ref.syntax().text_range(),
+        |edit| {
+            let type_ref = edit.make_mut(type_ref.clone());
+            let exprs_to_wrap: Vec<_> =
+                exprs_to_wrap.into_iter().map(|it| edit.make_mut(it)).collect();
+
+            for expr in exprs_to_wrap {
+                let boxed = make::expr_call(
+                    make::expr_path(make::path_concat(
+                        box_path.clone(),
+                        make::ext::ident_path("new"),
+                    )),
+                    make::arg_list(iter::once(expr.clone())),
+                )
+                .clone_for_update();
+                ted::replace(expr.syntax(), boxed.syntax());
+            }
+
+            let new_ty = make::ty(&format!("{box_path}<dyn {bounds}>")).clone_for_update();
+            ted::replace(type_ref.syntax(), new_ty.syntax());
+        },
+    )
+}
+
+// Assist: convert_box_dyn_to_impl_trait
+//
+// Converts a `Box<dyn Trait>` return type to `impl Trait` when every returned box holds a value
+// of the same type.
+//
+// ```
+// # //- minicore: coerce_unsized
+// # //- /main.rs crate:main deps:alloc
+// # use alloc::boxed::Box;
+// trait Shape {}
+// struct Square;
+// impl Shape for Square {}
+//
+// fn unit() -> Box<dyn Shape>$0 {
+//     Box::new(Square)
+// }
+// # //- /alloc.rs crate:alloc
+// # pub mod boxed {
+// #     use core::marker::Unsize;
+// #     use core::ops::CoerceUnsized;
+// #     pub struct Box<T: ?Sized>(*mut T);
+// #     impl<T> Box<T> {
+// #         pub fn new(value: T) -> Box<T> { loop {} }
+// #     }
+// #     impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+// # }
+// ```
+// ->
+// ```
+// # use alloc::boxed::Box;
+// trait Shape {}
+// struct Square;
+// impl Shape for Square {}
+//
+// fn unit() -> impl Shape {
+//     Square
+// }
+// ```
+pub(crate) fn convert_box_dyn_to_impl_trait(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let (ret_type, body) = fn_ret_type_at_offset(ctx)?;
+
+    let type_ref = ret_type.ty()?;
+    let ast::Type::PathType(path_ty) = &type_ref else { return None };
+    let scope = ctx.sema.scope(type_ref.syntax())?;
+    let box_ = FamousDefs(&ctx.sema, scope.krate()).alloc_boxed_Box()?;
+    if ctx.sema.resolve_type(&type_ref)?.as_adt() != Some(hir::Adt::Struct(box_)) {
+        return None;
+    }
+    let generic_args = path_ty.path()?.segment()?.generic_arg_list()?;
+    let mut generic_args = generic_args.generic_args();
+    let Some(ast::GenericArg::TypeArg(arg)) = generic_args.next() else { return None };
+    if generic_args.next().is_some() {
+        return None;
+    }
+    let ast::Type::DynTraitType(dyn_ty) = arg.ty()? else { return None };
+    let bounds = dyn_ty.type_bound_list()?;
+
+    // Every returned value has to be a `Box::new` of the one type the `impl Trait` stands for.
+    let mut values = Vec::new();
+    for expr in returned_exprs(ctx, &body) {
+        let Expr::CallExpr(call) = &expr else { return None };
+        let Expr::PathExpr(callee) = call.expr()? else { return None };
+        if callee.path()?.segment()?.name_ref()?.text() != "new"
+            || ctx.sema.type_of_expr(&expr)?.original.as_adt() != Some(hir::Adt::Struct(box_))
+        {
+            return None;
+        }
+        let mut args = call.arg_list()?.args();
+        let value = args.next()?;
+        if args.next().is_some() {
+            return None;
+        }
+        values.push((call.clone(), value));
+    }
+    let (_, first) = values.first()?;
+    let value_ty = ctx.sema.type_of_expr(first)?.original;
+    if value_ty.is_unknown() {
+        return None;
+    }
+    for (_, value) in &values[1..] {
+        if ctx.sema.type_of_expr(value)?.original != value_ty {
+            return None;
+        }
+    }
+
+    acc.add(
+        AssistId("convert_box_dyn_to_impl_trait", AssistKind::RefactorRewrite),
+        "Convert `Box<dyn Trait>` to `impl Trait`",
+        type_ref.syntax().text_range(),
+        |edit| {
+            let type_ref = edit.make_mut(type_ref.clone());
+            let values: Vec<_> = values
+                .into_iter()
+                .map(|(call, value)| (edit.make_mut(call), edit.make_mut(value)))
+                .collect();
+
+            for (call, value) in values {
+                ted::replace(call.syntax(), value.syntax().clone_subtree().clone_for_update());
+            }
+
+            let new_ty = make::ty(&format!("impl {bounds}")).clone_for_update();
+            ted::replace(type_ref.syntax(), new_ty.syntax());
+        },
+    )
+}
+
+/// The function return type the cursor is in, and the function's body.
+fn fn_ret_type_at_offset(ctx: &AssistContext<'_>) -> Option<(ast::RetType, ast::BlockExpr)> {
+    // Not `find_node_at_offset::<ast::RetType>`, which finds the `-> i32` of `impl Fn() -> i32`.
+    let func = ctx.find_node_at_offset::<ast::Fn>()?;
+    let ret_type = func.ret_type()?;
+    if !ret_type.syntax().text_range().contains_range(ctx.selection_trimmed()) {
+        return None;
+    }
+    Some((ret_type, func.body()?))
+}
+
+/// The values `body` returns, through `return` or as its tail. Diverging expressions like `loop {}`
+/// return nothing and are left out.
+fn returned_exprs(ctx: &AssistContext<'_>, body: &ast::BlockExpr) -> Vec<ast::Expr> {
+    let body = Expr::BlockExpr(body.clone());
+    let mut exprs = Vec::new();
+    let tail_cb = &mut |e: &_| tail_cb_impl(&mut exprs, e);
+    walk_expr(&body, &mut |expr| {
+        if let Expr::ReturnExpr(ret_expr) = expr {
+            if let Some(ret_expr_arg) = &ret_expr.expr() {
+                for_each_tail_expr(ret_expr_arg, tail_cb);
+            }
+        }
+    });
+    for_each_tail_expr(&body, tail_cb);
+    exprs.retain(|it| ctx.sema.type_of_expr(it).map_or(true, |ty| !ty.original.is_never()));
+    exprs
+}
+
+fn tail_cb_impl(acc: &mut Vec<ast::Expr>, e: &ast::Expr) {
+    match e {
+        Expr::BreakExpr(break_expr) => {
+            if let Some(break_expr_arg) = break_expr.expr() {
+                for_each_tail_expr(&break_expr_arg, &mut |e| tail_cb_impl(acc, e))
+            }
+        }
+        Expr::ReturnExpr(_) => {
+            // all return expressions have already been handled by the walk loop
+        }
+        e => acc.push(e.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    const ALLOC: &str = r#"//- /alloc.rs crate:alloc
+pub mod boxed {
+    use core::marker::Unsize;
+    use core::ops::CoerceUnsized;
+    pub struct Box<T: ?Sized>(*mut T);
+    impl<T> Box<T> {
+        pub fn new(value: T) -> Box<T> { loop {} }
+    }
+    impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+}
+"#;
+
+    #[test]
+    fn impl_trait_to_box_dyn_wraps_returns_and_tails() {
+        check_assist(
+            convert_impl_trait_to_box_dyn,
+            &format!(
+                r#"
+//- minicore: coerce_unsized, send
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+
+trait Shape {{}}
+struct Square;
+impl Shape for Square {{}}
+
+fn pick(n: u32) -> impl Shape + Send$0 {{
+    if n == 0 {{
+        return Square;
+    }}
+    let f = || {{
+        return 1;
+    }};
+    match n {{
+        1 => Square,
+        2 => loop {{}},
+        _ => {{
+            let s = Square;
+            s
+        }}
+    }}
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::boxed::Box;
+
+trait Shape {}
+struct Square;
+impl Shape for Square {}
+
+fn pick(n: u32) -> Box<dyn Shape + Send> {
+    if n == 0 {
+        return Box::new(Square);
+    }
+    let f = || {
+        return 1;
+    };
+    match n {
+        1 => Box::new(Square),
+        2 => loop {},
+        _ => {
+            let s = Square;
+            Box::new(s)
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn impl_trait_to_box_dyn_uses_path_to_box() {
+        check_assist(
+            convert_impl_trait_to_box_dyn,
+            &format!(
+                r#"
+//- minicore: coerce_unsized, fn
+//- /main.rs crate:main deps:alloc
+fn adder(n: i32) -> impl Fn(i32) -> i32$0 {{
+    move |it| it + n
+}}
+{ALLOC}"#
+            ),
+            r#"
+fn adder(n: i32) -> alloc::boxed::Box<dyn Fn(i32) -> i32> {
+    alloc::boxed::Box::new(move |it| it + n)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn impl_trait_to_box_dyn_not_applicable_to_nested_impl_trait() {
+        check_assist_not_applicable(
+            convert_impl_trait_to_box_dyn,
+            &format!(
+                r#"
+//- minicore: coerce_unsized, iterator
+//- /main.rs crate:main deps:alloc
+trait Shape {{}}
+fn shapes() -> impl Iterator<Item = impl Shape>$0 {{
+    loop {{}}
+}}
+{ALLOC}"#
+            ),
+        );
+    }
+
+    #[test]
+    fn box_dyn_to_impl_trait_unwraps_boxes() {
+        check_assist(
+            convert_box_dyn_to_impl_trait,
+            &format!(
+                r#"
+//- minicore: coerce_unsized
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+
+trait Shape {{}}
+struct Square(u32);
+impl Shape for Square {{}}
+
+fn pick(n: u32) -> Box<dyn Shape$0> {{
+    if n == 0 {{
+        return Box::new(Square(0));
+    }}
+    let square = Square(n);
+    Box::new(square)
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::boxed::Box;
+
+trait Shape {}
+struct Square(u32);
+impl Shape for Square {}
+
+fn pick(n: u32) -> impl Shape {
+    if n == 0 {
+        return Square(0);
+    }
+    let square = Square(n);
+    square
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn box_dyn_to_impl_trait_not_applicable_to_different_types() {
+        check_assist_not_applicable(
+            convert_box_dyn_to_impl_trait,
+            &format!(
+                r#"
+//- minicore: coerce_unsized
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+
+trait Shape {{}}
+struct Square;
+impl Shape for Square {{}}
+struct Circle;
+impl Shape for Circle {{}}
+
+fn pick(square: bool) -> Box<dyn Shape$0> {{
+    if square {{
+        Box::new(Square)
+    }} else {{
+        Box::new(Circle)
+    }}
+}}
+{ALLOC}"#
+            ),
+        );
+    }
+
+    #[test]
+    fn box_dyn_to_impl_trait_not_applicable_to_existing_box() {
+        check_assist_not_applicable(
+            convert_box_dyn_to_impl_trait,
+            &format!(
+                r#"
+//- minicore: coerce_unsized
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+
+trait Shape {{}}
+
+fn forward(shape: Box<dyn Shape>) -> Box<dyn Shape$0> {{
+    shape
+}}
+{ALLOC}"#
+            ),
+        );
+    }
+}
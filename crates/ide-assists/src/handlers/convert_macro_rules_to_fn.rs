@@ -0,0 +1,646 @@
+use hir::{DescendPreference, HirDisplay, ModuleDef, ScopeDef};
+use ide_db::{base_db::FileId, defs::Definition, search::FileReferenceNode, FxHashMap};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasAttrs, HasName},
+    AstNode, NodeOrToken, SyntaxKind, SyntaxToken, TextRange, T,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_macro_rules_to_fn
+//
+// Converts a `macro_rules!` with a single arm taking only expressions into a function, with the
+// parameter types taken from its call sites, and turns the macro calls into function calls.
+// Parameters whose types differ between call sites become type parameters, bounded by the
+// operators the body applies to them.
+//
+// ```
+// # //- minicore: copy
+// macro_rules! $0square {
+//     ($x:expr) => {
+//         $x * $x
+//     };
+// }
+//
+// fn f(n: i32) -> i32 {
+//     square!(n + 1)
+// }
+// ```
+// ->
+// ```
+// fn square(x: i32) -> i32 {
+//     x * x
+// }
+//
+// fn f(n: i32) -> i32 {
+//     square(n + 1)
+// }
+// ```
+pub(crate) fn convert_macro_rules_to_fn(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let macro_rules: ast::MacroRules = ctx.find_node_at_offset()?;
+    let name = macro_rules.name()?;
+    // Only offered up to the name, the arms are likely to have assists of their own.
+    if ctx.offset() > name.syntax().text_range().end() {
+        return None;
+    }
+    // Exported macros can be used from other crates, which can't be rewritten here.
+    if macro_rules.attrs().any(|attr| attr.simple_name().as_deref() == Some("macro_export")) {
+        return None;
+    }
+    let arm = Arm::parse(&macro_rules.token_tree()?)?;
+    let def = ctx.sema.to_def(&ast::Macro::MacroRules(macro_rules.clone()))?;
+    let scope = ctx.sema.scope(macro_rules.syntax())?;
+    let module = scope.module().nearest_non_block_module(ctx.db());
+    let fn_name = name.text().to_string();
+    let mut taken = false;
+    scope.process_all_names(&mut |name, def| {
+        if name.to_smol_str() == fn_name.as_str()
+            && matches!(def, ScopeDef::ModuleDef(ModuleDef::Function(_)) | ScopeDef::Local(_))
+        {
+            taken = true;
+        }
+    });
+    if taken {
+        return None;
+    }
+
+    let display = |ty: &hir::Type| {
+        if ty.contains_unknown() {
+            return None;
+        }
+        ty.display_source_code(ctx.db(), module.into(), true).ok()
+    };
+    let mut calls: FxHashMap<FileId, Vec<(TextRange, String)>> = FxHashMap::default();
+    // The types of the parameters and the result at each call.
+    let mut param_tys: Vec<Vec<String>> = vec![Vec::new(); arm.params.len()];
+    let mut ret_tys: Vec<Option<String>> = Vec::new();
+    let mut needs_pub = false;
+    for (file_id, references) in Definition::Macro(def).usages(&ctx.sema).all() {
+        for reference in references {
+            let FileReferenceNode::NameRef(name_ref) = reference.name else { return None };
+            if ctx.sema.hir_file_for(name_ref.syntax()).is_macro() {
+                return None;
+            }
+            let macro_call = name_ref.syntax().ancestors().find_map(ast::MacroCall::cast)?;
+            let macro_expr = ast::MacroExpr::cast(macro_call.syntax().parent()?)?;
+            let token_tree = macro_call.token_tree()?;
+            let args = call_args(&token_tree)?;
+            if args.len() != arm.params.len() {
+                return None;
+            }
+            for ((arg, param), tys) in args.iter().zip(&arm.params).zip(&mut param_tys) {
+                let expr = expanded_arg(ctx, &macro_call, *arg)?;
+                let ty = ctx.sema.type_of_expr(&expr)?.original;
+                // The macro evaluates each use of a fragment on its own, the function would move
+                // the argument into the first one.
+                if arm.uses(param) > 1 && !ty.is_copy(ctx.db()) {
+                    return None;
+                }
+                tys.push(display(&ty)?);
+            }
+            let call_ret_ty = ctx.sema.type_of_expr(&macro_expr.into()).map(|it| it.original);
+            let call_ret_ty = match call_ret_ty.filter(|it| !it.contains_unknown()) {
+                Some(it) => it,
+                // Calls in statement position expand to statements, with the result as the tail.
+                None => {
+                    let expansion = ast::MacroStmts::cast(ctx.sema.expand(&macro_call)?)?;
+                    ctx.sema.type_of_expr(&expansion.expr()?)?.original
+                }
+            };
+            let call_ret_ty =
+                if call_ret_ty.is_unit() { None } else { Some(display(&call_ret_ty)?) };
+            ret_tys.push(call_ret_ty);
+
+            let call_module =
+                ctx.sema.scope(macro_call.syntax())?.module().nearest_non_block_module(ctx.db());
+            let mut text = String::new();
+            if call_module != module {
+                if !call_module.path_to_root(ctx.db()).contains(&module) {
+                    needs_pub = true;
+                }
+                text.push_str("crate::");
+                for module in module.path_to_root(ctx.db()).into_iter().rev() {
+                    if let Some(name) = module.name(ctx.db()) {
+                        format_to!(text, "{}::", name.display(ctx.db()));
+                    }
+                }
+            }
+            let start = token_tree.syntax().text_range().start();
+            let args = args.iter().map(|range| token_tree.syntax().text().slice(*range - start));
+            let args = args.map(|it| it.to_string()).join(", ");
+            format_to!(text, "{fn_name}({args})");
+            calls.entry(file_id).or_default().push((macro_call.syntax().text_range(), text));
+        }
+    }
+    if calls.is_empty() {
+        return None;
+    }
+
+    // Parameters with the same types at each call share a type parameter.
+    let mut generics: Vec<(&Vec<String>, String, Vec<&str>)> = Vec::new();
+    let mut params = Vec::new();
+    for (param, tys) in arm.params.iter().zip(&param_tys) {
+        if tys.iter().all_equal() {
+            params.push(format!("{param}: {}", tys[0]));
+            continue;
+        }
+        let idx = match generics.iter().position(|(it, ..)| *it == tys) {
+            Some(idx) => idx,
+            None => {
+                let name = generic_param_name(generics.len(), &arm.params)?;
+                generics.push((tys, name, Vec::new()));
+                generics.len() - 1
+            }
+        };
+        let (_, name, bounds) = &mut generics[idx];
+        params.push(format!("{param}: {name}"));
+        bounds.extend(arm.operators(param));
+        if arm.uses(param) > 1 {
+            bounds.push("Copy");
+        }
+    }
+    let ret_ty = if ret_tys.iter().all_equal() {
+        ret_tys.into_iter().next().flatten()
+    } else {
+        // A result that differs between calls has to be one of the type parameters.
+        let ret_tys: Option<Vec<_>> = ret_tys.into_iter().collect();
+        let (_, name, _) = generics.iter().find(|(tys, ..)| Some(*tys) == ret_tys.as_ref())?;
+        Some(name.clone())
+    };
+
+    let indent = IndentLevel::from_node(macro_rules.syntax());
+    let vis = if needs_pub { "pub(crate) " } else { "" };
+    let ops = if ctx.config.prefer_no_std { "core::ops" } else { "std::ops" };
+    let generics = generics
+        .iter()
+        .map(|(_, name, bounds)| {
+            let bounds = bounds
+                .iter()
+                .unique()
+                .map(|bound| match *bound {
+                    "Copy" | "PartialOrd" => bound.to_string(),
+                    _ => format!("{ops}::{bound}<Output = {name}>"),
+                })
+                .join(" + ");
+            if bounds.is_empty() {
+                name.clone()
+            } else {
+                format!("{name}: {bounds}")
+            }
+        })
+        .join(", ");
+    let generics = if generics.is_empty() { generics } else { format!("<{generics}>") };
+    let params = params.join(", ");
+    let mut fn_text = format!("{vis}fn {fn_name}{generics}({params})");
+    if let Some(ret_ty) = ret_ty {
+        format_to!(fn_text, " -> {ret_ty}");
+    }
+    format_to!(fn_text, " {}", arm.body(indent));
+    let parse = ast::SourceFile::parse(&fn_text);
+    if !parse.errors().is_empty() {
+        return None;
+    }
+
+    // Replace the macro from `macro_rules`, keeping its doc comments and attributes.
+    let start = macro_rules.macro_rules_token()?.text_range().start();
+    let range = TextRange::new(start, macro_rules.syntax().text_range().end());
+    acc.add(
+        AssistId("convert_macro_rules_to_fn", AssistKind::RefactorRewrite),
+        "Convert macro to function",
+        name.syntax().text_range(),
+        |builder| {
+            for (file_id, calls) in calls {
+                builder.edit_file(file_id);
+                for (range, text) in calls {
+                    builder.replace(range, text);
+                }
+            }
+            builder.edit_file(ctx.file_id());
+            builder.replace(range, fn_text);
+        },
+    )
+}
+
+/// `T`, `U`, ... for the `idx`th type parameter, skipping the names of the fragments.
+fn generic_param_name(idx: usize, params: &[String]) -> Option<String> {
+    ('T'..='Z').chain('A'..'T').map(String::from).filter(|it| !params.contains(it)).nth(idx)
+}
+
+/// The single arm of a macro, with the names of the `$x:expr` fragments.
+struct Arm {
+    params: Vec<String>,
+    /// The tokens of the transcriber, without the delimiters.
+    body: Vec<SyntaxToken>,
+}
+
+impl Arm {
+    fn parse(token_tree: &ast::TokenTree) -> Option<Arm> {
+        let mut parts = token_tree_items(token_tree);
+        if parts.last().and_then(|it| it.as_token()).map_or(false, |it| it.kind() == T![;]) {
+            parts.pop();
+        }
+        let [NodeOrToken::Node(matcher), arrow @ .., NodeOrToken::Node(transcriber)] = &parts[..]
+        else {
+            return None;
+        };
+        if arrow.iter().map(|it| it.to_string()).collect::<String>() != "=>" {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        let matcher = ast::TokenTree::cast(matcher.clone())?;
+        for fragment in token_tree_items(&matcher).split(|it| it.kind() == T![,]) {
+            match fragment {
+                [dollar, name, colon, kind]
+                    if dollar.kind() == T![$]
+                        && name.kind() == SyntaxKind::IDENT
+                        && colon.kind() == T![:]
+                        && kind.to_string() == "expr" =>
+                {
+                    params.push(name.to_string());
+                }
+                // A trailing comma.
+                [] if !params.is_empty() => (),
+                _ => return None,
+            }
+        }
+
+        let transcriber = ast::TokenTree::cast(transcriber.clone())?;
+        let mut body: Vec<SyntaxToken> = transcriber
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .collect();
+        body.pop();
+        if !body.is_empty() {
+            body.remove(0);
+        }
+        // Any other `$` would be a repetition or an unknown fragment.
+        let is_param = |it: Option<&SyntaxToken>| {
+            it.map_or(false, |it| params.contains(&it.to_string()) || it.kind() == T![crate])
+        };
+        if body
+            .iter()
+            .enumerate()
+            .any(|(idx, it)| it.kind() == T![$] && !is_param(body.get(idx + 1)))
+        {
+            return None;
+        }
+        Some(Arm { params, body })
+    }
+
+    /// How often the fragment `param` is used in the transcriber.
+    fn uses(&self, param: &str) -> usize {
+        self.body
+            .iter()
+            .tuple_windows()
+            .filter(|(dollar, name)| dollar.kind() == T![$] && name.text() == param)
+            .count()
+    }
+
+    /// The operator traits of the operators applied to the fragment `param` in the transcriber.
+    fn operators(&self, param: &str) -> Vec<&'static str> {
+        let body: Vec<_> = self.body.iter().filter(|it| !it.kind().is_trivia()).collect();
+        let mut traits = Vec::new();
+        for (idx, window) in body.windows(2).enumerate() {
+            if window[0].kind() != T![$] || window[1].text() != param {
+                continue;
+            }
+            let prev = idx.checked_sub(1).and_then(|idx| body.get(idx));
+            let prev_prev = idx.checked_sub(2).and_then(|idx| body.get(idx));
+            let is_operand = |it: Option<&&SyntaxToken>| {
+                it.map_or(false, |it| {
+                    matches!(it.kind(), SyntaxKind::IDENT | T![')'] | T![']'])
+                        || it.kind().is_literal()
+                })
+            };
+            match prev.map(|it| it.kind()) {
+                Some(T![-]) if !is_operand(prev_prev) => traits.push("Neg"),
+                Some(T![!]) if !is_operand(prev_prev) => traits.push("Not"),
+                Some(kind) => traits.extend(operator_trait(kind)),
+                None => (),
+            }
+            traits.extend(body.get(idx + 2).and_then(|it| operator_trait(it.kind())));
+        }
+        traits
+    }
+
+    /// The transcriber as the block of a function, with the fragments replaced by the parameters.
+    fn body(&self, indent: IndentLevel) -> String {
+        let mut text = String::new();
+        for token in &self.body {
+            if token.kind() != T![$] {
+                text.push_str(token.text());
+            }
+        }
+        let text = text.trim();
+        let mut lines = text.lines();
+        let first = lines.next().unwrap_or_default();
+        let rest: Vec<_> = lines.collect();
+        let dedent = rest
+            .iter()
+            .filter(|it| !it.trim().is_empty())
+            .map(|it| it.len() - it.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let is_block = text.starts_with('{') && text.ends_with('}');
+        let inner = if is_block { indent } else { indent + 1 };
+        let mut buf = String::new();
+        if !is_block {
+            format_to!(buf, "{{\n{inner}");
+        }
+        buf.push_str(first);
+        for line in rest {
+            buf.push('\n');
+            if !line.trim().is_empty() {
+                format_to!(buf, "{inner}{}", &line[dedent..]);
+            }
+        }
+        if !is_block {
+            format_to!(buf, "\n{indent}}}");
+        }
+        buf
+    }
+}
+
+/// The trait of a binary operator.
+fn operator_trait(kind: SyntaxKind) -> Option<&'static str> {
+    let it = match kind {
+        T![+] => "Add",
+        T![-] => "Sub",
+        T![*] => "Mul",
+        T![/] => "Div",
+        T![%] => "Rem",
+        T![<] | T![>] => "PartialOrd",
+        _ => return None,
+    };
+    Some(it)
+}
+
+/// The items of a token tree, without its delimiters and trivia.
+fn token_tree_items(token_tree: &ast::TokenTree) -> Vec<syntax::SyntaxElement> {
+    let mut items: Vec<_> =
+        token_tree.syntax().children_with_tokens().filter(|it| !it.kind().is_trivia()).collect();
+    items.pop();
+    if !items.is_empty() {
+        items.remove(0);
+    }
+    items
+}
+
+/// The ranges of the comma separated arguments of a macro call.
+fn call_args(token_tree: &ast::TokenTree) -> Option<Vec<TextRange>> {
+    let items = token_tree_items(token_tree);
+    let mut args = Vec::new();
+    for arg in items.split(|it| it.kind() == T![,]) {
+        match arg {
+            [] => {
+                // Only a trailing comma may leave an argument empty.
+                if args.is_empty() {
+                    return None;
+                }
+            }
+            [first, .., last] => {
+                args.push(first.text_range().cover(last.text_range()));
+            }
+            [it] => args.push(it.text_range()),
+        }
+    }
+    Some(args)
+}
+
+/// The expression an argument of `macro_call` became in its expansion.
+fn expanded_arg(
+    ctx: &AssistContext<'_>,
+    macro_call: &ast::MacroCall,
+    arg: TextRange,
+) -> Option<ast::Expr> {
+    let token = macro_call.syntax().token_at_offset(arg.start()).right_biased()?;
+    ctx.sema.descend_into_macros(DescendPreference::None, token).into_iter().find_map(|token| {
+        token
+            .parent_ancestors()
+            .filter_map(ast::Expr::cast)
+            .take_while(|it| arg.contains_range(ctx.sema.original_range(it.syntax()).range))
+            .find(|it| ctx.sema.original_range(it.syntax()).range == arg)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn takes_types_from_calls() {
+        check_assist(
+            convert_macro_rules_to_fn,
+            r#"
+macro_rules! $0pick {
+    ($cond:expr, $a:expr, $b:expr) => {{
+        let c = $cond;
+        if c { $a } else { $b }
+    }};
+}
+
+fn f(flag: bool) {
+    let x = pick!(flag, 1u8, 2u8);
+    let y = pick![!flag, x, 3];
+}
+"#,
+            r#"
+fn pick(cond: bool, a: u8, b: u8) -> u8 {
+    let c = cond;
+    if c { a } else { b }
+}
+
+fn f(flag: bool) {
+    let x = pick(flag, 1u8, 2u8);
+    let y = pick(!flag, x, 3);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn differing_types_become_type_parameters() {
+        check_assist(
+            convert_macro_rules_to_fn,
+            r#"
+//- minicore: copy
+macro_rules! $0square {
+    ($x:expr) => {
+        $x * $x
+    };
+}
+
+fn f() {
+    let a = square!(2i32);
+    let b = square!(1.5f64);
+}
+"#,
+            r#"
+fn square<T: std::ops::Mul<Output = T> + Copy>(x: T) -> T {
+    x * x
+}
+
+fn f() {
+    let a = square(2i32);
+    let b = square(1.5f64);
+}
+"#,
+        );
+        check_assist(
+            convert_macro_rules_to_fn,
+            r#"
+macro_rules! $0pick {
+    ($cond:expr, $a:expr, $b:expr) => {
+        if $cond { $a } else { $b }
+    };
+}
+
+fn f(flag: bool) {
+    let x = pick!(flag, 1u8, 2u8);
+    let y = pick!(!flag, 'a', 'b');
+}
+"#,
+            r#"
+fn pick<T>(cond: bool, a: T, b: T) -> T {
+    if cond { a } else { b }
+}
+
+fn f(flag: bool) {
+    let x = pick(flag, 1u8, 2u8);
+    let y = pick(!flag, 'a', 'b');
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_differing_results_or_repeated_moves() {
+        check_assist_not_applicable(
+            convert_macro_rules_to_fn,
+            r#"
+macro_rules! $0pair {
+    ($x:expr) => {
+        ($x, 1)
+    };
+}
+
+fn f() {
+    let a = pair!(2i32);
+    let b = pair!(1.5f64);
+}
+"#,
+        );
+        check_assist_not_applicable(
+            convert_macro_rules_to_fn,
+            r#"
+struct S;
+fn take(_: S, _: S) {}
+macro_rules! $0twice {
+    ($s:expr) => {
+        take($s, $s)
+    };
+}
+
+fn f() {
+    twice!(S);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn calls_from_child_module() {
+        check_assist(
+            convert_macro_rules_to_fn,
+            r#"
+//- /main.rs
+/// Records a message.
+macro_rules! log$0 {
+    ($msg:expr,) => {
+        $crate::record($msg, 0)
+    };
+}
+
+mod net;
+
+fn record(_: &str, _: u32) {}
+
+fn main() {
+    log!("start",);
+}
+//- /net.rs
+fn connect() {
+    log!("connect",);
+}
+"#,
+            r#"
+//- /main.rs
+/// Records a message.
+fn log(msg: &str) {
+    crate::record(msg, 0)
+}
+
+mod net;
+
+fn record(_: &str, _: u32) {}
+
+fn main() {
+    log("start");
+}
+//- /net.rs
+fn connect() {
+    crate::log("connect");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_other_macros() {
+        check_assist_not_applicable(
+            convert_macro_rules_to_fn,
+            r#"
+macro_rules! $0twice {
+    ($x:expr) => { $x * 2 };
+    () => { 0 };
+}
+fn f() -> i32 { twice!(1) }
+"#,
+        );
+        check_assist_not_applicable(
+            convert_macro_rules_to_fn,
+            r#"
+macro_rules! $0sum {
+    ($($x:expr),*) => { 0 $(+ $x)* };
+}
+fn f() -> i32 { sum!(1, 2) }
+"#,
+        );
+        check_assist_not_applicable(
+            convert_macro_rules_to_fn,
+            r#"
+macro_rules! $0field {
+    ($s:expr, $f:ident) => { $s.$f };
+}
+struct S { a: i32 }
+fn f(s: S) -> i32 { field!(s, a) }
+"#,
+        );
+        check_assist_not_applicable(
+            convert_macro_rules_to_fn,
+            r#"
+#[macro_export]
+macro_rules! $0double {
+    ($x:expr) => { $x * 2 };
+}
+fn f() -> i32 { double!(1) }
+"#,
+        );
+    }
+}
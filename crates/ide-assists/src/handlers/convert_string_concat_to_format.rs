@@ -0,0 +1,525 @@
+use hir::{PathResolution, Semantics};
+use ide_db::{defs::Definition, famous_defs::FamousDefs, RootDatabase};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, HasArgList},
+    AstNode, Direction, SyntaxKind, TextRange,
+};
+
+use crate::{utils::is_mutable_use, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_string_concat_to_format
+//
+// Converts a concatenation of strings to a `format!` call.
+//
+// ```
+// # //- minicore: fmt, add
+// # //- /main.rs crate:main deps:alloc
+// # use alloc::{format, string::ToString};
+// fn main() {
+//     let (n, unit) = (92, "m");
+//     let s = n.to_string() +$0 " " + unit;
+// }
+// # //- /alloc.rs crate:alloc
+// # pub mod string {
+// #     pub struct String;
+// #     impl core::ops::Add<&str> for String {
+// #         type Output = String;
+// #         fn add(self, other: &str) -> String { self }
+// #     }
+// #     pub trait ToString {
+// #         fn to_string(&self) -> String;
+// #     }
+// #     impl<T: ?Sized> ToString for T {
+// #         fn to_string(&self) -> String { String }
+// #     }
+// # }
+// # pub mod fmt {
+// #     pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+// # }
+// # #[macro_export]
+// # macro_rules! format {
+// #     ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+// # }
+// ```
+// ->
+// ```
+// # use alloc::{format, string::ToString};
+// fn main() {
+//     let (n, unit) = (92, "m");
+//     let s = format!("{n} {unit}");
+// }
+// ```
+pub(crate) fn convert_string_concat_to_format(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let bin_expr = ctx.find_node_at_offset::<ast::BinExpr>().filter(is_add)?;
+    let mut concat = bin_expr;
+    while let Some(parent) = concat.syntax().parent().and_then(ast::BinExpr::cast) {
+        if !is_add(&parent) || parent.lhs()?.syntax() != concat.syntax() {
+            break;
+        }
+        concat = parent;
+    }
+    let concat = ast::Expr::BinExpr(concat);
+    if !is_string(&ctx.sema, &concat) {
+        return None;
+    }
+
+    let mut operands = Vec::new();
+    let mut lhs = concat.clone();
+    while let ast::Expr::BinExpr(bin_expr) = &lhs {
+        if !is_add(bin_expr) {
+            break;
+        }
+        operands.push(bin_expr.rhs()?);
+        lhs = bin_expr.lhs()?;
+    }
+    operands.push(lhs);
+    operands.reverse();
+
+    let range = concat.syntax().text_range();
+    acc.add(
+        AssistId("convert_string_concat_to_format", AssistKind::RefactorRewrite),
+        "Convert string concatenation to `format!`",
+        range,
+        |edit| edit.replace(range, format_call(operands.into_iter().map(Piece::of))),
+    )
+}
+
+// Assist: convert_push_str_to_format
+//
+// Converts consecutive `push_str` and `push` calls on a string to a single `format!` call.
+//
+// ```
+// # //- minicore: fmt
+// # //- /main.rs crate:main deps:alloc
+// # use alloc::{format, string::String};
+// fn greeting(name: &str) -> String {
+//     let mut s = String::new();
+//     s.push_str("Hello, ");$0
+//     s.push_str(name);
+//     s.push('!');
+//     s
+// }
+// # //- /alloc.rs crate:alloc
+// # pub mod string {
+// #     pub struct String;
+// #     impl String {
+// #         pub fn new() -> String { String }
+// #         pub fn push_str(&mut self, string: &str) {}
+// #         pub fn push(&mut self, ch: char) {}
+// #     }
+// # }
+// # pub mod fmt {
+// #     pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+// # }
+// # #[macro_export]
+// # macro_rules! format {
+// #     ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+// # }
+// ```
+// ->
+// ```
+// # use alloc::{format, string::String};
+// fn greeting(name: &str) -> String {
+//     let s = format!("Hello, {name}!");
+//     s
+// }
+// ```
+pub(crate) fn convert_push_str_to_format(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let stmt = ctx.find_node_at_offset::<ast::ExprStmt>()?;
+    let (local, _) = push_call(&ctx.sema, &ast::Stmt::ExprStmt(stmt.clone()))?;
+    let stmt_list = ast::StmtList::cast(stmt.syntax().parent()?)?;
+    let stmts: Vec<_> = stmt_list.statements().collect();
+    let idx = stmts.iter().position(|it| it.syntax() == stmt.syntax())?;
+    let is_push = |stmt: &ast::Stmt| push_call(&ctx.sema, stmt).map_or(false, |it| it.0 == local);
+    let start = stmts[..idx].iter().rposition(|it| !is_push(it)).map_or(0, |it| it + 1);
+    let end = stmts[idx..].iter().position(|it| !is_push(it)).map_or(stmts.len(), |it| idx + it);
+    let pushes = &stmts[start..end];
+    let pushes_range = TextRange::new(
+        pushes.first()?.syntax().text_range().start(),
+        pushes.last()?.syntax().text_range().end(),
+    );
+
+    // `let mut s = String::new();` right before the calls gets the `format!` as initializer.
+    let let_stmt = start.checked_sub(1).and_then(|it| match &stmts[it] {
+        ast::Stmt::LetStmt(let_stmt) => {
+            let ast::Pat::IdentPat(pat) = let_stmt.pat()? else { return None };
+            let_stmt.initializer()?;
+            (ctx.sema.to_def(&pat)? == local).then(|| (let_stmt.clone(), pat))
+        }
+        _ => None,
+    });
+    if let_stmt.is_none() && pushes.len() < 2 {
+        return None;
+    }
+
+    let mut pieces = Vec::new();
+    if let Some((let_stmt, _)) = &let_stmt {
+        let init = let_stmt.initializer()?;
+        if !is_empty_string(&init) {
+            pieces.push(Piece::of(init));
+        }
+    }
+    for push in pushes {
+        pieces.push(Piece::of(push_call(&ctx.sema, push)?.1));
+    }
+
+    acc.add(
+        AssistId("convert_push_str_to_format", AssistKind::RefactorRewrite),
+        "Convert `push_str` calls to `format!`",
+        pushes_range,
+        |edit| {
+            let format_call = format_call(pieces);
+            let Some((let_stmt, pat)) = let_stmt else {
+                let name = local.name(ctx.db());
+                let name = name.display(ctx.db());
+                edit.replace(pushes_range, format!("{name}.push_str(&{format_call});"));
+                return;
+            };
+            if let Some(init) = let_stmt.initializer() {
+                edit.replace(init.syntax().text_range(), format_call);
+            }
+            edit.delete(TextRange::new(let_stmt.syntax().text_range().end(), pushes_range.end()));
+
+            // The binding doesn't need to be mutable anymore if the calls were the only mutation.
+            let Some(mut_token) = pat.mut_token() else { return };
+            let mutated_later = Definition::Local(local)
+                .usages(&ctx.sema)
+                .all()
+                .iter()
+                .flat_map(|(_, refs)| refs)
+                .filter(|it| !pushes_range.contains_range(it.range))
+                .any(|it| {
+                    let expr = it.name.syntax().ancestors().find_map(ast::PathExpr::cast);
+                    expr.map_or(true, |it| is_mutable_use(ctx, &ast::Expr::PathExpr(it)))
+                });
+            if !mutated_later {
+                let ws = mut_token
+                    .siblings_with_tokens(Direction::Next)
+                    .nth(1)
+                    .filter(|it| it.kind() == SyntaxKind::WHITESPACE);
+                let end = ws.map_or(mut_token.text_range().end(), |it| it.text_range().end());
+                edit.delete(TextRange::new(mut_token.text_range().start(), end));
+            }
+        },
+    )
+}
+
+/// A part of the string a `format!` call builds.
+enum Piece {
+    /// Text of the format string, escaped.
+    Text(String),
+    Arg(ast::Expr),
+}
+
+impl Piece {
+    fn of(expr: ast::Expr) -> Piece {
+        let inner = match &expr {
+            ast::Expr::Literal(lit) => {
+                let value = match lit.kind() {
+                    ast::LiteralKind::String(it) => it.value().map(|it| it.into_owned()),
+                    ast::LiteralKind::Char(it) => it.value().map(|it| it.to_string()),
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    return Piece::Text(escape(&value));
+                }
+                None
+            }
+            ast::Expr::RefExpr(it) if it.mut_token().is_none() && it.raw_token().is_none() => {
+                it.expr()
+            }
+            ast::Expr::ParenExpr(it) => it.expr(),
+            // `x.to_string()` is what `format!` does with `x`.
+            ast::Expr::MethodCallExpr(it)
+                if it.arg_list().map_or(false, |it| it.args().next().is_none())
+                    && it.name_ref().map_or(false, |it| {
+                        matches!(it.text().as_str(), "to_string" | "to_owned")
+                    }) =>
+            {
+                it.receiver()
+            }
+            ast::Expr::CallExpr(it) => match it.expr() {
+                Some(ast::Expr::PathExpr(path)) if path.syntax().text() == "String::from" => {
+                    it.arg_list().and_then(|it| it.args().exactly_one().ok())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match inner {
+            Some(inner) => Piece::of(inner),
+            None => Piece::Arg(expr),
+        }
+    }
+}
+
+fn format_call(pieces: impl IntoIterator<Item = Piece>) -> String {
+    let mut fmt = String::new();
+    let mut args = String::new();
+    for piece in pieces {
+        match piece {
+            Piece::Text(text) => fmt.push_str(&text),
+            Piece::Arg(ast::Expr::PathExpr(path))
+                if path.path().map_or(false, |it| {
+                    it.qualifier().is_none()
+                        && it
+                            .segment()
+                            .and_then(|it| it.name_ref()?.ident_token())
+                            .map_or(false, |it| !it.text().starts_with("r#"))
+                        && it.segment().map_or(false, |it| it.generic_arg_list().is_none())
+                }) =>
+            {
+                format_to!(fmt, "{{{path}}}")
+            }
+            Piece::Arg(expr) => {
+                fmt.push_str("{}");
+                format_to!(args, ", {expr}");
+            }
+        }
+    }
+    format!("format!(\"{fmt}\"{args})")
+}
+
+/// Escapes `text` for a format string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for ch in text.chars() {
+        match ch {
+            '{' => escaped.push_str("{{"),
+            '}' => escaped.push_str("}}"),
+            '\'' => escaped.push(ch),
+            _ => escaped.extend(ch.escape_debug()),
+        }
+    }
+    escaped
+}
+
+fn is_add(bin_expr: &ast::BinExpr) -> bool {
+    matches!(bin_expr.op_kind(), Some(ast::BinaryOp::ArithOp(ast::ArithOp::Add)))
+}
+
+fn is_string(sema: &Semantics<'_, RootDatabase>, expr: &ast::Expr) -> bool {
+    let string =
+        sema.scope(expr.syntax()).and_then(|it| FamousDefs(sema, it.krate()).alloc_string_String());
+    let ty = sema.type_of_expr(expr).map(|it| it.original.strip_references());
+    matches!((string, ty), (Some(string), Some(ty)) if ty.as_adt() == Some(hir::Adt::Struct(string)))
+}
+
+/// `String::new()` or `String::with_capacity(n)`.
+fn is_empty_string(expr: &ast::Expr) -> bool {
+    let ast::Expr::CallExpr(call) = expr else { return false };
+    let Some(ast::Expr::PathExpr(path)) = call.expr() else { return false };
+    matches!(path.syntax().text().to_string().as_str(), "String::new" | "String::with_capacity")
+}
+
+/// The string a `s.push_str(value);` or `s.push(ch);` statement appends to, and the value.
+fn push_call(
+    sema: &Semantics<'_, RootDatabase>,
+    stmt: &ast::Stmt,
+) -> Option<(hir::Local, ast::Expr)> {
+    let ast::Stmt::ExprStmt(stmt) = stmt else { return None };
+    stmt.semicolon_token()?;
+    let ast::Expr::MethodCallExpr(call) = stmt.expr()? else { return None };
+    if !matches!(call.name_ref()?.text().as_str(), "push_str" | "push") {
+        return None;
+    }
+    let receiver = call.receiver()?;
+    let ast::Expr::PathExpr(path) = &receiver else { return None };
+    let Some(PathResolution::Local(local)) = sema.resolve_path(&path.path()?) else { return None };
+    if !is_string(sema, &receiver) {
+        return None;
+    }
+    let value = call.arg_list()?.args().exactly_one().ok()?;
+    Some((local, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    const ALLOC: &str = r#"//- /alloc.rs crate:alloc
+pub mod string {
+    pub struct String;
+    impl String {
+        pub fn new() -> String { String }
+        pub fn push_str(&mut self, string: &str) {}
+        pub fn push(&mut self, ch: char) {}
+    }
+    impl core::ops::Add<&str> for String {
+        type Output = String;
+        fn add(self, other: &str) -> String { self }
+    }
+    pub trait ToString {
+        fn to_string(&self) -> String;
+    }
+    impl<T: ?Sized> ToString for T {
+        fn to_string(&self) -> String { String }
+    }
+}
+pub mod fmt {
+    pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+}
+#[macro_export]
+macro_rules! format {
+    ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+}
+"#;
+
+    #[test]
+    fn concat_with_literals_refs_and_expressions() {
+        check_assist(
+            convert_string_concat_to_format,
+            &format!(
+                r#"
+//- minicore: fmt, add
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::{{String, ToString}}}};
+struct S {{ name: &'static str }}
+fn main() {{
+    let (a, s) = (1, S {{ name: "s" }});
+    let b = "b";
+    let x = a.to_string() + "{{\"}}\n" + &b + s.name +$0 &(a + 1).to_string();
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::{format, string::{String, ToString}};
+struct S { name: &'static str }
+fn main() {
+    let (a, s) = (1, S { name: "s" });
+    let b = "b";
+    let x = format!("{a}{{\"}}\n{b}{}{}", s.name, a + 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn concat_not_applicable_to_numbers() {
+        check_assist_not_applicable(
+            convert_string_concat_to_format,
+            &format!(
+                r#"
+//- minicore: fmt, add
+//- /main.rs crate:main deps:alloc
+fn main() {{
+    let x = 1 +$0 2;
+}}
+{ALLOC}"#
+            ),
+        );
+    }
+
+    #[test]
+    fn push_str_into_let_initializer() {
+        check_assist(
+            convert_push_str_to_format,
+            &format!(
+                r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::{{String, ToString}}}};
+fn main() {{
+    let n = 1;
+    let mut s = "n = ".to_string();
+    s.push_str(&n.to_string());
+    s.push$0(';');
+    s.push_str(&(n + 1).to_string());
+    drop(s);
+}}
+fn drop(s: String) {{}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::{format, string::{String, ToString}};
+fn main() {
+    let n = 1;
+    let s = format!("n = {n};{}", n + 1);
+    drop(s);
+}
+fn drop(s: String) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn push_str_keeps_mut_for_later_mutation() {
+        check_assist(
+            convert_push_str_to_format,
+            &format!(
+                r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::String}};
+fn main() {{
+    let mut s = String::new();
+    s.push_str("a");$0
+    let x = 1;
+    s.push_str("b");
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::{format, string::String};
+fn main() {
+    let mut s = format!("a");
+    let x = 1;
+    s.push_str("b");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn push_str_without_let() {
+        check_assist(
+            convert_push_str_to_format,
+            &format!(
+                r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::String}};
+fn main(s: &mut String, name: &str) {{
+    let mut out = String::new();
+    let x = 1;
+    out.push_str("Hello, ");
+    out.push_str(name);$0
+    out.push_str(s);
+}}
+{ALLOC}"#
+            ),
+            r#"
+use alloc::{format, string::String};
+fn main(s: &mut String, name: &str) {
+    let mut out = String::new();
+    let x = 1;
+    out.push_str(&format!("Hello, {name}{s}"));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn push_str_not_applicable_to_single_call() {
+        check_assist_not_applicable(
+            convert_push_str_to_format,
+            &format!(
+                r#"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{{format, string::String}};
+fn main(out: &mut String) {{
+    out.push_str("a");$0
+}}
+{ALLOC}"#
+            ),
+        );
+    }
+}
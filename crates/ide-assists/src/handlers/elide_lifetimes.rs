@@ -0,0 +1,542 @@
+use ide_db::{
+    syntax_helpers::lifetime_elision::{input_lifetimes, output_lifetime_source, output_refs},
+    FxHashSet,
+};
+use itertools::Itertools;
+use syntax::{
+    ast::{self, HasGenericParams, HasName},
+    AstNode, SyntaxKind, SyntaxNode, TextRange, TextSize,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: make_lifetimes_explicit
+//
+// Names all the lifetimes left to elision in a function signature, or in an impl header and the
+// signatures of its functions.
+//
+// ```
+// fn $0longest(a: &str, b: &str, c: &'_ str) {}
+//
+// fn first(s: &str) -> &str { s }
+// ```
+// ->
+// ```
+// fn longest<'a, 'b, 'c>(a: &'a str, b: &'b str, c: &'c str) {}
+//
+// fn first(s: &str) -> &str { s }
+// ```
+pub(crate) fn make_lifetimes_explicit(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let owner = Owner::find(ctx)?;
+    let mut taken = owner.lifetime_names();
+    let mut edits = Vec::new();
+    match &owner {
+        Owner::Fn(func) => {
+            explicit_fn(func, &mut taken, &mut edits)?;
+        }
+        Owner::Impl(impl_) => {
+            explicit_impl_header(impl_, &mut taken, &mut edits);
+            // The lifetimes of one function are free to use in the others.
+            for func in impl_fns(impl_) {
+                explicit_fn(&func, &mut taken.clone(), &mut edits);
+            }
+        }
+    }
+    if edits.is_empty() {
+        return None;
+    }
+    acc.add(
+        AssistId("make_lifetimes_explicit", AssistKind::RefactorRewrite),
+        "Make lifetimes explicit",
+        owner.target(),
+        |builder| {
+            for (range, text) in edits {
+                builder.replace(range, text);
+            }
+        },
+    )
+}
+
+// Assist: elide_lifetimes
+//
+// Removes the named lifetimes of a function, or of an impl and its functions, which lifetime
+// elision would give as well. Lifetimes used once in a path become `'_`.
+//
+// ```
+// fn $0count<'a, 'b>(s: &'a str, sep: Sep<'b>) -> usize { 0 }
+//
+// fn first<'a>(s: &'a str) -> &'a str { s }
+// ```
+// ->
+// ```
+// fn count(s: &str, sep: Sep<'_>) -> usize { 0 }
+//
+// fn first<'a>(s: &'a str) -> &'a str { s }
+// ```
+pub(crate) fn elide_lifetimes(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let owner = Owner::find(ctx)?;
+    let mut edits = Vec::new();
+    match &owner {
+        Owner::Fn(func) => {
+            elide_fn(func, &mut edits);
+        }
+        Owner::Impl(impl_) => {
+            elide_impl_header(impl_, &mut edits);
+            for func in impl_fns(impl_) {
+                elide_fn(&func, &mut edits);
+            }
+        }
+    }
+    if edits.is_empty() {
+        return None;
+    }
+    acc.add(
+        AssistId("elide_lifetimes", AssistKind::RefactorRewrite),
+        "Elide lifetimes",
+        owner.target(),
+        |builder| {
+            for (range, text) in edits {
+                builder.replace(range, text);
+            }
+        },
+    )
+}
+
+/// The signature the cursor is in.
+enum Owner {
+    Fn(ast::Fn),
+    Impl(ast::Impl),
+}
+
+impl Owner {
+    fn find(ctx: &AssistContext<'_>) -> Option<Owner> {
+        let in_header = |node: &SyntaxNode, body: Option<SyntaxNode>| {
+            body.map_or(true, |body| ctx.offset() < body.text_range().start())
+                && node.text_range().contains_inclusive(ctx.offset())
+        };
+        if let Some(func) = ctx.find_node_at_offset::<ast::Fn>() {
+            return in_header(func.syntax(), func.body().map(|it| it.syntax().clone()))
+                .then_some(Owner::Fn(func));
+        }
+        let impl_ = ctx.find_node_at_offset::<ast::Impl>()?;
+        in_header(impl_.syntax(), impl_.assoc_item_list().map(|it| it.syntax().clone()))
+            .then_some(Owner::Impl(impl_))
+    }
+
+    fn target(&self) -> TextRange {
+        let (node, body) = match self {
+            Owner::Fn(it) => (it.syntax(), it.body().map(|it| it.syntax().clone())),
+            Owner::Impl(it) => (it.syntax(), it.assoc_item_list().map(|it| it.syntax().clone())),
+        };
+        let end = body.map_or(node.text_range().end(), |it| it.text_range().start());
+        TextRange::new(node.text_range().start(), end)
+    }
+
+    /// The lifetime names already in use where new ones get added.
+    fn lifetime_names(&self) -> FxHashSet<String> {
+        let node = match self {
+            Owner::Fn(func) => func
+                .syntax()
+                .ancestors()
+                .find(|it| matches!(it.kind(), SyntaxKind::IMPL | SyntaxKind::TRAIT))
+                .unwrap_or_else(|| func.syntax().clone()),
+            Owner::Impl(impl_) => impl_.syntax().clone(),
+        };
+        node.descendants().filter_map(ast::Lifetime::cast).map(|it| it.to_string()).collect()
+    }
+}
+
+fn impl_fns(impl_: &ast::Impl) -> impl Iterator<Item = ast::Fn> {
+    impl_.assoc_item_list().into_iter().flat_map(|it| it.assoc_items()).filter_map(|it| match it {
+        ast::AssocItem::Fn(it) => Some(it),
+        _ => None,
+    })
+}
+
+fn fresh_lifetime(taken: &mut FxHashSet<String>) -> String {
+    let name = (0..)
+        .flat_map(|n| {
+            ('a'..='z').map(move |c| if n == 0 { format!("'{c}") } else { format!("'{c}{n}") })
+        })
+        .find(|it| !taken.contains(it))
+        .unwrap_or_default();
+    taken.insert(name.clone());
+    name
+}
+
+/// Names the elided lifetimes in the signature of `func`.
+fn explicit_fn(
+    func: &ast::Fn,
+    taken: &mut FxHashSet<String>,
+    edits: &mut Vec<(TextRange, String)>,
+) -> Option<()> {
+    let param_list = func.param_list()?;
+    // `'_` in a path is an input lifetime of its own, which the elision rules here don't cover.
+    if param_list.syntax().descendants().filter_map(ast::Lifetime::cast).any(|it| {
+        it.text() == "'_"
+            && !matches!(it.syntax().parent().map(|it| it.kind()), Some(SyntaxKind::REF_TYPE))
+    }) {
+        return None;
+    }
+
+    let inputs = input_lifetimes(&param_list);
+    let mut new = Vec::new();
+    let mut names = Vec::new();
+    for input in &inputs {
+        let name = if input.is_elided() {
+            let name = fresh_lifetime(taken);
+            match (&input.lifetime, &input.amp_token) {
+                (Some(lifetime), _) => edits.push((lifetime.syntax().text_range(), name.clone())),
+                (None, Some(amp)) => {
+                    edits.push((TextRange::empty(amp.text_range().end()), format!("{name} ")))
+                }
+                (None, None) => (),
+            }
+            new.push(name.clone());
+            name
+        } else {
+            input.lifetime.as_ref().map_or(String::new(), |it| it.to_string())
+        };
+        names.push(name);
+    }
+
+    let source = output_lifetime_source(&inputs)
+        .and_then(|source| inputs.iter().position(|it| it.amp_token == source.amp_token));
+    if let (Some(source), Some(ret_ty)) = (source, func.ret_type().and_then(|it| it.ty())) {
+        let name = &names[source];
+        for ty in output_refs(&ret_ty) {
+            match (ty.lifetime(), ty.amp_token()) {
+                (None, Some(amp)) => {
+                    edits.push((TextRange::empty(amp.text_range().end()), format!("{name} ")))
+                }
+                (Some(lifetime), _) if lifetime.text() == "'_" => {
+                    edits.push((lifetime.syntax().text_range(), name.clone()))
+                }
+                _ => (),
+            }
+        }
+        for lifetime in ret_ty.syntax().descendants().filter_map(ast::Lifetime::cast) {
+            if lifetime.text() == "'_"
+                && !ast::RefType::can_cast(lifetime.syntax().parent()?.kind())
+            {
+                edits.push((lifetime.syntax().text_range(), name.clone()));
+            }
+        }
+    }
+
+    let anchor = func.name()?.syntax().text_range().end();
+    add_lifetime_params(func.generic_param_list(), anchor, &new, edits);
+    Some(())
+}
+
+/// Names the elided lifetimes in the self type and trait of `impl_`.
+fn explicit_impl_header(
+    impl_: &ast::Impl,
+    taken: &mut FxHashSet<String>,
+    edits: &mut Vec<(TextRange, String)>,
+) -> Option<()> {
+    let mut new = Vec::new();
+    let types = impl_.trait_().into_iter().chain(impl_.self_ty());
+    for node in types.flat_map(|it| it.syntax().descendants().collect::<Vec<_>>()) {
+        if let Some(ref_type) = ast::RefType::cast(node.clone()) {
+            if let (None, Some(amp)) = (ref_type.lifetime(), ref_type.amp_token()) {
+                let name = fresh_lifetime(taken);
+                edits.push((TextRange::empty(amp.text_range().end()), format!("{name} ")));
+                new.push(name);
+            }
+        } else if let Some(lifetime) = ast::Lifetime::cast(node) {
+            if lifetime.text() == "'_" {
+                let name = fresh_lifetime(taken);
+                edits.push((lifetime.syntax().text_range(), name.clone()));
+                new.push(name);
+            }
+        }
+    }
+    let anchor = impl_.impl_token()?.text_range().end();
+    add_lifetime_params(impl_.generic_param_list(), anchor, &new, edits);
+    Some(())
+}
+
+/// Adds `new` after the lifetime parameters in `list`, or as a new list at `anchor`.
+fn add_lifetime_params(
+    list: Option<ast::GenericParamList>,
+    anchor: TextSize,
+    new: &[String],
+    edits: &mut Vec<(TextRange, String)>,
+) {
+    if new.is_empty() {
+        return;
+    }
+    let new = new.join(", ");
+    let Some(list) = list else {
+        edits.push((TextRange::empty(anchor), format!("<{new}>")));
+        return;
+    };
+    if let Some(last) = list.lifetime_params().last() {
+        edits.push((TextRange::empty(last.syntax().text_range().end()), format!(", {new}")));
+    } else if let Some(l_angle) = list.l_angle_token() {
+        let text = match list.generic_params().next() {
+            Some(_) => format!("{new}, "),
+            None => new,
+        };
+        edits.push((TextRange::empty(l_angle.text_range().end()), text));
+    }
+}
+
+/// Removes the lifetime parameters of `func` the elision rules would give as well.
+fn elide_fn(func: &ast::Fn, edits: &mut Vec<(TextRange, String)>) -> Option<()> {
+    let list = func.generic_param_list()?;
+    let param_list = func.param_list()?;
+    let ret_ty = func.ret_type();
+    let inputs = input_lifetimes(&param_list);
+    let source = output_lifetime_source(&inputs);
+    // Lifetimes in paths count as inputs as well.
+    let positions = inputs.len()
+        + param_list
+            .syntax()
+            .descendants()
+            .filter_map(ast::Lifetime::cast)
+            .filter(|it| !is_ref_lifetime(it))
+            .count();
+
+    let mut removed = Vec::new();
+    for param in list.lifetime_params() {
+        let Some(lifetime) = param.lifetime() else { continue };
+        if has_bounds(&param) {
+            continue;
+        }
+        let uses: Vec<ast::Lifetime> = func
+            .syntax()
+            .descendants()
+            .filter_map(ast::Lifetime::cast)
+            .filter(|it| it.text() == lifetime.text() && *it != lifetime)
+            .collect();
+        let (in_params, rest): (Vec<_>, Vec<_>) = uses.into_iter().partition(|it| {
+            param_list.syntax().text_range().contains_range(it.syntax().text_range())
+        });
+        let (in_ret, others): (Vec<_>, Vec<_>) = rest.into_iter().partition(|it| {
+            ret_ty.as_ref().map_or(false, |ret_ty| {
+                ret_ty.syntax().text_range().contains_range(it.syntax().text_range())
+            })
+        });
+        let [input] = &in_params[..] else { continue };
+        if !others.is_empty() || in_fn_like(input, &param_list) {
+            continue;
+        }
+        if !in_ret.is_empty() {
+            // The output only gets the lifetime from `&self` or the only input.
+            let Some(source) = source else { continue };
+            if source.lifetime.as_ref() != Some(input) || !(source.is_self || positions == 1) {
+                continue;
+            }
+        }
+        for lifetime in in_params.iter().chain(&in_ret) {
+            elide(lifetime, edits);
+        }
+        removed.push(param);
+    }
+    remove_lifetime_params(&list, &removed, edits);
+    Some(())
+}
+
+/// Removes the lifetime parameters of `impl_` used once in its header only.
+fn elide_impl_header(impl_: &ast::Impl, edits: &mut Vec<(TextRange, String)>) -> Option<()> {
+    let list = impl_.generic_param_list()?;
+    let header: Vec<TextRange> = impl_
+        .trait_()
+        .into_iter()
+        .chain(impl_.self_ty())
+        .map(|it| it.syntax().text_range())
+        .collect();
+    let mut removed = Vec::new();
+    for param in list.lifetime_params() {
+        let Some(lifetime) = param.lifetime() else { continue };
+        if has_bounds(&param) {
+            continue;
+        }
+        let uses: Vec<ast::Lifetime> = impl_
+            .syntax()
+            .descendants()
+            .filter_map(ast::Lifetime::cast)
+            .filter(|it| it.text() == lifetime.text() && *it != lifetime)
+            .collect();
+        let [use_] = &uses[..] else { continue };
+        if !header.iter().any(|it| it.contains_range(use_.syntax().text_range())) {
+            continue;
+        }
+        elide(use_, edits);
+        removed.push(param);
+    }
+    remove_lifetime_params(&list, &removed, edits);
+    Some(())
+}
+
+/// Whether the lifetime parameter has bounds, `'a: 'b`.
+fn has_bounds(param: &ast::LifetimeParam) -> bool {
+    param.syntax().children().filter_map(ast::Lifetime::cast).nth(1).is_some()
+}
+
+fn is_ref_lifetime(lifetime: &ast::Lifetime) -> bool {
+    lifetime
+        .syntax()
+        .parent()
+        .map_or(false, |it| matches!(it.kind(), SyntaxKind::REF_TYPE | SyntaxKind::SELF_PARAM))
+}
+
+/// Whether `lifetime` is in a function pointer or `Fn` trait, where elision means something else.
+fn in_fn_like(lifetime: &ast::Lifetime, param_list: &ast::ParamList) -> bool {
+    lifetime
+        .syntax()
+        .ancestors()
+        .take_while(|it| it != param_list.syntax())
+        .any(|it| matches!(it.kind(), SyntaxKind::FN_PTR_TYPE | SyntaxKind::PARAM_LIST))
+}
+
+/// Removes `lifetime` from a reference, or replaces it with `'_`.
+fn elide(lifetime: &ast::Lifetime, edits: &mut Vec<(TextRange, String)>) {
+    if !is_ref_lifetime(lifetime) {
+        edits.push((lifetime.syntax().text_range(), "'_".to_owned()));
+        return;
+    }
+    let mut range = lifetime.syntax().text_range();
+    if let Some(ws) = lifetime.syntax().next_sibling_or_token() {
+        if ws.kind() == SyntaxKind::WHITESPACE {
+            range = range.cover(ws.text_range());
+        }
+    }
+    edits.push((range, String::new()));
+}
+
+fn remove_lifetime_params(
+    list: &ast::GenericParamList,
+    removed: &[ast::LifetimeParam],
+    edits: &mut Vec<(TextRange, String)>,
+) {
+    if removed.is_empty() {
+        return;
+    }
+    let removed: Vec<_> = removed.iter().map(|it| it.syntax().clone()).collect();
+    let rest = list.generic_params().filter(|it| !removed.contains(it.syntax())).join(", ");
+    let text = if rest.is_empty() { String::new() } else { format!("<{rest}>") };
+    edits.push((list.syntax().text_range(), text));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn explicit_output_lifetime() {
+        check_assist(
+            make_lifetimes_explicit,
+            r#"
+fn get<T>$0(items: &[T], idx: usize) -> Option<&T> {}
+"#,
+            r#"
+fn get<'a, T>(items: &'a [T], idx: usize) -> Option<&'a T> {}
+"#,
+        );
+        check_assist(
+            make_lifetimes_explicit,
+            r#"
+fn name<'s>(s: &'s Ctx, _: &'_ u8)$0 {}
+fn split(s: &str) -> Parts<'_> {}
+"#,
+            r#"
+fn name<'s, 'a>(s: &'s Ctx, _: &'a u8) {}
+fn split(s: &str) -> Parts<'_> {}
+"#,
+        );
+    }
+
+    #[test]
+    fn explicit_impl_and_methods() {
+        check_assist(
+            make_lifetimes_explicit,
+            r#"
+impl$0 Reader for &Buf<'_> {
+    fn read(&mut self, out: &mut [u8]) -> &[u8] {}
+    fn peek(&self) -> Parts<'_> {}
+}
+"#,
+            r#"
+impl<'a, 'b> Reader for &'a Buf<'b> {
+    fn read<'c, 'd>(&'c mut self, out: &'d mut [u8]) -> &'c [u8] {}
+    fn peek<'c>(&'c self) -> Parts<'c> {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn elide_output_lifetime_of_self() {
+        check_assist(
+            elide_lifetimes,
+            r#"
+impl Foo {
+    fn name<'a, 'b>$0(&'a self, other: &'b str, third: &'a str) -> &'a str {}
+    fn get<'s>(&'s self, key: &str) -> Option<&'s str> {}
+}
+"#,
+            r#"
+impl Foo {
+    fn name<'a>(&'a self, other: &str, third: &'a str) -> &'a str {}
+    fn get<'s>(&'s self, key: &str) -> Option<&'s str> {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn elide_impl_header_and_methods() {
+        check_assist(
+            elide_lifetimes,
+            r#"
+impl<'a, 'b, T> $0Parser<'a> for &'b Tokens<T> where T: 'b {
+    fn parse<'c>(&'c self) -> &'c str {}
+}
+"#,
+            r#"
+impl<'b, T> Parser<'_> for &'b Tokens<T> where T: 'b {
+    fn parse(&self) -> &str {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_elision_differs() {
+        check_assist_not_applicable(
+            elide_lifetimes,
+            r#"
+fn pick<'a>$0(a: &'a str, b: &str) -> &'a str {}
+"#,
+        );
+        check_assist_not_applicable(
+            elide_lifetimes,
+            r#"
+fn bounded<'a, 'b: 'a>$0(a: &'a str, b: &'b str) {}
+"#,
+        );
+        check_assist_not_applicable(
+            elide_lifetimes,
+            r#"
+fn call<'a>$0(f: fn(&'a u8)) {}
+"#,
+        );
+        check_assist_not_applicable(
+            elide_lifetimes,
+            r#"
+fn body<'a>$0(x: &'a u8) { let y: &'a u8 = x; }
+"#,
+        );
+        check_assist_not_applicable(
+            make_lifetimes_explicit,
+            r#"
+fn $0f<'a>(a: &'a str) -> &'a str {}
+"#,
+        );
+    }
+}
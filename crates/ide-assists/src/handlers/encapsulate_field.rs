@@ -0,0 +1,631 @@
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    search::{FileReferenceNode, ReferenceCategory},
+    FxHashMap,
+};
+use stdx::format_to;
+use syntax::{
+    ast::{self, HasName, HasVisibility},
+    AstNode, SyntaxKind, SyntaxNode, TextRange, TextSize, T,
+};
+
+use crate::{
+    utils::{find_impl_block_end, find_struct_impl, generate_impl_text, is_mutable_use},
+    AssistContext, AssistId, AssistKind, Assists,
+};
+
+// Assist: encapsulate_field
+//
+// Makes a field private behind a getter and a setter, and rewrites the accesses to the field
+// to use them. The field keeps its visibility if accesses outside its module can't be rewritten.
+//
+// ```
+// # //- minicore: copy
+// pub struct Counter {
+//     pub $0count: u32,
+// }
+//
+// fn tick(c: &mut Counter) {
+//     if c.count < 10 {
+//         c.count = 0;
+//     }
+// }
+// ```
+// ->
+// ```
+// pub struct Counter {
+//     count: u32,
+// }
+//
+// impl Counter {
+//     pub fn $0count(&self) -> u32 {
+//         self.count
+//     }
+//
+//     pub fn set_count(&mut self, count: u32) {
+//         self.count = count;
+//     }
+// }
+//
+// fn tick(c: &mut Counter) {
+//     if c.count() < 10 {
+//         c.set_count(0);
+//     }
+// }
+// ```
+pub(crate) fn encapsulate_field(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let record_field: ast::RecordField = ctx.find_node_at_offset()?;
+    let strukt = record_field.syntax().ancestors().find_map(ast::Struct::cast)?;
+    let field_name = record_field.name()?;
+    let field_ty = record_field.ty()?;
+    let field = ctx.sema.to_def(&record_field)?;
+    let adt = ast::Adt::Struct(strukt.clone());
+    let setter_name = format!("set_{field_name}");
+    let impl_def = find_struct_impl(ctx, &adt, &[field_name.to_string(), setter_name.clone()])?;
+    let struct_def = ctx.sema.to_def(&strukt)?;
+    let module = struct_def.module(ctx.db());
+    let in_module = |node: Option<SyntaxNode>| {
+        node.and_then(|it| ctx.sema.scope(&it))
+            .map_or(false, |it| it.module().path_to_root(ctx.db()).contains(&module))
+    };
+
+    let is_copy = field.ty(ctx.db()).is_copy(ctx.db());
+    let mut edits: FxHashMap<FileId, Vec<Edit>> = FxHashMap::default();
+    let mut unchanged = 0;
+    // Accesses the field's visibility has to stay for, because they still use the field.
+    let mut unchanged_outside_module = false;
+    for (file_id, references) in Definition::Field(field).usages(&ctx.sema).all() {
+        for reference in references {
+            let edit = match &reference.name {
+                FileReferenceNode::NameRef(name_ref) => {
+                    if ctx.config.encapsulate_field_skip_impl
+                        && in_impl_of(ctx, name_ref, struct_def)
+                        && in_module(name_ref.syntax().parent())
+                    {
+                        continue;
+                    }
+                    if ctx.sema.hir_file_for(name_ref.syntax()).is_macro() {
+                        None
+                    } else {
+                        let access =
+                            Access { field_name: &field_name, setter_name: &setter_name, is_copy };
+                        access.edit(ctx, name_ref, reference.category)
+                    }
+                }
+                _ => None,
+            };
+            match edit {
+                Some(edit) => edits.entry(file_id).or_default().push(edit),
+                None => {
+                    unchanged += 1;
+                    unchanged_outside_module |= !in_module(reference.name.syntax().parent());
+                }
+            }
+        }
+    }
+
+    let mut label = "Encapsulate field".to_owned();
+    if unchanged > 0 {
+        let noun = if unchanged == 1 { "access" } else { "accesses" };
+        format_to!(label, " ({unchanged} {noun} left unchanged)");
+    }
+
+    acc.add(
+        AssistId("encapsulate_field", AssistKind::RefactorRewrite),
+        label,
+        record_field.syntax().text_range(),
+        |builder| {
+            for (file_id, edits) in edits {
+                builder.edit_file(file_id);
+                let text = ctx.sema.parse(file_id).syntax().text().to_string();
+                for edit in outermost(&edits, TextRange::up_to(TextSize::of(&text))) {
+                    builder.replace(edit.range, edit.render(&text, &edits));
+                }
+            }
+
+            builder.edit_file(ctx.file_id());
+            let vis = record_field.visibility();
+            if let Some(vis) = vis.as_ref().filter(|_| !unchanged_outside_module) {
+                let mut range = vis.syntax().text_range();
+                if let Some(ws) = vis.syntax().next_sibling_or_token() {
+                    if ws.kind() == SyntaxKind::WHITESPACE {
+                        range = range.cover(ws.text_range());
+                    }
+                }
+                builder.delete(range);
+            }
+
+            let vis = vis.map_or(String::new(), |vis| format!("{vis} "));
+            let cursor = if ctx.config.snippet_cap.is_some() { "$0" } else { "" };
+            let (ret_ty, body) = match is_copy {
+                true => (field_ty.to_string(), format!("self.{field_name}")),
+                false => (format!("&{field_ty}"), format!("&self.{field_name}")),
+            };
+            let mut buf = String::new();
+            if impl_def
+                .as_ref()
+                .and_then(|it| it.assoc_item_list())
+                .map_or(false, |it| it.assoc_items().next().is_some())
+            {
+                buf.push('\n');
+            }
+            format_to!(
+                buf,
+                "    {vis}fn {cursor}{field_name}(&self) -> {ret_ty} {{
+        {body}
+    }}
+
+    {vis}fn {setter_name}(&mut self, {field_name}: {field_ty}) {{
+        self.{field_name} = {field_name};
+    }}"
+            );
+            let offset = impl_def
+                .and_then(|impl_def| find_impl_block_end(impl_def, &mut buf))
+                .unwrap_or_else(|| {
+                    buf = generate_impl_text(&adt, &buf);
+                    strukt.syntax().text_range().end()
+                });
+            match ctx.config.snippet_cap {
+                Some(cap) => builder.insert_snippet(cap, offset, buf),
+                None => builder.insert(offset, buf),
+            }
+        },
+    )
+}
+
+fn in_impl_of(ctx: &AssistContext<'_>, name_ref: &ast::NameRef, strukt: hir::Struct) -> bool {
+    name_ref.syntax().ancestors().filter_map(ast::Impl::cast).any(|impl_| {
+        ctx.sema.to_def(&impl_).and_then(|it| it.self_ty(ctx.db()).as_adt())
+            == Some(hir::Adt::Struct(strukt))
+    })
+}
+
+struct Access<'a> {
+    field_name: &'a ast::Name,
+    setter_name: &'a str,
+    is_copy: bool,
+}
+
+impl Access<'_> {
+    /// The edit turning the field access at `name_ref` into an accessor call, if it can be done.
+    fn edit(
+        &self,
+        ctx: &AssistContext<'_>,
+        name_ref: &ast::NameRef,
+        category: Option<ReferenceCategory>,
+    ) -> Option<Edit> {
+        let field_expr = ast::FieldExpr::cast(name_ref.syntax().parent()?)?;
+        let receiver = field_expr.expr()?.syntax().text_range();
+        let getter = format!(".{}()", self.field_name);
+
+        if category == Some(ReferenceCategory::Write) {
+            let assignment = ast::BinExpr::cast(field_expr.syntax().parent()?)?;
+            let rhs = assignment.rhs()?;
+            let ast::BinaryOp::Assignment { op } = assignment.op_kind()? else { return None };
+            let mut pieces =
+                vec![Piece::Source(receiver), Piece::Text(format!(".{}(", self.setter_name))];
+            if let Some(op) = op {
+                // `x.f += 1` reads the field as well, so the receiver has to be evaluated twice.
+                if !self.is_copy || !is_place(&field_expr.expr()?) {
+                    return None;
+                }
+                pieces.extend([Piece::Source(receiver), Piece::Text(format!("{getter} {op} "))]);
+                let parens = matches!(rhs, ast::Expr::BinExpr(_) | ast::Expr::RangeExpr(_));
+                if parens {
+                    pieces.push(Piece::Text("(".to_owned()));
+                }
+                pieces.push(Piece::Source(rhs.syntax().text_range()));
+                if parens {
+                    pieces.push(Piece::Text(")".to_owned()));
+                }
+            } else {
+                pieces.push(Piece::Source(rhs.syntax().text_range()));
+            }
+            pieces.push(Piece::Text(")".to_owned()));
+            return Some(Edit { range: assignment.syntax().text_range(), pieces });
+        }
+
+        if is_mutable_use(ctx, &field_expr.clone().into()) {
+            return None;
+        }
+        let pieces = vec![Piece::Source(receiver), Piece::Text(getter)];
+        if self.is_copy {
+            return Some(Edit { range: field_expr.syntax().text_range(), pieces });
+        }
+        // The getter returns a reference, which only works where the field is borrowed.
+        let parent = field_expr.syntax().parent()?;
+        let range = borrowed_range(ctx, &field_expr, &parent)?;
+        Some(Edit { range, pieces })
+    }
+}
+
+/// The range replaced by the getter call for a non-`Copy` field, if the use only borrows it.
+fn borrowed_range(
+    ctx: &AssistContext<'_>,
+    field_expr: &ast::FieldExpr,
+    parent: &SyntaxNode,
+) -> Option<TextRange> {
+    let is_base =
+        |expr: Option<ast::Expr>| expr.map_or(false, |it| it.syntax() == field_expr.syntax());
+    if let Some(ref_expr) = ast::RefExpr::cast(parent.clone()) {
+        return (ref_expr.mut_token().is_none() && is_base(ref_expr.expr()))
+            .then(|| ref_expr.syntax().text_range());
+    }
+    let borrows = if let Some(it) = ast::FieldExpr::cast(parent.clone()) {
+        is_base(it.expr())
+    } else if let Some(it) = ast::IndexExpr::cast(parent.clone()) {
+        is_base(it.base())
+    } else if let Some(it) = ast::MethodCallExpr::cast(parent.clone()) {
+        is_base(it.receiver())
+            && ctx
+                .sema
+                .resolve_method_call(&it)
+                .and_then(|func| func.self_param(ctx.db()))
+                .map_or(false, |param| param.access(ctx.db()) == hir::Access::Shared)
+    } else {
+        false
+    };
+    borrows.then(|| field_expr.syntax().text_range())
+}
+
+/// Whether `expr` can be evaluated twice without side effects.
+fn is_place(expr: &ast::Expr) -> bool {
+    expr.syntax().descendants().filter_map(ast::Expr::cast).all(|it| match it {
+        ast::Expr::PathExpr(_) | ast::Expr::FieldExpr(_) | ast::Expr::ParenExpr(_) => true,
+        ast::Expr::PrefixExpr(it) => it.op_token().map_or(false, |op| op.kind() == T![*]),
+        _ => false,
+    })
+}
+
+/// A replacement of `range` made up of new text and source text, which may contain edits itself.
+struct Edit {
+    range: TextRange,
+    pieces: Vec<Piece>,
+}
+
+enum Piece {
+    Text(String),
+    Source(TextRange),
+}
+
+impl Edit {
+    fn render(&self, text: &str, edits: &[Edit]) -> String {
+        let mut buf = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(it) => buf.push_str(it),
+                Piece::Source(range) => {
+                    let mut offset = range.start();
+                    for edit in outermost(edits, *range) {
+                        buf.push_str(&text[TextRange::new(offset, edit.range.start())]);
+                        buf.push_str(&edit.render(text, edits));
+                        offset = edit.range.end();
+                    }
+                    buf.push_str(&text[TextRange::new(offset, range.end())]);
+                }
+            }
+        }
+        buf
+    }
+}
+
+/// The edits within `range` that are not nested in other edits within it, in source order.
+fn outermost(edits: &[Edit], range: TextRange) -> Vec<&Edit> {
+    let mut inside: Vec<&Edit> = edits.iter().filter(|it| range.contains_range(it.range)).collect();
+    inside.sort_by_key(|it| (it.range.start(), std::cmp::Reverse(it.range.len())));
+    let mut result: Vec<&Edit> = Vec::new();
+    for edit in inside {
+        if result.last().map_or(true, |last| last.range.end() <= edit.range.start()) {
+            result.push(edit);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        check_assist, check_assist_by_label, check_assist_not_applicable, check_assist_with_config,
+        TEST_CONFIG,
+    };
+    use crate::AssistConfig;
+
+    use super::*;
+
+    #[test]
+    fn rewrites_reads_and_writes_of_copy_field() {
+        check_assist(
+            encapsulate_field,
+            r#"
+//- minicore: copy
+struct Point {
+    pub(crate) x$0: i32,
+    y: i32,
+}
+
+impl Point {
+    fn len(&self) -> i32 { self.x + self.y }
+}
+
+fn shift(p: &mut Point, q: &Point) {
+    p.x = q.x;
+    p.x += 1 + q.x;
+    let _ = (p.x, &p.x);
+}
+"#,
+            r#"
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    fn len(&self) -> i32 { self.x + self.y }
+
+    pub(crate) fn $0x(&self) -> i32 {
+        self.x
+    }
+
+    pub(crate) fn set_x(&mut self, x: i32) {
+        self.x = x;
+    }
+}
+
+fn shift(p: &mut Point, q: &Point) {
+    p.set_x(q.x());
+    p.set_x(p.x() + (1 + q.x()));
+    let _ = (p.x(), &p.x());
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn borrows_non_copy_field() {
+        check_assist_by_label(
+            encapsulate_field,
+            r#"
+struct Name;
+impl Name {
+    fn len(&self) -> usize { 0 }
+    fn clear(&mut self) {}
+}
+pub struct User {
+    pub name$0: Name,
+}
+fn f(u: &mut User, v: User) {
+    let a = &u.name;
+    let b = u.name.len();
+    u.name.clear();
+    let c = &mut u.name;
+    let d = v.name;
+}
+"#,
+            r#"
+struct Name;
+impl Name {
+    fn len(&self) -> usize { 0 }
+    fn clear(&mut self) {}
+}
+pub struct User {
+    name: Name,
+}
+
+impl User {
+    pub fn $0name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: Name) {
+        self.name = name;
+    }
+}
+fn f(u: &mut User, v: User) {
+    let a = u.name();
+    let b = u.name().len();
+    u.name.clear();
+    let c = &mut u.name;
+    let d = v.name;
+}
+"#,
+            "Encapsulate field (3 accesses left unchanged)",
+        );
+    }
+
+    #[test]
+    fn rewrites_accesses_in_other_files() {
+        check_assist(
+            encapsulate_field,
+            r#"
+//- minicore: copy
+//- /main.rs
+mod shape;
+fn area(s: &mut shape::Square) -> u8 {
+    s.side = 2;
+    s.side * s.side
+}
+//- /shape.rs
+pub struct Square<T> {
+    pub side$0: u8,
+    marker: T,
+}
+"#,
+            r#"
+//- /main.rs
+mod shape;
+fn area(s: &mut shape::Square) -> u8 {
+    s.set_side(2);
+    s.side() * s.side()
+}
+//- /shape.rs
+pub struct Square<T> {
+    side: u8,
+    marker: T,
+}
+
+impl<T> Square<T> {
+    pub fn $0side(&self) -> u8 {
+        self.side
+    }
+
+    pub fn set_side(&mut self, side: u8) {
+        self.side = side;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rewrites_accesses_in_impl_when_configured() {
+        check_assist_with_config(
+            encapsulate_field,
+            AssistConfig { encapsulate_field_skip_impl: false, ..TEST_CONFIG },
+            r#"
+//- minicore: copy
+struct S {
+    f$0: bool,
+}
+impl S {
+    fn flip(&mut self) {
+        self.f = !self.f;
+    }
+}
+"#,
+            r#"
+struct S {
+    f: bool,
+}
+impl S {
+    fn flip(&mut self) {
+        self.set_f(!self.f());
+    }
+
+    fn $0f(&self) -> bool {
+        self.f
+    }
+
+    fn set_f(&mut self, f: bool) {
+        self.f = f;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_visibility_for_unchanged_accesses_in_other_modules() {
+        check_assist_by_label(
+            encapsulate_field,
+            r#"
+//- /main.rs
+mod user;
+fn f(u: &mut user::User) {
+    let a = &u.name;
+    u.name.clear();
+}
+//- /user.rs
+pub struct Name;
+impl Name {
+    pub fn clear(&mut self) {}
+}
+pub struct User {
+    pub name$0: Name,
+}
+"#,
+            r#"
+//- /main.rs
+mod user;
+fn f(u: &mut user::User) {
+    let a = u.name();
+    u.name.clear();
+}
+//- /user.rs
+pub struct Name;
+impl Name {
+    pub fn clear(&mut self) {}
+}
+pub struct User {
+    pub name: Name,
+}
+
+impl User {
+    pub fn $0name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: Name) {
+        self.name = name;
+    }
+}
+"#,
+            "Encapsulate field (1 access left unchanged)",
+        );
+    }
+
+    #[test]
+    fn rewrites_accesses_in_impls_outside_module() {
+        check_assist(
+            encapsulate_field,
+            r#"
+//- minicore: copy
+//- /main.rs
+mod m;
+impl m::S {
+    fn flip(&mut self) {
+        self.f = !self.f;
+    }
+}
+//- /m.rs
+pub struct S {
+    pub f$0: bool,
+}
+"#,
+            r#"
+//- /main.rs
+mod m;
+impl m::S {
+    fn flip(&mut self) {
+        self.set_f(!self.f());
+    }
+}
+//- /m.rs
+pub struct S {
+    f: bool,
+}
+
+impl S {
+    pub fn $0f(&self) -> bool {
+        self.f
+    }
+
+    pub fn set_f(&mut self, f: bool) {
+        self.f = f;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_accessors_exist() {
+        check_assist_not_applicable(
+            encapsulate_field,
+            r#"
+struct S {
+    f$0: u32,
+}
+impl S {
+    fn set_f(&mut self, f: u32) {}
+}
+"#,
+        );
+        check_assist_not_applicable(
+            encapsulate_field,
+            r#"
+struct S(u32$0);
+"#,
+        );
+    }
+}
@@ -6,13 +6,13 @@ use std::{
 use hir::{HasSource, HirFileIdExt, ModuleSource};
 use ide_db::{
     assists::{AssistId, AssistKind},
-    base_db::FileId,
+    base_db::{AnchoredPathBuf, FileId},
     defs::{Definition, NameClass, NameRefClass},
     search::{FileReference, SearchScope},
 };
 use itertools::Itertools;
 use smallvec::SmallVec;
-use stdx::format_to;
+use stdx::{format_to, to_lower_snake_case};
 use syntax::{
     algo::find_node_at_range,
     ast::{
@@ -207,6 +207,171 @@ pub(crate) fn extract_module(acc: &mut Assists, ctx: &AssistContext<'_>) -> Opti
     )
 }
 
+// Assist: split_module
+//
+// Moves the selected items into a new module file and re-exports them, so their paths don't
+// change. The module is named after the first selected item. References, visibility and imports
+// are resolved as for `extract_module`.
+//
+// ```
+// $0pub struct Parser;
+//
+// fn parse() -> Parser {
+//     Parser
+// }$0
+//
+// fn main() {
+//     parse();
+// }
+// ```
+// ->
+// ```
+// mod parser;
+// pub use parser::Parser;
+// use parser::parse;
+//
+// fn main() {
+//     parse();
+// }
+// ```
+pub(crate) fn split_module(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    if ctx.has_empty_selection() {
+        return None;
+    }
+
+    let node = match ctx.covering_element() {
+        syntax::NodeOrToken::Node(n) => n,
+        syntax::NodeOrToken::Token(t) => t.parent()?,
+    };
+    // Only items of the file's module, the file of a module inside an inline module or a block
+    // would go somewhere else.
+    if !node.ancestors().skip(1).all(|it| ast::SourceFile::can_cast(it.kind())) {
+        return None;
+    }
+    let parent_module = ctx.sema.to_module_def(ctx.file_id())?;
+
+    let mut module = extract_target(&node, ctx.selection_trimmed())?;
+    if module.body_items.is_empty() {
+        return None;
+    }
+
+    // The items to re-export, grouped by their visibility. Private items are only imported if
+    // something outside of the selection uses them.
+    let mut reexports: Vec<(String, Vec<String>)> = Vec::new();
+    for item in &module.body_items {
+        if matches!(
+            item,
+            ast::Item::Impl(_)
+                | ast::Item::MacroCall(_)
+                | ast::Item::MacroRules(_)
+                | ast::Item::Use(_)
+        ) {
+            continue;
+        }
+        let Some(name) = ast::AnyHasName::cast(item.syntax().clone()).and_then(|it| it.name())
+        else {
+            continue;
+        };
+        let vis = ast::AnyHasVisibility::cast(item.syntax().clone()).and_then(|it| it.visibility());
+        let vis = match vis {
+            Some(vis) => format!("{vis} "),
+            None => {
+                let Some(NameClass::Definition(def)) = NameClass::classify(&ctx.sema, &name) else {
+                    continue;
+                };
+                let used_outside = def.usages(&ctx.sema).all().iter().any(|(file_id, refs)| {
+                    *file_id != ctx.file_id()
+                        || refs.iter().any(|it| !module.text_range.contains_range(it.range))
+                });
+                if !used_outside {
+                    continue;
+                }
+                String::new()
+            }
+        };
+        let name = name.to_string();
+        match reexports.iter_mut().find(|(it, _)| *it == vis) {
+            Some((_, names)) => names.push(name),
+            None => reexports.push((vis, vec![name])),
+        }
+    }
+
+    let old_item_indent = module.body_items[0].indent_level();
+    let module_name = split_module_name(ctx, parent_module, &module.body_items);
+
+    acc.add(
+        AssistId("split_module", AssistKind::RefactorExtract),
+        "Split items into a new module file",
+        module.text_range,
+        |builder| {
+            // The paths to the items stay valid through the re-exports, so the usages aren't
+            // updated.
+            let (_, record_fields) = module.get_usages_and_record_fields(ctx);
+            let import_paths_to_be_removed = module.resolve_imports(None, ctx);
+            module.change_visibility(record_fields);
+
+            let items = module
+                .use_items
+                .iter()
+                .cloned()
+                .chain(module.body_items.iter().map(|it| it.dedent(old_item_indent)))
+                .join("\n\n");
+            let contents = format!("{items}\n");
+
+            let mut module_def = format!("mod {module_name};");
+            for (vis, names) in reexports {
+                let names = match &names[..] {
+                    [name] => name.clone(),
+                    names => format!("{{{}}}", names.join(", ")),
+                };
+                format_to!(module_def, "\n{old_item_indent}{vis}use {module_name}::{names};");
+            }
+
+            for import_path_text_range in import_paths_to_be_removed {
+                builder.delete(import_path_text_range);
+            }
+            builder.replace(module.text_range, module_def);
+
+            let mut path = String::from("./");
+            match parent_module.name(ctx.db()) {
+                Some(name) if !parent_module.is_mod_rs(ctx.db()) => {
+                    format_to!(path, "{}/", name.display(ctx.db()))
+                }
+                _ => (),
+            }
+            format_to!(path, "{module_name}.rs");
+            builder.create_file(AnchoredPathBuf { anchor: ctx.file_id(), path }, contents);
+        },
+    )
+}
+
+/// Names the new module after the first selected item with a name, or after the module it's
+/// split from, without clashing with the other submodules.
+fn split_module_name(
+    ctx: &AssistContext<'_>,
+    parent_module: hir::Module,
+    items: &[ast::Item],
+) -> String {
+    let db = ctx.db();
+    let base = items
+        .iter()
+        .find_map(|it| ast::AnyHasName::cast(it.syntax().clone())?.name())
+        .map(|it| to_lower_snake_case(it.text().as_str()))
+        .or_else(|| Some(parent_module.name(db)?.display(db).to_string()))
+        .unwrap_or_else(|| "modname".to_owned());
+    let taken: HashSet<String> = parent_module
+        .children(db)
+        .filter_map(|it| Some(it.name(db)?.display(db).to_string()))
+        .collect();
+    let mut name = base.clone();
+    let mut counter = 1;
+    while taken.contains(&name) {
+        name = format!("{base}{counter}");
+        counter += 1;
+    }
+    name
+}
+
 #[derive(Debug)]
 struct Module {
     text_range: TextRange,
@@ -433,7 +598,10 @@ impl Module {
                     }
                 }
 
-                if let Some(name_ref) = ast::NameRef::cast(x) {
+                // `crate` and `self` in paths and visibilities don't need imports.
+                if let Some(name_ref) =
+                    ast::NameRef::cast(x).filter(|it| it.ident_token().is_some())
+                {
                     if let Some(name_classify) = NameRefClass::classify(&ctx.sema, &name_ref) {
                         //Necessary to avoid two same names going through
                         if !node_set.contains(&name_ref.syntax().to_string()) {
@@ -1806,6 +1974,164 @@ mod modname {
         pub(crate) condvar: B,
     }
 }
+"#,
+        );
+    }
+
+    #[test]
+    fn test_extract_module_with_crate_visibility() {
+        check_assist(
+            extract_module,
+            r#"
+//- /main.rs
+mod lexer;
+use crate::lexer::Token;
+
+$0pub(crate) struct Parser {
+    tokens: Token,
+}$0
+//- /lexer.rs
+pub struct Token;
+"#,
+            r#"
+mod lexer;
+use crate::lexer::Token;
+
+mod modname {
+    use crate::lexer::Token;
+
+    pub(crate) struct Parser {
+        pub(crate) tokens: Token,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn split_module_reexports_items() {
+        check_assist(
+            split_module,
+            r#"
+//- /main.rs
+mod lexer;
+mod parser;
+use lexer::Token;
+
+fn main() {
+    parser::Parser::new();
+}
+//- /parser.rs
+use crate::lexer::Token;
+
+$0pub struct Parser {
+    tokens: Vec<Token>,
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser { tokens: Vec::new() }
+    }
+}
+
+pub(crate) fn helper() -> u32 {
+    private()
+}
+
+fn private() -> u32 {
+    0
+}$0
+
+fn outside(_: Token) -> u32 {
+    helper()
+}
+//- /lexer.rs
+pub struct Token;
+"#,
+            r#"
+//- /parser.rs
+use crate::lexer::Token;
+
+mod parser;
+pub use parser::Parser;
+pub(crate) use parser::helper;
+
+fn outside(_: Token) -> u32 {
+    helper()
+}
+//- /parser/parser.rs
+use crate::lexer::Token;
+
+pub struct Parser {
+    pub(crate) tokens: Vec<Token>,
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser { tokens: Vec::new() }
+    }
+}
+
+pub(crate) fn helper() -> u32 {
+    private()
+}
+
+pub(crate) fn private() -> u32 {
+    0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn split_module_name_skips_impls_and_existing_modules() {
+        check_assist(
+            split_module,
+            r#"
+//- /main.rs
+mod helpers;
+
+struct S;
+
+$0impl S {}
+
+fn helpers() {}$0
+
+fn main() {
+    helpers();
+}
+//- /helpers.rs
+"#,
+            r#"
+//- /main.rs
+mod helpers;
+
+struct S;
+
+mod helpers1;
+use helpers1::helpers;
+
+fn main() {
+    helpers();
+}
+//- /helpers1.rs
+use super::S;
+
+impl S {}
+
+pub(crate) fn helpers() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn split_module_not_applicable_inside_inline_module() {
+        check_assist_not_applicable(
+            split_module,
+            r#"
+mod m {
+    $0fn f() {}$0
+}
 "#,
         );
     }
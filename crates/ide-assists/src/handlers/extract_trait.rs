@@ -0,0 +1,572 @@
+use hir::ModuleDef;
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    helpers::mod_path_to_ast,
+    imports::insert_use::{insert_use, ImportScope},
+    search::FileReference,
+    source_change::SourceChangeBuilder,
+    FxHashMap, FxHashSet,
+};
+use syntax::{
+    ast::{self, edit::IndentLevel, make, HasGenericParams, HasName, HasVisibility},
+    ted, AstNode, SyntaxKind,
+};
+
+use super::generate_trait_from_impl::strip_body;
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: extract_trait
+//
+// Moves the selected methods of an inherent impl into a new trait that is implemented for the
+// type, importing the trait wherever the methods are used.
+//
+// ```
+// struct Counter(u32);
+//
+// impl Counter {
+//     pub fn new() -> Self {
+//         Counter(0)
+//     }
+//
+//     $0pub fn increment(&mut self) {
+//         self.0 += 1;
+//     }$0
+// }
+// ```
+// ->
+// ```
+// struct Counter(u32);
+//
+// impl Counter {
+//     pub fn new() -> Self {
+//         Counter(0)
+//     }
+// }
+//
+// pub trait NewTrait {
+//     fn increment(&mut self);
+// }
+//
+// impl NewTrait for Counter {
+//     fn increment(&mut self) {
+//         self.0 += 1;
+//     }
+// }
+// ```
+pub(crate) fn extract_trait(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let assoc_items = ctx.covering_element().ancestors().find_map(ast::AssocItemList::cast)?;
+    let impl_ = ast::Impl::cast(assoc_items.syntax().parent()?)?;
+    if impl_.trait_().is_some()
+        || impl_.generic_param_list().is_some()
+        || impl_.where_clause().is_some()
+    {
+        return None;
+    }
+    let self_ty = impl_.self_ty()?;
+
+    let selected: Vec<ast::Fn> = if ctx.has_empty_selection() {
+        // Without a selection, the cursor has to be on the method's name.
+        let fn_ = ctx.find_node_at_offset::<ast::Fn>()?;
+        if !fn_.name()?.syntax().text_range().contains_inclusive(ctx.offset()) {
+            return None;
+        }
+        vec![fn_]
+    } else {
+        let selection = ctx.selection_trimmed();
+        let items: Vec<_> = assoc_items
+            .assoc_items()
+            .filter(|it| it.syntax().text_range().intersect(selection).is_some())
+            .collect();
+        items
+            .into_iter()
+            .map(|it| match it {
+                ast::AssocItem::Fn(it) => Some(it),
+                _ => None,
+            })
+            .collect::<Option<_>>()?
+    };
+    // Trait functions can't be `const` (E0379).
+    if selected.is_empty() || selected.iter().any(|it| it.const_token().is_some()) {
+        return None;
+    }
+
+    let selected_indices: Vec<usize> = assoc_items
+        .assoc_items()
+        .enumerate()
+        .filter(|(_, item)| selected.iter().any(|it| it.syntax() == item.syntax()))
+        .map(|(idx, _)| idx)
+        .collect();
+    let impl_module = ctx.sema.scope(impl_.syntax())?.module();
+    let funcs = selected.iter().map(|it| ctx.sema.to_def(it)).collect::<Option<Vec<_>>>()?;
+    let target =
+        selected.iter().map(|it| it.syntax().text_range()).reduce(|acc, it| acc.cover(it))?;
+
+    acc.add(
+        AssistId("extract_trait", AssistKind::RefactorExtract),
+        "Extract methods into trait",
+        target,
+        |builder| {
+            let trait_name = "NewTrait";
+            let trait_path = make::ext::ident_path(trait_name);
+
+            // Uses of the methods outside of the impl's module need the trait to be imported.
+            let mut references: FxHashMap<FileId, Vec<FileReference>> = FxHashMap::default();
+            for func in funcs {
+                for (file_id, refs) in Definition::Function(func).usages(&ctx.sema).all() {
+                    references.entry(file_id).or_default().extend(refs);
+                }
+            }
+            let mut visited_modules = FxHashSet::default();
+            visited_modules.insert(impl_module);
+            // Edit the current file last, as we only want to switch to it in the builder once.
+            let def_file_references = references.remove(&ctx.file_id());
+            for (file_id, refs) in references {
+                builder.edit_file(file_id);
+                let imports = import_scopes(
+                    ctx,
+                    builder,
+                    &mut visited_modules,
+                    impl_module,
+                    &trait_path,
+                    refs,
+                );
+                for (scope, path) in imports {
+                    insert_use(&scope, path, &ctx.config.insert_use);
+                }
+            }
+            builder.edit_file(ctx.file_id());
+            let imports = def_file_references
+                .map(|refs| {
+                    import_scopes(
+                        ctx,
+                        builder,
+                        &mut visited_modules,
+                        impl_module,
+                        &trait_path,
+                        refs,
+                    )
+                })
+                .unwrap_or_default();
+            let impl_ = builder.make_mut(impl_.clone());
+            let assoc_items_mut = builder.make_mut(assoc_items.clone());
+            for (scope, path) in imports {
+                insert_use(&scope, path, &ctx.config.insert_use);
+            }
+
+            let trait_items = assoc_items.clone_for_update();
+            let impl_items = assoc_items.clone_for_update();
+            retain_items(&trait_items, |idx| selected_indices.contains(&idx));
+            retain_items(&impl_items, |idx| selected_indices.contains(&idx));
+
+            // The trait gets the visibility of the most visible method, so that it's usable
+            // wherever they were.
+            let vis = selected
+                .iter()
+                .filter_map(|it| it.visibility())
+                .find(|it| it.syntax().text() == "pub")
+                .or_else(|| selected.iter().find_map(|it| it.visibility()));
+            trait_items.assoc_items().for_each(|item| {
+                strip_body(&item);
+                remove_items_visibility(&item);
+            });
+            impl_items.assoc_items().for_each(|item| remove_items_visibility(&item));
+
+            let trait_ =
+                make::trait_(false, trait_name, None, None, trait_items).clone_for_update();
+            if let Some(vis) = vis {
+                ted::insert_all(
+                    ted::Position::first_child_of(trait_.syntax()),
+                    vec![
+                        vis.syntax().clone_for_update().into(),
+                        make::tokens::single_space().into(),
+                    ],
+                );
+            }
+            let trait_impl = make::impl_trait(
+                false,
+                None,
+                None,
+                None,
+                None,
+                false,
+                make::ty(trait_name),
+                self_ty.clone(),
+                None,
+                None,
+                None,
+            )
+            .clone_for_update();
+            if let Some(list) = trait_impl.assoc_item_list() {
+                ted::replace(list.syntax(), impl_items.syntax());
+            }
+
+            let indent = IndentLevel::from_node(impl_.syntax());
+            let new_items = vec![
+                trait_.syntax().clone().into(),
+                make::tokens::whitespace(&format!("\n\n{indent}")).into(),
+                trait_impl.syntax().clone().into(),
+            ];
+            if selected_indices.len() == assoc_items.assoc_items().count() {
+                ted::replace_with_many(impl_.syntax(), new_items);
+            } else {
+                retain_items(&assoc_items_mut, |idx| !selected_indices.contains(&idx));
+                let mut elements = vec![make::tokens::whitespace(&format!("\n\n{indent}")).into()];
+                elements.extend(new_items);
+                ted::insert_all_raw(ted::Position::after(impl_.syntax()), elements);
+            }
+        },
+    )
+}
+
+/// Trait items share the visibility of the trait, so it's removed along with the whitespace after
+/// it.
+fn remove_items_visibility(item: &ast::AssocItem) {
+    let vis = match item {
+        ast::AssocItem::Const(c) => c.visibility(),
+        ast::AssocItem::Fn(f) => f.visibility(),
+        ast::AssocItem::TypeAlias(t) => t.visibility(),
+        _ => None,
+    };
+    if let Some(vis) = vis {
+        if let Some(ws) = vis.syntax().next_sibling_or_token() {
+            if ws.kind() == SyntaxKind::WHITESPACE {
+                ted::remove(ws);
+            }
+        }
+        ted::remove(vis.syntax());
+    }
+}
+
+/// Computes the imports of the trait needed by the references, at most one per module.
+fn import_scopes(
+    ctx: &AssistContext<'_>,
+    builder: &mut SourceChangeBuilder,
+    visited_modules: &mut FxHashSet<hir::Module>,
+    impl_module: hir::Module,
+    trait_path: &ast::Path,
+    refs: Vec<FileReference>,
+) -> Vec<(ImportScope, ast::Path)> {
+    refs.into_iter()
+        .filter_map(|reference| {
+            let node = reference.name.as_name_ref()?.syntax().clone();
+            let module = ctx.sema.scope(&node)?.module();
+            if visited_modules.contains(&module) {
+                return None;
+            }
+            let mod_path = module.find_use_path_prefixed(
+                ctx.db(),
+                ModuleDef::Module(impl_module),
+                ctx.config.insert_use.prefix_kind,
+                ctx.config.prefer_no_std,
+                ctx.config.prefer_prelude,
+            )?;
+            let scope_node = builder.make_syntax_mut(node);
+            let scope = ImportScope::find_insert_use_container(&scope_node, &ctx.sema)?;
+            visited_modules.insert(module);
+            Some((scope, make::path_concat(mod_path_to_ast(&mod_path), trait_path.clone())))
+        })
+        .collect()
+}
+
+/// Removes the items of the (mutable) item list whose index doesn't satisfy `keep`, along with
+/// the whitespace in front of them.
+fn retain_items(list: &ast::AssocItemList, keep: impl Fn(usize) -> bool) {
+    let removed: Vec<_> =
+        list.assoc_items().enumerate().filter(|&(idx, _)| !keep(idx)).map(|(_, it)| it).collect();
+    for item in removed {
+        if let Some(ws) = item.syntax().prev_sibling_or_token() {
+            if ws.kind() == SyntaxKind::WHITESPACE {
+                ted::remove(ws);
+            }
+        }
+        ted::remove(item.syntax());
+    }
+    // The first remaining item may have been preceded by a blank line.
+    if let Some(first) = list.assoc_items().next() {
+        if let Some(ws) = first.syntax().prev_sibling_or_token() {
+            if ws.kind() == SyntaxKind::WHITESPACE && ws.to_string().matches('\n').count() > 1 {
+                let indent = IndentLevel::from_node(first.syntax());
+                ted::replace(ws, make::tokens::whitespace(&format!("\n{indent}")));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extract_selected_methods() {
+        check_assist(
+            extract_trait,
+            r#"
+struct Counter(u32);
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter(0)
+    }
+
+    $0pub fn increment(&mut self) {
+        self.0 += 1;
+    }
+
+    pub(crate) fn get(&self) -> u32 {
+        self.0
+    }$0
+}
+"#,
+            r#"
+struct Counter(u32);
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter(0)
+    }
+}
+
+pub trait NewTrait {
+    fn increment(&mut self);
+
+    fn get(&self) -> u32;
+}
+
+impl NewTrait for Counter {
+    fn increment(&mut self) {
+        self.0 += 1;
+    }
+
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_method_at_name() {
+        check_assist(
+            extract_trait,
+            r#"
+struct Counter(u32);
+
+impl Counter {
+    fn get(&self) -> u32 {
+        self.0
+    }
+
+    fn res$0et(&mut self) {
+        self.0 = 0;
+    }
+}
+"#,
+            r#"
+struct Counter(u32);
+
+impl Counter {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+trait NewTrait {
+    fn reset(&mut self);
+}
+
+impl NewTrait for Counter {
+    fn reset(&mut self) {
+        self.0 = 0;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_all_methods_replaces_impl() {
+        check_assist(
+            extract_trait,
+            r#"
+mod m {
+    pub struct Counter(u32);
+
+    impl Counter {
+        $0pub fn get(&self) -> u32 {
+            self.0
+        }$0
+    }
+}
+"#,
+            r#"
+mod m {
+    pub struct Counter(u32);
+
+    pub trait NewTrait {
+        fn get(&self) -> u32;
+    }
+
+    impl NewTrait for Counter {
+        fn get(&self) -> u32 {
+            self.0
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_trait_at_usages() {
+        check_assist(
+            extract_trait,
+            r#"
+//- /main.rs
+mod counter;
+mod user;
+
+use counter::Counter;
+
+fn main() {
+    let c = Counter(0);
+    c.get();
+}
+
+//- /counter.rs
+pub struct Counter(pub u32);
+
+impl Counter {
+    pub fn g$0et(&self) -> u32 {
+        self.0
+    }
+
+    fn helper(&self) -> u32 {
+        self.get()
+    }
+}
+
+//- /user.rs
+use crate::counter::Counter;
+
+fn first(c: &Counter) -> u32 {
+    c.get()
+}
+
+fn second(c: &Counter) -> u32 {
+    Counter::get(c)
+}
+"#,
+            r#"
+//- /main.rs
+mod counter;
+mod user;
+
+use counter::{Counter, NewTrait};
+
+fn main() {
+    let c = Counter(0);
+    c.get();
+}
+
+//- /counter.rs
+pub struct Counter(pub u32);
+
+impl Counter {
+    fn helper(&self) -> u32 {
+        self.get()
+    }
+}
+
+pub trait NewTrait {
+    fn get(&self) -> u32;
+}
+
+impl NewTrait for Counter {
+    fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+//- /user.rs
+use crate::counter::{Counter, NewTrait};
+
+fn first(c: &Counter) -> u32 {
+    c.get()
+}
+
+fn second(c: &Counter) -> u32 {
+    Counter::get(c)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_trait_impl() {
+        check_assist_not_applicable(
+            extract_trait,
+            r#"
+trait T {
+    fn f(&self);
+}
+struct S;
+impl T for S {
+    fn f$0(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_generic_impl() {
+        check_assist_not_applicable(
+            extract_trait,
+            r#"
+struct S<T>(T);
+impl<T> S<T> {
+    fn f$0(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_non_fn_items_or_const_fns() {
+        check_assist_not_applicable(
+            extract_trait,
+            r#"
+struct S;
+impl S {
+    $0const C: u32 = 0;
+    fn f(&self) {}$0
+}
+"#,
+        );
+        check_assist_not_applicable(
+            extract_trait,
+            r#"
+struct S;
+impl S {
+    const fn f$0(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_body() {
+        check_assist_not_applicable(
+            extract_trait,
+            r#"
+struct S;
+impl S {
+    fn f(&self) { $0 }
+}
+"#,
+        );
+    }
+}
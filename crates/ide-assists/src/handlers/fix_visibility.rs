@@ -62,11 +62,7 @@ fn add_vis_to_referenced_module_def(acc: &mut Assists, ctx: &AssistContext<'_>)
 
     let (vis_owner, target, target_file, target_name) = target_data_for_def(ctx.db(), def)?;
 
-    let missing_visibility = if current_module.krate() == target_module.krate() {
-        make::visibility_pub_crate()
-    } else {
-        make::visibility_pub()
-    };
+    let missing_visibility = missing_visibility(current_module, target_module);
 
     let assist_label = match target_name {
         None => format!("Change visibility to {missing_visibility}"),
@@ -101,24 +97,9 @@ fn add_vis_to_referenced_record_field(acc: &mut Assists, ctx: &AssistContext<'_>
     let parent_name = parent.name(ctx.db());
     let target_module = parent.module(ctx.db());
 
-    let in_file_source = record_field_def.source(ctx.db())?;
-    let (vis_owner, target) = match in_file_source.value {
-        hir::FieldSource::Named(it) => {
-            let range = it.syntax().text_range();
-            (ast::AnyHasVisibility::new(it), range)
-        }
-        hir::FieldSource::Pos(it) => {
-            let range = it.syntax().text_range();
-            (ast::AnyHasVisibility::new(it), range)
-        }
-    };
+    let (vis_owner, target, target_file) = target_data_for_field(ctx.db(), record_field_def)?;
 
-    let missing_visibility = if current_module.krate() == target_module.krate() {
-        make::visibility_pub_crate()
-    } else {
-        make::visibility_pub()
-    };
-    let target_file = in_file_source.file_id.original_file(ctx.db());
+    let missing_visibility = missing_visibility(current_module, target_module);
 
     let target_name = record_field_def.name(ctx.db());
     let assist_label = format!(
@@ -139,7 +120,37 @@ fn add_vis_to_referenced_record_field(acc: &mut Assists, ctx: &AssistContext<'_>
     })
 }
 
-fn target_data_for_def(
+/// The visibility an item of `target_module` needs to be accessible from `current_module`.
+pub(crate) fn missing_visibility(
+    current_module: hir::Module,
+    target_module: hir::Module,
+) -> ast::Visibility {
+    if current_module.krate() == target_module.krate() {
+        make::visibility_pub_crate()
+    } else {
+        make::visibility_pub()
+    }
+}
+
+pub(crate) fn target_data_for_field(
+    db: &dyn HirDatabase,
+    field: hir::Field,
+) -> Option<(ast::AnyHasVisibility, TextRange, FileId)> {
+    let in_file_source = field.source(db)?;
+    let (vis_owner, target) = match in_file_source.value {
+        hir::FieldSource::Named(it) => {
+            let range = it.syntax().text_range();
+            (ast::AnyHasVisibility::new(it), range)
+        }
+        hir::FieldSource::Pos(it) => {
+            let range = it.syntax().text_range();
+            (ast::AnyHasVisibility::new(it), range)
+        }
+    };
+    Some((vis_owner, target, in_file_source.file_id.original_file(db.upcast())))
+}
+
+pub(crate) fn target_data_for_def(
     db: &dyn HirDatabase,
     def: hir::ModuleDef,
 ) -> Option<(ast::AnyHasVisibility, TextRange, FileId, Option<hir::Name>)> {
@@ -0,0 +1,401 @@
+use ide_db::famous_defs::FamousDefs;
+use stdx::format_to;
+use syntax::{
+    ast::{self, make, AstNode, HasAttrs, HasGenericParams, HasName, HasVisibility, StructKind},
+    SourceFile,
+};
+
+use crate::{
+    utils::{find_impl_block_start, find_struct_impl, generate_impl_text},
+    AssistContext, AssistId, AssistKind, Assists,
+};
+
+// Assist: generate_builder
+//
+// Generates a builder for a struct, with a setter per field and a `build` method that fails if a
+// field without a default value hasn't been set.
+//
+// ```
+// # //- minicore: option, default, builtin_impls
+// # struct String;
+// struct Config {
+//     name: String,
+//     port: u16,$0
+//     label: Option<String>,
+// }
+// ```
+// ->
+// ```
+// # struct String;
+// struct Config {
+//     name: String,
+//     port: u16,
+//     label: Option<String>,
+// }
+//
+// impl Config {
+//     fn $0builder() -> ConfigBuilder {
+//         ConfigBuilder { name: None, port: None, label: None }
+//     }
+// }
+//
+// struct ConfigBuilder {
+//     name: Option<String>,
+//     port: Option<u16>,
+//     label: Option<String>,
+// }
+//
+// impl ConfigBuilder {
+//     fn name(mut self, name: String) -> Self {
+//         self.name = Some(name);
+//         self
+//     }
+//
+//     fn port(mut self, port: u16) -> Self {
+//         self.port = Some(port);
+//         self
+//     }
+//
+//     fn label(mut self, label: String) -> Self {
+//         self.label = Some(label);
+//         self
+//     }
+//
+//     fn build(self) -> Result<Config, String> {
+//         Ok(Config {
+//             name: self.name.ok_or("`name` is not set")?,
+//             port: self.port.unwrap_or_default(),
+//             label: self.label,
+//         })
+//     }
+// }
+// ```
+pub(crate) fn generate_builder(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let strukt = ctx.find_node_at_offset::<ast::Struct>()?;
+    let StructKind::Record(field_list) = strukt.kind() else { return None };
+    let struct_name = strukt.name()?;
+    let builder_name = format!("{struct_name}Builder");
+
+    let adt = ast::Adt::Struct(strukt.clone());
+    let impl_def = find_struct_impl(ctx, &adt, &[String::from("builder")])?;
+    let scope = ctx.sema.scope(strukt.syntax())?;
+    if scope.speculative_resolve(&make::ext::ident_path(&builder_name)).is_some() {
+        cov_mark::hit!(generate_builder_name_taken);
+        return None;
+    }
+
+    let famous_defs = FamousDefs(&ctx.sema, scope.krate());
+    let option = famous_defs.core_option_Option();
+    let default = famous_defs.core_default_Default();
+    let mut fields = Vec::new();
+    for field in field_list.fields() {
+        let name = field.name()?;
+        let ty = field.ty()?;
+        let hir_ty = ctx.sema.resolve_type(&ty)?;
+        let kind = match option_inner_type(&ty) {
+            Some(inner) if hir_ty.as_adt() == option.map(hir::Adt::Enum) => {
+                FieldKind::Optional(inner)
+            }
+            _ if default.map_or(false, |it| hir_ty.impls_trait(ctx.db(), it, &[])) => {
+                FieldKind::Defaulted
+            }
+            _ => FieldKind::Required,
+        };
+        fields.push(BuilderField { name, ty, kind });
+    }
+
+    let target = strukt.syntax().text_range();
+    acc.add(
+        AssistId("generate_builder", AssistKind::Generate),
+        format!("Generate `{builder_name}`"),
+        target,
+        |builder| {
+            let vis = strukt.visibility().map_or(String::new(), |v| format!("{v} "));
+            let generic_args = strukt
+                .generic_param_list()
+                .map_or(String::new(), |it| it.to_generic_args().to_string());
+
+            let mut builder_fn = String::new();
+            if impl_def.is_some() {
+                builder_fn.push('\n');
+            }
+            let unset = fields.iter().map(|field| format!("{}: None", field.name));
+            format_to!(
+                builder_fn,
+                "    {vis}fn builder() -> {builder_name}{generic_args} {{\n        \
+                         {builder_name} {{ {} }}\n    \
+                     }}",
+                unset.collect::<Vec<_>>().join(", ")
+            );
+
+            let builder_struct = builder_struct(&strukt, &builder_name, &fields);
+            let mut methods = Vec::new();
+            for BuilderField { name, ty, kind } in &fields {
+                let param_ty = match kind {
+                    FieldKind::Optional(inner) => inner,
+                    FieldKind::Defaulted | FieldKind::Required => ty,
+                };
+                methods.push(format!(
+                    "    {vis}fn {name}(mut self, {name}: {param_ty}) -> Self {{\n        \
+                             self.{name} = Some({name});\n        \
+                             self\n    \
+                         }}"
+                ));
+            }
+            let mut build = format!(
+                "    {vis}fn build(self) -> Result<{struct_name}{generic_args}, String> {{\n        \
+                         Ok({struct_name} {{\n"
+            );
+            for BuilderField { name, kind, .. } in &fields {
+                match kind {
+                    FieldKind::Optional(_) => format_to!(build, "            {name}: self.{name},\n"),
+                    FieldKind::Defaulted => {
+                        format_to!(build, "            {name}: self.{name}.unwrap_or_default(),\n")
+                    }
+                    FieldKind::Required => format_to!(
+                        build,
+                        "            {name}: self.{name}.ok_or(\"`{name}` is not set\")?,\n"
+                    ),
+                }
+            }
+            build.push_str("        })\n    }");
+            methods.push(build);
+            let builder_impl = generate_impl_text(
+                &ast::Adt::Struct(builder_struct.clone()),
+                &methods.join("\n\n"),
+            );
+            let builder_items = format!("\n\n{builder_struct}{builder_impl}");
+
+            let struct_end = strukt.syntax().text_range().end();
+            let (builder_fn_offset, mut builder_fn) =
+                match impl_def.and_then(|impl_def| find_impl_block_start(impl_def, &mut builder_fn))
+                {
+                    Some(offset) => {
+                        builder.insert(struct_end, builder_items);
+                        (offset, builder_fn)
+                    }
+                    None => (struct_end, generate_impl_text(&adt, &builder_fn) + &builder_items),
+                };
+            match ctx.config.snippet_cap {
+                None => builder.insert(builder_fn_offset, builder_fn),
+                Some(cap) => {
+                    builder_fn = builder_fn.replacen("fn builder", "fn $0builder", 1);
+                    builder.insert_snippet(cap, builder_fn_offset, builder_fn);
+                }
+            }
+        },
+    )
+}
+
+struct BuilderField {
+    name: ast::Name,
+    ty: ast::Type,
+    kind: FieldKind,
+}
+
+enum FieldKind {
+    /// An `Option`, which the setter takes the inner type of.
+    Optional(ast::Type),
+    /// A type implementing `Default`, which is used if the field isn't set.
+    Defaulted,
+    Required,
+}
+
+/// `T` for `Option<T>`.
+fn option_inner_type(ty: &ast::Type) -> Option<ast::Type> {
+    let ast::Type::PathType(path_ty) = ty else { return None };
+    let segment = path_ty.path()?.segment()?;
+    if segment.name_ref()?.text() != "Option" {
+        return None;
+    }
+    let mut args = segment.generic_arg_list()?.generic_args();
+    match (args.next(), args.next()) {
+        (Some(ast::GenericArg::TypeArg(arg)), None) => arg.ty(),
+        _ => None,
+    }
+}
+
+fn builder_struct(
+    strukt: &ast::Struct,
+    builder_name: &str,
+    fields: &[BuilderField],
+) -> ast::Struct {
+    let mut buf = String::new();
+    // The impl generated for the builder copies these, like the one for the struct does.
+    let cfg_attrs = strukt
+        .attrs()
+        .filter(|attr| attr.as_simple_call().map_or(false, |(name, _arg)| name == "cfg"));
+    cfg_attrs.for_each(|attr| format_to!(buf, "{attr}\n"));
+    if let Some(vis) = strukt.visibility() {
+        format_to!(buf, "{vis} ");
+    }
+    format_to!(buf, "struct {builder_name}");
+    if let Some(generic_params) = strukt.generic_param_list() {
+        format_to!(buf, "{generic_params}");
+    }
+    if let Some(where_clause) = strukt.where_clause() {
+        format_to!(buf, " {where_clause}");
+    }
+    buf.push_str(" {\n");
+    for BuilderField { name, ty, kind } in fields {
+        match kind {
+            FieldKind::Optional(_) => format_to!(buf, "    {name}: {ty},\n"),
+            FieldKind::Defaulted | FieldKind::Required => {
+                format_to!(buf, "    {name}: Option<{ty}>,\n")
+            }
+        }
+    }
+    buf.push('}');
+    // FIXME: use `make` once it can create structs with where clauses and attributes
+    let parse = SourceFile::parse(&buf);
+    parse.tree().syntax().descendants().find_map(ast::Struct::cast).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn generates_builder() {
+        check_assist(
+            generate_builder,
+            r#"
+//- minicore: option, default, builtin_impls
+#[derive(Debug)]
+pub struct Config {
+    pub name: &'static str,
+    retries: Option<u8>,$0
+    level: u8,
+}
+"#,
+            r#"
+#[derive(Debug)]
+pub struct Config {
+    pub name: &'static str,
+    retries: Option<u8>,
+    level: u8,
+}
+
+impl Config {
+    pub fn $0builder() -> ConfigBuilder {
+        ConfigBuilder { name: None, retries: None, level: None }
+    }
+}
+
+pub struct ConfigBuilder {
+    name: Option<&'static str>,
+    retries: Option<u8>,
+    level: Option<u8>,
+}
+
+impl ConfigBuilder {
+    pub fn name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn retries(mut self, retries: u8) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    pub fn build(self) -> Result<Config, String> {
+        Ok(Config {
+            name: self.name.ok_or("`name` is not set")?,
+            retries: self.retries,
+            level: self.level.unwrap_or_default(),
+        })
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_struct_with_existing_impl() {
+        check_assist(
+            generate_builder,
+            r#"
+struct Pair<T> where T: Copy {
+    first: T,$0
+    second: T,
+}
+
+impl<T> Pair<T> where T: Copy {
+    fn first(&self) -> T { self.first }
+}
+"#,
+            r#"
+struct Pair<T> where T: Copy {
+    first: T,
+    second: T,
+}
+
+struct PairBuilder<T> where T: Copy {
+    first: Option<T>,
+    second: Option<T>,
+}
+
+impl<T> PairBuilder<T>
+where T: Copy
+{
+    fn first(mut self, first: T) -> Self {
+        self.first = Some(first);
+        self
+    }
+
+    fn second(mut self, second: T) -> Self {
+        self.second = Some(second);
+        self
+    }
+
+    fn build(self) -> Result<Pair<T>, String> {
+        Ok(Pair {
+            first: self.first.ok_or("`first` is not set")?,
+            second: self.second.ok_or("`second` is not set")?,
+        })
+    }
+}
+
+impl<T> Pair<T> where T: Copy {
+    fn $0builder() -> PairBuilder<T> {
+        PairBuilder { first: None, second: None }
+    }
+
+    fn first(&self) -> T { self.first }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_tuple_structs() {
+        check_assist_not_applicable(
+            generate_builder,
+            r#"
+struct Foo(u32$0);
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_existing_builder() {
+        cov_mark::check!(generate_builder_name_taken);
+        check_assist_not_applicable(
+            generate_builder,
+            r#"
+struct Foo {
+    a: u32,$0
+}
+struct FooBuilder;
+"#,
+        );
+    }
+}
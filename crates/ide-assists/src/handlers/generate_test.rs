@@ -0,0 +1,417 @@
+use hir::{AsAssocItem, ModuleSource};
+use ide_db::{base_db::FileId, famous_defs::FamousDefs};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasAttrs, HasModuleItem, HasName},
+    AstNode, SyntaxNode, TextSize,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: generate_test
+//
+// Adds a unit test calling the function to the `tests` module, creating the module if needed.
+//
+// ```
+// # //- minicore: default, builtin_impls
+// # struct Config;
+// pub fn parse$0(input: &str, config: &Config, limit: u32) -> u32 { 0 }
+// ```
+// ->
+// ```
+// # struct Config;
+// pub fn parse(input: &str, config: &Config, limit: u32) -> u32 { 0 }
+//
+// #[cfg(test)]
+// mod tests {
+//     use super::*;
+//
+//     #[test]
+//     fn test_parse() {
+//         let result = parse(todo!(), &todo!(), Default::default());
+//         assert_eq!(result, ${0:todo!()});
+//     }
+// }
+// ```
+pub(crate) fn generate_test(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let function: ast::Fn = ctx.find_node_at_offset()?;
+    if ctx.offset() >= function.body()?.syntax().text_range().start() {
+        return None;
+    }
+    let func = ctx.sema.to_def(&function)?;
+    if func.is_test(ctx.db())
+        || function.attrs().any(|attr| attr.simple_name().as_deref() == Some("test"))
+    {
+        return None;
+    }
+    if func
+        .as_assoc_item(ctx.db())
+        .and_then(|it| it.containing_trait_or_trait_impl(ctx.db()))
+        .is_some()
+    {
+        return None;
+    }
+
+    // The test module is a child of the module containing the function (or its impl).
+    let mut item = function.syntax().clone();
+    let mut path = String::new();
+    if let Some(impl_) = function.syntax().ancestors().nth(2).and_then(ast::Impl::cast) {
+        let ast::Type::PathType(self_ty) = impl_.self_ty()? else { return None };
+        format_to!(path, "{}::", self_ty.path()?.segment()?.name_ref()?);
+        item = impl_.syntax().clone();
+    }
+    let container = item.parent()?;
+    let items = module_items(&container)?;
+    format_to!(path, "{}", function.name()?);
+
+    let krate = ctx.sema.scope(function.syntax())?.krate();
+    let default = FamousDefs(&ctx.sema, krate).core_default_Default();
+    let args = func
+        .assoc_fn_params(ctx.db())
+        .iter()
+        .map(|param| placeholder(ctx.db(), param.ty(), default))
+        .join(", ");
+    let is_async = function.async_token().is_some();
+    let mut call = format!("{path}({args})");
+    if is_async {
+        call.push_str(".await");
+    }
+    let returns_unit = match function.ret_type().and_then(|it| it.ty()) {
+        Some(ast::Type::TupleType(tuple)) => tuple.fields().next().is_none(),
+        Some(_) => false,
+        None => true,
+    };
+    let test_attr = if is_async { async_test_attr(ctx.db(), krate) } else { "test" };
+
+    // Reuse an existing `tests` module.
+    let tests_module = items.iter().find_map(|item| match item {
+        ast::Item::Module(module) if module.name()?.text() == "tests" => Some(module.clone()),
+        _ => None,
+    });
+    let target = match &tests_module {
+        Some(module) => match module.item_list() {
+            Some(item_list) => {
+                let indent = IndentLevel::from_node(module.syntax()) + 1;
+                let offset = match item_list.items().last() {
+                    Some(last) => last.syntax().text_range().end(),
+                    None => item_list.l_curly_token()?.text_range().end(),
+                };
+                let names = fn_names(item_list.items());
+                Target::Existing { file_id: ctx.file_id(), offset, indent, names }
+            }
+            None => {
+                let source = ctx.sema.to_def(module)?.definition_source(ctx.db());
+                let ModuleSource::SourceFile(file) = source.value else { return None };
+                let offset = file
+                    .items()
+                    .last()
+                    .map_or(TextSize::from(0), |it| it.syntax().text_range().end());
+                let names = fn_names(file.items());
+                Target::Existing {
+                    file_id: source.file_id.file_id()?,
+                    offset,
+                    indent: IndentLevel(0),
+                    names,
+                }
+            }
+        },
+        None => {
+            let last = items.last()?;
+            Target::New {
+                offset: last.syntax().text_range().end(),
+                indent: IndentLevel::from_node(&item),
+            }
+        }
+    };
+
+    acc.add(
+        AssistId("generate_test", AssistKind::Generate),
+        "Generate test",
+        function.syntax().text_range(),
+        |builder| {
+            let (file_id, offset, indent, names, new_module_indent) = match target {
+                Target::Existing { file_id, offset, indent, names } => {
+                    (file_id, offset, indent, names, None)
+                }
+                Target::New { offset, indent } => {
+                    (ctx.file_id(), offset, indent + 1, Vec::new(), Some(indent))
+                }
+            };
+            let fn_name = function.name().map_or(String::new(), |it| it.to_string());
+            let base_name = format!("test_{fn_name}");
+            let test_name = (0..)
+                .map(|idx| if idx == 0 { base_name.clone() } else { format!("{base_name}_{idx}") })
+                .find(|name| !names.contains(name))
+                .unwrap();
+
+            let (cursor, expected) = match ctx.config.snippet_cap {
+                Some(_) => ("$0", "${0:todo!()}"),
+                None => ("", "todo!()"),
+            };
+            let body_indent = indent + 1;
+            let mut test = format!("{indent}#[{test_attr}]\n{indent}");
+            if is_async {
+                test.push_str("async ");
+            }
+            format_to!(test, "fn {test_name}() {{\n");
+            if returns_unit {
+                format_to!(test, "{body_indent}{call};{cursor}\n");
+            } else {
+                format_to!(test, "{body_indent}let result = {call};\n");
+                format_to!(test, "{body_indent}assert_eq!(result, {expected});\n");
+            }
+            format_to!(test, "{indent}}}");
+
+            let text = match new_module_indent {
+                None if offset == TextSize::from(0) => format!("{test}\n"),
+                None => format!("\n\n{test}"),
+                Some(module_indent) => {
+                    let mut module = format!("\n\n{module_indent}#[cfg(test)]\n");
+                    format_to!(module, "{module_indent}mod tests {{\n");
+                    format_to!(module, "{indent}use super::*;\n\n{test}\n{module_indent}}}");
+                    module
+                }
+            };
+            builder.edit_file(file_id);
+            match ctx.config.snippet_cap {
+                Some(cap) => builder.insert_snippet(cap, offset, text),
+                None => builder.insert(offset, text),
+            }
+        },
+    )
+}
+
+enum Target {
+    Existing { file_id: FileId, offset: TextSize, indent: IndentLevel, names: Vec<String> },
+    New { offset: TextSize, indent: IndentLevel },
+}
+
+fn module_items(container: &SyntaxNode) -> Option<Vec<ast::Item>> {
+    if let Some(file) = ast::SourceFile::cast(container.clone()) {
+        return Some(file.items().collect());
+    }
+    let item_list = ast::ItemList::cast(container.clone())?;
+    ast::Module::cast(item_list.syntax().parent()?)?;
+    Some(item_list.items().collect())
+}
+
+fn fn_names(items: impl Iterator<Item = ast::Item>) -> Vec<String> {
+    items
+        .filter_map(|item| match item {
+            ast::Item::Fn(it) => Some(it.name()?.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An argument of type `ty` for the call in the test.
+fn placeholder(db: &ide_db::RootDatabase, ty: &hir::Type, default: Option<hir::Trait>) -> String {
+    if default.map_or(false, |it| ty.impls_trait(db, it, &[])) {
+        return "Default::default()".to_string();
+    }
+    // `todo!()` can stand in for a reference itself, but not for an unsized value behind it.
+    let is_unsized = |it: &hir::Type| {
+        it.as_builtin().map_or(false, |it| it.is_str())
+            || it.is_slice()
+            || it.as_dyn_trait().is_some()
+    };
+    match ty.as_reference() {
+        Some((inner, _)) if is_unsized(&inner) => "todo!()".to_string(),
+        Some((inner, hir::Mutability::Shared)) => format!("&{}", placeholder(db, &inner, default)),
+        Some((inner, hir::Mutability::Mut)) => format!("&mut {}", placeholder(db, &inner, default)),
+        None => "todo!()".to_string(),
+    }
+}
+
+/// The attribute for async tests, which depends on the runtime the crate uses.
+fn async_test_attr(db: &ide_db::RootDatabase, krate: hir::Crate) -> &'static str {
+    let deps = krate.dependencies(db);
+    let has_dep = |name: &str| deps.iter().any(|dep| dep.name.display(db).to_string() == name);
+    if has_dep("tokio") {
+        "tokio::test"
+    } else if has_dep("async_std") {
+        "async_std::test"
+    } else {
+        "test"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn reuses_tests_module() {
+        check_assist(
+            generate_test,
+            r#"
+fn add$0(a: u32, b: u32) -> u32 { a }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {}
+}
+"#,
+            r#"
+fn add(a: u32, b: u32) -> u32 { a }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {}
+
+    #[test]
+    fn test_add_1() {
+        let result = add(todo!(), todo!());
+        assert_eq!(result, ${0:todo!()});
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn method_in_inline_module() {
+        check_assist(
+            generate_test,
+            r#"
+//- minicore: default
+mod counter {
+    pub struct Counter(u32);
+    impl Default for Counter {
+        fn default() -> Self { Counter(0) }
+    }
+    impl Counter {
+        pub fn reset$0(&mut self) {}
+    }
+}
+"#,
+            r#"
+mod counter {
+    pub struct Counter(u32);
+    impl Default for Counter {
+        fn default() -> Self { Counter(0) }
+    }
+    impl Counter {
+        pub fn reset(&mut self) {}
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_reset() {
+            Counter::reset(&mut Default::default());$0
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn async_fn_uses_tokio_test() {
+        check_assist(
+            generate_test,
+            r#"
+//- /main.rs crate:main deps:tokio
+async fn fetch$0() {}
+//- /tokio.rs crate:tokio
+"#,
+            r#"
+async fn fetch() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch() {
+        fetch().await;$0
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_test_to_tests_file() {
+        check_assist(
+            generate_test,
+            r#"
+//- /main.rs cfg:test
+fn check$0() -> bool { true }
+#[cfg(test)]
+mod tests;
+//- /tests.rs
+use super::*;
+"#,
+            r#"
+use super::*;
+
+#[test]
+fn test_check() {
+    let result = check();
+    assert_eq!(result, ${0:todo!()});
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsized_referents_are_not_borrowed() {
+        check_assist(
+            generate_test,
+            r#"
+trait Sink {}
+fn join$0(parts: &[u32], sep: &str, out: &mut dyn Sink, n: &u32) -> bool { true }
+"#,
+            r#"
+trait Sink {}
+fn join(parts: &[u32], sep: &str, out: &mut dyn Sink, n: &u32) -> bool { true }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        let result = join(todo!(), todo!(), todo!(), &todo!());
+        assert_eq!(result, ${0:todo!()});
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_tests_and_trait_methods() {
+        check_assist_not_applicable(
+            generate_test,
+            r#"
+#[test]
+fn test_foo$0() {}
+"#,
+        );
+        check_assist_not_applicable(
+            generate_test,
+            r#"
+trait T {
+    fn f(&self);
+}
+impl T for () {
+    fn f$0(&self) {}
+}
+"#,
+        );
+    }
+}
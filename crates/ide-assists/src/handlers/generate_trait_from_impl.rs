@@ -183,7 +183,7 @@ fn remove_items_visibility(item: &ast::AssocItem) {
     }
 }
 
-fn strip_body(item: &ast::AssocItem) {
+pub(crate) fn strip_body(item: &ast::AssocItem) {
     match item {
         ast::AssocItem::Fn(f) => {
             if let Some(body) = f.body() {
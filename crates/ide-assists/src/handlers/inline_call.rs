@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 
 use ast::make;
 use either::Either;
-use hir::{db::HirDatabase, PathResolution, Semantics, TypeInfo};
+use hir::{db::HirDatabase, AsAssocItem, HirDisplay, PathResolution, Semantics, TypeInfo};
 use ide_db::{
     base_db::{FileId, FileRange},
     defs::Definition,
@@ -16,7 +16,7 @@ use ide_db::{
 use itertools::{izip, Itertools};
 use syntax::{
     ast::{self, edit::IndentLevel, edit_in_place::Indent, HasArgList, PathExpr},
-    ted, AstNode, NodeOrToken, SyntaxKind,
+    match_ast, ted, AstNode, NodeOrToken, SyntaxKind, SyntaxNode, TextRange, TextSize,
 };
 
 use crate::{
@@ -28,7 +28,9 @@ use crate::{
 //
 // Inline a function or method body into all of its callers where possible, creating a `let` statement per parameter
 // unless the parameter can be inlined. The parameter will be inlined either if it the supplied argument is a simple local
-// or if the parameter is only accessed inside the function body once.
+// or if the parameter is only accessed inside the function body once. Uses of the function as a value are replaced by
+// closures. Call sites that can't be inlined, like ones inside of macro calls, are left as they are and listed in the
+// assist's label.
 // If all calls can be inlined the function will be removed.
 //
 // ```
@@ -90,52 +92,123 @@ pub(crate) fn inline_into_callers(acc: &mut Assists, ctx: &AssistContext<'_>) ->
         return None;
     }
 
+    let mut usages = usages.all();
+    let current_file_usage = usages.references.remove(&def_file);
+    let mut skipped = Vec::new();
+    let mut files = Vec::new();
+    for (file_id, refs) in usages.into_iter().chain(current_file_usage.map(|it| (def_file, it))) {
+        let mut sites = Vec::new();
+        let mut use_trees = Vec::new();
+        let mut all_inlined = true;
+        let name_refs = refs.into_iter().filter_map(|file_ref| match file_ref.name {
+            FileReferenceNode::NameRef(name_ref) => Some(name_ref),
+            _ => None,
+        });
+        for name_ref in name_refs {
+            if let Some(use_tree) = name_ref.syntax().ancestors().find_map(ast::UseTree::cast) {
+                use_trees.push(use_tree);
+                continue;
+            }
+            match InlineSite::new(&ctx.sema, function, &ast_func, &params, name_ref) {
+                Ok(site) => sites.push(site),
+                Err(reason) => {
+                    skipped.push(reason);
+                    all_inlined = false;
+                }
+            }
+        }
+        files.push((file_id, sites, use_trees, all_inlined));
+    }
+    if files.iter().all(|(_, sites, ..)| sites.is_empty()) {
+        return None;
+    }
+
+    let label = if skipped.is_empty() {
+        "Inline into all callers".to_owned()
+    } else {
+        let inlined: usize = files.iter().map(|(_, sites, ..)| sites.len()).sum();
+        let reasons = skipped
+            .iter()
+            .counts()
+            .into_iter()
+            .sorted()
+            .map(|(reason, count)| format!("{count} {}", reason.description()))
+            .join(", ");
+        format!("Inline into {inlined} of {} callers (skipped {reasons})", inlined + skipped.len())
+    };
+
     acc.add(
         AssistId("inline_into_callers", AssistKind::RefactorInline),
-        "Inline into all callers",
+        label,
         name.syntax().text_range(),
         |builder| {
-            let mut usages = usages.all();
-            let current_file_usage = usages.references.remove(&def_file);
-
-            let mut remove_def = true;
-            let mut inline_refs_for_file = |file_id, refs: Vec<FileReference>| {
+            let mut current_file_edited = false;
+            for (file_id, sites, use_trees, all_inlined) in files {
                 builder.edit_file(file_id);
-                let count = refs.len();
-                // The collects are required as we are otherwise iterating while mutating 🙅‍♀️🙅‍♂️
-                let (name_refs, name_refs_use) = split_refs_and_uses(builder, refs, Some);
-                let call_infos: Vec<_> = name_refs
+                current_file_edited |= file_id == def_file;
+                // Everything has to be made mutable before the first edit.
+                let sites: Vec<_> = sites
                     .into_iter()
-                    .filter_map(CallInfo::from_name_ref)
-                    .map(|call_info| {
-                        let mut_node = builder.make_syntax_mut(call_info.node.syntax().clone());
-                        (call_info, mut_node)
+                    .map(|site| {
+                        let hoist_before = match &site {
+                            InlineSite::Call(_, Some(anchor)) => {
+                                Some(builder.make_syntax_mut(anchor.clone()))
+                            }
+                            _ => None,
+                        };
+                        let mut_node = builder.make_syntax_mut(site.syntax().clone());
+                        (site, mut_node, hoist_before)
                     })
                     .collect();
-                let replaced = call_infos
+                let use_paths: Vec<_> = use_trees
                     .into_iter()
-                    .map(|(call_info, mut_node)| {
-                        let replacement =
-                            inline(&ctx.sema, def_file, function, &func_body, &params, &call_info);
-                        ted::replace(mut_node, replacement.syntax());
-                    })
-                    .count();
-                if replaced + name_refs_use.len() == count {
+                    .filter_map(|use_tree| builder.make_mut(use_tree).path())
+                    .collect();
+                for (site, mut_node, hoist_before) in sites {
+                    let replacement = match &site {
+                        InlineSite::Call(call_info, _) => {
+                            let (replacement, hoisted) = inline(
+                                &ctx.sema,
+                                def_file,
+                                function,
+                                &func_body,
+                                &params,
+                                call_info,
+                                hoist_before.is_some(),
+                            );
+                            if let Some((anchor, stmt)) = hoist_before.zip(hoisted) {
+                                insert_hoisted_stmt(&anchor, stmt);
+                            }
+                            replacement
+                        }
+                        InlineSite::Value(path_expr) => {
+                            fn_as_closure(&ctx.sema, &ast_func, &func_body, path_expr)
+                        }
+                    };
+                    ted::replace(mut_node, replacement.syntax());
+                }
+                if all_inlined {
                     // we replaced all usages in this file, so we can remove the imports
-                    name_refs_use.iter().for_each(remove_path_if_in_use_stmt);
-                } else {
-                    remove_def = false;
+                    use_paths.iter().for_each(remove_path_if_in_use_stmt);
                 }
-            };
-            for (file_id, refs) in usages.into_iter() {
-                inline_refs_for_file(file_id, refs);
             }
-            match current_file_usage {
-                Some(refs) => inline_refs_for_file(def_file, refs),
-                None => builder.edit_file(def_file),
+            if !current_file_edited {
+                builder.edit_file(def_file);
             }
-            if remove_def {
-                builder.delete(ast_func.syntax().text_range());
+            if skipped.is_empty() {
+                // Don't leave the indentation of the function behind.
+                let mut range = ast_func.syntax().text_range();
+                if let Some(ws) = ast_func
+                    .syntax()
+                    .prev_sibling_or_token()
+                    .and_then(|it| it.into_token().filter(|it| it.kind() == SyntaxKind::WHITESPACE))
+                {
+                    if let Some(line_start) = ws.text().rfind('\n') {
+                        let indent = TextSize::of(&ws.text()[line_start + 1..]);
+                        range = TextRange::new(range.start() - indent, range.end());
+                    }
+                }
+                builder.delete(range);
             }
         },
     )
@@ -209,13 +282,8 @@ pub(crate) fn inline_call(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<
         return None;
     }
     let params = get_fn_params(ctx.sema.db, function, &param_list)?;
-
-    if call_info.arguments.len() != params.len() {
-        // Can't inline the function because they've passed the wrong number of
-        // arguments to this function
-        cov_mark::hit!(inline_call_incorrect_number_of_arguments);
-        return None;
-    }
+    let (call_info, hoist_before) =
+        prepare_call(&ctx.sema, function, &fn_source.value, &params, call_info).ok()?;
 
     let syntax = call_info.node.syntax().clone();
     acc.add(
@@ -223,14 +291,21 @@ pub(crate) fn inline_call(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<
         label,
         syntax.text_range(),
         |builder| {
-            let replacement = inline(&ctx.sema, file_id, function, &fn_body, &params, &call_info);
-            builder.replace_ast(
-                match call_info.node {
-                    ast::CallableExpr::Call(it) => ast::Expr::CallExpr(it),
-                    ast::CallableExpr::MethodCall(it) => ast::Expr::MethodCallExpr(it),
-                },
-                replacement,
+            let (replacement, hoisted) = inline(
+                &ctx.sema,
+                file_id,
+                function,
+                &fn_body,
+                &params,
+                &call_info,
+                hoist_before.is_some(),
             );
+            let hoist_before = hoist_before.map(|it| builder.make_syntax_mut(it));
+            let node = builder.make_syntax_mut(syntax);
+            if let Some((anchor, stmt)) = hoist_before.zip(hoisted) {
+                insert_hoisted_stmt(&anchor, stmt);
+            }
+            ted::replace(node, replacement.syntax());
         },
     )
 }
@@ -269,6 +344,242 @@ impl CallInfo {
     }
 }
 
+/// A use of the function that can be inlined.
+enum InlineSite {
+    /// A call, along with the statement its receiver has to be bound in front of, if any.
+    Call(CallInfo, Option<SyntaxNode>),
+    /// The function used as a value, which gets replaced by a closure.
+    Value(ast::PathExpr),
+}
+
+/// Why a use of the function can't be inlined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Skipped {
+    InMacroCall,
+    ArgumentCount,
+    GenericArgs,
+    TemporaryReceiver,
+    FnValue,
+    Other,
+}
+
+impl Skipped {
+    fn description(self) -> &'static str {
+        match self {
+            Skipped::InMacroCall => "in macro calls",
+            Skipped::ArgumentCount => "with the wrong number of arguments",
+            Skipped::GenericArgs => "with generic arguments that can't be written out",
+            Skipped::TemporaryReceiver => "borrowing from a temporary receiver",
+            Skipped::FnValue => "using the function as a value",
+            Skipped::Other => "of other kinds",
+        }
+    }
+}
+
+impl InlineSite {
+    fn new(
+        sema: &Semantics<'_, RootDatabase>,
+        function: hir::Function,
+        fn_source: &ast::Fn,
+        params: &[(ast::Pat, Option<ast::Type>, hir::Param)],
+        name_ref: ast::NameRef,
+    ) -> Result<InlineSite, Skipped> {
+        if sema.hir_file_for(name_ref.syntax()).is_macro() {
+            return Err(Skipped::InMacroCall);
+        }
+        if let Some(call_info) = CallInfo::from_name_ref(name_ref.clone()) {
+            let (call_info, hoist_before) =
+                prepare_call(sema, function, fn_source, params, call_info)?;
+            return Ok(InlineSite::Call(call_info, hoist_before));
+        }
+        let path_expr = name_ref
+            .syntax()
+            .ancestors()
+            .find_map(ast::PathExpr::cast)
+            .filter(|it| {
+                it.path().and_then(|it| it.segment()?.name_ref()).as_ref() == Some(&name_ref)
+            })
+            .ok_or(Skipped::Other)?;
+        if !can_be_closure(sema.db, function) {
+            return Err(Skipped::FnValue);
+        }
+        Ok(InlineSite::Value(path_expr))
+    }
+
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            InlineSite::Call(call_info, _) => call_info.node.syntax(),
+            InlineSite::Value(path_expr) => path_expr.syntax(),
+        }
+    }
+}
+
+/// Checks that a call can be inlined, spelling out the generic arguments it leaves to inference
+/// and finding where to bind a temporary receiver the result borrows from.
+fn prepare_call(
+    sema: &Semantics<'_, RootDatabase>,
+    function: hir::Function,
+    fn_source: &ast::Fn,
+    params: &[(ast::Pat, Option<ast::Type>, hir::Param)],
+    mut call_info: CallInfo,
+) -> Result<(CallInfo, Option<SyntaxNode>), Skipped> {
+    if call_info.arguments.len() != params.len() {
+        // Can't inline the function because they've passed the wrong number of
+        // arguments to this function
+        cov_mark::hit!(inline_call_incorrect_number_of_arguments);
+        return Err(Skipped::ArgumentCount);
+    }
+    if call_info.generic_arg_list.is_none() {
+        call_info.generic_arg_list = inferred_generic_arg_list(sema, function, &call_info)?;
+    }
+    let hoist_before = match &call_info.node {
+        ast::CallableExpr::MethodCall(call) if borrows_temporary_receiver(fn_source, call) => {
+            cov_mark::hit!(inline_call_hoist_receiver);
+            Some(hoist_anchor(call).ok_or(Skipped::TemporaryReceiver)?)
+        }
+        _ => None,
+    };
+    Ok((call_info, hoist_before))
+}
+
+fn inferred_generic_arg_list(
+    sema: &Semantics<'_, RootDatabase>,
+    function: hir::Function,
+    CallInfo { node, .. }: &CallInfo,
+) -> Result<Option<ast::GenericArgList>, Skipped> {
+    let db = sema.db;
+    if hir::GenericDef::from(function).type_params(db).is_empty() {
+        return Ok(None);
+    }
+    let callable = match node {
+        ast::CallableExpr::Call(call) => call
+            .expr()
+            .and_then(|it| sema.type_of_expr(&it))
+            .and_then(|it| it.original.as_callable(db)),
+        ast::CallableExpr::MethodCall(call) => sema.resolve_method_call_as_callable(call),
+    };
+    let module = sema.scope(node.syntax()).ok_or(Skipped::GenericArgs)?.module();
+    let args = callable
+        .and_then(|it| it.type_arguments(db))
+        .ok_or(Skipped::GenericArgs)?
+        .into_iter()
+        .map(|ty| {
+            let ty = ty.display_source_code(db, module.into(), false).ok()?;
+            Some(make::type_arg(make::ty(&ty)).into())
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(Skipped::GenericArgs)?;
+    if args.is_empty() {
+        return Ok(None);
+    }
+    cov_mark::hit!(inline_call_inferred_generic_args);
+    Ok(Some(make::generic_arg_list(args)))
+}
+
+/// Whether the call's receiver is a temporary that is borrowed by the result of the call, and so
+/// has to outlive the block the function is inlined as.
+fn borrows_temporary_receiver(fn_source: &ast::Fn, call: &ast::MethodCallExpr) -> bool {
+    let borrowed_self = fn_source
+        .param_list()
+        .and_then(|it| it.self_param())
+        .map_or(false, |it| it.amp_token().is_some());
+    let returns_borrow = fn_source.ret_type().and_then(|it| it.ty()).map_or(false, |ty| {
+        ty.syntax()
+            .descendants()
+            .any(|it| matches!(it.kind(), SyntaxKind::REF_TYPE | SyntaxKind::LIFETIME))
+    });
+    borrowed_self && returns_borrow && call.receiver().map_or(false, |it| !is_place_expr(&it))
+}
+
+fn is_place_expr(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::PathExpr(_) | ast::Expr::FieldExpr(_) | ast::Expr::IndexExpr(_) => true,
+        ast::Expr::PrefixExpr(it) => it.op_kind() == Some(ast::UnaryOp::Deref),
+        ast::Expr::ParenExpr(it) => it.expr().map_or(false, |it| is_place_expr(&it)),
+        _ => false,
+    }
+}
+
+/// The statement the receiver of `call` can be bound in front of without changing how often it
+/// is evaluated.
+fn hoist_anchor(call: &ast::MethodCallExpr) -> Option<SyntaxNode> {
+    for node in call.syntax().ancestors() {
+        if node.parent().map_or(false, |it| ast::StmtList::can_cast(it.kind())) {
+            return Some(node);
+        }
+        let conditional = match_ast! {
+            match node {
+                ast::BinExpr(it) => matches!(it.op_kind(), Some(ast::BinaryOp::LogicOp(_))),
+                ast::ClosureExpr(_) => true,
+                ast::IfExpr(_) => true,
+                ast::MatchArm(_) => true,
+                ast::LoopExpr(_) => true,
+                ast::WhileExpr(_) => true,
+                ast::ForExpr(_) => true,
+                _ => ast::Item::can_cast(node.kind()),
+            }
+        };
+        if conditional {
+            return None;
+        }
+    }
+    None
+}
+
+fn insert_hoisted_stmt(anchor: &SyntaxNode, stmt: ast::Stmt) {
+    let indent = IndentLevel::from_node(anchor);
+    ted::insert_all(
+        ted::Position::before(anchor),
+        vec![stmt.syntax().clone().into(), make::tokens::whitespace(&format!("\n{indent}")).into()],
+    );
+}
+
+/// Closures can't be generic or unsafe, and can't have receivers.
+fn can_be_closure(db: &dyn HirDatabase, function: hir::Function) -> bool {
+    let in_generic_or_trait_impl =
+        function.as_assoc_item(db).map_or(false, |it| match it.container(db) {
+            hir::AssocItemContainer::Impl(impl_) => {
+                impl_.trait_(db).is_some() || !hir::GenericDef::from(impl_).params(db).is_empty()
+            }
+            hir::AssocItemContainer::Trait(_) => true,
+        });
+    function.self_param(db).is_none()
+        && hir::GenericDef::from(function).params(db).is_empty()
+        && !function.is_async(db)
+        && !function.is_unsafe_to_call(db)
+        && !in_generic_or_trait_impl
+}
+
+/// Replaces a use of the function as a value with a closure with its body.
+fn fn_as_closure(
+    sema: &Semantics<'_, RootDatabase>,
+    fn_source: &ast::Fn,
+    fn_body: &ast::BlockExpr,
+    path_expr: &ast::PathExpr,
+) -> ast::Expr {
+    let body = clone_fn_body(sema, fn_body);
+    replace_self_type(sema, fn_body, &body, path_expr.syntax());
+    body.reindent_to(path_expr.indent_level());
+    let params = fn_source
+        .param_list()
+        .into_iter()
+        .flat_map(|it| it.params())
+        .filter_map(|param| Some(make::param(param.pat()?, param.ty()?)));
+    let body = match body.tail_expr() {
+        Some(tail) if body.statements().next().is_none() => tail,
+        _ => ast::Expr::BlockExpr(body),
+    };
+    let closure = make::expr_closure(params, body);
+    let needs_parens = path_expr.syntax().parent().map_or(false, |it| {
+        ast::Expr::can_cast(it.kind())
+            && !matches!(
+                it.kind(),
+                SyntaxKind::PAREN_EXPR | SyntaxKind::ARRAY_EXPR | SyntaxKind::TUPLE_EXPR
+            )
+    });
+    if needs_parens { make::expr_paren(closure) } else { closure }.clone_for_update()
+}
+
 fn get_fn_params(
     db: &dyn HirDatabase,
     function: hir::Function,
@@ -304,17 +615,9 @@ fn inline(
     fn_body: &ast::BlockExpr,
     params: &[(ast::Pat, Option<ast::Type>, hir::Param)],
     CallInfo { node, arguments, generic_arg_list }: &CallInfo,
-) -> ast::Expr {
-    let mut body = if sema.hir_file_for(fn_body.syntax()).is_macro() {
-        cov_mark::hit!(inline_call_defined_in_macro);
-        if let Some(body) = ast::BlockExpr::cast(insert_ws_into(fn_body.syntax().clone())) {
-            body
-        } else {
-            fn_body.clone_for_update()
-        }
-    } else {
-        fn_body.clone_for_update()
-    };
+    hoist_receiver: bool,
+) -> (ast::Expr, Option<ast::Stmt>) {
+    let mut body = clone_fn_body(sema, fn_body);
     let usages_for_locals = |local| {
         Definition::Local(local)
             .usages(sema)
@@ -373,23 +676,7 @@ fn inline(
     // We should place the following code after last usage of `usages_for_locals`
     // because `ted::replace` will change the offset in syntax tree, which makes
     // `FileReference` incorrect
-    if let Some(imp) =
-        sema.ancestors_with_macros(fn_body.syntax().clone()).find_map(ast::Impl::cast)
-    {
-        if !node.syntax().ancestors().any(|anc| &anc == imp.syntax()) {
-            if let Some(t) = imp.self_ty() {
-                while let Some(self_tok) = body
-                    .syntax()
-                    .descendants_with_tokens()
-                    .filter_map(NodeOrToken::into_token)
-                    .find(|tok| tok.kind() == SyntaxKind::SELF_TYPE_KW)
-                {
-                    let replace_with = t.clone_subtree().syntax().clone_for_update();
-                    ted::replace(self_tok, replace_with);
-                }
-            }
-        }
-    }
+    replace_self_type(sema, fn_body, &body, node.syntax());
 
     let mut func_let_vars: BTreeSet<String> = BTreeSet::new();
 
@@ -407,18 +694,26 @@ fn inline(
     }
 
     let mut let_stmts = Vec::new();
+    let mut hoisted = None;
 
     // Inline parameter expressions or generate `let` statements depending on whether inlining works or not.
-    for ((pat, param_ty, _), usages, expr) in izip!(params, param_use_nodes, arguments) {
+    for (idx, ((pat, param_ty, _), usages, expr)) in
+        izip!(params, param_use_nodes, arguments).enumerate()
+    {
         // izip confuses RA due to our lack of hygiene info currently losing us type info causing incorrect errors
         let usages: &[ast::PathExpr] = &usages;
         let expr: &ast::Expr = expr;
 
         let mut insert_let_stmt = || {
             let ty = sema.type_of_expr(expr).filter(TypeInfo::has_adjustment).and(param_ty.clone());
-            let_stmts.push(
-                make::let_stmt(pat.clone(), ty, Some(expr.clone())).clone_for_update().into(),
-            );
+            let stmt =
+                make::let_stmt(pat.clone(), ty, Some(expr.clone())).clone_for_update().into();
+            // The receiver is always the first argument.
+            if hoist_receiver && idx == 0 {
+                hoisted = Some(stmt);
+            } else {
+                let_stmts.push(stmt);
+            }
         };
 
         // check if there is a local var in the function that conflicts with parameter
@@ -499,7 +794,7 @@ fn inline(
     body.reindent_to(original_indentation);
 
     let no_stmts = body.statements().next().is_none();
-    match body.tail_expr() {
+    let expr = match body.tail_expr() {
         Some(expr) if matches!(expr, ast::Expr::ClosureExpr(_)) && no_stmts => {
             make::expr_paren(expr).clone_for_update()
         }
@@ -515,6 +810,45 @@ fn inline(
             }
             _ => ast::Expr::BlockExpr(body),
         },
+    };
+    (expr, hoisted)
+}
+
+fn clone_fn_body(sema: &Semantics<'_, RootDatabase>, fn_body: &ast::BlockExpr) -> ast::BlockExpr {
+    if sema.hir_file_for(fn_body.syntax()).is_macro() {
+        cov_mark::hit!(inline_call_defined_in_macro);
+        if let Some(body) = ast::BlockExpr::cast(insert_ws_into(fn_body.syntax().clone())) {
+            return body;
+        }
+    }
+    fn_body.clone_for_update()
+}
+
+/// Replaces `Self` in the copied `body` with the self type of the function's impl, unless it is
+/// inlined at `node` within that impl.
+fn replace_self_type(
+    sema: &Semantics<'_, RootDatabase>,
+    fn_body: &ast::BlockExpr,
+    body: &ast::BlockExpr,
+    node: &SyntaxNode,
+) {
+    let Some(imp) = sema.ancestors_with_macros(fn_body.syntax().clone()).find_map(ast::Impl::cast)
+    else {
+        return;
+    };
+    if node.ancestors().any(|anc| &anc == imp.syntax()) {
+        return;
+    }
+    if let Some(t) = imp.self_ty() {
+        while let Some(self_tok) = body
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(NodeOrToken::into_token)
+            .find(|tok| tok.kind() == SyntaxKind::SELF_TYPE_KW)
+        {
+            let replace_with = t.clone_subtree().syntax().clone_for_update();
+            ted::replace(self_tok, replace_with);
+        }
     }
 }
 
@@ -526,7 +860,7 @@ fn path_expr_as_record_field(usage: &PathExpr) -> Option<ast::RecordExprField> {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_assist, check_assist_not_applicable};
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
 
     use super::*;
 
@@ -1138,6 +1472,162 @@ fn foo() {
         );
     }
 
+    #[test]
+    fn inline_callers_infers_generic_args() {
+        cov_mark::check!(inline_call_inferred_generic_args);
+        check_assist(
+            inline_into_callers,
+            r#"
+fn wrap$0<T>(value: T) -> Option<T> {
+    let wrapped: Option<T> = Some(value);
+    wrapped
+}
+fn foo() {
+    let a = wrap(1u8);
+    let b = wrap(true);
+}
+"#,
+            r#"
+
+fn foo() {
+    let a = {
+        let wrapped: Option<u8> = Some(1u8);
+        wrapped
+    };
+    let b = {
+        let wrapped: Option<bool> = Some(true);
+        wrapped
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_callers_fn_as_value() {
+        check_assist(
+            inline_into_callers,
+            r#"
+fn apply(f: fn(u32) -> u32) -> u32 { f(2) }
+fn double$0(x: u32) -> u32 {
+    x * 2
+}
+fn foo() {
+    let a = double(1);
+    let b = apply(double);
+    let f = double;
+}
+"#,
+            r#"
+fn apply(f: fn(u32) -> u32) -> u32 { f(2) }
+
+fn foo() {
+    let a = 1 * 2;
+    let b = apply(|x: u32| x * 2);
+    let f = |x: u32| x * 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_callers_hoists_temporary_receiver() {
+        cov_mark::check!(inline_call_hoist_receiver);
+        check_assist(
+            inline_into_callers,
+            r#"
+struct Name(&'static str);
+impl Name {
+    fn get$0(&self) -> &&'static str {
+        let name = &self.0;
+        name
+    }
+}
+fn foo() -> usize {
+    let name = Name("a").get();
+    Name("b").get().len()
+}
+"#,
+            r#"
+struct Name(&'static str);
+impl Name {
+
+}
+fn foo() -> usize {
+    let ref this = Name("a");
+    let name = {
+        let name = &this.0;
+        name
+    };
+    let ref this = Name("b");
+    {
+        let name = &this.0;
+        name
+    }.len()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_call_temporary_receiver_in_closure() {
+        check_assist_not_applicable(
+            inline_call,
+            r#"
+struct Name(&'static str);
+impl Name {
+    fn get(&self) -> &&'static str { &self.0 }
+}
+fn foo() {
+    let f = || Name("a").get$0().len();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_callers_reports_skipped_sites() {
+        check_assist_by_label(
+            inline_into_callers,
+            r#"
+macro_rules! id {
+    ($e:expr) => { $e };
+}
+struct S;
+impl S {
+    fn value$0(self) -> u32 {
+        1
+    }
+}
+fn foo() {
+    let a = S.value();
+    let b = id!(S.value());
+    let f = S::value;
+}
+"#,
+            r#"
+macro_rules! id {
+    ($e:expr) => { $e };
+}
+struct S;
+impl S {
+    fn value(self) -> u32 {
+        1
+    }
+}
+fn foo() {
+    let a = {
+        let this = S;
+        1
+    };
+    let b = id!(S.value());
+    let f = S::value;
+}
+"#,
+            "Inline into 1 of 3 callers (skipped 1 in macro calls, 1 using the function as a value)",
+        );
+    }
+
     #[test]
     fn inline_callers_recursive() {
         cov_mark::check!(inline_into_callers_recursive);
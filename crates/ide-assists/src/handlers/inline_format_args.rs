@@ -0,0 +1,264 @@
+use hir::DescendPreference;
+use ide_db::{
+    assists::{AssistId, AssistKind},
+    syntax_helpers::{
+        format_string::is_format_string,
+        format_string_exprs::{parse_placeholders, PlaceholderArg},
+    },
+};
+use stdx::format_to;
+use syntax::{
+    ast::{self, IsString},
+    hacks::parse_expr_from_str,
+    AstNode, AstToken, TextRange,
+};
+
+use crate::{utils::format_string_args, AssistContext, Assists};
+
+// Assist: inline_format_args
+//
+// Moves the arguments of a format string which are plain names into the format string.
+//
+// ```
+// # //- minicore: fmt
+// fn main() {
+//     let (name, count) = ("x", 1);
+//     print!("{} has {:>3} {}$0", name, count, count + 1);
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let (name, count) = ("x", 1);
+//     print!("{name} has {count:>3} {}", count + 1);
+// }
+// ```
+pub(crate) fn inline_format_args(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let fmt_string = ctx.find_token_at_offset::<ast::String>()?;
+    let tt = fmt_string.syntax().parent().and_then(ast::TokenTree::cast)?;
+
+    let expanded_t = ast::String::cast(
+        ctx.sema
+            .descend_into_macros_single(DescendPreference::SameKind, fmt_string.syntax().clone()),
+    )?;
+    if !is_format_string(&expanded_t) {
+        return None;
+    }
+
+    let text = fmt_string.text_without_quotes();
+    if fmt_string.is_raw() && text.contains('\\') {
+        return None;
+    }
+    let placeholders = parse_placeholders(text).ok()?;
+    // `{:1$}` and `{:.*}` refer to arguments as well.
+    if placeholders.iter().any(|it| it.spec.as_ref().map_or(false, |it| it.contains(['$', '*']))) {
+        return None;
+    }
+    let (args, args_range) = format_string_args(&tt, &fmt_string)?;
+    let positional = args.iter().take_while(|it| !is_named_arg(it)).count();
+
+    // The positional argument each placeholder refers to.
+    let mut next = 0;
+    let mut indices = Vec::new();
+    for placeholder in &placeholders {
+        let idx = match placeholder.arg {
+            PlaceholderArg::Next => {
+                next += 1;
+                next - 1
+            }
+            PlaceholderArg::Index(idx) => idx,
+            PlaceholderArg::Name(_) => {
+                indices.push(None);
+                continue;
+            }
+        };
+        if idx >= positional {
+            return None;
+        }
+        indices.push(Some(idx));
+    }
+
+    let names: Vec<_> = args[..positional].iter().map(|it| plain_name(it)).collect();
+    if !indices.iter().flatten().any(|&idx| names[idx].is_some()) {
+        return None;
+    }
+    // The index of every positional argument that stays.
+    let mut new_indices = Vec::new();
+    let mut kept = 0;
+    for name in &names {
+        new_indices.push(kept);
+        if name.is_none() {
+            kept += 1;
+        }
+    }
+
+    let fmt_range = fmt_string.text_range_between_quotes()?;
+    acc.add(
+        AssistId("inline_format_args", AssistKind::RefactorInline),
+        "Inline format arguments",
+        fmt_string.syntax().text_range(),
+        |edit| {
+            let mut new_fmt = String::new();
+            let mut last = 0.into();
+            let mut next = 0;
+            for (placeholder, idx) in placeholders.iter().zip(&indices) {
+                new_fmt.push_str(&text[TextRange::new(last, placeholder.range.start())]);
+                last = placeholder.range.end();
+                let arg = match *idx {
+                    Some(idx) => match &names[idx] {
+                        Some(name) => name.clone(),
+                        // `{}` only stays implicit if it refers to the next argument.
+                        None if placeholder.arg == PlaceholderArg::Next
+                            && new_indices[idx] == next =>
+                        {
+                            next += 1;
+                            String::new()
+                        }
+                        None => new_indices[idx].to_string(),
+                    },
+                    None => {
+                        new_fmt.push_str(&text[placeholder.range]);
+                        continue;
+                    }
+                };
+                new_fmt.push('{');
+                new_fmt.push_str(&arg);
+                if let Some(spec) = &placeholder.spec {
+                    format_to!(new_fmt, ":{spec}");
+                }
+                new_fmt.push('}');
+            }
+            new_fmt.push_str(&text[TextRange::new(last, text.len().try_into().unwrap())]);
+            edit.replace(fmt_range, new_fmt);
+
+            let mut new_args = String::new();
+            let kept_args = args
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| names.get(*idx).map_or(true, |name| name.is_none()));
+            for (_, arg) in kept_args {
+                format_to!(new_args, ", {arg}");
+            }
+            edit.replace(args_range, new_args);
+        },
+    )
+}
+
+/// Whether `arg` is a `name = value` argument.
+fn is_named_arg(arg: &str) -> bool {
+    match arg.split_once('=') {
+        Some((name, value)) => {
+            !value.starts_with('=')
+                && matches!(parse_expr_from_str(name), Some(ast::Expr::PathExpr(_)))
+        }
+        None => false,
+    }
+}
+
+/// The name `arg` consists of, if it can be captured by a format string.
+fn plain_name(arg: &str) -> Option<String> {
+    let Some(ast::Expr::PathExpr(path_expr)) = parse_expr_from_str(arg) else { return None };
+    let path = path_expr.path()?;
+    if path.qualifier().is_some() || path.syntax().text() != arg {
+        return None;
+    }
+    let name_ref = path.segment()?.name_ref()?;
+    let ident = name_ref.ident_token()?;
+    // Raw identifiers can't be captured.
+    (!ident.text().starts_with("r#")).then(|| ident.text().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    #[test]
+    fn inlines_names_and_keeps_expressions() {
+        check_assist(
+            inline_format_args,
+            r#"
+//- minicore: fmt
+fn main() {
+    let (a, b) = (1, 2);
+    print!("{} {:?} {} {}$0", a, b + 1, b, a,);
+}
+"#,
+            r#"
+fn main() {
+    let (a, b) = (1, 2);
+    print!("{a} {:?} {b} {a}", b + 1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn renumbers_explicit_indices() {
+        check_assist(
+            inline_format_args,
+            r#"
+//- minicore: fmt
+fn main() {
+    let a = 1;
+    print!("{1} {0} {2:x} {}$0", a, a + 1, 3, name = 4);
+}
+"#,
+            r#"
+fn main() {
+    let a = 1;
+    print!("{0} {a} {1:x} {a}", a + 1, 3, name = 4);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_existing_captures() {
+        check_assist(
+            inline_format_args,
+            r#"
+//- minicore: fmt
+fn main() {
+    let (a, b) = (1, 2);
+    print!("{b} {{}} {}$0", a);
+}
+"#,
+            r#"
+fn main() {
+    let (a, b) = (1, 2);
+    print!("{b} {{}} {a}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_expressions_and_paths() {
+        check_assist_not_applicable(
+            inline_format_args,
+            r#"
+//- minicore: fmt
+mod m { pub const A: i32 = 1; }
+fn main() {
+    let a = 1;
+    print!("{} {}$0", a + 1, m::A);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_args_in_format_spec() {
+        check_assist_not_applicable(
+            inline_format_args,
+            r#"
+//- minicore: fmt
+fn main() {
+    let (a, width) = (1, 3);
+    print!("{:1$}$0", a, width);
+}
+"#,
+        );
+    }
+}
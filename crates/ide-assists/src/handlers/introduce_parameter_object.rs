@@ -0,0 +1,563 @@
+use either::Either;
+use hir::{AsAssocItem, ModuleDef, PathResolution};
+use ide_db::{
+    defs::Definition,
+    helpers::mod_path_to_ast,
+    imports::insert_use::{insert_use, ImportScope},
+    search::FileReferenceNode,
+    syntax_helpers::node_ext::full_path_of_name_ref,
+    FxHashSet,
+};
+use itertools::Itertools;
+use stdx::{format_to, to_camel_case};
+use syntax::{
+    ast::{self, edit::IndentLevel, make, HasArgList, HasGenericParams, HasName},
+    AstNode, SyntaxKind, TextRange, T,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: introduce_parameter_object
+//
+// Replaces the selected parameters of a function with a struct holding them, and constructs the
+// struct at every call site.
+//
+// ```
+// fn draw($0x: i32, y: i32$0, color: u32) {}
+//
+// fn main() {
+//     draw(1, 2, 0);
+// }
+// ```
+// ->
+// ```
+// struct DrawParams {
+//     x: i32,
+//     y: i32,
+// }
+//
+// fn draw(DrawParams { x, y }: DrawParams, color: u32) {}
+//
+// fn main() {
+//     draw(DrawParams { x: 1, y: 2 }, 0);
+// }
+// ```
+pub(crate) fn introduce_parameter_object(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    if ctx.has_empty_selection() {
+        return None;
+    }
+    let param_list: ast::ParamList = ctx.find_node_at_range()?;
+    let fn_ = ast::Fn::cast(param_list.syntax().parent()?)?;
+    let selection = ctx.selection_trimmed();
+    let selected: Vec<(usize, ast::Param)> = param_list
+        .params()
+        .enumerate()
+        .filter(|(_, param)| {
+            param.syntax().text_range().intersect(selection).map_or(false, |it| !it.is_empty())
+        })
+        .collect();
+    if selected.len() < 2 {
+        return None;
+    }
+    let first_idx = selected.first()?.0;
+    let last_idx = selected.last()?.0;
+
+    let mut fields = Vec::new();
+    for (_, param) in &selected {
+        let ast::Pat::IdentPat(pat) = param.pat()? else { return None };
+        if pat.ref_token().is_some() || pat.pat().is_some() {
+            return None;
+        }
+        let ty = param.ty()?;
+        let unsupported = ty.syntax().descendants().any(|it| match it.kind() {
+            SyntaxKind::IMPL_TRAIT_TYPE => true,
+            SyntaxKind::PATH_SEGMENT => it.first_token().map_or(false, |it| it.kind() == T![Self]),
+            _ => false,
+        });
+        if unsupported {
+            return None;
+        }
+        fields.push((pat, ty));
+    }
+
+    let func = ctx.sema.to_def(&fn_)?;
+    if func
+        .as_assoc_item(ctx.db())
+        .and_then(|it| it.containing_trait_or_trait_impl(ctx.db()))
+        .is_some()
+    {
+        return None;
+    }
+    let has_self_arg = func.self_param(ctx.db()).is_some();
+    let struct_name = format!("{}Params", to_camel_case(&fn_.name()?.text()));
+    let parent = fn_.syntax().ancestors().find_map(<Either<ast::Impl, ast::Trait>>::cast);
+    let impl_ = parent.as_ref().and_then(|it| it.as_ref().left().cloned());
+    let item = parent.as_ref().map_or(fn_.syntax().clone(), |it| it.syntax().clone());
+    let target_module = ctx.sema.scope(fn_.syntax())?.module().nearest_non_block_module(ctx.db());
+    let generics = Generics::collect(ctx, &fn_, impl_.as_ref(), fields.iter().map(|(_, ty)| ty));
+
+    // Rewrite the calls, and remember the modules that will have to import the struct.
+    let mut call_edits = Vec::new();
+    let mut importing_modules = Vec::new();
+    let mut unchanged = 0;
+    for (file_id, references) in Definition::Function(func).usages(&ctx.sema).all() {
+        for reference in references {
+            let FileReferenceNode::NameRef(name_ref) = reference.name else { continue };
+            if name_ref.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind())) {
+                continue;
+            }
+            let args = if ctx.sema.hir_file_for(name_ref.syntax()).is_macro() {
+                None
+            } else {
+                call_args(&name_ref, has_self_arg)
+            };
+            let Some(args) = args.filter(|args| args.len() == param_list.params().count()) else {
+                unchanged += 1;
+                continue;
+            };
+            let lit_fields = fields
+                .iter()
+                .zip(&args[first_idx..=last_idx])
+                .map(|((pat, _), arg)| {
+                    let name = pat.name().map_or(String::new(), |it| it.to_string());
+                    match arg {
+                        ast::Expr::PathExpr(path) if path.to_string() == name => name,
+                        _ => format!("{name}: {arg}"),
+                    }
+                })
+                .join(", ");
+            let range = TextRange::new(
+                args[first_idx].syntax().text_range().start(),
+                args[last_idx].syntax().text_range().end(),
+            );
+            call_edits.push((file_id, range, format!("{struct_name} {{ {lit_fields} }}")));
+
+            if let Some(module) = ctx.sema.scope(name_ref.syntax()) {
+                let module = module.module();
+                if module.nearest_non_block_module(ctx.db()) != target_module
+                    && !importing_modules.iter().any(|(_, it, _)| *it == module)
+                {
+                    importing_modules.push((file_id, module, name_ref));
+                }
+            }
+        }
+    }
+
+    let mut label = "Introduce parameter object".to_owned();
+    if unchanged > 0 {
+        let noun = if unchanged == 1 { "reference" } else { "references" };
+        format_to!(label, " ({unchanged} {noun} left unchanged)");
+    }
+
+    let target = TextRange::new(
+        selected.first()?.1.syntax().text_range().start(),
+        selected.last()?.1.syntax().text_range().end(),
+    );
+    acc.add(
+        AssistId("introduce_parameter_object", AssistKind::RefactorRewrite),
+        label,
+        target,
+        move |builder| {
+            let vis = if importing_modules.is_empty() { "" } else { "pub " };
+            let indent = IndentLevel::from_node(&item);
+            let mut buf = format!("{vis}struct {struct_name}{}", generics.params());
+            if generics.where_preds.is_empty() {
+                buf.push_str(" {\n");
+            } else {
+                format_to!(buf, "\n{indent}where\n");
+                for pred in &generics.where_preds {
+                    format_to!(buf, "{indent}    {pred},\n");
+                }
+                format_to!(buf, "{indent}{{\n");
+            }
+            for (pat, ty) in &fields {
+                let name = pat.name().map_or(String::new(), |it| it.to_string());
+                let ty = generics.field_ty(ty);
+                format_to!(buf, "{indent}    {vis}{name}: {ty},\n");
+            }
+            format_to!(buf, "{indent}}}\n\n{indent}");
+
+            let pat_fields = fields.iter().map(|(pat, _)| pat.to_string()).join(", ");
+            let new_param =
+                format!("{struct_name} {{ {pat_fields} }}: {struct_name}{}", generics.args());
+
+            builder.edit_file(ctx.file_id());
+            builder.insert(item.text_range().start(), buf);
+            builder.replace(target, new_param);
+
+            call_edits.sort_by_key(|(file_id, _, _)| *file_id);
+            for (file_id, edits) in &call_edits.into_iter().group_by(|(file_id, _, _)| *file_id) {
+                builder.edit_file(file_id);
+                for (_, range, text) in edits {
+                    builder.replace(range, text);
+                }
+                for (_, module, name_ref) in
+                    importing_modules.iter().filter(|(it, _, _)| *it == file_id)
+                {
+                    let path = module.find_use_path_prefixed(
+                        ctx.sema.db,
+                        ModuleDef::Module(target_module),
+                        ctx.config.insert_use.prefix_kind,
+                        ctx.config.prefer_no_std,
+                        ctx.config.prefer_prelude,
+                    );
+                    let name_ref = builder.make_mut(name_ref.clone());
+                    let scope =
+                        ImportScope::find_insert_use_container(name_ref.syntax(), &ctx.sema);
+                    if let Some((scope, path)) = scope.zip(path) {
+                        let path = make::path_concat(
+                            mod_path_to_ast(&path),
+                            make::path_from_text(&struct_name),
+                        );
+                        insert_use(&scope, path, &ctx.config.insert_use);
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// The arguments of the call `name_ref` is the callee of, including the receiver of
+/// method calls written as paths.
+fn call_args(name_ref: &ast::NameRef, has_self_arg: bool) -> Option<Vec<ast::Expr>> {
+    if let Some(path) = full_path_of_name_ref(name_ref) {
+        let path_expr = ast::PathExpr::cast(path.syntax().parent()?)?;
+        let call = ast::CallExpr::cast(path_expr.syntax().parent()?)?;
+        if call.expr()?.syntax() != path_expr.syntax() {
+            return None;
+        }
+        Some(call.arg_list()?.args().skip(has_self_arg as usize).collect())
+    } else {
+        let call = ast::MethodCallExpr::cast(name_ref.syntax().parent()?)?;
+        Some(call.arg_list()?.args().collect())
+    }
+}
+
+/// The generic parameters of the function and its impl that the new struct needs.
+struct Generics {
+    params: Vec<ast::GenericParam>,
+    where_preds: Vec<ast::WherePred>,
+    /// The lifetime given to the references that have their lifetime elided.
+    elided_lifetime: Option<String>,
+}
+
+impl Generics {
+    fn collect<'a>(
+        ctx: &AssistContext<'_>,
+        fn_: &ast::Fn,
+        impl_: Option<&ast::Impl>,
+        types: impl Iterator<Item = &'a ast::Type>,
+    ) -> Generics {
+        let mut used = FxHashSet::default();
+        let mut has_elided = false;
+        for ty in types {
+            for node in ty.syntax().descendants() {
+                if let Some(path) = ast::Path::cast(node.clone()) {
+                    match ctx.sema.resolve_path(&path) {
+                        Some(PathResolution::TypeParam(it)) => {
+                            used.insert(hir::GenericParam::TypeParam(it));
+                        }
+                        Some(PathResolution::ConstParam(it)) => {
+                            used.insert(hir::GenericParam::ConstParam(it));
+                        }
+                        _ => (),
+                    }
+                } else if let Some(lifetime) = ast::Lifetime::cast(node.clone()) {
+                    if lifetime.text() == "'_" {
+                        has_elided |= !in_fn_signature_type(&node);
+                    } else if let Some(it) = ctx.sema.resolve_lifetime_param(&lifetime) {
+                        used.insert(hir::GenericParam::LifetimeParam(it));
+                    }
+                } else if let Some(ref_ty) = ast::RefType::cast(node.clone()) {
+                    has_elided |= ref_ty.lifetime().is_none() && !in_fn_signature_type(&node);
+                }
+            }
+        }
+
+        let owners = impl_
+            .and_then(|it| it.generic_param_list())
+            .into_iter()
+            .chain(fn_.generic_param_list());
+        let params: Vec<_> = owners
+            .flat_map(|it| it.generic_params())
+            .filter(|param| ctx.sema.to_def(param).map_or(false, |it| used.contains(&it)))
+            .collect();
+
+        let is_used = |ty: Option<ast::Type>| {
+            match ty {
+            Some(ast::Type::PathType(ty)) => ty.path().map_or(false, |path| {
+                matches!(
+                    ctx.sema.resolve_path(&path),
+                    Some(PathResolution::TypeParam(it)) if used.contains(&hir::GenericParam::TypeParam(it))
+                )
+            }),
+            _ => false,
+        }
+        };
+        let where_preds = impl_
+            .and_then(|it| it.where_clause())
+            .into_iter()
+            .chain(fn_.where_clause())
+            .flat_map(|it| it.predicates())
+            .filter(|pred| match pred.lifetime() {
+                Some(lifetime) => ctx
+                    .sema
+                    .resolve_lifetime_param(&lifetime)
+                    .map_or(false, |it| used.contains(&hir::GenericParam::LifetimeParam(it))),
+                None => is_used(pred.ty()),
+            })
+            .collect();
+
+        let elided_lifetime = has_elided.then(|| {
+            let taken: Vec<String> = impl_
+                .and_then(|it| it.generic_param_list())
+                .into_iter()
+                .chain(fn_.generic_param_list())
+                .flat_map(|it| it.lifetime_params())
+                .filter_map(|it| Some(it.lifetime()?.to_string()))
+                .collect();
+            ('a'..='z').map(|c| format!("'{c}")).find(|it| !taken.contains(it)).unwrap()
+        });
+
+        Generics { params, where_preds, elided_lifetime }
+    }
+
+    /// The generic parameter list of the struct.
+    fn params(&self) -> String {
+        self.list(|param| Some(param.to_string()), |lifetime| lifetime.to_owned())
+    }
+
+    /// The generic arguments of the struct in the function signature.
+    fn args(&self) -> String {
+        self.list(
+            |param| match param {
+                ast::GenericParam::LifetimeParam(it) => Some(it.lifetime()?.to_string()),
+                ast::GenericParam::TypeParam(it) => Some(it.name()?.to_string()),
+                ast::GenericParam::ConstParam(it) => Some(it.name()?.to_string()),
+            },
+            |_| "'_".to_owned(),
+        )
+    }
+
+    /// Renders the parameters, with the lifetime for elided lifetimes after the named ones.
+    fn list(
+        &self,
+        param: impl Fn(&ast::GenericParam) -> Option<String>,
+        elided: impl Fn(&str) -> String,
+    ) -> String {
+        let (lifetimes, others): (Vec<_>, Vec<_>) =
+            self.params.iter().partition(|it| matches!(it, ast::GenericParam::LifetimeParam(_)));
+        let list = lifetimes
+            .into_iter()
+            .filter_map(&param)
+            .chain(self.elided_lifetime.as_deref().map(elided))
+            .chain(others.into_iter().filter_map(&param))
+            .join(", ");
+        if list.is_empty() {
+            list
+        } else {
+            format!("<{list}>")
+        }
+    }
+
+    /// The type of a field, with elided lifetimes named.
+    fn field_ty(&self, ty: &ast::Type) -> String {
+        let Some(lifetime) = &self.elided_lifetime else { return ty.to_string() };
+        let mut buf = String::new();
+        for token in ty.syntax().descendants_with_tokens().filter_map(|it| it.into_token()) {
+            let parent = token.parent();
+            let in_fn_type = parent.as_ref().map_or(false, in_fn_signature_type);
+            match token.kind() {
+                T![&] if !in_fn_type => {
+                    let ref_ty = parent.and_then(ast::RefType::cast);
+                    buf.push('&');
+                    if ref_ty.map_or(false, |it| it.lifetime().is_none()) {
+                        format_to!(buf, "{lifetime} ");
+                    }
+                }
+                SyntaxKind::LIFETIME_IDENT if token.text() == "'_" && !in_fn_type => {
+                    buf.push_str(lifetime)
+                }
+                _ => buf.push_str(token.text()),
+            }
+        }
+        buf
+    }
+}
+
+/// Whether `node` is part of a function pointer or `Fn` trait signature, where lifetimes are
+/// elided independently.
+fn in_fn_signature_type(node: &syntax::SyntaxNode) -> bool {
+    node.ancestors().any(|it| {
+        matches!(it.kind(), SyntaxKind::FN_PTR_TYPE | SyntaxKind::PARAM_LIST)
+            && it.ancestors().any(|it| ast::Type::can_cast(it.kind()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn groups_parameters_of_method() {
+        check_assist(
+            introduce_parameter_object,
+            r#"
+struct Canvas;
+impl Canvas {
+    fn draw(&mut self, $0x: i32, mut y: i32, color: u32$0) {
+        y += x;
+    }
+    fn clear(&mut self) {
+        let color = 0;
+        self.draw(0, 0, color);
+        Canvas::draw(self, 1, 2, color + 1);
+    }
+}
+"#,
+            r#"
+struct Canvas;
+struct DrawParams {
+    x: i32,
+    y: i32,
+    color: u32,
+}
+
+impl Canvas {
+    fn draw(&mut self, DrawParams { x, mut y, color }: DrawParams) {
+        y += x;
+    }
+    fn clear(&mut self) {
+        let color = 0;
+        self.draw(DrawParams { x: 0, y: 0, color });
+        Canvas::draw(self, DrawParams { x: 1, y: 2, color: color + 1 });
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn carries_over_generics_and_lifetimes() {
+        check_assist(
+            introduce_parameter_object,
+            r#"
+trait Debug {}
+fn find<'a, T: Clone, U>(n$0ame: &'a str, items: &[T]$0, extra: U) -> &'a str
+where
+    T: Debug,
+    U: Debug,
+{
+    name
+}
+"#,
+            r#"
+trait Debug {}
+struct FindParams<'a, 'b, T: Clone>
+where
+    T: Debug,
+{
+    name: &'a str,
+    items: &'b [T],
+}
+
+fn find<'a, T: Clone, U>(FindParams { name, items }: FindParams<'a, '_, T>, extra: U) -> &'a str
+where
+    T: Debug,
+    U: Debug,
+{
+    name
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_struct_at_calls_in_other_modules() {
+        check_assist(
+            introduce_parameter_object,
+            r#"
+//- /main.rs
+mod geometry;
+fn main() {
+    geometry::area(3, 4);
+}
+//- /geometry.rs
+pub fn area($0width: u32, height: u32$0) -> u32 {
+    width * height
+}
+"#,
+            r#"
+//- /main.rs
+use geometry::AreaParams;
+
+mod geometry;
+fn main() {
+    geometry::area(AreaParams { width: 3, height: 4 });
+}
+//- /geometry.rs
+pub struct AreaParams {
+    pub width: u32,
+    pub height: u32,
+}
+
+pub fn area(AreaParams { width, height }: AreaParams) -> u32 {
+    width * height
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reports_references_left_unchanged() {
+        check_assist_by_label(
+            introduce_parameter_object,
+            r#"
+fn add($0a: u8, b: u8$0) -> u8 { a + b }
+fn main() {
+    let f: fn(u8, u8) -> u8 = add;
+    add(1, 2);
+}
+"#,
+            r#"
+struct AddParams {
+    a: u8,
+    b: u8,
+}
+
+fn add(AddParams { a, b }: AddParams) -> u8 { a + b }
+fn main() {
+    let f: fn(u8, u8) -> u8 = add;
+    add(AddParams { a: 1, b: 2 });
+}
+"#,
+            "Introduce parameter object (1 reference left unchanged)",
+        );
+    }
+
+    #[test]
+    fn not_applicable() {
+        check_assist_not_applicable(
+            introduce_parameter_object,
+            r#"
+fn f($0a: u8$0, b: u8) {}
+"#,
+        );
+        check_assist_not_applicable(
+            introduce_parameter_object,
+            r#"
+fn f($0(a, b): (u8, u8), c: u8$0) {}
+"#,
+        );
+        check_assist_not_applicable(
+            introduce_parameter_object,
+            r#"
+trait T {
+    fn f(&self, $0a: u8, b: u8$0);
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use hir::AsAssocItem;
+use ide_db::{
+    base_db::FileId, defs::Definition, search::FileReferenceNode,
+    source_change::SourceChangeBuilder, syntax_helpers::node_ext::full_path_of_name_ref, FxHashMap,
+    FxHashSet, RootDatabase,
+};
+use itertools::Itertools;
+use syntax::{ast, match_ast, AstNode, SyntaxNode, TextSize};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: make_async
+//
+// Makes a function async and awaits it at every call site.
+//
+// ```
+// f$0n read() -> u32 { 0 }
+// async fn run() -> u32 { read() }
+// ```
+// ->
+// ```
+// async fn read() -> u32 { 0 }
+// async fn run() -> u32 { read().await }
+// ```
+pub(crate) fn make_async(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let (function, func) = target_fn(ctx)?;
+    let mut edits = CallSites::find(&ctx.sema, func).awaits;
+    edits.entry(ctx.file_id()).or_default().push(Edit::Async(async_insert_offset(&function)?));
+
+    acc.add(
+        AssistId("make_async", AssistKind::RefactorRewrite),
+        "Make function async",
+        function.syntax().text_range(),
+        |builder| apply(builder, edits),
+    )
+}
+
+// Assist: make_async_with_callers
+//
+// Makes a function async and awaits it at every call site, making the functions containing the
+// calls async as well until an async context is reached.
+//
+// ```
+// f$0n read() -> u32 { 0 }
+// fn parse() -> u32 { read() }
+// async fn run() { parse(); }
+// ```
+// ->
+// ```
+// async fn read() -> u32 { 0 }
+// async fn parse() -> u32 { read().await }
+// async fn run() { parse().await; }
+// ```
+pub(crate) fn make_async_with_callers(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let (function, func) = target_fn(ctx)?;
+
+    let mut edits: FxHashMap<FileId, Vec<Edit>> = FxHashMap::default();
+    let mut blocked = Vec::new();
+    let mut made_async = FxHashSet::default();
+    made_async.insert(func);
+    let mut queue = vec![(ctx.file_id(), function.clone(), func)];
+    while let Some((file_id, function, func)) = queue.pop() {
+        edits.entry(file_id).or_default().push(Edit::Async(async_insert_offset(&function)?));
+        let sites = CallSites::find(&ctx.sema, func);
+        for (file_id, awaits) in sites.awaits {
+            edits.entry(file_id).or_default().extend(awaits);
+        }
+        blocked.extend(sites.blocked);
+        for (file_id, caller) in sites.callers {
+            let Some(caller_def) = ctx.sema.to_def(&caller) else { continue };
+            if made_async.insert(caller_def) {
+                queue.push((file_id, caller, caller_def));
+            }
+        }
+    }
+    // Only the called function itself would be made async, which the plain assist already does.
+    if made_async.len() == 1 {
+        return None;
+    }
+
+    let callers = made_async.len() - 1;
+    let mut label = format!(
+        "Make function async and propagate to {callers} {}",
+        if callers == 1 { "caller" } else { "callers" }
+    );
+    if !blocked.is_empty() {
+        let places = blocked
+            .iter()
+            .counts()
+            .into_iter()
+            .sorted()
+            .map(|(place, count)| {
+                format!("{count} {}{}", place.description(), if count == 1 { "" } else { "s" })
+            })
+            .join(", ");
+        label.push_str(&format!(" (not changed: {places})"));
+    }
+
+    acc.add(
+        AssistId("make_async_with_callers", AssistKind::RefactorRewrite),
+        label,
+        function.syntax().text_range(),
+        |builder| apply(builder, edits),
+    )
+}
+
+/// The function at the cursor, if it can be made async.
+fn target_fn(ctx: &AssistContext<'_>) -> Option<(ast::Fn, hir::Function)> {
+    let function: ast::Fn = ctx.find_node_at_offset()?;
+    // Only offer this on the prototype, like `unnecessary_async` does.
+    if ctx.offset() >= function.body()?.syntax().text_range().start() {
+        return None;
+    }
+    if function.async_token().is_some() || function.const_token().is_some() {
+        return None;
+    }
+    let func = ctx.sema.to_def(&function)?;
+    // Trait methods have to keep the signature of the trait.
+    if is_trait_item(ctx.db(), func) {
+        return None;
+    }
+    Some((function, func))
+}
+
+fn is_trait_item(db: &RootDatabase, func: hir::Function) -> bool {
+    func.as_assoc_item(db).and_then(|it| it.containing_trait_or_trait_impl(db)).is_some()
+}
+
+/// `async` goes after `const` and before `unsafe` and `extern`.
+fn async_insert_offset(function: &ast::Fn) -> Option<TextSize> {
+    let token = function
+        .unsafe_token()
+        .or_else(|| function.abi().and_then(|abi| abi.syntax().first_token()))
+        .or_else(|| function.fn_token())?;
+    Some(token.text_range().start())
+}
+
+enum Edit {
+    Async(TextSize),
+    Await(TextSize),
+}
+
+fn apply(builder: &mut SourceChangeBuilder, edits: FxHashMap<FileId, Vec<Edit>>) {
+    for (file_id, edits) in edits {
+        builder.edit_file(file_id);
+        for edit in edits {
+            match edit {
+                Edit::Async(offset) => builder.insert(offset, "async "),
+                Edit::Await(offset) => builder.insert(offset, ".await"),
+            }
+        }
+    }
+}
+
+/// A place that keeps using the function synchronously.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Blocked {
+    Closure,
+    FnPointer,
+    TraitMethod,
+    ConstContext,
+    MacroCall,
+    Main,
+    Test,
+}
+
+impl Blocked {
+    fn description(self) -> &'static str {
+        match self {
+            Blocked::Closure => "closure",
+            Blocked::FnPointer => "function pointer",
+            Blocked::TraitMethod => "trait method",
+            Blocked::ConstContext => "const context",
+            Blocked::MacroCall => "macro call",
+            Blocked::Main => "main function",
+            Blocked::Test => "test",
+        }
+    }
+}
+
+#[derive(Default)]
+struct CallSites {
+    /// The ends of the calls to the function.
+    awaits: FxHashMap<FileId, Vec<Edit>>,
+    /// Sync functions calling the function, which have to be made async for the calls to compile.
+    callers: Vec<(FileId, ast::Fn)>,
+    blocked: Vec<Blocked>,
+}
+
+impl CallSites {
+    fn find(sema: &hir::Semantics<'_, RootDatabase>, func: hir::Function) -> CallSites {
+        let mut sites = CallSites::default();
+        let usages = Definition::Function(func).usages(sema).all();
+        for (file_id, references) in usages {
+            for reference in references {
+                let FileReferenceNode::NameRef(name_ref) = reference.name else { continue };
+                if name_ref.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind())) {
+                    continue;
+                }
+                if sema.hir_file_for(name_ref.syntax()).is_macro() {
+                    sites.blocked.push(Blocked::MacroCall);
+                    continue;
+                }
+                let Some(call) = call_of_name_ref(&name_ref) else {
+                    sites.blocked.push(Blocked::FnPointer);
+                    continue;
+                };
+                let blocked = match call_context(&call) {
+                    Context::Async => None,
+                    Context::Blocked(place) => Some(place),
+                    Context::Fn(caller) => match sema.to_def(&caller) {
+                        Some(caller_def) if is_trait_item(sema.db, caller_def) => {
+                            Some(Blocked::TraitMethod)
+                        }
+                        // Those would need an async runtime to run them.
+                        Some(caller_def) if caller_def.is_main(sema.db) => Some(Blocked::Main),
+                        Some(caller_def) if caller_def.is_test(sema.db) => Some(Blocked::Test),
+                        _ => {
+                            sites.callers.push((file_id, caller));
+                            None
+                        }
+                    },
+                };
+                match blocked {
+                    Some(place) => sites.blocked.push(place),
+                    None => sites
+                        .awaits
+                        .entry(file_id)
+                        .or_default()
+                        .push(Edit::Await(call.text_range().end())),
+                }
+            }
+        }
+        sites
+    }
+}
+
+/// The call expression `name_ref` is the callee of.
+fn call_of_name_ref(name_ref: &ast::NameRef) -> Option<SyntaxNode> {
+    if let Some(path) = full_path_of_name_ref(name_ref) {
+        let path_expr = ast::PathExpr::cast(path.syntax().parent()?)?;
+        let call = ast::CallExpr::cast(path_expr.syntax().parent()?)?;
+        (call.expr()?.syntax() == path_expr.syntax()).then(|| call.syntax().clone())
+    } else {
+        let call = ast::MethodCallExpr::cast(name_ref.syntax().parent()?)?;
+        Some(call.syntax().clone())
+    }
+}
+
+enum Context {
+    Async,
+    Fn(ast::Fn),
+    Blocked(Blocked),
+}
+
+/// What a call is evaluated in.
+fn call_context(call: &SyntaxNode) -> Context {
+    for node in call.ancestors().skip(1) {
+        match_ast! {
+            match node {
+                ast::ClosureExpr(it) => {
+                    return match it.async_token() {
+                        Some(_) => Context::Async,
+                        None => Context::Blocked(Blocked::Closure),
+                    };
+                },
+                ast::BlockExpr(it) => {
+                    if it.async_token().is_some() {
+                        return Context::Async;
+                    }
+                    if it.const_token().is_some() {
+                        return Context::Blocked(Blocked::ConstContext);
+                    }
+                },
+                ast::Fn(it) => {
+                    return if it.async_token().is_some() {
+                        Context::Async
+                    } else if it.const_token().is_some() {
+                        Context::Blocked(Blocked::ConstContext)
+                    } else {
+                        Context::Fn(it)
+                    };
+                },
+                _ => (),
+            }
+        }
+    }
+    Context::Blocked(Blocked::ConstContext)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn awaits_calls_and_method_calls() {
+        check_assist(
+            make_async,
+            r#"
+struct S;
+impl S {
+    pub unsafe f$0n get(&self) -> u32 { 0 }
+}
+async fn run(s: S) -> u32 {
+    let a = unsafe { s.get() };
+    let b = unsafe { S::get(&s) };
+    a + b
+}
+"#,
+            r#"
+struct S;
+impl S {
+    pub async unsafe fn get(&self) -> u32 { 0 }
+}
+async fn run(s: S) -> u32 {
+    let a = unsafe { s.get().await };
+    let b = unsafe { S::get(&s).await };
+    a + b
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn awaits_calls_in_other_files() {
+        check_assist(
+            make_async,
+            r#"
+//- /main.rs
+mod io;
+use io::read;
+async fn run() { read(); }
+//- /io.rs
+pub fn read$0() {}
+"#,
+            r#"
+//- /main.rs
+mod io;
+use io::read;
+async fn run() { read().await; }
+//- /io.rs
+pub async fn read() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn propagates_through_callers() {
+        check_assist(
+            make_async_with_callers,
+            r#"
+fn read$0() -> u32 { 0 }
+fn parse() -> u32 { read() + read() }
+fn check() -> bool { parse() > 0 }
+async fn run() {
+    check();
+}
+"#,
+            r#"
+async fn read() -> u32 { 0 }
+async fn parse() -> u32 { read().await + read().await }
+async fn check() -> bool { parse().await > 0 }
+async fn run() {
+    check().await;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reports_places_it_could_not_change() {
+        check_assist_by_label(
+            make_async_with_callers,
+            r#"
+trait Load {
+    fn load(&self) -> u32;
+}
+impl Load for () {
+    fn load(&self) -> u32 { parse() }
+}
+fn read$0() -> u32 { 0 }
+fn parse() -> u32 { read() }
+fn main() {
+    let f: fn() -> u32 = parse;
+    let g = || read();
+    let n = parse();
+}
+#[test]
+fn parses() {
+    parse();
+}
+"#,
+            r#"
+trait Load {
+    fn load(&self) -> u32;
+}
+impl Load for () {
+    fn load(&self) -> u32 { parse() }
+}
+async fn read() -> u32 { 0 }
+async fn parse() -> u32 { read().await }
+fn main() {
+    let f: fn() -> u32 = parse;
+    let g = || read();
+    let n = parse();
+}
+#[test]
+fn parses() {
+    parse();
+}
+"#,
+            "Make function async and propagate to 1 caller (not changed: 1 closure, 1 function pointer, 1 trait method, 1 main function, 1 test)",
+        );
+    }
+
+    #[test]
+    fn propagation_not_offered_without_sync_callers() {
+        check_assist_not_applicable(
+            make_async_with_callers,
+            r#"
+fn read$0() -> u32 { 0 }
+async fn run() -> u32 { read() }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_trait_methods_and_const_fns() {
+        check_assist_not_applicable(
+            make_async,
+            r#"
+trait T {
+    fn f(&self);
+}
+impl T for () {
+    fn f$0(&self) {}
+}
+"#,
+        );
+        check_assist_not_applicable(
+            make_async,
+            r#"
+const fn f$0() {}
+"#,
+        );
+        check_assist_not_applicable(
+            make_async,
+            r#"
+async fn f$0() {}
+"#,
+        );
+    }
+}
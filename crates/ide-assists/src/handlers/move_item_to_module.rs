@@ -0,0 +1,976 @@
+use hir::{AsAssocItem, HasSource, ModuleDef, ModuleSource, PathResolution};
+use ide_db::{
+    assists::GroupLabel,
+    base_db::FileId,
+    defs::{Definition, NameRefClass},
+    helpers::mod_path_to_ast,
+    imports::insert_use::{insert_use, ImportScope},
+    source_change::SourceChangeBuilder,
+    FxHashMap, FxHashSet,
+};
+use itertools::Itertools;
+use syntax::{
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        edit_in_place::HasVisibilityEdit,
+        make, HasVisibility as _,
+    },
+    ted, AstNode, SyntaxElement, SyntaxKind, SyntaxNode, TextRange, TextSize, T,
+};
+
+use super::fix_visibility::{missing_visibility, target_data_for_def, target_data_for_field};
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: move_item_to_module
+//
+// Moves an item, along with the inherent impls of a type, to another module of the crate,
+// updating the paths and imports referring to it.
+//
+// ```
+// mod shapes {
+//     pub struct Circle;
+// }
+//
+// struct Squ$0are;
+//
+// impl Square {
+//     fn new() -> Self {
+//         Square
+//     }
+// }
+//
+// fn main() {
+//     let _ = Square::new();
+// }
+// ```
+// ->
+// ```
+// use shapes::Square;
+//
+// mod shapes {
+//     pub struct Circle;
+//
+//     pub(crate) struct Square;
+//
+//     impl Square {
+//         pub(crate) fn new() -> Self {
+//             Square
+//         }
+//     }
+// }
+//
+// fn main() {
+//     let _ = Square::new();
+// }
+// ```
+pub(crate) fn move_item_to_module(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let item = ast::Item::cast(name.syntax().parent()?)?;
+    // Only module level items, associated items move with their impl.
+    if !matches!(item.syntax().parent()?.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST) {
+        return None;
+    }
+    let def: ModuleDef = match &item {
+        ast::Item::Fn(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Struct(it) => hir::Adt::from(ctx.sema.to_def(it)?).into(),
+        ast::Item::Enum(it) => hir::Adt::from(ctx.sema.to_def(it)?).into(),
+        ast::Item::Union(it) => hir::Adt::from(ctx.sema.to_def(it)?).into(),
+        ast::Item::Trait(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::TypeAlias(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Const(it) => ctx.sema.to_def(it)?.into(),
+        ast::Item::Static(it) => ctx.sema.to_def(it)?.into(),
+        _ => return None,
+    };
+    let db = ctx.db();
+    let source_module = def.module(db)?;
+    let item_name = def.name(db)?;
+
+    // The inherent impls of a type defined next to it move along with it.
+    let impls: Vec<ast::Impl> = match def {
+        ModuleDef::Adt(adt) => hir::Impl::all_for_type(db, adt.ty(db))
+            .into_iter()
+            .filter(|it| it.trait_(db).is_none() && it.module(db) == source_module)
+            .filter_map(|it| it.source(db))
+            .filter(|it| it.file_id == ctx.file_id().into())
+            .map(|it| it.value)
+            .sorted_by_key(|it| it.syntax().text_range().start())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let targets = source_module.krate().modules(db).into_iter().filter(|&module| {
+        module != source_module
+            && module.definition_source_file_id(db).file_id().is_some()
+            && !module
+                .scope(db, None)
+                .iter()
+                .any(|(name, it)| *name == item_name && *it != hir::ScopeDef::ModuleDef(def))
+    });
+
+    let group = GroupLabel("Move item to module...".to_owned());
+    for target in targets {
+        acc.add_group(
+            &group,
+            AssistId("move_item_to_module", AssistKind::RefactorRewrite),
+            format!("Move `{}` to `{}`", item_name.display(db), module_path_display(ctx, target)),
+            name.syntax().text_range(),
+            |builder| {
+                let mut mover = Mover {
+                    ctx,
+                    file_id: ctx.file_id(),
+                    target,
+                    moved: std::iter::once(item.clone())
+                        .chain(impls.iter().cloned().map(ast::Item::Impl))
+                        .collect(),
+                    edits: FxHashMap::default(),
+                    target_imports: FxHashSet::default(),
+                };
+                if mover.collect_edits(def).is_some() {
+                    mover.apply(builder);
+                }
+            },
+        );
+    }
+    Some(())
+}
+
+fn module_path_display(ctx: &AssistContext<'_>, module: hir::Module) -> String {
+    let mut path = module
+        .path_to_root(ctx.db())
+        .into_iter()
+        .rev()
+        .filter_map(|it| it.name(ctx.db()))
+        .map(|it| it.display(ctx.db()).to_string())
+        .peekable();
+    if path.peek().is_none() {
+        return "crate".to_owned();
+    }
+    format!("crate::{}", path.join("::"))
+}
+
+enum Edit {
+    Remove(SyntaxNode),
+    Replace(SyntaxNode, SyntaxNode),
+    InsertAfter(SyntaxNode, ast::Item),
+    RemoveUseTree(ast::UseTree),
+    SetVisibility(ast::AnyHasVisibility, ast::Visibility),
+    Import(ImportScope, ast::Path),
+    AppendItems(SyntaxNode, IndentLevel, Vec<ast::Item>),
+}
+
+impl Edit {
+    fn make_mut(self, builder: &mut SourceChangeBuilder) -> Edit {
+        match self {
+            Edit::Remove(node) => Edit::Remove(builder.make_syntax_mut(node)),
+            Edit::Replace(node, new) => Edit::Replace(builder.make_syntax_mut(node), new),
+            Edit::InsertAfter(node, new) => Edit::InsertAfter(builder.make_syntax_mut(node), new),
+            Edit::RemoveUseTree(tree) => Edit::RemoveUseTree(builder.make_mut(tree)),
+            Edit::SetVisibility(owner, vis) => Edit::SetVisibility(builder.make_mut(owner), vis),
+            Edit::Import(scope, path) => {
+                let scope = match scope {
+                    ImportScope::File(it) => ImportScope::File(builder.make_mut(it)),
+                    ImportScope::Module(it) => ImportScope::Module(builder.make_mut(it)),
+                    ImportScope::Block(it) => ImportScope::Block(builder.make_mut(it)),
+                };
+                Edit::Import(scope, path)
+            }
+            Edit::AppendItems(node, indent, items) => {
+                Edit::AppendItems(builder.make_syntax_mut(node), indent, items)
+            }
+        }
+    }
+
+    fn apply(self, ctx: &AssistContext<'_>) {
+        match self {
+            Edit::Remove(node) => {
+                let is_ws = |it: &SyntaxElement| it.kind() == SyntaxKind::WHITESPACE;
+                let prev_ws = node.prev_sibling_or_token().filter(is_ws);
+                let next_ws = node.next_sibling_or_token().filter(is_ws);
+                let is_first = prev_ws
+                    .as_ref()
+                    .map_or_else(|| node.prev_sibling_or_token(), |it| it.prev_sibling_or_token())
+                    .map_or(true, |it| it.kind() == T!['{']);
+                let is_last = next_ws
+                    .as_ref()
+                    .map_or_else(|| node.next_sibling_or_token(), |it| it.next_sibling_or_token())
+                    .map_or(true, |it| it.kind() == T!['}']);
+                let ws = match (is_first, is_last) {
+                    (true, true) => vec![prev_ws, next_ws],
+                    (true, false) => vec![next_ws],
+                    (false, _) => vec![prev_ws],
+                };
+                ws.into_iter().flatten().for_each(ted::remove);
+                ted::remove(node);
+            }
+            Edit::Replace(node, new) => ted::replace(node, new.clone_for_update()),
+            Edit::InsertAfter(node, new) => {
+                let indent = IndentLevel::from_node(&node);
+                ted::insert_all_raw(
+                    ted::Position::after(node),
+                    vec![
+                        make::tokens::whitespace(&format!("\n{indent}")).into(),
+                        new.clone_for_update().syntax().clone().into(),
+                    ],
+                );
+            }
+            Edit::RemoveUseTree(tree) => tree.remove_recursive(),
+            Edit::SetVisibility(owner, vis) => owner.set_visibility(vis.clone_for_update()),
+            Edit::Import(scope, path) => insert_use(&scope, path, &ctx.config.insert_use),
+            Edit::AppendItems(container, indent, items) => append_items(&container, indent, items),
+        }
+    }
+}
+
+/// Appends the items to a mutable `SourceFile` or `ItemList`.
+fn append_items(container: &SyntaxNode, indent: IndentLevel, items: Vec<ast::Item>) {
+    let item_list = ast::ItemList::cast(container.clone());
+    let last_item = container.children().filter(|it| ast::Item::can_cast(it.kind())).last();
+    let (position, mut separator) = match (&last_item, &item_list) {
+        (Some(last_item), _) => (ted::Position::after(last_item), format!("\n\n{indent}")),
+        (None, Some(item_list)) => match item_list.l_curly_token() {
+            Some(l_curly) => (ted::Position::after(l_curly), format!("\n{indent}")),
+            None => return,
+        },
+        (None, None) => (ted::Position::last_child_of(container), String::new()),
+    };
+    let mut elements: Vec<SyntaxElement> = Vec::new();
+    for item in items {
+        elements.push(make::tokens::whitespace(&separator).into());
+        elements.push(item.clone_for_update().syntax().clone().into());
+        separator = format!("\n\n{indent}");
+    }
+    if last_item.is_none() {
+        // An empty `{}` needs a line break before the closing brace, a file a trailing newline.
+        match item_list.and_then(|it| it.r_curly_token()) {
+            Some(r_curly) => {
+                if r_curly.prev_token().map_or(true, |it| it.kind() != SyntaxKind::WHITESPACE) {
+                    let outer = IndentLevel(indent.0.saturating_sub(1));
+                    elements.push(make::tokens::whitespace(&format!("\n{outer}")).into());
+                }
+            }
+            None => elements.push(make::tokens::single_newline().into()),
+        }
+    }
+    elements.retain(|it| it.as_token().map_or(true, |it| !it.text().is_empty()));
+    ted::insert_all_raw(position, elements);
+}
+
+struct Mover<'a, 'db> {
+    ctx: &'a AssistContext<'db>,
+    file_id: FileId,
+    target: hir::Module,
+    /// The item and the impls moving along with it.
+    moved: Vec<ast::Item>,
+    edits: FxHashMap<FileId, Vec<Edit>>,
+    target_imports: FxHashSet<ModuleDef>,
+}
+
+impl Mover<'_, '_> {
+    fn collect_edits(&mut self, def: ModuleDef) -> Option<()> {
+        let db = self.ctx.db();
+        let target_source = self.target.definition_source(db);
+        let target_file = target_source.file_id.file_id()?;
+        let (target_container, target_scope, target_indent) = match target_source.value {
+            ModuleSource::SourceFile(it) => {
+                (it.syntax().clone(), ImportScope::File(it), IndentLevel(0))
+            }
+            ModuleSource::Module(it) => {
+                let item_list = it.item_list()?;
+                let indent = IndentLevel::from_node(it.syntax()) + 1;
+                (item_list.syntax().clone(), ImportScope::Module(item_list), indent)
+            }
+            ModuleSource::BlockExpr(_) => return None,
+        };
+
+        // Work on copies of the moved items, mapping the nodes to change into them up front.
+        let copies: Vec<_> = self
+            .moved
+            .iter()
+            .map(|it| (it.syntax().clone_subtree().clone_for_update(), it.syntax().text_range()))
+            .collect();
+        let mut copy_edits: Vec<(SyntaxNode, CopyEdit)> = Vec::new();
+
+        let mut moved_defs = vec![Definition::from(def)];
+        let mut vis_owners = vec![(
+            Definition::from(def),
+            ast::AnyHasVisibility::cast(self.moved[0].syntax().clone())?,
+        )];
+        match def {
+            ModuleDef::Adt(hir::Adt::Struct(_) | hir::Adt::Union(_)) => {
+                let hir::ModuleDef::Adt(adt) = def else { unreachable!() };
+                let fields = match adt {
+                    hir::Adt::Struct(it) => it.fields(db),
+                    hir::Adt::Union(it) => it.fields(db),
+                    hir::Adt::Enum(_) => Vec::new(),
+                };
+                for field in fields {
+                    moved_defs.push(Definition::Field(field));
+                    let (owner, ..) = target_data_for_field(db, field)?;
+                    vis_owners.push((Definition::Field(field), owner));
+                }
+            }
+            ModuleDef::Adt(hir::Adt::Enum(it)) => {
+                moved_defs.extend(it.variants(db).into_iter().map(Definition::Variant))
+            }
+            _ => (),
+        }
+        for impl_ in self.moved.iter().skip(1).filter_map(|it| ast::Impl::cast(it.syntax().clone()))
+        {
+            let Some(impl_) = self.ctx.sema.to_def(&impl_) else { continue };
+            for assoc in impl_.items(db) {
+                match assoc {
+                    hir::AssocItem::Function(it) => {
+                        moved_defs.push(Definition::Function(it));
+                        let (owner, ..) = target_data_for_def(db, it.into())?;
+                        vis_owners.push((Definition::Function(it), owner));
+                    }
+                    hir::AssocItem::Const(it) => {
+                        moved_defs.push(Definition::Const(it));
+                        let (owner, ..) = target_data_for_def(db, it.into())?;
+                        vis_owners.push((Definition::Const(it), owner));
+                    }
+                    hir::AssocItem::TypeAlias(it) => moved_defs.push(Definition::TypeAlias(it)),
+                }
+            }
+        }
+
+        // Moved definitions used outside of their new module need to be made visible there.
+        for (def, owner) in vis_owners {
+            let visible_in = match owner.visibility().map(|it| it.kind()) {
+                None | Some(ast::VisibilityKind::PubSelf) => Some(self.target),
+                Some(ast::VisibilityKind::PubSuper) => self.target.parent(db),
+                _ => continue,
+            };
+            let usage_module = def
+                .usages(&self.ctx.sema)
+                .all()
+                .into_iter()
+                .flat_map(|(file_id, refs)| refs.into_iter().map(move |it| (file_id, it)))
+                .filter(|(file_id, reference)| !self.is_moved(*file_id, reference.range))
+                .filter_map(|(_, reference)| {
+                    let node = reference.name.as_name_ref()?.syntax().clone();
+                    Some(self.ctx.sema.scope(&node)?.module().nearest_non_block_module(db))
+                })
+                .find(|module| {
+                    visible_in
+                        .map_or(true, |visible_in| !module.path_to_root(db).contains(&visible_in))
+                });
+            if let Some(usage_module) = usage_module {
+                let vis = missing_visibility(usage_module, self.target);
+                copy_edits.push((owner.syntax().clone(), CopyEdit::SetVisibility(vis)));
+            }
+        }
+
+        // Names used by the moved items have to stay resolvable from the new module.
+        let mut fixed_visibility = FxHashSet::default();
+        for item in self.moved.clone() {
+            for node in item.syntax().descendants() {
+                if let Some(path) = ast::Path::cast(node.clone()) {
+                    self.fix_path_in_moved_item(&path, &moved_defs, &target_scope, &mut copy_edits);
+                } else if let Some(name_ref) = ast::NameRef::cast(node.clone()) {
+                    self.fix_visibility_of_used_item(&name_ref, &moved_defs, &mut fixed_visibility);
+                } else if let Some(call) = ast::MethodCallExpr::cast(node) {
+                    let trait_ = self
+                        .ctx
+                        .sema
+                        .resolve_method_call(&call)
+                        .and_then(|it| it.as_assoc_item(db))
+                        .and_then(|it| it.containing_trait_or_trait_impl(db));
+                    if let Some(trait_) = trait_ {
+                        self.import_into_target(trait_.into(), &target_scope);
+                    }
+                }
+            }
+        }
+
+        self.fix_references(def, &moved_defs)?;
+
+        let mapped: Vec<_> = copy_edits
+            .into_iter()
+            .filter_map(|(node, edit)| {
+                let (copy, range) =
+                    copies.iter().find(|(_, range)| range.contains_range(node.text_range()))?;
+                Some((node_in_copy(copy, range.start(), &node)?, edit))
+            })
+            .collect();
+        for (node, edit) in mapped {
+            match edit {
+                CopyEdit::SetVisibility(vis) => {
+                    if let Some(owner) = ast::AnyHasVisibility::cast(node) {
+                        owner.set_visibility(vis.clone_for_update());
+                    }
+                }
+                CopyEdit::Replace(new) => ted::replace(node, new.clone_for_update().syntax()),
+            }
+        }
+
+        let items = copies
+            .into_iter()
+            .zip(&self.moved)
+            .filter_map(|((copy, _), item)| {
+                let indent = IndentLevel::from_node(item.syntax());
+                Some(ast::Item::cast(copy)?.dedent(indent).indent(target_indent))
+            })
+            .collect();
+        for item in self.moved.clone() {
+            self.push_edit(self.file_id, Edit::Remove(item.syntax().clone()));
+        }
+        self.push_edit(target_file, Edit::AppendItems(target_container, target_indent, items));
+        Some(())
+    }
+
+    /// Rewrites `self::` and `super::` prefixes relative to the new module and imports the
+    /// items the moved code refers to by name.
+    fn fix_path_in_moved_item(
+        &mut self,
+        path: &ast::Path,
+        moved_defs: &[Definition],
+        target_scope: &ImportScope,
+        copy_edits: &mut Vec<(SyntaxNode, CopyEdit)>,
+    ) -> Option<()> {
+        if path.qualifier().is_some() {
+            return None;
+        }
+        let sema = &self.ctx.sema;
+        match path.segment()?.kind()? {
+            ast::PathSegmentKind::SelfKw | ast::PathSegmentKind::SuperKw => {
+                let mut module_path = path.clone();
+                let mut module = match sema.resolve_path(path)? {
+                    PathResolution::Def(ModuleDef::Module(it)) => it,
+                    _ => return None,
+                };
+                while let Some(parent) = module_path.parent_path() {
+                    match sema.resolve_path(&parent) {
+                        Some(PathResolution::Def(ModuleDef::Module(it))) => {
+                            module = it;
+                            module_path = parent;
+                        }
+                        _ => break,
+                    }
+                }
+                let new_path = self
+                    .path_to_module(self.target, module)
+                    .unwrap_or_else(|| make::ext::ident_path("self"));
+                copy_edits.push((module_path.syntax().clone(), CopyEdit::Replace(new_path)));
+            }
+            ast::PathSegmentKind::Name(_) => {
+                let PathResolution::Def(def) = sema.resolve_path(path)? else { return None };
+                if matches!(def, ModuleDef::BuiltinType(_) | ModuleDef::Macro(_))
+                    || moved_defs.contains(&Definition::from(def))
+                {
+                    return None;
+                }
+                self.import_into_target(def, target_scope);
+            }
+            _ => (),
+        }
+        Some(())
+    }
+
+    fn import_into_target(&mut self, def: ModuleDef, target_scope: &ImportScope) -> Option<()> {
+        if !self.target_imports.insert(def) {
+            return None;
+        }
+        let db = self.ctx.db();
+        let cfg = &self.ctx.config;
+        let path = match self.target.find_use_path_prefixed(
+            db,
+            def,
+            cfg.insert_use.prefix_kind,
+            cfg.prefer_no_std,
+            cfg.prefer_prelude,
+        ) {
+            // The name is already in scope.
+            Some(it) if it.kind == hir::PathKind::Plain && it.segments().len() == 1 => return None,
+            Some(it) => mod_path_to_ast(&it),
+            // Private items of the old module are made visible separately.
+            None => {
+                let name = make::ext::ident_path(&def.name(db)?.display(db).to_string());
+                match self.path_to_module(self.target, def.module(db)?) {
+                    Some(qualifier) => make::path_concat(qualifier, name),
+                    None => return None,
+                }
+            }
+        };
+        let target_file = self.target.definition_source_file_id(db).file_id()?;
+        self.push_edit(target_file, Edit::Import(target_scope.clone(), path));
+        Some(())
+    }
+
+    /// Items of the old module might not be visible from the new one.
+    fn fix_visibility_of_used_item(
+        &mut self,
+        name_ref: &ast::NameRef,
+        moved_defs: &[Definition],
+        fixed: &mut FxHashSet<Definition>,
+    ) -> Option<()> {
+        let db = self.ctx.db();
+        let NameRefClass::Definition(mut def) = NameRefClass::classify(&self.ctx.sema, name_ref)?
+        else {
+            return None;
+        };
+        // Trait items share the visibility of their trait.
+        if let Some(assoc) = def.as_assoc_item(db) {
+            match assoc.container(db) {
+                hir::AssocItemContainer::Trait(it) => def = Definition::Trait(it),
+                hir::AssocItemContainer::Impl(it) if it.trait_(db).is_some() => return None,
+                hir::AssocItemContainer::Impl(_) => (),
+            }
+        }
+        let module = def.module(db)?;
+        if moved_defs.contains(&def)
+            || module.nearest_non_block_module(db) != module
+            || def.visibility(db)?.is_visible_from(db, self.target.into())
+            || !fixed.insert(def)
+        {
+            return None;
+        }
+        let (owner, _, file_id) = match def {
+            Definition::Field(it) => target_data_for_field(db, it)?,
+            Definition::Module(it) => {
+                let (owner, range, file_id, _) = target_data_for_def(db, it.into())?;
+                (owner, range, file_id)
+            }
+            _ => {
+                let def = def_to_module_def(def)?;
+                let (owner, range, file_id, _) = target_data_for_def(db, def)?;
+                (owner, range, file_id)
+            }
+        };
+        let vis = missing_visibility(self.target, module);
+        self.push_edit(file_id, Edit::SetVisibility(owner, vis));
+        Some(())
+    }
+
+    /// Points the paths and imports of the moved item to its new location.
+    fn fix_references(&mut self, def: ModuleDef, moved_defs: &[Definition]) -> Option<()> {
+        let db = self.ctx.db();
+        let usages = moved_defs[0].usages(&self.ctx.sema).all();
+        let name = make::name_ref(&def.name(db)?.display(db).to_string());
+        let mut modules_with_use = FxHashSet::default();
+        let mut unqualified = Vec::new();
+        for (file_id, refs) in usages {
+            for reference in refs {
+                if self.is_moved(file_id, reference.range) {
+                    continue;
+                }
+                let Some(name_ref) = reference.name.as_name_ref() else { continue };
+                let Some(segment) = name_ref.syntax().parent().and_then(ast::PathSegment::cast)
+                else {
+                    continue;
+                };
+                let path = segment.parent_path();
+                let Some(module) = self.ctx.sema.scope(name_ref.syntax()).map(|it| it.module())
+                else {
+                    continue;
+                };
+                let module = module.nearest_non_block_module(db);
+                let new_path = |this: &Self| {
+                    let name = make::path_from_segments([segment.clone()], false);
+                    match this.path_to_module(module, this.target) {
+                        Some(qualifier) => make::path_concat(qualifier, name),
+                        None => name,
+                    }
+                };
+
+                let use_tree = path.top_path().syntax().parent().and_then(ast::UseTree::cast);
+                if let Some(use_tree) = use_tree {
+                    modules_with_use.insert(module);
+                    let is_leaf = use_tree.path().as_ref() == Some(&path)
+                        && use_tree.use_tree_list().is_none()
+                        && use_tree.star_token().is_none();
+                    let is_top_level = use_tree
+                        .syntax()
+                        .parent()
+                        .map_or(false, |it| ast::Use::can_cast(it.kind()));
+                    if is_top_level {
+                        if module == self.target && is_leaf && use_tree.rename().is_none() {
+                            let use_item = use_tree.syntax().parent()?;
+                            self.push_edit(file_id, Edit::Remove(use_item));
+                        } else {
+                            let new_path = match self.path_to_module(module, self.target) {
+                                Some(_) => new_path(self),
+                                None => make::path_concat(
+                                    make::ext::ident_path("self"),
+                                    make::path_from_segments([segment.clone()], false),
+                                ),
+                            };
+                            self.push_edit(
+                                file_id,
+                                Edit::Replace(path.syntax().clone(), new_path.syntax().clone()),
+                            );
+                        }
+                    } else if is_leaf {
+                        // Nested trees are split out of their group.
+                        let use_item = use_tree.syntax().ancestors().find_map(ast::Use::cast)?;
+                        if module != self.target {
+                            let new_path = new_path(self);
+                            if use_item.visibility().is_some() || use_tree.rename().is_some() {
+                                let new_use = make::use_(
+                                    use_item.visibility(),
+                                    make::use_tree(new_path, None, use_tree.rename(), false),
+                                );
+                                self.push_edit(
+                                    file_id,
+                                    Edit::InsertAfter(use_item.syntax().clone(), new_use.into()),
+                                );
+                            } else if let Some(scope) = ImportScope::find_insert_use_container(
+                                use_item.syntax(),
+                                &self.ctx.sema,
+                            ) {
+                                self.push_edit(file_id, Edit::RemoveUseTree(use_tree));
+                                self.push_edit(file_id, Edit::Import(scope, new_path));
+                                continue;
+                            }
+                        }
+                        self.push_edit(file_id, Edit::RemoveUseTree(use_tree));
+                    }
+                } else if path.qualifier().is_some() {
+                    self.push_edit(
+                        file_id,
+                        Edit::Replace(path.syntax().clone(), new_path(self).syntax().clone()),
+                    );
+                } else if module != self.target {
+                    unqualified.push((file_id, module, path.clone()));
+                }
+            }
+        }
+
+        // Unqualified uses that weren't imported, like the ones in the old module, need an
+        // import now.
+        let mut imported = FxHashSet::default();
+        for (file_id, module, path) in unqualified {
+            if modules_with_use.contains(&module) || !imported.insert(module) {
+                continue;
+            }
+            let Some(scope) = ImportScope::find_insert_use_container(path.syntax(), &self.ctx.sema)
+            else {
+                continue;
+            };
+            let name = make::path_unqualified(make::path_segment(name.clone()));
+            let new_path = match self.path_to_module(module, self.target) {
+                Some(qualifier) => make::path_concat(qualifier, name),
+                None => continue,
+            };
+            self.push_edit(file_id, Edit::Import(scope, new_path));
+        }
+        Some(())
+    }
+
+    /// The path to `to` from `from`, `None` if they are the same module.
+    fn path_to_module(&self, from: hir::Module, to: hir::Module) -> Option<ast::Path> {
+        if from == to {
+            return None;
+        }
+        let cfg = &self.ctx.config;
+        let path = from
+            .find_use_path_prefixed(
+                self.ctx.db(),
+                ModuleDef::Module(to),
+                cfg.insert_use.prefix_kind,
+                cfg.prefer_no_std,
+                cfg.prefer_prelude,
+            )
+            .map(|it| mod_path_to_ast(&it))
+            .unwrap_or_else(|| make::path_from_text(&module_path_display(self.ctx, to)));
+        Some(path)
+    }
+
+    fn is_moved(&self, file_id: FileId, range: TextRange) -> bool {
+        file_id == self.file_id
+            && self.moved.iter().any(|it| it.syntax().text_range().contains_range(range))
+    }
+
+    fn push_edit(&mut self, file_id: FileId, edit: Edit) {
+        self.edits.entry(file_id).or_default().push(edit);
+    }
+
+    fn apply(self, builder: &mut SourceChangeBuilder) {
+        // Edit the current file last, so that the builder ends up on it.
+        let (current, others): (Vec<_>, Vec<_>) =
+            self.edits.into_iter().partition(|(file_id, _)| *file_id == self.file_id);
+        for (file_id, edits) in others.into_iter().chain(current) {
+            builder.edit_file(file_id);
+            let edits: Vec<_> = edits.into_iter().map(|it| it.make_mut(builder)).collect();
+            for edit in edits {
+                edit.apply(self.ctx);
+            }
+        }
+    }
+}
+
+enum CopyEdit {
+    SetVisibility(ast::Visibility),
+    Replace(ast::Path),
+}
+
+/// Finds the counterpart of `node` in `copy`, a copy of the subtree starting at `offset`.
+fn node_in_copy(copy: &SyntaxNode, offset: TextSize, node: &SyntaxNode) -> Option<SyntaxNode> {
+    let range = node.text_range() - offset;
+    let element = copy.covering_element(range);
+    let start = match element {
+        syntax::NodeOrToken::Node(it) => it,
+        syntax::NodeOrToken::Token(it) => it.parent()?,
+    };
+    start.ancestors().find(|it| it.text_range() == range && it.kind() == node.kind())
+}
+
+fn def_to_module_def(def: Definition) -> Option<ModuleDef> {
+    let def = match def {
+        Definition::Function(it) => it.into(),
+        Definition::Adt(it) => it.into(),
+        Definition::Const(it) => it.into(),
+        Definition::Static(it) => it.into(),
+        Definition::Trait(it) => it.into(),
+        Definition::TraitAlias(it) => it.into(),
+        Definition::TypeAlias(it) => it.into(),
+        _ => return None,
+    };
+    Some(def)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn move_struct_with_impl_to_inline_module() {
+        check_assist_by_label(
+            move_item_to_module,
+            r#"
+mod shapes {
+    pub struct Circle;
+}
+
+struct Squ$0are;
+
+impl Square {
+    fn new() -> Self {
+        Square
+    }
+}
+
+fn main() {
+    let _ = Square::new();
+}
+"#,
+            r#"
+use shapes::Square;
+
+mod shapes {
+    pub struct Circle;
+
+    pub(crate) struct Square;
+
+    impl Square {
+        pub(crate) fn new() -> Self {
+            Square
+        }
+    }
+}
+
+fn main() {
+    let _ = Square::new();
+}
+"#,
+            "Move `Square` to `crate::shapes`",
+        );
+    }
+
+    #[test]
+    fn move_fn_across_files() {
+        check_assist_by_label(
+            move_item_to_module,
+            r#"
+//- /main.rs
+mod a;
+mod b;
+mod c;
+
+fn main() {
+    a::run();
+    c::go();
+}
+//- /a.rs
+pub fn r$0un() {}
+//- /b.rs
+pub fn other() {}
+//- /c.rs
+use crate::a::run;
+
+pub fn go() {
+    run();
+}
+"#,
+            r#"
+//- /main.rs
+mod a;
+mod b;
+mod c;
+
+fn main() {
+    b::run();
+    c::go();
+}
+//- /a.rs
+//- /b.rs
+pub fn other() {}
+
+pub fn run() {}
+//- /c.rs
+use crate::b::run;
+
+pub fn go() {
+    run();
+}
+"#,
+            "Move `run` to `crate::b`",
+        );
+    }
+
+    #[test]
+    fn fixes_items_used_by_moved_item() {
+        check_assist_by_label(
+            move_item_to_module,
+            r#"
+mod a {
+    struct Config {
+        verbose: bool,
+    }
+
+    fn helper(config: &Config) -> bool {
+        config.verbose
+    }
+
+    pub fn ru$0n(config: &Config) -> bool {
+        helper(config) && config.verbose && super::enabled()
+    }
+}
+mod b {}
+fn enabled() -> bool { true }
+"#,
+            r#"
+mod a {
+    pub(crate) struct Config {
+        pub(crate) verbose: bool,
+    }
+
+    pub(crate) fn helper(config: &Config) -> bool {
+        config.verbose
+    }
+}
+mod b {
+    use crate::a::{Config, helper};
+
+    pub fn run(config: &Config) -> bool {
+        helper(config) && config.verbose && crate::enabled()
+    }
+}
+fn enabled() -> bool { true }
+"#,
+            "Move `run` to `crate::b`",
+        );
+    }
+
+    #[test]
+    fn splits_nested_imports() {
+        check_assist_by_label(
+            move_item_to_module,
+            r#"
+mod a {
+    pub struct Fo$0o;
+    pub struct Bar;
+}
+mod b {}
+mod c {
+    use crate::a::{Bar, Foo};
+
+    fn f(_: Foo, _: Bar) {}
+}
+"#,
+            r#"
+mod a {
+    pub struct Bar;
+}
+mod b {
+    pub struct Foo;
+}
+mod c {
+    use crate::{a::Bar, b::Foo};
+
+    fn f(_: Foo, _: Bar) {}
+}
+"#,
+            "Move `Foo` to `crate::b`",
+        );
+    }
+
+    #[test]
+    fn removes_import_in_target_module() {
+        check_assist_by_label(
+            move_item_to_module,
+            r#"
+mod a {
+    pub fn f$0() {}
+}
+mod b {
+    use crate::a::f;
+
+    fn g() {
+        f();
+    }
+}
+"#,
+            r#"
+mod a {}
+mod b {
+    fn g() {
+        f();
+    }
+
+    pub fn f() {}
+}
+"#,
+            "Move `f` to `crate::b`",
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_assoc_and_local_items() {
+        check_assist_not_applicable(
+            move_item_to_module,
+            r#"
+mod m {}
+struct S;
+impl S {
+    fn f$0() {}
+}
+"#,
+        );
+        check_assist_not_applicable(
+            move_item_to_module,
+            r#"
+mod m {}
+fn f() {
+    struct S$0;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_on_name_conflict() {
+        check_assist_not_applicable(
+            move_item_to_module,
+            r#"
+mod m {
+    fn f() {}
+}
+fn f$0() {}
+"#,
+        );
+    }
+}
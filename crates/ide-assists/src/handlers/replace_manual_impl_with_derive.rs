@@ -0,0 +1,348 @@
+use ide_db::derivable_impl::derivable_impl;
+use syntax::{ast, AstNode};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_manual_impl_with_derive
+//
+// Replaces a manual trait impl that does exactly what `#[derive]` generates with the derive.
+//
+// ```
+// # //- minicore: clone, derive, builtin_impls
+// struct Point {
+//     x: u32,
+//     y: u32,
+// }
+//
+// impl Clone for $0Point {
+//     fn clone(&self) -> Self {
+//         Point { x: self.x.clone(), y: self.y.clone() }
+//     }
+// }
+// ```
+// ->
+// ```
+// #[derive(Clone)]
+// struct Point {
+//     x: u32,
+//     y: u32,
+// }
+// ```
+pub(crate) fn replace_manual_impl_with_derive(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let impl_: ast::Impl = ctx.find_node_at_offset()?;
+    // Only offer this on the impl header.
+    if ctx.offset() >= impl_.assoc_item_list()?.syntax().text_range().start() {
+        return None;
+    }
+    let derivable = derivable_impl(&ctx.sema, &impl_)?;
+
+    acc.add(
+        AssistId("replace_manual_impl_with_derive", AssistKind::Refactor),
+        format!("Replace manual impl with `#[derive({})]`", derivable.derive),
+        impl_.syntax().text_range(),
+        |builder| derivable.replace_with_derive(builder, ctx.file_id(), &impl_),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn clone_of_tuple_struct() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: clone, copy, derive, builtin_impls
+pub struct Meters(u32, bool);
+
+impl Clone$0 for Meters {
+    fn clone(&self) -> Self {
+        Self(self.0, self.1.clone())
+    }
+}
+"#,
+            r#"
+#[derive(Clone)]
+pub struct Meters(u32, bool);
+"#,
+        );
+    }
+
+    #[test]
+    fn ord_with_then_chain_and_existing_derive() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: ord, derive, builtin_impls
+#[derive(PartialEq, Eq, PartialOrd)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+
+impl Ord for Version$0 {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.major.cmp(&other.major).then(self.minor.cmp(&other.minor))
+    }
+}
+"#,
+            r#"
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    major: u32,
+    minor: u32,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn default_with_literals() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: default, derive, option
+mod config {
+    struct Config {
+        retries: u32,
+        verbose: bool,
+        name: Option<u32>,
+    }
+
+    impl Default $0for Config {
+        fn default() -> Self {
+            Config { retries: 0, verbose: false, name: None }
+        }
+    }
+}
+"#,
+            r#"
+mod config {
+    #[derive(Default)]
+    struct Config {
+        retries: u32,
+        verbose: bool,
+        name: Option<u32>,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_hash() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: hash, derive
+struct Pair<T> {
+    a: T,
+    b: T,
+}
+
+impl<T: core::hash::Hash> core::hash::Hash for Pair<T>$0 {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.a.hash(state);
+        self.b.hash(state);
+    }
+}
+"#,
+            r#"
+#[derive(Hash)]
+struct Pair<T> {
+    a: T,
+    b: T,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn clone_of_enum() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: clone, copy, derive, builtin_impls
+enum Shape {
+    Empty,
+    Circle(u32),
+    Rect { w: u32, h: u32 },
+}
+
+impl Clone for $0Shape {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Empty => Self::Empty,
+            Self::Circle(r) => Self::Circle(r.clone()),
+            Shape::Rect { w, h: height } => Shape::Rect { w: *w, h: Clone::clone(height) },
+        }
+    }
+}
+"#,
+            r#"
+#[derive(Clone)]
+enum Shape {
+    Empty,
+    Circle(u32),
+    Rect { w: u32, h: u32 },
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn default_of_enum_marks_variant() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: default, derive
+pub enum Mode {
+    Fast,
+    Slow,
+}
+
+impl Default for $0Mode {
+    fn default() -> Self {
+        Mode::Slow
+    }
+}
+"#,
+            r#"
+#[derive(Default)]
+pub enum Mode {
+    Fast,
+    #[default]
+    Slow,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn debug_of_enum() {
+        check_assist(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: fmt, derive, builtin_impls
+enum Token {
+    Eof,
+    Num(u32),
+    Op { sym: char },
+}
+
+impl core::fmt::Debug for $0Token {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Eof => f.write_str("Eof"),
+            Self::Num(n) => f.debug_tuple("Num").field(n).finish(),
+            Self::Op { sym } => f.debug_struct("Op").field("sym", &sym).finish(),
+        }
+    }
+}
+"#,
+            r#"
+#[derive(Debug)]
+enum Token {
+    Eof,
+    Num(u32),
+    Op { sym: char },
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn enums_only_support_some_derives() {
+        // Comparisons and hashes of enums go through the discriminant, which isn't recognized.
+        check_assist_not_applicable(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: eq, derive, builtin_impls
+enum E {
+    A,
+}
+
+impl PartialEq for $0E {
+    fn eq(&self, other: &Self) -> bool {
+        true
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: default, derive
+enum E {
+    A(u32),
+}
+
+impl Default for $0E {
+    fn default() -> Self {
+        Self::A(0)
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: clone, derive, builtin_impls
+enum E {
+    A,
+    B(u32),
+}
+
+impl Clone for $0E {
+    fn clone(&self) -> Self {
+        match self {
+            Self::B(b) => Self::B(b.clone()),
+            _ => Self::A,
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_impl_differs_from_derive() {
+        check_assist_not_applicable(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: eq, derive, builtin_impls
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+impl PartialEq for Point$0 {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_manual_impl_with_derive,
+            r#"
+//- minicore: clone, derive, builtin_impls
+struct Point {
+    x: u32,
+}
+
+impl Clone for Point {
+    fn clone(&self) -> Self {
+        Point { x: self.x$0.clone() }
+    }
+}
+"#,
+        );
+    }
+}
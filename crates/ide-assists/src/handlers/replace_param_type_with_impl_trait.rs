@@ -0,0 +1,293 @@
+use hir::{AsAssocItem, ModuleDef};
+use ide_db::{defs::Definition, helpers::mod_path_to_ast};
+use syntax::{
+    ast::{self, make},
+    ted, AstNode,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_param_type_with_impl_trait
+//
+// Replaces the concrete type of a parameter with `impl Trait`, when the parameter is only used
+// through the methods of that trait.
+//
+// ```
+// struct Counter(u32);
+// trait Get {
+//     fn get(&self) -> u32;
+// }
+// impl Get for Counter {
+//     fn get(&self) -> u32 { self.0 }
+// }
+//
+// fn print(counter$0: &Counter) {
+//     counter.get();
+// }
+// ```
+// ->
+// ```
+// struct Counter(u32);
+// trait Get {
+//     fn get(&self) -> u32;
+// }
+// impl Get for Counter {
+//     fn get(&self) -> u32 { self.0 }
+// }
+//
+// fn print(counter: &impl Get) {
+//     counter.get();
+// }
+// ```
+pub(crate) fn replace_param_type_with_impl_trait(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let param = ctx.find_node_at_offset::<ast::Param>()?;
+    let fn_ = param.syntax().ancestors().find_map(ast::Fn::cast)?;
+    let func = ctx.sema.to_def(&fn_)?;
+    // Trait methods have to keep the signature of the trait.
+    if func
+        .as_assoc_item(ctx.db())
+        .and_then(|it| it.containing_trait_or_trait_impl(ctx.db()))
+        .is_some()
+    {
+        return None;
+    }
+
+    // `Foo`, `&Foo` and `&mut Foo`
+    let ty = match param.ty()? {
+        ast::Type::RefType(ref_ty) => ref_ty.ty()?,
+        ty => ty,
+    };
+    let ast::Type::PathType(path_ty) = ty else { return None };
+    ctx.sema.resolve_type(&ast::Type::PathType(path_ty.clone()))?.as_adt()?;
+
+    let ast::Pat::IdentPat(ident_pat) = param.pat()? else { return None };
+    let local = ctx.sema.to_def(&ident_pat)?;
+
+    // Every use of the parameter has to be a method call of the same trait.
+    let mut trait_ = None;
+    let usages = Definition::Local(local).usages(&ctx.sema).all();
+    for reference in usages.iter().flat_map(|(_, refs)| refs) {
+        let name_ref = reference.name.as_name_ref()?;
+        let path_expr =
+            name_ref.syntax().ancestors().find_map(ast::PathExpr::cast).filter(|it| {
+                it.path().and_then(|it| it.as_single_name_ref()).as_ref() == Some(name_ref)
+            })?;
+        let method_call = ast::MethodCallExpr::cast(path_expr.syntax().parent()?)?;
+        if method_call.receiver()?.syntax() != path_expr.syntax() {
+            return None;
+        }
+        let method_trait = ctx
+            .sema
+            .resolve_method_call(&method_call)?
+            .as_assoc_item(ctx.db())?
+            .containing_trait_impl(ctx.db())?;
+        match trait_ {
+            Some(it) if it != method_trait => return None,
+            _ => trait_ = Some(method_trait),
+        }
+    }
+    let trait_ = trait_?;
+    if trait_.type_or_const_param_count(ctx.db(), false) != 0 {
+        return None;
+    }
+
+    let module = ctx.sema.scope(param.syntax())?.module();
+    let trait_path = module.find_use_path(
+        ctx.db(),
+        ModuleDef::Trait(trait_),
+        ctx.config.prefer_no_std,
+        ctx.config.prefer_prelude,
+    )?;
+    let bound = make::type_bound(&mod_path_to_ast(&trait_path).to_string());
+    let impl_trait_ty = make::impl_trait_type(make::type_bound_list([bound])?);
+
+    acc.add(
+        AssistId("replace_param_type_with_impl_trait", AssistKind::RefactorRewrite),
+        "Replace parameter type with impl trait",
+        param.syntax().text_range(),
+        |builder| {
+            let path_ty = builder.make_mut(path_ty);
+            ted::replace(path_ty.syntax(), impl_trait_ty.clone_for_update().syntax());
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn replace_ref_param() {
+        check_assist(
+            replace_param_type_with_impl_trait,
+            r#"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+    fn set(&mut self, value: u32);
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+    fn set(&mut self, value: u32) { self.0 = value; }
+}
+
+fn reset(counter$0: &mut Counter) {
+    let value = counter.get();
+    counter.set(value - value);
+}
+"#,
+            r#"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+    fn set(&mut self, value: u32);
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+    fn set(&mut self, value: u32) { self.0 = value; }
+}
+
+fn reset(counter: &mut impl Get) {
+    let value = counter.get();
+    counter.set(value - value);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_owned_param_of_method() {
+        check_assist(
+            replace_param_type_with_impl_trait,
+            r#"
+mod counter {
+    pub struct Counter(pub u32);
+    pub trait Get {
+        fn get(&self) -> u32;
+    }
+    impl Get for Counter {
+        fn get(&self) -> u32 { self.0 }
+    }
+}
+use counter::Get;
+struct S;
+impl S {
+    fn take(&self, c$0: counter::Counter) -> u32 {
+        c.get()
+    }
+}
+"#,
+            r#"
+mod counter {
+    pub struct Counter(pub u32);
+    pub trait Get {
+        fn get(&self) -> u32;
+    }
+    impl Get for Counter {
+        fn get(&self) -> u32 { self.0 }
+    }
+}
+use counter::Get;
+struct S;
+impl S {
+    fn take(&self, c: impl Get) -> u32 {
+        c.get()
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_other_uses() {
+        check_assist_not_applicable(
+            replace_param_type_with_impl_trait,
+            r#"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+}
+
+fn f(counter$0: &Counter) -> u32 {
+    counter.get() + counter.0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_inherent_methods() {
+        check_assist_not_applicable(
+            replace_param_type_with_impl_trait,
+            r#"
+struct Counter(u32);
+impl Counter {
+    fn get(&self) -> u32 { self.0 }
+}
+
+fn f(counter$0: &Counter) -> u32 {
+    counter.get()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_methods_of_several_traits() {
+        check_assist_not_applicable(
+            replace_param_type_with_impl_trait,
+            r#"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+}
+trait Reset {
+    fn reset(&mut self);
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+}
+impl Reset for Counter {
+    fn reset(&mut self) { self.0 = 0; }
+}
+
+fn f(counter$0: &mut Counter) -> u32 {
+    counter.reset();
+    counter.get()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_trait_impl() {
+        check_assist_not_applicable(
+            replace_param_type_with_impl_trait,
+            r#"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+}
+trait Show {
+    fn show(&self, counter: &Counter);
+}
+impl Show for () {
+    fn show(&self, counter$0: &Counter) {
+        counter.get();
+    }
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use ide_db::assists::{AssistId, AssistKind};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasAttrs, HasVisibility},
+    AstNode, SyntaxKind, TextRange,
+};
+
+use crate::{AssistContext, Assists};
+
+// Assist: split_impl
+//
+// Moves the selected items of an inherent impl into a new impl block.
+//
+// ```
+// struct S;
+//
+// impl S {
+//     pub fn new() -> S { S }
+//
+//     $0fn helper(&self) {}
+//
+//     fn other_helper(&self) {}$0
+// }
+// ```
+// ->
+// ```
+// struct S;
+//
+// impl S {
+//     pub fn new() -> S { S }
+// }
+//
+// impl S {
+//     fn helper(&self) {}
+//
+//     fn other_helper(&self) {}
+// }
+// ```
+pub(crate) fn split_impl(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    if ctx.has_empty_selection() {
+        return None;
+    }
+    let selection = ctx.selection_trimmed();
+    let assoc_item_list = ctx.covering_element().ancestors().find_map(ast::AssocItemList::cast)?;
+    let impl_ = ast::Impl::cast(assoc_item_list.syntax().parent()?)?;
+    if impl_.trait_().is_some() {
+        return None;
+    }
+
+    let (selected, kept): (Vec<_>, Vec<_>) = assoc_item_list
+        .assoc_items()
+        .partition(|it| selection.contains_range(it.syntax().text_range()));
+    if selected.is_empty() || kept.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("split_impl", AssistKind::RefactorExtract),
+        "Split selected items into a new impl block",
+        selection,
+        |builder| {
+            let new_impls = new_impls(&impl_, &assoc_item_list, [selected]);
+            builder.replace(
+                assoc_item_list.syntax().text_range(),
+                items_text(&assoc_item_list, &kept),
+            );
+            builder.insert(impl_.syntax().text_range().end(), new_impls);
+        },
+    )
+}
+
+// Assist: split_impl_by_visibility
+//
+// Splits an inherent impl into one impl block for each visibility of its items.
+//
+// ```
+// struct S;
+//
+// impl$0 S {
+//     pub fn new() -> S { S }
+//
+//     fn helper(&self) {}
+//
+//     pub fn len(&self) -> usize { 0 }
+// }
+// ```
+// ->
+// ```
+// struct S;
+//
+// impl S {
+//     pub fn new() -> S { S }
+//
+//     pub fn len(&self) -> usize { 0 }
+// }
+//
+// impl S {
+//     fn helper(&self) {}
+// }
+// ```
+pub(crate) fn split_impl_by_visibility(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let impl_ = ctx.find_node_at_offset::<ast::Impl>()?;
+    let assoc_item_list = impl_.assoc_item_list()?;
+    if ctx.offset() >= assoc_item_list.syntax().text_range().start() || impl_.trait_().is_some() {
+        return None;
+    }
+
+    // Grouped in the order the visibilities first appear.
+    let mut groups: Vec<(String, Vec<ast::AssocItem>)> = Vec::new();
+    for item in assoc_item_list.assoc_items() {
+        let vis = visibility(&item).map_or(String::new(), |it| it.syntax().to_string());
+        match groups.iter_mut().find(|(it, _)| *it == vis) {
+            Some((_, items)) => items.push(item),
+            None => groups.push((vis, vec![item])),
+        }
+    }
+    if groups.len() < 2 {
+        return None;
+    }
+
+    let target = TextRange::new(
+        impl_.syntax().text_range().start(),
+        assoc_item_list.syntax().text_range().start(),
+    );
+    acc.add(
+        AssistId("split_impl_by_visibility", AssistKind::RefactorRewrite),
+        "Split impl by visibility",
+        target,
+        |builder| {
+            let mut groups = groups.into_iter().map(|(_, items)| items);
+            let Some(kept) = groups.next() else { return };
+            let new_impls = new_impls(&impl_, &assoc_item_list, groups);
+            builder.replace(
+                assoc_item_list.syntax().text_range(),
+                items_text(&assoc_item_list, &kept),
+            );
+            builder.insert(impl_.syntax().text_range().end(), new_impls);
+        },
+    )
+}
+
+fn visibility(item: &ast::AssocItem) -> Option<ast::Visibility> {
+    match item {
+        ast::AssocItem::Const(it) => it.visibility(),
+        ast::AssocItem::Fn(it) => it.visibility(),
+        ast::AssocItem::MacroCall(_) => None,
+        ast::AssocItem::TypeAlias(it) => it.visibility(),
+    }
+}
+
+/// The text of impl blocks like `impl_` for each group of its items, to be inserted after it.
+fn new_impls(
+    impl_: &ast::Impl,
+    assoc_item_list: &ast::AssocItemList,
+    groups: impl IntoIterator<Item = Vec<ast::AssocItem>>,
+) -> String {
+    let indent = IndentLevel::from_node(impl_.syntax());
+
+    // Attributes like `#[cfg]` apply to the new blocks as well, the docs don't.
+    let mut header = String::new();
+    for attr in impl_.attrs().filter(|it| !it.simple_name().map_or(false, |it| it == "doc")) {
+        format_to!(header, "{attr}\n{indent}");
+    }
+    let header_start = impl_
+        .syntax()
+        .children_with_tokens()
+        .find(|it| {
+            !matches!(it.kind(), SyntaxKind::ATTR | SyntaxKind::COMMENT | SyntaxKind::WHITESPACE)
+        })
+        .map_or(impl_.syntax().text_range().start(), |it| it.text_range().start());
+    let header_range = TextRange::new(header_start, assoc_item_list.syntax().text_range().start());
+    header.push_str(
+        &impl_
+            .syntax()
+            .text()
+            .slice(header_range - impl_.syntax().text_range().start())
+            .to_string(),
+    );
+
+    let mut buf = String::new();
+    for items in groups {
+        let items = items_text(assoc_item_list, &items);
+        format_to!(buf, "\n\n{indent}{header}{items}");
+    }
+    buf
+}
+
+/// The braced list of `items`, which are in `assoc_item_list`, with the comments and whitespace
+/// before each of them.
+fn items_text(assoc_item_list: &ast::AssocItemList, items: &[ast::AssocItem]) -> String {
+    let Some(l_curly) = assoc_item_list.l_curly_token() else {
+        return assoc_item_list.to_string();
+    };
+    let list_start = assoc_item_list.syntax().text_range().start();
+    let text = assoc_item_list.syntax().text();
+    let all_items = assoc_item_list.assoc_items().collect_vec();
+    let prefix = |item: &ast::AssocItem| {
+        let idx = all_items.iter().position(|it| it == item).unwrap_or(0);
+        let start = match idx.checked_sub(1) {
+            Some(prev) => all_items[prev].syntax().text_range().end(),
+            None => l_curly.text_range().end(),
+        };
+        text.slice(TextRange::new(start, item.syntax().text_range().start()) - list_start)
+            .to_string()
+    };
+
+    let indent = IndentLevel::from_node(assoc_item_list.syntax());
+    let first_prefix = all_items.first().map(prefix).unwrap_or_default();
+    let first_prefix = &first_prefix[..first_prefix.len() - first_prefix.trim_start().len()];
+    let mut buf = String::from("{");
+    for (idx, item) in items.iter().enumerate() {
+        let prefix = prefix(item);
+        if idx == 0 {
+            format_to!(buf, "{first_prefix}{}", prefix.trim_start());
+        } else {
+            buf.push_str(&prefix);
+        }
+        format_to!(buf, "{item}");
+    }
+    format_to!(buf, "\n{indent}}}");
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn split_selected_items_of_generic_impl() {
+        check_assist(
+            split_impl,
+            r#"
+mod m {
+    struct S<T>(T);
+
+    /// Docs.
+    #[cfg(test)]
+    impl<T> S<T>
+    where
+        T: Copy,
+    {
+        $0// Helpers.
+        fn a(&self) {}
+
+        const C: u32 = 0;$0
+
+        pub fn b(&self) -> T {
+            self.0
+        }
+    }
+}
+"#,
+            r#"
+mod m {
+    struct S<T>(T);
+
+    /// Docs.
+    #[cfg(test)]
+    impl<T> S<T>
+    where
+        T: Copy,
+    {
+        pub fn b(&self) -> T {
+            self.0
+        }
+    }
+
+    #[cfg(test)]
+    impl<T> S<T>
+    where
+        T: Copy,
+    {
+        // Helpers.
+        fn a(&self) {}
+
+        const C: u32 = 0;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn split_not_applicable_to_all_items_or_trait_impls() {
+        check_assist_not_applicable(
+            split_impl,
+            r#"
+struct S;
+impl S {
+    $0fn a(&self) {}
+    fn b(&self) {}$0
+}
+"#,
+        );
+        check_assist_not_applicable(
+            split_impl,
+            r#"
+trait T { fn a(&self); fn b(&self); }
+struct S;
+impl T for S {
+    $0fn a(&self) {}$0
+    fn b(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn split_by_visibility() {
+        check_assist(
+            split_impl_by_visibility,
+            r#"
+struct S;
+
+impl S$0 {
+    fn a(&self) {}
+    pub(crate) fn b(&self) {}
+    pub fn c(&self) {}
+    pub(crate) const D: u32 = 0;
+}
+"#,
+            r#"
+struct S;
+
+impl S {
+    fn a(&self) {}
+}
+
+impl S {
+    pub(crate) fn b(&self) {}
+    pub(crate) const D: u32 = 0;
+}
+
+impl S {
+    pub fn c(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn split_by_visibility_not_applicable_to_single_visibility_or_body() {
+        check_assist_not_applicable(
+            split_impl_by_visibility,
+            r#"
+struct S;
+impl S$0 {
+    pub fn a(&self) {}
+    pub fn b(&self) {}
+}
+"#,
+        );
+        check_assist_not_applicable(
+            split_impl_by_visibility,
+            r#"
+struct S;
+impl S {
+    pub fn a(&self) {$0}
+    fn b(&self) {}
+}
+"#,
+        );
+    }
+}
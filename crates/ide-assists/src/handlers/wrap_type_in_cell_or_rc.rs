@@ -0,0 +1,753 @@
+use hir::{ModuleDef, PathResolution};
+use ide_db::{
+    assists::GroupLabel,
+    base_db::FileId,
+    defs::Definition,
+    famous_defs::FamousDefs,
+    helpers::mod_path_to_ast,
+    imports::insert_use::{insert_use, ImportScope},
+    search::{FileReferenceNode, ReferenceCategory},
+    FxHashMap,
+};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, make},
+    AstNode, SyntaxKind, SyntaxNode, TextRange, TextSize,
+};
+
+use crate::{utils::is_mutable_use, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: wrap_type_in_cell_or_rc
+//
+// Changes the type of a field or local variable to `RefCell<T>`, `Rc<RefCell<T>>`, `Mutex<T>`,
+// `Arc<Mutex<T>>`, `Rc<T>` or `Arc<T>`, and rewrites its constructors and accesses to match.
+//
+// ```
+// # //- minicore: copy
+// # //- /main.rs crate:main deps:std
+// struct Counter {
+//     $0hits: u32,
+// }
+//
+// fn record(c: &mut Counter) -> u32 {
+//     c.hits += 1;
+//     c.hits
+// }
+// # //- /std.rs crate:std
+// # pub mod cell {
+// #     pub struct RefCell<T>(T);
+// # }
+// ```
+// ->
+// ```
+// use std::cell::RefCell;
+//
+// struct Counter {
+//     hits: RefCell<u32>,
+// }
+//
+// fn record(c: &mut Counter) -> u32 {
+//     *c.hits.borrow_mut() += 1;
+//     *c.hits.borrow()
+// }
+// ```
+pub(crate) fn wrap_type_in_cell_or_rc(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let name: ast::Name = ctx.find_node_at_offset()?;
+    let target = Target::new(ctx, &name)?;
+    if target.def_ty.is_reference() || target.def_ty.contains_unknown() {
+        return None;
+    }
+    let is_copy = target.def_ty.is_copy(ctx.db());
+
+    let mut uses: FxHashMap<FileId, Vec<Use>> = FxHashMap::default();
+    let mut unchanged = 0;
+    for (file_id, references) in target.def.usages(&ctx.sema).all() {
+        for reference in references {
+            let FileReferenceNode::NameRef(name_ref) = reference.name else { continue };
+            let use_ = if ctx.sema.hir_file_for(name_ref.syntax()).is_macro() {
+                None
+            } else {
+                Use::new(ctx, &target, &name_ref, reference.category, is_copy)
+            };
+            match use_ {
+                Some(use_) => uses.entry(file_id).or_default().push(use_),
+                None => unchanged += 1,
+            }
+        }
+    }
+    let famous_defs = FamousDefs(&ctx.sema, ctx.sema.scope(&target.node)?.krate());
+    let writes = uses.values().flatten().any(|it| matches!(it, Use::Access { write: true, .. }));
+
+    let group = GroupLabel("Wrap type in...".to_owned());
+    for wrapper in Wrapper::ALL {
+        // `Rc` and `Arc` alone don't allow mutating the value.
+        if writes && wrapper.write_accessor().is_none() {
+            continue;
+        }
+        // Without `std`, there's no `Mutex`, and without `alloc` no `Rc` or `Arc`.
+        let Some(layers) =
+            wrapper.layers().iter().map(|it| it.resolve(&famous_defs)).collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+        let (prefix, suffix) = wrapper.ty();
+        let mut label = format!("Wrap type in `{prefix}_{suffix}`");
+        if unchanged > 0 {
+            let s = if unchanged == 1 { "" } else { "s" };
+            format_to!(label, " ({unchanged} reference{s} left unchanged)");
+        }
+        acc.add_group(
+            &group,
+            AssistId("wrap_type_in_cell_or_rc", AssistKind::RefactorRewrite),
+            label,
+            name.syntax().text_range(),
+            |builder| {
+                let mut edits: FxHashMap<FileId, Vec<Insertion>> = FxHashMap::default();
+                // The nodes in need of the wrapper types in scope.
+                let mut users: Vec<(FileId, SyntaxNode)> = Vec::new();
+
+                let def_edits = edits.entry(ctx.file_id()).or_default();
+                if let Some(ty) = &target.ty {
+                    wrap_type(def_edits, ty.syntax().text_range(), wrapper);
+                }
+                if let Some(initializer) = &target.initializer {
+                    construct(def_edits, initializer.syntax().text_range(), wrapper);
+                }
+                users.push((ctx.file_id(), target.node.clone()));
+
+                for (file_id, uses) in &uses {
+                    let file_edits = edits.entry(*file_id).or_default();
+                    for use_ in uses {
+                        use_.edit(file_edits, wrapper);
+                        if let Use::Constructor { field, .. } = use_ {
+                            users.push((*file_id, field.syntax().clone()));
+                        }
+                    }
+                }
+
+                for (file_id, mut edits) in edits {
+                    builder.edit_file(file_id);
+                    edits.sort_by_key(|it| (it.offset, it.rank));
+                    for (offset, edits) in &edits.into_iter().group_by(|it| it.offset) {
+                        builder.insert(offset, edits.map(|it| it.text).collect::<String>());
+                    }
+                    if file_id == ctx.file_id() {
+                        if let Some(mut_token) = &target.mut_token {
+                            let mut range = mut_token.text_range();
+                            if let Some(ws) = mut_token.next_token() {
+                                if ws.kind() == SyntaxKind::WHITESPACE {
+                                    range = range.cover(ws.text_range());
+                                }
+                            }
+                            builder.delete(range);
+                        }
+                    }
+
+                    let mut scopes: Vec<(ImportScope, Vec<ast::Path>)> = Vec::new();
+                    for (_, node) in users.iter().filter(|(it, _)| *it == file_id) {
+                        let missing = missing_imports(ctx, node, &layers);
+                        if missing.is_empty() {
+                            continue;
+                        }
+                        let node = builder.make_syntax_mut(node.clone());
+                        let Some(scope) = ImportScope::find_insert_use_container(&node, &ctx.sema)
+                        else {
+                            continue;
+                        };
+                        if scopes
+                            .iter()
+                            .all(|(it, _)| it.as_syntax_node() != scope.as_syntax_node())
+                        {
+                            scopes.push((scope, missing));
+                        }
+                    }
+                    for (scope, missing) in scopes {
+                        for path in missing {
+                            insert_use(&scope, path, &ctx.config.insert_use);
+                        }
+                    }
+                }
+            },
+        );
+    }
+    Some(())
+}
+
+/// The field or local variable whose type gets wrapped.
+struct Target {
+    def: Definition,
+    def_ty: hir::Type,
+    /// The field or let statement.
+    node: SyntaxNode,
+    ty: Option<ast::Type>,
+    initializer: Option<ast::Expr>,
+    /// The `mut` of a local, which becomes unnecessary.
+    mut_token: Option<syntax::SyntaxToken>,
+}
+
+impl Target {
+    fn new(ctx: &AssistContext<'_>, name: &ast::Name) -> Option<Target> {
+        let parent = name.syntax().parent()?;
+        if let Some(field) = ast::RecordField::cast(parent.clone()) {
+            let def = ctx.sema.to_def(&field)?;
+            return Some(Target {
+                def: Definition::Field(def),
+                def_ty: def.ty(ctx.db()),
+                node: field.syntax().clone(),
+                ty: Some(field.ty()?),
+                initializer: None,
+                mut_token: None,
+            });
+        }
+        let ident_pat = ast::IdentPat::cast(parent)?;
+        let let_stmt = ast::LetStmt::cast(ident_pat.syntax().parent()?)?;
+        if ident_pat.ref_token().is_some() {
+            return None;
+        }
+        let def = ctx.sema.to_def(&ident_pat)?;
+        Some(Target {
+            def: Definition::Local(def),
+            def_ty: def.ty(ctx.db()),
+            node: let_stmt.syntax().clone(),
+            ty: let_stmt.ty(),
+            initializer: Some(let_stmt.initializer()?),
+            mut_token: ident_pat.mut_token(),
+        })
+    }
+}
+
+/// A use of the target that has to change along with its type.
+enum Use {
+    /// A field initializer in a struct literal, `S { field: value }` or `S { field }`.
+    Constructor { field: ast::RecordExprField, value: TextRange, shorthand: Option<String> },
+    /// A read or write of the value.
+    Access { range: TextRange, write: bool, position: Position },
+}
+
+/// How the value is used where it is accessed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+    /// As a method receiver or the base of a field access or index, which auto-derefs.
+    Base,
+    /// Anywhere else, where the value needs an explicit deref.
+    Value,
+    /// A value position binding tighter than a deref, like the operand of `?`.
+    Tight,
+}
+
+impl Use {
+    fn new(
+        ctx: &AssistContext<'_>,
+        target: &Target,
+        name_ref: &ast::NameRef,
+        category: Option<ReferenceCategory>,
+        is_copy: bool,
+    ) -> Option<Use> {
+        if let Some(field) = ast::RecordExprField::for_field_name(name_ref) {
+            // A local used in a field shorthand is a move out of it.
+            if !matches!(target.def, Definition::Field(_)) {
+                return None;
+            }
+            let value = field.expr()?;
+            let shorthand = field.name_ref().is_none().then(|| value.to_string());
+            let value = value.syntax().text_range();
+            return Some(Use::Constructor { field, value, shorthand });
+        }
+        let expr: ast::Expr = match target.def {
+            Definition::Field(_) => ast::FieldExpr::cast(name_ref.syntax().parent()?)?.into(),
+            _ => name_ref.syntax().ancestors().find_map(ast::PathExpr::cast)?.into(),
+        };
+        let write = category == Some(ReferenceCategory::Write) || is_mutable_use(ctx, &expr);
+        let parent = expr.syntax().parent()?;
+        let is_base = |it: Option<ast::Expr>| it.map_or(false, |it| it.syntax() == expr.syntax());
+        let position = if ast::MethodCallExpr::cast(parent.clone())
+            .map_or(false, |it| is_base(it.receiver()))
+            || ast::FieldExpr::cast(parent.clone()).map_or(false, |it| is_base(it.expr()))
+            || ast::IndexExpr::cast(parent.clone()).map_or(false, |it| is_base(it.base()))
+        {
+            Position::Base
+        } else if ast::TryExpr::can_cast(parent.kind())
+            || ast::AwaitExpr::can_cast(parent.kind())
+            || ast::CallExpr::cast(parent.clone()).map_or(false, |it| is_base(it.expr()))
+        {
+            Position::Tight
+        } else {
+            // Reading a value out of the wrapper only works for `Copy` types, but it can always
+            // be borrowed.
+            if !is_copy && !ast::RefExpr::can_cast(parent.kind()) && !write {
+                return None;
+            }
+            Position::Value
+        };
+        Some(Use::Access { range: expr.syntax().text_range(), write, position })
+    }
+
+    fn edit(&self, edits: &mut Vec<Insertion>, wrapper: Wrapper) {
+        match self {
+            Use::Constructor { value, shorthand: Some(name), .. } => {
+                let (prefix, suffix) = wrapper.constructor();
+                let text = format!(": {prefix}{name}{suffix}");
+                edits.push(Insertion { offset: value.end(), rank: 1, text });
+            }
+            Use::Constructor { value, shorthand: None, .. } => construct(edits, *value, wrapper),
+            Use::Access { range, write, position } => {
+                let accessor =
+                    if *write { wrapper.write_accessor() } else { wrapper.read_accessor() };
+                let suffix = accessor.map_or(String::new(), |it| format!(".{it}"));
+                let (prefix, suffix) = match position {
+                    Position::Base => (String::new(), suffix),
+                    Position::Value => ("*".to_owned(), suffix),
+                    Position::Tight => ("(*".to_owned(), format!("{suffix})")),
+                };
+                edits.push(Insertion { offset: range.start(), rank: 1, text: prefix });
+                edits.push(Insertion { offset: range.end(), rank: 0, text: suffix });
+            }
+        }
+    }
+}
+
+/// Text inserted at `offset`. Insertions at the same offset are ordered by `rank`, so that
+/// enclosing code comes first at the start of an expression and last at its end.
+struct Insertion {
+    offset: TextSize,
+    rank: u8,
+    text: String,
+}
+
+fn wrap_type(edits: &mut Vec<Insertion>, range: TextRange, wrapper: Wrapper) {
+    let (prefix, suffix) = wrapper.ty();
+    edits.push(Insertion { offset: range.start(), rank: 0, text: prefix });
+    edits.push(Insertion { offset: range.end(), rank: 1, text: suffix });
+}
+
+fn construct(edits: &mut Vec<Insertion>, range: TextRange, wrapper: Wrapper) {
+    let (prefix, suffix) = wrapper.constructor();
+    edits.push(Insertion { offset: range.start(), rank: 0, text: prefix });
+    edits.push(Insertion { offset: range.end(), rank: 1, text: suffix });
+}
+
+/// The paths to import for the wrapper types that aren't in scope at `node` yet.
+fn missing_imports(
+    ctx: &AssistContext<'_>,
+    node: &SyntaxNode,
+    layers: &[hir::Struct],
+) -> Vec<ast::Path> {
+    let Some(scope) = ctx.sema.scope(node) else { return Vec::new() };
+    let db = ctx.db();
+    layers
+        .iter()
+        .filter_map(|&it| {
+            let def = ModuleDef::Adt(hir::Adt::Struct(it));
+            let name = make::ext::ident_path(&it.name(db).to_smol_str());
+            if scope.speculative_resolve(&name) == Some(PathResolution::Def(def)) {
+                return None;
+            }
+            let path = scope.module().find_use_path(
+                db,
+                def,
+                ctx.config.prefer_no_std,
+                ctx.config.prefer_prelude,
+            )?;
+            Some(mod_path_to_ast(&path))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+enum Wrapper {
+    RefCell,
+    RcRefCell,
+    Mutex,
+    ArcMutex,
+    Rc,
+    Arc,
+}
+
+impl Wrapper {
+    const ALL: [Wrapper; 6] = [
+        Wrapper::RefCell,
+        Wrapper::RcRefCell,
+        Wrapper::Mutex,
+        Wrapper::ArcMutex,
+        Wrapper::Rc,
+        Wrapper::Arc,
+    ];
+
+    /// The wrapper types from the outside in.
+    fn layers(self) -> &'static [Layer] {
+        match self {
+            Wrapper::RefCell => &[Layer::RefCell],
+            Wrapper::RcRefCell => &[Layer::Rc, Layer::RefCell],
+            Wrapper::Mutex => &[Layer::Mutex],
+            Wrapper::ArcMutex => &[Layer::Arc, Layer::Mutex],
+            Wrapper::Rc => &[Layer::Rc],
+            Wrapper::Arc => &[Layer::Arc],
+        }
+    }
+
+    /// The text around a type to wrap it.
+    fn ty(self) -> (String, String) {
+        let layers = self.layers();
+        let prefix = layers.iter().map(|it| format!("{}<", it.name())).collect();
+        (prefix, ">".repeat(layers.len()))
+    }
+
+    /// The text around an expression to wrap its value.
+    fn constructor(self) -> (String, String) {
+        let layers = self.layers();
+        let prefix = layers.iter().map(|it| format!("{}::new(", it.name())).collect();
+        (prefix, ")".repeat(layers.len()))
+    }
+
+    fn read_accessor(self) -> Option<&'static str> {
+        match self {
+            Wrapper::RefCell | Wrapper::RcRefCell => Some("borrow()"),
+            _ => self.write_accessor(),
+        }
+    }
+
+    fn write_accessor(self) -> Option<&'static str> {
+        match self {
+            Wrapper::RefCell | Wrapper::RcRefCell => Some("borrow_mut()"),
+            Wrapper::Mutex | Wrapper::ArcMutex => Some("lock().unwrap()"),
+            Wrapper::Rc | Wrapper::Arc => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Layer {
+    RefCell,
+    Mutex,
+    Rc,
+    Arc,
+}
+
+impl Layer {
+    fn name(self) -> &'static str {
+        match self {
+            Layer::RefCell => "RefCell",
+            Layer::Mutex => "Mutex",
+            Layer::Rc => "Rc",
+            Layer::Arc => "Arc",
+        }
+    }
+
+    fn resolve(self, famous_defs: &FamousDefs<'_, '_>) -> Option<hir::Struct> {
+        match self {
+            Layer::RefCell => famous_defs.core_cell_RefCell(),
+            Layer::Mutex => famous_defs.std_sync_Mutex(),
+            Layer::Rc => famous_defs.alloc_rc_Rc(),
+            Layer::Arc => famous_defs.alloc_sync_Arc(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{
+            check_assist_by_label, check_assist_not_applicable,
+            check_assist_not_applicable_by_label, check_assist_with_config, TEST_CONFIG,
+        },
+        AssistConfig,
+    };
+
+    use super::*;
+
+    const STD: &str = r#"//- /std.rs crate:std
+pub mod cell {
+    pub struct RefCell<T>(T);
+    impl<T> RefCell<T> {
+        pub fn new(value: T) -> RefCell<T> { loop {} }
+    }
+}
+pub mod rc {
+    pub struct Rc<T>(T);
+    impl<T> Rc<T> {
+        pub fn new(value: T) -> Rc<T> { loop {} }
+    }
+}
+pub mod sync {
+    pub struct Arc<T>(T);
+    impl<T> Arc<T> {
+        pub fn new(value: T) -> Arc<T> { loop {} }
+    }
+    pub struct Mutex<T>(T);
+    impl<T> Mutex<T> {
+        pub fn new(value: T) -> Mutex<T> { loop {} }
+    }
+}
+"#;
+
+    #[test]
+    fn field_in_arc_mutex_with_constructors() {
+        check_assist_by_label(
+            wrap_type_in_cell_or_rc,
+            &format!(
+                r#"
+//- minicore: copy
+//- /main.rs crate:main deps:std
+mod worker;
+
+struct Stats {{
+    $0total: u64,
+    name: Name,
+}}
+
+struct Name;
+impl Name {{
+    fn rename(&mut self) {{}}
+}}
+
+fn new_stats(total: u64) -> Stats {{
+    Stats {{ total, name: Name }}
+}}
+
+fn bump(s: &mut Stats, n: u64) -> u64 {{
+    s.total += n;
+    let doubled = s.total * 2;
+    let r = &s.total;
+    doubled + *r
+}}
+//- /worker.rs
+use crate::{{Name, Stats}};
+
+fn fresh() -> Stats {{
+    Stats {{ total: 0, name: Name }}
+}}
+{STD}"#
+            ),
+            r#"
+//- /main.rs
+use std::sync::{Arc, Mutex};
+
+mod worker;
+
+struct Stats {
+    total: Arc<Mutex<u64>>,
+    name: Name,
+}
+
+struct Name;
+impl Name {
+    fn rename(&mut self) {}
+}
+
+fn new_stats(total: u64) -> Stats {
+    Stats { total: Arc::new(Mutex::new(total)), name: Name }
+}
+
+fn bump(s: &mut Stats, n: u64) -> u64 {
+    *s.total.lock().unwrap() += n;
+    let doubled = *s.total.lock().unwrap() * 2;
+    let r = &*s.total.lock().unwrap();
+    doubled + *r
+}
+//- /worker.rs
+use std::sync::{Arc, Mutex};
+
+use crate::{Name, Stats};
+
+fn fresh() -> Stats {
+    Stats { total: Arc::new(Mutex::new(0)), name: Name }
+}
+"#,
+            "Wrap type in `Arc<Mutex<_>>`",
+        );
+    }
+
+    #[test]
+    fn local_in_rc_ref_cell_with_method_calls() {
+        check_assist_by_label(
+            wrap_type_in_cell_or_rc,
+            &format!(
+                r#"
+//- minicore: copy
+//- /main.rs crate:main deps:std
+use std::rc::Rc;
+
+struct Log(u32);
+impl Log {{
+    fn push(&mut self, n: u32) {{ self.0 += n; }}
+    fn len(&self) -> u32 {{ self.0 }}
+}}
+
+fn f() -> u32 {{
+    let mut $0log: Log = Log(0);
+    log.push(1);
+    if log.len() > 0 {{
+        log.0 = 2;
+    }}
+    log.len() + log.0
+}}
+{STD}"#
+            ),
+            r#"
+use std::{rc::Rc, cell::RefCell};
+
+struct Log(u32);
+impl Log {
+    fn push(&mut self, n: u32) { self.0 += n; }
+    fn len(&self) -> u32 { self.0 }
+}
+
+fn f() -> u32 {
+    let log: Rc<RefCell<Log>> = Rc::new(RefCell::new(Log(0)));
+    log.borrow_mut().push(1);
+    if log.borrow().len() > 0 {
+        log.borrow_mut().0 = 2;
+    }
+    log.borrow().len() + log.borrow().0
+}
+"#,
+            "Wrap type in `Rc<RefCell<_>>`",
+        );
+    }
+
+    #[test]
+    fn rc_for_read_only_field() {
+        check_assist_by_label(
+            wrap_type_in_cell_or_rc,
+            &format!(
+                r#"
+//- minicore: copy
+//- /main.rs crate:main deps:std
+struct Config {{
+    $0retries: u8,
+}}
+
+fn retries(c: &Config) -> u8 {{
+    c.retries.min(3) + c.retries
+}}
+
+fn config() -> Config {{
+    Config {{ retries: 1 }}
+}}
+{STD}"#
+            ),
+            r#"
+use std::rc::Rc;
+
+struct Config {
+    retries: Rc<u8>,
+}
+
+fn retries(c: &Config) -> u8 {
+    c.retries.min(3) + *c.retries
+}
+
+fn config() -> Config {
+    Config { retries: Rc::new(1) }
+}
+"#,
+            "Wrap type in `Rc<_>`",
+        );
+    }
+
+    #[test]
+    fn moves_of_non_copy_values_are_left_unchanged() {
+        check_assist_by_label(
+            wrap_type_in_cell_or_rc,
+            &format!(
+                r#"
+//- /main.rs crate:main deps:std
+struct Buf;
+fn take(b: Buf) {{}}
+
+fn f() {{
+    let $0buf = Buf;
+    take(buf);
+}}
+{STD}"#
+            ),
+            r#"
+use std::cell::RefCell;
+
+struct Buf;
+fn take(b: Buf) {}
+
+fn f() {
+    let buf = RefCell::new(Buf);
+    take(buf);
+}
+"#,
+            "Wrap type in `RefCell<_>` (1 reference left unchanged)",
+        );
+    }
+
+    #[test]
+    fn rc_not_offered_for_written_values() {
+        check_assist_not_applicable_by_label(
+            wrap_type_in_cell_or_rc,
+            &format!(
+                r#"
+//- minicore: copy
+//- /main.rs crate:main deps:std
+fn f() {{
+    let mut $0n = 0;
+    n += 1;
+}}
+{STD}"#
+            ),
+            "Wrap type in `Rc<_>`",
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_references() {
+        check_assist_not_applicable(
+            wrap_type_in_cell_or_rc,
+            r#"
+fn f(v: &u32) {
+    let $0r = v;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn imports_from_alloc_with_prefer_no_std() {
+        check_assist_with_config(
+            wrap_type_in_cell_or_rc,
+            AssistConfig { prefer_no_std: true, ..TEST_CONFIG },
+            r#"
+//- minicore: copy
+//- /main.rs crate:main deps:std,alloc
+fn f() -> u32 {
+    let $0n = 1;
+    n + 1
+}
+//- /std.rs crate:std deps:alloc
+pub use alloc::rc;
+//- /alloc.rs crate:alloc
+pub mod rc {
+    pub struct Rc<T>(T);
+}
+"#,
+            r#"
+use alloc::rc::Rc;
+
+fn f() -> u32 {
+    let n = Rc::new(1);
+    *n + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_std() {
+        check_assist_not_applicable(
+            wrap_type_in_cell_or_rc,
+            r#"
+//- minicore: copy
+fn f() -> u32 {
+    let $0n = 1;
+    n + 1
+}
+"#,
+        );
+    }
+}
@@ -116,28 +116,39 @@ mod handlers {
     mod auto_import;
     mod bind_unused_param;
     mod bool_to_enum;
+    mod change_signature;
     mod change_visibility;
     mod convert_bool_then;
+    mod convert_closure_to_fn;
     mod convert_comment_block;
+    mod convert_dyn_trait_to_enum;
+    mod convert_for_loop_to_iterator_chain;
+    mod convert_format_to_to_string;
+    mod convert_impl_trait_to_box_dyn;
     mod convert_integer_literal;
     mod convert_into_to_from;
     mod convert_iter_for_each_to_for;
     mod convert_let_else_to_match;
+    mod convert_macro_rules_to_fn;
     mod convert_match_to_let_else;
     mod convert_nested_function_to_closure;
     mod convert_tuple_return_type_to_struct;
     mod convert_tuple_struct_to_named_struct;
     mod convert_named_struct_to_tuple_struct;
+    mod convert_string_concat_to_format;
     mod convert_to_guarded_return;
     mod convert_two_arm_bool_match_to_matches_macro;
     mod convert_while_to_loop;
     mod desugar_doc_comment;
     mod destructure_tuple_binding;
+    mod encapsulate_field;
+    mod elide_lifetimes;
     mod expand_glob_import;
     mod extract_expressions_from_format_string;
     mod extract_function;
     mod extract_module;
     mod extract_struct_from_enum_variant;
+    mod extract_trait;
     mod extract_type_alias;
     mod extract_variable;
     mod add_missing_match_arms;
@@ -145,6 +156,7 @@ mod handlers {
     mod flip_binexpr;
     mod flip_comma;
     mod flip_trait_bound;
+    mod generate_builder;
     mod generate_constant;
     mod generate_default_from_enum_variant;
     mod generate_default_from_new;
@@ -163,20 +175,25 @@ mod handlers {
     mod generate_mut_trait_impl;
     mod generate_new;
     mod generate_delegate_methods;
+    mod generate_test;
     mod generate_trait_from_impl;
     mod add_return_type;
     mod inline_call;
     mod inline_const_as_literal;
+    mod inline_format_args;
     mod inline_local_variable;
     mod inline_macro;
     mod inline_type_alias;
     mod introduce_named_lifetime;
+    mod introduce_parameter_object;
     mod invert_if;
+    mod make_async;
     mod merge_imports;
     mod merge_match_arms;
     mod move_bounds;
     mod move_const_to_impl;
     mod move_guard;
+    mod move_item_to_module;
     mod move_module_to_file;
     mod move_to_mod_rs;
     mod move_from_mod_rs;
@@ -195,6 +212,7 @@ mod handlers {
     mod reorder_impl_items;
     mod replace_try_expr_with_match;
     mod replace_derive_with_manual_impl;
+    mod replace_manual_impl_with_derive;
     mod replace_if_let_with_match;
     mod replace_is_method_with_if_let_method;
     mod replace_method_eager_lazy;
@@ -202,9 +220,11 @@ mod handlers {
     mod introduce_named_generic;
     mod replace_let_with_if_let;
     mod replace_named_generic_with_impl;
+    mod replace_param_type_with_impl_trait;
     mod replace_qualified_name_with_use;
     mod replace_string_with_char;
     mod replace_turbofish_with_explicit_type;
+    mod split_impl;
     mod split_import;
     mod unmerge_match_arm;
     mod unwrap_tuple;
@@ -216,6 +236,7 @@ mod handlers {
     mod unwrap_result_return_type;
     mod unqualify_method_call;
     mod wrap_return_type_in_result;
+    mod wrap_type_in_cell_or_rc;
     mod into_to_qualified_from;
 
     pub(crate) fn all() -> &'static [Handler] {
@@ -233,33 +254,54 @@ mod handlers {
             auto_import::auto_import,
             bind_unused_param::bind_unused_param,
             bool_to_enum::bool_to_enum,
+            change_signature::add_param,
+            change_signature::move_param_left,
+            change_signature::move_param_right,
+            change_signature::remove_param,
+            change_signature::rename_param,
             change_visibility::change_visibility,
             convert_bool_then::convert_bool_then_to_if,
             convert_bool_then::convert_if_to_bool_then,
+            convert_closure_to_fn::convert_closure_to_fn,
             convert_comment_block::convert_comment_block,
+            convert_dyn_trait_to_enum::convert_dyn_trait_to_enum,
+            convert_dyn_trait_to_enum::generate_enum_dispatch,
+            convert_impl_trait_to_box_dyn::convert_box_dyn_to_impl_trait,
+            convert_impl_trait_to_box_dyn::convert_impl_trait_to_box_dyn,
+            convert_for_loop_to_iterator_chain::convert_for_loop_to_iterator_chain,
+            convert_for_loop_to_iterator_chain::convert_iterator_chain_to_for_loop,
+            convert_format_to_to_string::convert_format_to_to_string,
             convert_integer_literal::convert_integer_literal,
             convert_into_to_from::convert_into_to_from,
             convert_iter_for_each_to_for::convert_iter_for_each_to_for,
             convert_iter_for_each_to_for::convert_for_loop_with_for_each,
             convert_let_else_to_match::convert_let_else_to_match,
+            convert_macro_rules_to_fn::convert_macro_rules_to_fn,
             convert_match_to_let_else::convert_match_to_let_else,
             convert_tuple_return_type_to_struct::convert_tuple_return_type_to_struct,
             convert_named_struct_to_tuple_struct::convert_named_struct_to_tuple_struct,
             convert_nested_function_to_closure::convert_nested_function_to_closure,
+            convert_string_concat_to_format::convert_push_str_to_format,
+            convert_string_concat_to_format::convert_string_concat_to_format,
             convert_to_guarded_return::convert_to_guarded_return,
             convert_tuple_struct_to_named_struct::convert_tuple_struct_to_named_struct,
             convert_two_arm_bool_match_to_matches_macro::convert_two_arm_bool_match_to_matches_macro,
             convert_while_to_loop::convert_while_to_loop,
             desugar_doc_comment::desugar_doc_comment,
             destructure_tuple_binding::destructure_tuple_binding,
+            encapsulate_field::encapsulate_field,
+            elide_lifetimes::elide_lifetimes,
+            elide_lifetimes::make_lifetimes_explicit,
             expand_glob_import::expand_glob_import,
             extract_expressions_from_format_string::extract_expressions_from_format_string,
             extract_struct_from_enum_variant::extract_struct_from_enum_variant,
+            extract_trait::extract_trait,
             extract_type_alias::extract_type_alias,
             fix_visibility::fix_visibility,
             flip_binexpr::flip_binexpr,
             flip_comma::flip_comma,
             flip_trait_bound::flip_trait_bound,
+            generate_builder::generate_builder,
             generate_constant::generate_constant,
             generate_default_from_enum_variant::generate_default_from_enum_variant,
             generate_default_from_new::generate_default_from_new,
@@ -278,23 +320,29 @@ mod handlers {
             generate_mut_trait_impl::generate_mut_trait_impl,
             generate_is_empty_from_len::generate_is_empty_from_len,
             generate_new::generate_new,
+            generate_test::generate_test,
             generate_trait_from_impl::generate_trait_from_impl,
             inline_call::inline_call,
             inline_call::inline_into_callers,
             inline_const_as_literal::inline_const_as_literal,
+            inline_format_args::inline_format_args,
             inline_local_variable::inline_local_variable,
             inline_type_alias::inline_type_alias,
             inline_type_alias::inline_type_alias_uses,
             into_to_qualified_from::into_to_qualified_from,
             introduce_named_generic::introduce_named_generic,
             introduce_named_lifetime::introduce_named_lifetime,
+            introduce_parameter_object::introduce_parameter_object,
             invert_if::invert_if,
+            make_async::make_async,
+            make_async::make_async_with_callers,
             merge_imports::merge_imports,
             merge_match_arms::merge_match_arms,
             move_bounds::move_bounds_to_where_clause,
             move_const_to_impl::move_const_to_impl,
             move_guard::move_arm_cond_to_match_guard,
             move_guard::move_guard_to_arm_body,
+            move_item_to_module::move_item_to_module,
             move_module_to_file::move_module_to_file,
             move_to_mod_rs::move_to_mod_rs,
             move_from_mod_rs::move_from_mod_rs,
@@ -314,6 +362,7 @@ mod handlers {
             reorder_impl_items::reorder_impl_items,
             replace_try_expr_with_match::replace_try_expr_with_match,
             replace_derive_with_manual_impl::replace_derive_with_manual_impl,
+            replace_manual_impl_with_derive::replace_manual_impl_with_derive,
             replace_if_let_with_match::replace_if_let_with_match,
             replace_if_let_with_match::replace_match_with_if_let,
             replace_is_method_with_if_let_method::replace_is_method_with_if_let_method,
@@ -321,12 +370,15 @@ mod handlers {
             replace_method_eager_lazy::replace_with_eager_method,
             replace_method_eager_lazy::replace_with_lazy_method,
             replace_named_generic_with_impl::replace_named_generic_with_impl,
+            replace_param_type_with_impl_trait::replace_param_type_with_impl_trait,
             replace_turbofish_with_explicit_type::replace_turbofish_with_explicit_type,
             replace_qualified_name_with_use::replace_qualified_name_with_use,
             replace_arith_op::replace_arith_with_wrapping,
             replace_arith_op::replace_arith_with_checked,
             replace_arith_op::replace_arith_with_saturating,
             sort_items::sort_items,
+            split_impl::split_impl,
+            split_impl::split_impl_by_visibility,
             split_import::split_import,
             toggle_ignore::toggle_ignore,
             unmerge_match_arm::unmerge_match_arm,
@@ -337,6 +389,7 @@ mod handlers {
             unwrap_tuple::unwrap_tuple,
             unqualify_method_call::unqualify_method_call,
             wrap_return_type_in_result::wrap_return_type_in_result,
+            wrap_type_in_cell_or_rc::wrap_type_in_cell_or_rc,
             // These are manually sorted for better priorities. By default,
             // priority is determined by the size of the target range (smaller
             // target wins). If the ranges are equal, position in this list is
@@ -351,6 +404,8 @@ mod handlers {
             extract_variable::extract_variable,
             extract_function::extract_function,
             extract_module::extract_module,
+            extract_module::split_module,
+            change_signature::introduce_param,
             //
             generate_getter_or_setter::generate_getter,
             generate_getter_or_setter::generate_getter_mut,
@@ -195,6 +195,27 @@ fn handle(action: Action) {
     )
 }
 
+#[test]
+fn doctest_add_param() {
+    check_doc_test(
+        "add_param",
+        r#####"
+fn greet($0name: &str) {}
+
+fn main() {
+    greet("world");
+}
+"#####,
+        r#####"
+fn greet(name: &str, new_param: ${1:_}) {}
+
+fn main() {
+    greet("world", ${0:todo!()});
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_add_return_type() {
     check_doc_test(
@@ -367,6 +388,155 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_box_dyn_to_impl_trait() {
+    check_doc_test(
+        "convert_box_dyn_to_impl_trait",
+        r#####"
+//- minicore: coerce_unsized
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+trait Shape {}
+struct Square;
+impl Shape for Square {}
+
+fn unit() -> Box<dyn Shape>$0 {
+    Box::new(Square)
+}
+//- /alloc.rs crate:alloc
+pub mod boxed {
+    use core::marker::Unsize;
+    use core::ops::CoerceUnsized;
+    pub struct Box<T: ?Sized>(*mut T);
+    impl<T> Box<T> {
+        pub fn new(value: T) -> Box<T> { loop {} }
+    }
+    impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+}
+"#####,
+        r#####"
+use alloc::boxed::Box;
+trait Shape {}
+struct Square;
+impl Shape for Square {}
+
+fn unit() -> impl Shape {
+    Square
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_closure_to_fn() {
+    check_doc_test(
+        "convert_closure_to_fn",
+        r#####"
+fn main() {
+    let mut total = 0;
+    let add = $0|n: u32| total += n;
+    add(1);
+    add(2);
+}
+"#####,
+        r#####"
+fn main() {
+    let mut total = 0;
+    add(1, &mut total);
+    add(2, &mut total);
+}
+
+fn add(n: u32, total: &mut u32) {
+    *total += n
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_dyn_trait_to_enum() {
+    check_doc_test(
+        "convert_dyn_trait_to_enum",
+        r#####"
+//- minicore: coerce_unsized
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+struct Square(f64);
+impl Shape for Square {
+    fn area(&self) -> f64 { self.0 * self.0 }
+}
+
+fn unit() -> Box<dyn $0Shape> {
+    Box::new(Square(1.0))
+}
+//- /alloc.rs crate:alloc
+pub mod boxed {
+    use core::marker::Unsize;
+    use core::ops::CoerceUnsized;
+    pub struct Box<T: ?Sized>(*mut T);
+    impl<T> Box<T> {
+        pub fn new(value: T) -> Box<T> { loop {} }
+    }
+    impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+}
+"#####,
+        r#####"
+use alloc::boxed::Box;
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+enum AnyShape {
+    Square(Square),
+}
+
+impl Shape for AnyShape {
+    fn area(&self) -> f64 {
+        match self {
+            AnyShape::Square(it) => Shape::area(it),
+        }
+    }
+}
+
+struct Square(f64);
+impl Shape for Square {
+    fn area(&self) -> f64 { self.0 * self.0 }
+}
+
+fn unit() -> AnyShape {
+    AnyShape::Square(Square(1.0))
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_for_loop_to_iterator_chain() {
+    check_doc_test(
+        "convert_for_loop_to_iterator_chain",
+        r#####"
+//- minicore: iterators, copy
+fn main() {
+    let mut total = 0;
+    for$0 x in [1, 2, 3] {
+        if x > 1 {
+            total += x;
+        }
+    }
+}
+"#####,
+        r#####"
+fn main() {
+    let total: i32 = [1, 2, 3].into_iter().filter(|&x| x > 1).sum();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_for_loop_with_for_each() {
     check_doc_test(
@@ -390,6 +560,46 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_format_to_to_string() {
+    check_doc_test(
+        "convert_format_to_to_string",
+        r#####"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{format, string::ToString};
+fn main() {
+    let n = 92;
+    let s = format!$0("{}", n + 1);
+}
+//- /alloc.rs crate:alloc
+pub mod string {
+    pub struct String;
+    pub trait ToString {
+        fn to_string(&self) -> String;
+    }
+    impl<T: ?Sized> ToString for T {
+        fn to_string(&self) -> String { String }
+    }
+}
+pub mod fmt {
+    pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+}
+#[macro_export]
+macro_rules! format {
+    ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+}
+"#####,
+        r#####"
+use alloc::{format, string::ToString};
+fn main() {
+    let n = 92;
+    let s = (n + 1).to_string();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_if_to_bool_then() {
     check_doc_test(
@@ -412,6 +622,45 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_impl_trait_to_box_dyn() {
+    check_doc_test(
+        "convert_impl_trait_to_box_dyn",
+        r#####"
+//- minicore: coerce_unsized
+//- /main.rs crate:main deps:alloc
+use alloc::boxed::Box;
+trait Shape {}
+struct Square;
+impl Shape for Square {}
+
+fn unit() -> impl Shape$0 {
+    Square
+}
+//- /alloc.rs crate:alloc
+pub mod boxed {
+    use core::marker::Unsize;
+    use core::ops::CoerceUnsized;
+    pub struct Box<T: ?Sized>(*mut T);
+    impl<T> Box<T> {
+        pub fn new(value: T) -> Box<T> { loop {} }
+    }
+    impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Box<U>> for Box<T> {}
+}
+"#####,
+        r#####"
+use alloc::boxed::Box;
+trait Shape {}
+struct Square;
+impl Shape for Square {}
+
+fn unit() -> Box<dyn Shape> {
+    Box::new(Square)
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_integer_literal() {
     check_doc_test(
@@ -479,6 +728,30 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_iterator_chain_to_for_loop() {
+    check_doc_test(
+        "convert_iterator_chain_to_for_loop",
+        r#####"
+//- minicore: iterators
+fn main() {
+    let found = [1, 2, 3].into_iter().any$0(|x| x > 2);
+}
+"#####,
+        r#####"
+fn main() {
+    let mut found = false;
+    for x in [1, 2, 3] {
+        if x > 2 {
+            found = true;
+            break;
+        }
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_let_else_to_match() {
     check_doc_test(
@@ -499,6 +772,34 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_macro_rules_to_fn() {
+    check_doc_test(
+        "convert_macro_rules_to_fn",
+        r#####"
+//- minicore: copy
+macro_rules! $0square {
+    ($x:expr) => {
+        $x * $x
+    };
+}
+
+fn f(n: i32) -> i32 {
+    square!(n + 1)
+}
+"#####,
+        r#####"
+fn square(x: i32) -> i32 {
+    x * x
+}
+
+fn f(n: i32) -> i32 {
+    square(n + 1)
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_match_to_let_else() {
     check_doc_test(
@@ -586,6 +887,92 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_push_str_to_format() {
+    check_doc_test(
+        "convert_push_str_to_format",
+        r#####"
+//- minicore: fmt
+//- /main.rs crate:main deps:alloc
+use alloc::{format, string::String};
+fn greeting(name: &str) -> String {
+    let mut s = String::new();
+    s.push_str("Hello, ");$0
+    s.push_str(name);
+    s.push('!');
+    s
+}
+//- /alloc.rs crate:alloc
+pub mod string {
+    pub struct String;
+    impl String {
+        pub fn new() -> String { String }
+        pub fn push_str(&mut self, string: &str) {}
+        pub fn push(&mut self, ch: char) {}
+    }
+}
+pub mod fmt {
+    pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+}
+#[macro_export]
+macro_rules! format {
+    ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+}
+"#####,
+        r#####"
+use alloc::{format, string::String};
+fn greeting(name: &str) -> String {
+    let s = format!("Hello, {name}!");
+    s
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_string_concat_to_format() {
+    check_doc_test(
+        "convert_string_concat_to_format",
+        r#####"
+//- minicore: fmt, add
+//- /main.rs crate:main deps:alloc
+use alloc::{format, string::ToString};
+fn main() {
+    let (n, unit) = (92, "m");
+    let s = n.to_string() +$0 " " + unit;
+}
+//- /alloc.rs crate:alloc
+pub mod string {
+    pub struct String;
+    impl core::ops::Add<&str> for String {
+        type Output = String;
+        fn add(self, other: &str) -> String { self }
+    }
+    pub trait ToString {
+        fn to_string(&self) -> String;
+    }
+    impl<T: ?Sized> ToString for T {
+        fn to_string(&self) -> String { String }
+    }
+}
+pub mod fmt {
+    pub fn format(args: core::fmt::Arguments<'_>) -> crate::string::String { loop {} }
+}
+#[macro_export]
+macro_rules! format {
+    ($($arg:tt)*) => { $crate::fmt::format(core::format_args!($($arg)*)) };
+}
+"#####,
+        r#####"
+use alloc::{format, string::ToString};
+fn main() {
+    let (n, unit) = (92, "m");
+    let s = format!("{n} {unit}");
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_to_guarded_return() {
     check_doc_test(
@@ -756,6 +1143,63 @@ comment"]
     )
 }
 
+#[test]
+fn doctest_elide_lifetimes() {
+    check_doc_test(
+        "elide_lifetimes",
+        r#####"
+fn $0count<'a, 'b>(s: &'a str, sep: Sep<'b>) -> usize { 0 }
+
+fn first<'a>(s: &'a str) -> &'a str { s }
+"#####,
+        r#####"
+fn count(s: &str, sep: Sep<'_>) -> usize { 0 }
+
+fn first<'a>(s: &'a str) -> &'a str { s }
+"#####,
+    )
+}
+
+#[test]
+fn doctest_encapsulate_field() {
+    check_doc_test(
+        "encapsulate_field",
+        r#####"
+//- minicore: copy
+pub struct Counter {
+    pub $0count: u32,
+}
+
+fn tick(c: &mut Counter) {
+    if c.count < 10 {
+        c.count = 0;
+    }
+}
+"#####,
+        r#####"
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn $0count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn set_count(&mut self, count: u32) {
+        self.count = count;
+    }
+}
+
+fn tick(c: &mut Counter) {
+    if c.count() < 10 {
+        c.set_count(0);
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_expand_glob_import() {
     check_doc_test(
@@ -872,6 +1316,45 @@ enum A { One(One) }
     )
 }
 
+#[test]
+fn doctest_extract_trait() {
+    check_doc_test(
+        "extract_trait",
+        r#####"
+struct Counter(u32);
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter(0)
+    }
+
+    $0pub fn increment(&mut self) {
+        self.0 += 1;
+    }$0
+}
+"#####,
+        r#####"
+struct Counter(u32);
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter(0)
+    }
+}
+
+pub trait NewTrait {
+    fn increment(&mut self);
+}
+
+impl NewTrait for Counter {
+    fn increment(&mut self) {
+        self.0 += 1;
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_extract_type_alias() {
     check_doc_test(
@@ -979,6 +1462,67 @@ fn foo<T: Copy + Clone>() { }
     )
 }
 
+#[test]
+fn doctest_generate_builder() {
+    check_doc_test(
+        "generate_builder",
+        r#####"
+//- minicore: option, default, builtin_impls
+struct String;
+struct Config {
+    name: String,
+    port: u16,$0
+    label: Option<String>,
+}
+"#####,
+        r#####"
+struct String;
+struct Config {
+    name: String,
+    port: u16,
+    label: Option<String>,
+}
+
+impl Config {
+    fn $0builder() -> ConfigBuilder {
+        ConfigBuilder { name: None, port: None, label: None }
+    }
+}
+
+struct ConfigBuilder {
+    name: Option<String>,
+    port: Option<u16>,
+    label: Option<String>,
+}
+
+impl ConfigBuilder {
+    fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    fn label(mut self, label: String) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    fn build(self) -> Result<Config, String> {
+        Ok(Config {
+            name: self.name.ok_or("`name` is not set")?,
+            port: self.port.unwrap_or_default(),
+            label: self.label,
+        })
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_constant() {
     check_doc_test(
@@ -1292,6 +1836,45 @@ impl Value {
     )
 }
 
+#[test]
+fn doctest_generate_enum_dispatch() {
+    check_doc_test(
+        "generate_enum_dispatch",
+        r#####"
+trait Shape$0 {
+    fn area(&self) -> f64;
+}
+
+struct Square(f64);
+impl Shape for Square {
+    fn area(&self) -> f64 { self.0 * self.0 }
+}
+"#####,
+        r#####"
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+enum AnyShape {
+    Square(Square),
+}
+
+impl Shape for AnyShape {
+    fn area(&self) -> f64 {
+        match self {
+            AnyShape::Square(it) => Shape::area(it),
+        }
+    }
+}
+
+struct Square(f64);
+impl Shape for Square {
+    fn area(&self) -> f64 { self.0 * self.0 }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_enum_is_method() {
     check_doc_test(
@@ -1618,6 +2201,33 @@ impl Person {
     )
 }
 
+#[test]
+fn doctest_generate_test() {
+    check_doc_test(
+        "generate_test",
+        r#####"
+//- minicore: default, builtin_impls
+struct Config;
+pub fn parse$0(input: &str, config: &Config, limit: u32) -> u32 { 0 }
+"#####,
+        r#####"
+struct Config;
+pub fn parse(input: &str, config: &Config, limit: u32) -> u32 { 0 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let result = parse(todo!(), &todo!(), Default::default());
+        assert_eq!(result, ${0:todo!()});
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_trait_from_impl() {
     check_doc_test(
@@ -1737,6 +2347,26 @@ fn something() -> &'static str {
     )
 }
 
+#[test]
+fn doctest_inline_format_args() {
+    check_doc_test(
+        "inline_format_args",
+        r#####"
+//- minicore: fmt
+fn main() {
+    let (name, count) = ("x", 1);
+    print!("{} has {:>3} {}$0", name, count, count + 1);
+}
+"#####,
+        r#####"
+fn main() {
+    let (name, count) = ("x", 1);
+    print!("{name} has {count:>3} {}", count + 1);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_inline_into_callers() {
     check_doc_test(
@@ -1932,12 +2562,67 @@ impl Cursor<'_$0> {
 }
 "#####,
         r#####"
-impl<'a> Cursor<'a> {
-    fn node(self) -> &SyntaxNode {
-        match self {
-            Cursor::Replace(node) | Cursor::Before(node) => node,
-        }
-    }
+impl<'a> Cursor<'a> {
+    fn node(self) -> &SyntaxNode {
+        match self {
+            Cursor::Replace(node) | Cursor::Before(node) => node,
+        }
+    }
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_introduce_param() {
+    check_doc_test(
+        "introduce_param",
+        r#####"
+fn circle(radius: u32) {}
+
+fn draw() {
+    circle($010$0);
+}
+
+fn main() {
+    draw();
+}
+"#####,
+        r#####"
+fn circle(radius: u32) {}
+
+fn draw(radius: u32) {
+    circle(radius);
+}
+
+fn main() {
+    draw(10);
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_introduce_parameter_object() {
+    check_doc_test(
+        "introduce_parameter_object",
+        r#####"
+fn draw($0x: i32, y: i32$0, color: u32) {}
+
+fn main() {
+    draw(1, 2, 0);
+}
+"#####,
+        r#####"
+struct DrawParams {
+    x: i32,
+    y: i32,
+}
+
+fn draw(DrawParams { x, y }: DrawParams, color: u32) {}
+
+fn main() {
+    draw(DrawParams { x: 1, y: 2 }, 0);
 }
 "#####,
     )
@@ -1977,6 +2662,55 @@ fn doctest_line_to_block() {
     )
 }
 
+#[test]
+fn doctest_make_async() {
+    check_doc_test(
+        "make_async",
+        r#####"
+f$0n read() -> u32 { 0 }
+async fn run() -> u32 { read() }
+"#####,
+        r#####"
+async fn read() -> u32 { 0 }
+async fn run() -> u32 { read().await }
+"#####,
+    )
+}
+
+#[test]
+fn doctest_make_async_with_callers() {
+    check_doc_test(
+        "make_async_with_callers",
+        r#####"
+f$0n read() -> u32 { 0 }
+fn parse() -> u32 { read() }
+async fn run() { parse(); }
+"#####,
+        r#####"
+async fn read() -> u32 { 0 }
+async fn parse() -> u32 { read().await }
+async fn run() { parse().await; }
+"#####,
+    )
+}
+
+#[test]
+fn doctest_make_lifetimes_explicit() {
+    check_doc_test(
+        "make_lifetimes_explicit",
+        r#####"
+fn $0longest(a: &str, b: &str, c: &'_ str) {}
+
+fn first(s: &str) -> &str { s }
+"#####,
+        r#####"
+fn longest<'a, 'b, 'c>(a: &'a str, b: &'b str, c: &'c str) {}
+
+fn first(s: &str) -> &str { s }
+"#####,
+    )
+}
+
 #[test]
 fn doctest_make_raw_string() {
     check_doc_test(
@@ -2169,6 +2903,49 @@ fn handle(action: Action) {
     )
 }
 
+#[test]
+fn doctest_move_item_to_module() {
+    check_doc_test(
+        "move_item_to_module",
+        r#####"
+mod shapes {
+    pub struct Circle;
+}
+
+struct Squ$0are;
+
+impl Square {
+    fn new() -> Self {
+        Square
+    }
+}
+
+fn main() {
+    let _ = Square::new();
+}
+"#####,
+        r#####"
+use shapes::Square;
+
+mod shapes {
+    pub struct Circle;
+
+    pub(crate) struct Square;
+
+    impl Square {
+        pub(crate) fn new() -> Self {
+            Square
+        }
+    }
+}
+
+fn main() {
+    let _ = Square::new();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_move_module_to_file() {
     check_doc_test(
@@ -2184,6 +2961,48 @@ mod foo;
     )
 }
 
+#[test]
+fn doctest_move_param_left() {
+    check_doc_test(
+        "move_param_left",
+        r#####"
+fn div(divisor: i32, $0dividend: i32) -> i32 { dividend / divisor }
+
+fn main() {
+    div(2, 10);
+}
+"#####,
+        r#####"
+fn div(dividend: i32, divisor: i32) -> i32 { dividend / divisor }
+
+fn main() {
+    div(10, 2);
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_move_param_right() {
+    check_doc_test(
+        "move_param_right",
+        r#####"
+fn div($0divisor: i32, dividend: i32) -> i32 { dividend / divisor }
+
+fn main() {
+    div(2, 10);
+}
+"#####,
+        r#####"
+fn div(dividend: i32, divisor: i32) -> i32 { dividend / divisor }
+
+fn main() {
+    div(10, 2);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_move_to_mod_rs() {
     check_doc_test(
@@ -2368,6 +3187,41 @@ impl Walrus {
     )
 }
 
+#[test]
+fn doctest_remove_param() {
+    check_doc_test(
+        "remove_param",
+        r#####"
+trait Shape {
+    fn area(&self, $0scale: f32) -> f32;
+}
+
+struct Square;
+impl Shape for Square {
+    fn area(&self, _: f32) -> f32 { 1.0 }
+}
+
+fn main() {
+    Square.area(2.0);
+}
+"#####,
+        r#####"
+trait Shape {
+    fn area(&self) -> f32;
+}
+
+struct Square;
+impl Shape for Square {
+    fn area(&self) -> f32 { 1.0 }
+}
+
+fn main() {
+    Square.area();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_remove_parentheses() {
     check_doc_test(
@@ -2424,6 +3278,37 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_rename_param() {
+    check_doc_test(
+        "rename_param",
+        r#####"
+trait Shape {
+    fn scale(&self, f: f32);
+}
+
+struct Square;
+impl Shape for Square {
+    fn scale(&self, $0factor: f32) {
+        let _ = factor;
+    }
+}
+"#####,
+        r#####"
+trait Shape {
+    fn scale(&self, factor: f32);
+}
+
+struct Square;
+impl Shape for Square {
+    fn scale(&self, factor: f32) {
+        let _ = factor;
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_reorder_fields() {
     check_doc_test(
@@ -2639,6 +3524,33 @@ fn compute() -> Option<i32> { None }
     )
 }
 
+#[test]
+fn doctest_replace_manual_impl_with_derive() {
+    check_doc_test(
+        "replace_manual_impl_with_derive",
+        r#####"
+//- minicore: clone, derive, builtin_impls
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+impl Clone for $0Point {
+    fn clone(&self) -> Self {
+        Point { x: self.x.clone(), y: self.y.clone() }
+    }
+}
+"#####,
+        r#####"
+#[derive(Clone)]
+struct Point {
+    x: u32,
+    y: u32,
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_match_with_if_let() {
     check_doc_test(
@@ -2680,6 +3592,39 @@ fn new(location: impl AsRef<Path>) -> Self {}
     )
 }
 
+#[test]
+fn doctest_replace_param_type_with_impl_trait() {
+    check_doc_test(
+        "replace_param_type_with_impl_trait",
+        r#####"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+}
+
+fn print(counter$0: &Counter) {
+    counter.get();
+}
+"#####,
+        r#####"
+struct Counter(u32);
+trait Get {
+    fn get(&self) -> u32;
+}
+impl Get for Counter {
+    fn get(&self) -> u32 { self.0 }
+}
+
+fn print(counter: &impl Get) {
+    counter.get();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_qualified_name_with_use() {
     check_doc_test(
@@ -2885,6 +3830,68 @@ enum Animal {
     )
 }
 
+#[test]
+fn doctest_split_impl() {
+    check_doc_test(
+        "split_impl",
+        r#####"
+struct S;
+
+impl S {
+    pub fn new() -> S { S }
+
+    $0fn helper(&self) {}
+
+    fn other_helper(&self) {}$0
+}
+"#####,
+        r#####"
+struct S;
+
+impl S {
+    pub fn new() -> S { S }
+}
+
+impl S {
+    fn helper(&self) {}
+
+    fn other_helper(&self) {}
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_split_impl_by_visibility() {
+    check_doc_test(
+        "split_impl_by_visibility",
+        r#####"
+struct S;
+
+impl$0 S {
+    pub fn new() -> S { S }
+
+    fn helper(&self) {}
+
+    pub fn len(&self) -> usize { 0 }
+}
+"#####,
+        r#####"
+struct S;
+
+impl S {
+    pub fn new() -> S { S }
+
+    pub fn len(&self) -> usize { 0 }
+}
+
+impl S {
+    fn helper(&self) {}
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_split_import() {
     check_doc_test(
@@ -2898,6 +3905,33 @@ use std::{collections::HashMap};
     )
 }
 
+#[test]
+fn doctest_split_module() {
+    check_doc_test(
+        "split_module",
+        r#####"
+$0pub struct Parser;
+
+fn parse() -> Parser {
+    Parser
+}$0
+
+fn main() {
+    parse();
+}
+"#####,
+        r#####"
+mod parser;
+pub use parser::Parser;
+use parser::parse;
+
+fn main() {
+    parse();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_toggle_ignore() {
     check_doc_test(
@@ -3059,3 +4093,38 @@ fn foo() -> Result<i32, ${0:_}> { Ok(42i32) }
 "#####,
     )
 }
+
+#[test]
+fn doctest_wrap_type_in_cell_or_rc() {
+    check_doc_test(
+        "wrap_type_in_cell_or_rc",
+        r#####"
+//- minicore: copy
+//- /main.rs crate:main deps:std
+struct Counter {
+    $0hits: u32,
+}
+
+fn record(c: &mut Counter) -> u32 {
+    c.hits += 1;
+    c.hits
+}
+//- /std.rs crate:std
+pub mod cell {
+    pub struct RefCell<T>(T);
+}
+"#####,
+        r#####"
+use std::cell::RefCell;
+
+struct Counter {
+    hits: RefCell<u32>,
+}
+
+fn record(c: &mut Counter) -> u32 {
+    *c.hits.borrow_mut() += 1;
+    *c.hits.borrow()
+}
+"#####,
+    )
+}
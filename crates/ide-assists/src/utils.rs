@@ -16,7 +16,7 @@ use syntax::{
         edit_in_place::{AttrsOwnerEdit, Indent, Removable},
         make, HasArgList, HasAttrs, HasGenericParams, HasName, HasTypeBounds, Whitespace,
     },
-    ted, AstNode, AstToken, Direction, SourceFile,
+    match_ast, ted, AstNode, AstToken, Direction, NodeOrToken, SourceFile,
     SyntaxKind::*,
     SyntaxNode, TextRange, TextSize, T,
 };
@@ -831,3 +831,81 @@ pub(crate) fn replace_record_field_expr(
         edit.replace(file_range.range, initializer.syntax().text());
     }
 }
+
+/// Whether the place `expr` is part of gets assigned to or mutably borrowed.
+pub(crate) fn is_mutable_use(ctx: &AssistContext<'_>, expr: &ast::Expr) -> bool {
+    let mut place = expr.syntax().clone();
+    while let Some(parent) = place.parent() {
+        let is_base = |expr: Option<ast::Expr>| expr.map_or(false, |it| it.syntax() == &place);
+        let projected = match_ast! {
+            match parent {
+                ast::FieldExpr(it) => is_base(it.expr()),
+                ast::IndexExpr(it) => is_base(it.base()),
+                ast::ParenExpr(_) => true,
+                ast::BinExpr(it) => {
+                    return matches!(it.op_kind(), Some(ast::BinaryOp::Assignment { .. }))
+                        && is_base(it.lhs());
+                },
+                ast::RefExpr(it) => return it.mut_token().is_some(),
+                ast::MethodCallExpr(it) => {
+                    return is_base(it.receiver())
+                        && ctx
+                            .sema
+                            .resolve_method_call(&it)
+                            .and_then(|func| func.self_param(ctx.db()))
+                            .map_or(false, |param| param.access(ctx.db()) == hir::Access::Exclusive);
+                },
+                _ => false,
+            }
+        };
+        if !projected {
+            return false;
+        }
+        place = parent;
+    }
+    false
+}
+
+/// The text of the arguments after the format string `fmt_string` of a `format_args!`-like macro
+/// call, and the range from the end of the format string to the closing delimiter.
+pub(crate) fn format_string_args(
+    tt: &ast::TokenTree,
+    fmt_string: &ast::String,
+) -> Option<(Vec<String>, TextRange)> {
+    let tokens: Vec<_> = tt
+        .token_trees_and_tokens()
+        .skip_while(|it| it.as_token() != Some(fmt_string.syntax()))
+        .skip(1)
+        .collect();
+    let (NodeOrToken::Token(r_delim), tokens) = tokens.split_last()? else { return None };
+    if !matches!(r_delim.kind(), T![')'] | T![']'] | T!['}']) {
+        return None;
+    }
+
+    let mut args = Vec::new();
+    // `None` until the comma after the format string.
+    let mut current: Option<String> = None;
+    for it in tokens {
+        match it {
+            NodeOrToken::Token(token) if token.kind() == T![,] => {
+                if let Some(arg) = current.replace(String::new()) {
+                    args.push(arg.trim().to_owned());
+                }
+            }
+            NodeOrToken::Token(token) if token.kind().is_trivia() => {
+                if let Some(arg) = &mut current {
+                    arg.push_str(token.text());
+                }
+            }
+            NodeOrToken::Token(token) => current.as_mut()?.push_str(token.text()),
+            NodeOrToken::Node(tt) => format_to!(current.as_mut()?, "{tt}"),
+        }
+    }
+    if let Some(arg) = current.filter(|it| !it.trim().is_empty()) {
+        args.push(arg.trim().to_owned());
+    }
+    Some((
+        args,
+        TextRange::new(fmt_string.syntax().text_range().end(), r_delim.text_range().start()),
+    ))
+}
@@ -1,6 +1,7 @@
 //! This module defines an accumulator for completions which are going to be presented to user.
 
 pub(crate) mod attribute;
+pub(crate) mod doc_link;
 pub(crate) mod dot;
 pub(crate) mod expr;
 pub(crate) mod extern_abi;
@@ -11,6 +12,7 @@ pub(crate) mod format_string;
 pub(crate) mod item_list;
 pub(crate) mod keyword;
 pub(crate) mod lifetime;
+pub(crate) mod macro_input;
 pub(crate) mod mod_;
 pub(crate) mod pattern;
 pub(crate) mod postfix;
@@ -26,7 +28,7 @@ use std::iter;
 
 use hir::{known, HasAttrs, ScopeDef, Variant};
 use ide_db::{imports::import_assets::LocatedImport, RootDatabase, SymbolKind};
-use syntax::ast;
+use syntax::{ast, TextRange};
 
 use crate::{
     context::{
@@ -40,7 +42,8 @@ use crate::{
         literal::{render_struct_literal, render_variant_lit},
         macro_::render_macro,
         pattern::{render_struct_pat, render_variant_pat},
-        render_field, render_path_resolution, render_pattern_resolution, render_tuple_field,
+        render_field, render_path_resolution, render_pattern_resolution, render_resolution_simple,
+        render_tuple_field,
         type_alias::{render_type_alias, render_type_alias_with_eq},
         union_literal::render_union_literal,
         RenderContext,
@@ -221,6 +224,43 @@ impl Completions {
         );
     }
 
+    pub(crate) fn add_doc_link_resolution(
+        &mut self,
+        ctx: &CompletionContext<'_>,
+        source_range: TextRange,
+        local_name: hir::Name,
+        resolution: hir::ScopeDef,
+    ) {
+        // Intra-doc links may point to private items, so visibility is not checked here.
+        if !ctx.check_stability(resolution.attrs(ctx.db).as_deref()) {
+            return;
+        }
+        self.add(
+            render_resolution_simple(RenderContext::new(ctx), source_range, local_name, resolution)
+                .build(ctx.db),
+        );
+    }
+
+    pub(crate) fn add_macro_input_resolution(
+        &mut self,
+        ctx: &CompletionContext<'_>,
+        local_name: hir::Name,
+        resolution: hir::ScopeDef,
+    ) {
+        if !ctx.check_stability(resolution.attrs(ctx.db).as_deref()) {
+            return;
+        }
+        self.add(
+            render_resolution_simple(
+                RenderContext::new(ctx),
+                ctx.source_range(),
+                local_name,
+                resolution,
+            )
+            .build(ctx.db),
+        );
+    }
+
     pub(crate) fn add_enum_variants(
         &mut self,
         ctx: &CompletionContext<'_>,
@@ -28,7 +28,7 @@ mod lint;
 mod repr;
 mod macro_use;
 
-pub(crate) use self::derive::complete_derive_path;
+pub(crate) use self::{cfg::complete_cfg_string, derive::complete_derive_path};
 
 /// Complete inputs to known builtin attributes as well as derive attributes
 pub(crate) fn complete_known_attribute_input(
@@ -68,6 +68,9 @@ pub(crate) fn complete_known_attribute_input(
             lint::complete_lint(acc, ctx, colon_prefix, &existing_lints, &lints);
         }
         "cfg" => cfg::complete_cfg(acc, ctx),
+        "cfg_attr" if cfg::is_in_cfg_attr_predicate(&tt, ctx.position.offset) => {
+            cfg::complete_cfg(acc, ctx)
+        }
         "macro_use" => {
             macro_use::complete_macro_use(acc, ctx, extern_crate, &parse_tt_as_comma_sep_paths(tt)?)
         }
@@ -2,12 +2,16 @@
 
 use ide_db::SymbolKind;
 use itertools::Itertools;
-use syntax::{algo, ast::Ident, AstToken, Direction, NodeOrToken, SyntaxKind};
+use syntax::{
+    algo,
+    ast::{self, Ident, IsString},
+    AstNode, AstToken, Direction, NodeOrToken, SmolStr, SyntaxKind, TextSize, T,
+};
 
 use crate::{completions::Completions, context::CompletionContext, CompletionItem};
 
 pub(crate) fn complete_cfg(acc: &mut Completions, ctx: &CompletionContext<'_>) {
-    let add_completion = |item: &str| {
+    let mut add_completion = |item: &str| {
         let mut completion = CompletionItem::new(SymbolKind::BuiltinAttr, ctx.source_range(), item);
         completion.insert_text(format!(r#""{item}""#));
         acc.add(completion.build(ctx.db));
@@ -32,20 +36,7 @@ pub(crate) fn complete_cfg(acc: &mut Completions, ctx: &CompletionContext<'_>) {
         });
     match previous {
         Some(None) => (),
-        Some(Some(p)) => match p.text() {
-            "target_arch" => KNOWN_ARCH.iter().copied().for_each(add_completion),
-            "target_env" => KNOWN_ENV.iter().copied().for_each(add_completion),
-            "target_os" => KNOWN_OS.iter().copied().for_each(add_completion),
-            "target_vendor" => KNOWN_VENDOR.iter().copied().for_each(add_completion),
-            "target_endian" => ["little", "big"].into_iter().for_each(add_completion),
-            name => ctx.krate.potential_cfg(ctx.db).get_cfg_values(name).cloned().for_each(|s| {
-                let insert_text = format!(r#""{s}""#);
-                let mut item = CompletionItem::new(SymbolKind::BuiltinAttr, ctx.source_range(), s);
-                item.insert_text(insert_text);
-
-                acc.add(item.build(ctx.db));
-            }),
-        },
+        Some(Some(p)) => cfg_values(ctx, p.text()).iter().for_each(|it| add_completion(it)),
         None => ctx.krate.potential_cfg(ctx.db).get_cfg_keys().cloned().unique().for_each(|s| {
             let item = CompletionItem::new(SymbolKind::BuiltinAttr, ctx.source_range(), s);
             acc.add(item.build(ctx.db));
@@ -53,6 +44,61 @@ pub(crate) fn complete_cfg(acc: &mut Completions, ctx: &CompletionContext<'_>) {
     }
 }
 
+/// Completes the value of a cfg key-value pair when the cursor is already inside of the string
+/// literal, as in `#[cfg(feature = "$0")]`.
+pub(crate) fn complete_cfg_string(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    original: &ast::String,
+) -> Option<()> {
+    let tt = original.syntax().parent().and_then(ast::TokenTree::cast)?;
+    let attr = tt.syntax().ancestors().find_map(ast::Attr::cast)?;
+    match attr.simple_name()?.as_str() {
+        "cfg" => (),
+        "cfg_attr" if is_in_cfg_attr_predicate(&tt, original.syntax().text_range().start()) => (),
+        _ => return None,
+    }
+
+    let eq = algo::skip_trivia_token(original.syntax().prev_token()?, Direction::Prev)
+        .filter(|it| it.kind() == T![=])?;
+    let key = algo::skip_trivia_token(eq.prev_token()?, Direction::Prev).and_then(Ident::cast)?;
+    let range = original.text_range_between_quotes()?;
+
+    cfg_values(ctx, key.text()).into_iter().for_each(|value| {
+        CompletionItem::new(SymbolKind::BuiltinAttr, range, value).add_to(acc, ctx.db)
+    });
+    Some(())
+}
+
+/// Whether `offset` lies within the predicate of a `cfg_attr`, that is before its first comma.
+pub(crate) fn is_in_cfg_attr_predicate(tt: &ast::TokenTree, offset: TextSize) -> bool {
+    tt.syntax()
+        .children_with_tokens()
+        .filter(|it| it.kind() == T![,])
+        .all(|comma| offset <= comma.text_range().start())
+}
+
+/// The values known for the given cfg key. These are the values the crate could be built with
+/// (enabled and declared features, custom `--cfg`s and the configured target's cfgs as
+/// reported by `rustc --print cfg`), followed by the well-known values for `target_*` keys.
+fn cfg_values(ctx: &CompletionContext<'_>, key: &str) -> Vec<SmolStr> {
+    let known: &[&str] = match key {
+        "target_arch" => &KNOWN_ARCH,
+        "target_env" => &KNOWN_ENV,
+        "target_os" => &KNOWN_OS,
+        "target_vendor" => &KNOWN_VENDOR,
+        "target_endian" => &["little", "big"],
+        _ => &[],
+    };
+    let potential_cfg = ctx.krate.potential_cfg(ctx.db);
+    potential_cfg
+        .get_cfg_values(key)
+        .cloned()
+        .chain(known.iter().copied().map(SmolStr::new))
+        .unique()
+        .collect()
+}
+
 const KNOWN_ARCH: [&str; 20] = [
     "aarch64",
     "arm",
@@ -0,0 +1,350 @@
+//! Completes paths in intra-doc links, like ``[`Foo::bar`]``.
+use hir::{AsAssocItem, AssocItemContainer, HirDisplay, ScopeDef};
+use ide_db::{
+    defs::Definition,
+    documentation::{doc_attributes, resolve_doc_path_for_def},
+    syntax_helpers::intra_doc_links::parse_intra_doc_link,
+    SymbolKind,
+};
+use syntax::{ast, AstNode, AstToken, TextRange, TextSize};
+
+use crate::{
+    completions::Completions, context::CompletionContext, CompletionItem, CompletionItemKind,
+};
+
+pub(crate) fn complete_doc_link(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    original: &ast::Comment,
+) -> Option<()> {
+    let comment_start = original.syntax().text_range().start();
+    let cursor = usize::from(ctx.position.offset - comment_start);
+    let text = original.text().get(original.prefix().len()..cursor)?;
+    let line = text.rsplit('\n').next()?;
+    let (link, _) = parse_intra_doc_link(link_before_cursor(line)?);
+    if !link.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ':') {
+        return None;
+    }
+    let (qualifier, segment) = match link.rsplit_once("::") {
+        Some((qualifier, segment)) => (Some(qualifier), segment),
+        None => (None, link),
+    };
+    let source_range =
+        TextRange::new(ctx.position.offset - TextSize::of(segment), ctx.position.offset);
+
+    let owner = original.syntax().parent()?;
+    // Inner doc comments of inline modules are part of the module's item list.
+    let owner = match ast::ItemList::cast(owner.clone()) {
+        Some(item_list) => item_list.syntax().parent()?,
+        None => owner,
+    };
+    let (_, def) = doc_attributes(&ctx.sema, &owner)?;
+
+    match qualifier {
+        None => {
+            ctx.process_all_names(&mut |name, res, _| match res {
+                ScopeDef::ModuleDef(_) | ScopeDef::AdtSelfType(_) | ScopeDef::ImplSelfType(_) => {
+                    acc.add_doc_link_resolution(ctx, source_range, name, res)
+                }
+                _ => (),
+            });
+            let has_parent = ctx.module.parent(ctx.db).is_some();
+            for kw in ["crate", "self"].into_iter().chain(has_parent.then_some("super")) {
+                CompletionItem::new(CompletionItemKind::Keyword, source_range, kw)
+                    .add_to(acc, ctx.db);
+            }
+        }
+        Some(qualifier) => {
+            let resolved = match qualifier {
+                // `Self` on its own isn't a valid doc path, but `Self::foo` is.
+                "Self" => self_def(ctx, def)?,
+                _ => resolve_doc_path_for_def(ctx.db, def, qualifier, Some(hir::Namespace::Types))?,
+            };
+            complete_members(acc, ctx, source_range, resolved);
+        }
+    }
+    Some(())
+}
+
+/// Returns the text of the link the cursor is in, if any, from either `[link` or `[text](link`.
+fn link_before_cursor(line: &str) -> Option<&str> {
+    let start = line.rfind(|c| c == '[' || c == '(')?;
+    if line[start..].starts_with('(') && !line[..start].ends_with(']') {
+        return None;
+    }
+    let link = &line[start + 1..];
+    (!link.contains(']')).then_some(link)
+}
+
+fn self_def(ctx: &CompletionContext<'_>, def: Definition) -> Option<Definition> {
+    let res = match def {
+        Definition::Adt(_) | Definition::Trait(_) | Definition::SelfType(_) => def,
+        Definition::Variant(it) => Definition::Adt(it.parent_enum(ctx.db).into()),
+        Definition::Field(it) => match it.parent_def(ctx.db) {
+            hir::VariantDef::Struct(it) => Definition::Adt(it.into()),
+            hir::VariantDef::Union(it) => Definition::Adt(it.into()),
+            hir::VariantDef::Variant(it) => Definition::Adt(it.parent_enum(ctx.db).into()),
+        },
+        Definition::Function(it) => assoc_item_self_def(ctx, it)?,
+        Definition::Const(it) => assoc_item_self_def(ctx, it)?,
+        Definition::TypeAlias(it) => assoc_item_self_def(ctx, it)?,
+        _ => return None,
+    };
+    Some(res)
+}
+
+fn assoc_item_self_def(ctx: &CompletionContext<'_>, item: impl AsAssocItem) -> Option<Definition> {
+    Some(match item.as_assoc_item(ctx.db)?.container(ctx.db) {
+        AssocItemContainer::Trait(it) => Definition::Trait(it),
+        AssocItemContainer::Impl(it) => Definition::SelfType(it),
+    })
+}
+
+fn complete_members(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    source_range: TextRange,
+    resolved: Definition,
+) {
+    let ty = match resolved {
+        Definition::Module(module) => {
+            for (name, def) in module.scope(ctx.db, None) {
+                if let ScopeDef::ModuleDef(_) = def {
+                    acc.add_doc_link_resolution(ctx, source_range, name, def);
+                }
+            }
+            return;
+        }
+        Definition::Trait(it) => {
+            // Like rustdoc, only the trait's own items, not the ones of its supertraits.
+            for item in it.items(ctx.db) {
+                if let Some(name) = item.name(ctx.db) {
+                    acc.add_doc_link_resolution(
+                        ctx,
+                        source_range,
+                        name,
+                        ScopeDef::ModuleDef(item.into()),
+                    );
+                }
+            }
+            return;
+        }
+        Definition::Variant(it) => {
+            add_fields(acc, ctx, source_range, it.fields(ctx.db));
+            return;
+        }
+        Definition::Adt(it) => it.ty(ctx.db),
+        Definition::SelfType(it) => it.self_ty(ctx.db),
+        Definition::TypeAlias(it) => it.ty(ctx.db),
+        Definition::BuiltinType(it) => it.ty(ctx.db),
+        _ => return,
+    };
+
+    ctx.iterate_path_candidates(&ty, |item| {
+        if let Some(name) = item.name(ctx.db) {
+            acc.add_doc_link_resolution(ctx, source_range, name, ScopeDef::ModuleDef(item.into()));
+        }
+    });
+    match ty.as_adt() {
+        Some(hir::Adt::Enum(it)) => {
+            for variant in it.variants(ctx.db) {
+                let def = ScopeDef::ModuleDef(variant.into());
+                acc.add_doc_link_resolution(ctx, source_range, variant.name(ctx.db), def);
+            }
+        }
+        Some(hir::Adt::Struct(it)) => add_fields(acc, ctx, source_range, it.fields(ctx.db)),
+        Some(hir::Adt::Union(it)) => add_fields(acc, ctx, source_range, it.fields(ctx.db)),
+        None => (),
+    }
+}
+
+fn add_fields(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    source_range: TextRange,
+    fields: Vec<hir::Field>,
+) {
+    for field in fields {
+        let mut item =
+            CompletionItem::new(SymbolKind::Field, source_range, field.name(ctx.db).to_smol_str());
+        item.detail(field.ty(ctx.db).display(ctx.db).to_string());
+        item.add_to(acc, ctx.db);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::tests::{check_edit, completion_list};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_items_in_scope() {
+        check(
+            r#"
+mod module {}
+struct Foo;
+/// See [`Fo$0`]
+fn bar() {}
+"#,
+            expect![[r#"
+                fn bar
+                md module
+                st Foo
+                bt u32
+                kw crate
+                kw self
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_assoc_items_and_fields() {
+        check(
+            r#"
+struct Foo { field: u32 }
+impl Foo {
+    fn method(&self) {}
+    const CONST: u32 = 0;
+}
+/// See [`Foo::$0`]
+fn bar() {}
+"#,
+            expect![[r#"
+                ct CONST
+                fd field  u32
+                fn method
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_enum_variants() {
+        check(
+            r#"
+enum E { A, B(u32) }
+/// [E::$0]
+fn bar() {}
+"#,
+            expect![[r#"
+                ev A
+                ev B
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_self() {
+        check(
+            r#"
+struct Foo;
+impl Foo {
+    /// Unlike [`Self::$0`]
+    fn new() -> Self { Foo }
+    fn other(&self) {}
+}
+"#,
+            expect![[r#"
+                fn new
+                fn other
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_crate_paths() {
+        check(
+            r#"
+mod module {
+    pub struct Inner;
+    //! Links to [crate::$0]
+}
+"#,
+            expect![[r#"
+                md module
+            "#]],
+        );
+        check(
+            r#"
+mod module {
+    /// [text](crate::module::$0)
+    pub struct Inner;
+}
+pub fn func() {}
+"#,
+            expect![[r#"
+                st Inner
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_in_other_files() {
+        check(
+            r#"
+//- /lib.rs
+mod module;
+//- /module.rs
+/// [crate::module::$0]
+pub struct Inner;
+"#,
+            expect![[r#"
+                st Inner
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_trait_items() {
+        check(
+            r#"
+trait Trait {
+    fn required();
+    type Assoc;
+}
+/// [Trait::$0]
+fn bar() {}
+"#,
+            expect![[r#"
+                fn required
+                ta Assoc
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_completions_outside_of_links() {
+        check(
+            r#"
+struct Foo;
+/// [Foo] is great, Fo$0
+fn bar() {}
+"#,
+            expect![[r#""#]],
+        );
+    }
+
+    #[test]
+    fn completes_in_disambiguated_link() {
+        check_edit(
+            "method",
+            r#"
+struct Foo;
+impl Foo { fn method(&self) {} }
+/// [fn@Foo::me$0]
+fn bar() {}
+"#,
+            r#"
+struct Foo;
+impl Foo { fn method(&self) {} }
+/// [fn@Foo::method]
+fn bar() {}
+"#,
+        );
+    }
+}
@@ -1,8 +1,13 @@
-//! Completes identifiers in format string literals.
+//! Completes identifiers and format specs in format string literals.
 
-use ide_db::syntax_helpers::format_string::is_format_string;
+use ide_db::syntax_helpers::format_string::{
+    format_argument_ty, format_placeholders, is_format_string, FormatTrait,
+};
 use itertools::Itertools;
-use syntax::{ast, AstToken, TextRange, TextSize};
+use syntax::{
+    ast::{self, IsString},
+    AstToken, TextRange, TextSize,
+};
 
 use crate::{context::CompletionContext, CompletionItem, CompletionItemKind, Completions};
 
@@ -21,6 +26,9 @@ pub(crate) fn format_string(
     let cursor_in_lit = cursor - lit_start;
 
     let prefix = &original.text()[..cursor_in_lit.into()];
+    if format_spec(acc, ctx, original, prefix).is_some() {
+        return;
+    }
     let braces = prefix.char_indices().rev().skip_while(|&(_, c)| c.is_alphanumeric()).next_tuple();
     let brace_offset = match braces {
         // escaped brace
@@ -36,11 +44,68 @@ pub(crate) fn format_string(
     })
 }
 
+const FORMAT_SPECS: &[(&str, &str)] = &[
+    ("?", "Debug"),
+    ("#?", "pretty-printed Debug"),
+    ("x", "lower-case hexadecimal"),
+    ("#x", "lower-case hexadecimal with `0x` prefix"),
+    ("X", "upper-case hexadecimal"),
+    ("#X", "upper-case hexadecimal with `0x` prefix"),
+    ("o", "octal"),
+    ("b", "binary"),
+    ("#b", "binary with `0b` prefix"),
+    ("e", "lower-case exponential notation"),
+    ("E", "upper-case exponential notation"),
+    ("p", "pointer address"),
+    (">8", "right-aligned to a width of 8"),
+    ("<8", "left-aligned to a width of 8"),
+    ("^8", "centered in a width of 8"),
+    ("08", "zero-padded to a width of 8"),
+    (".3", "precision of 3"),
+    ("+", "always print the sign"),
+];
+
+/// Complete format specs after the `:` of a placeholder, offering only those whose formatting
+/// trait is implemented by the type of the argument.
+fn format_spec(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    original: &ast::String,
+    prefix: &str,
+) -> Option<()> {
+    let lit_start = original.syntax().text_range().start();
+    let contents_start = usize::from(original.text_range_between_quotes()?.start() - lit_start);
+    let contents = prefix.get(contents_start..)?;
+    let placeholder = format_placeholders(contents).pop()?;
+    // the cursor has to be inside of the spec of a placeholder that isn't closed yet
+    if contents.ends_with('}') || placeholder.range.end() != TextSize::of(contents) {
+        return None;
+    }
+    let spec = placeholder.spec?;
+    let source_range =
+        TextRange::new(lit_start + TextSize::from(contents_start as u32), ctx.position.offset);
+    let source_range = TextRange::new(source_range.start() + spec.start(), source_range.end());
+
+    let ty = format_argument_ty(&ctx.sema, original, &placeholder.argument);
+    let famous_defs = ctx.famous_defs();
+    for &(spec, detail) in FORMAT_SPECS {
+        let Some(trait_) = FormatTrait::from_spec(spec) else { continue };
+        let implemented = ty.as_ref().and_then(|ty| trait_.is_implemented_by(&famous_defs, ty));
+        if implemented == Some(false) {
+            continue;
+        }
+        let mut item = CompletionItem::new(CompletionItemKind::Keyword, source_range, spec);
+        item.detail(detail);
+        item.add_to(acc, ctx.db);
+    }
+    Some(())
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
 
-    use crate::tests::{check_edit, completion_list_no_kw};
+    use crate::tests::{check_edit, completion_list, completion_list_no_kw};
 
     fn check(ra_fixture: &str, expect: Expect) {
         let actual = completion_list_no_kw(ra_fixture);
@@ -113,4 +178,87 @@ fn main() {
 "#,
         );
     }
+
+    #[test]
+    fn completes_format_specs() {
+        check_edit(
+            "#?",
+            r#"
+//- minicore: fmt, builtin_impls
+fn main() {
+    let foobar = 1;
+    format_args!("{foobar:$0");
+}
+"#,
+            r#"
+fn main() {
+    let foobar = 1;
+    format_args!("{foobar:#?");
+}
+"#,
+        );
+        check_edit(
+            "x",
+            r#"
+//- minicore: fmt, builtin_impls
+fn main() {
+    format_args!("{} {:#$0}", 1, 2);
+}
+"#,
+            r#"
+fn main() {
+    format_args!("{} {:x}", 1, 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn filters_format_specs_by_trait() {
+        let check = |ra_fixture, expect: Expect| expect.assert_eq(&completion_list(ra_fixture));
+        check(
+            r#"
+//- minicore: fmt, builtin_impls
+fn main() {
+    let foobar = 1;
+    format_args!("{foobar:$0");
+}
+"#,
+            expect![[r#"
+                kw #? pretty-printed Debug
+                kw #X upper-case hexadecimal with `0x` prefix
+                kw #b binary with `0b` prefix
+                kw #x lower-case hexadecimal with `0x` prefix
+                kw +  always print the sign
+                kw .3 precision of 3
+                kw 08 zero-padded to a width of 8
+                kw <8 left-aligned to a width of 8
+                kw >8 right-aligned to a width of 8
+                kw ?  Debug
+                kw E  upper-case exponential notation
+                kw X  upper-case hexadecimal
+                kw ^8 centered in a width of 8
+                kw b  binary
+                kw e  lower-case exponential notation
+                kw o  octal
+                kw x  lower-case hexadecimal
+            "#]],
+        );
+        check(
+            r#"
+//- minicore: fmt
+struct S;
+impl core::fmt::Debug for S {
+    fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { Ok(()) }
+}
+fn main() {
+    format_args!("{0:$0}", S);
+}
+"#,
+            expect![[r#"
+                kw #? pretty-printed Debug
+                kw ?  Debug
+            "#]],
+        );
+    }
 }
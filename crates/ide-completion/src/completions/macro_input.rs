@@ -0,0 +1,252 @@
+//! Completes the input of `macro_rules!` macro calls based on what the macro's matchers expect.
+use hir::{ExpectedToken, MetaVarKind, ModuleDef, ScopeDef};
+
+use crate::{
+    context::{CompletionContext, MacroInputCtx},
+    CompletionItem, CompletionItemKind, Completions,
+};
+
+/// Completes the identifiers the matchers expect at the cursor, like `mode` for a
+/// `(mode $e:expr)` rule, and, if the cursor couldn't be mapped into the expansion (the usual
+/// case for incomplete input), names fitting the fragments the matchers expect.
+pub(crate) fn complete_macro_input(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    macro_input: &MacroInputCtx,
+    is_unexpanded: bool,
+) -> Option<()> {
+    let mac = ctx.sema.resolve_macro_call(&macro_input.call)?;
+    let expected =
+        mac.expected_tokens_at(ctx.db, &macro_input.fake_token_tree, macro_input.fake_offset);
+
+    let mut fragments = Vec::new();
+    for expected in expected {
+        match expected {
+            ExpectedToken::Ident(ident) => {
+                CompletionItem::new(CompletionItemKind::Keyword, ctx.source_range(), ident)
+                    .add_to(acc, ctx.db);
+            }
+            ExpectedToken::Fragment(kind) => fragments.push(kind),
+        }
+    }
+    if !is_unexpanded || fragments.is_empty() {
+        return Some(());
+    }
+    ctx.process_all_names(&mut |name, def, _| {
+        if fragments.iter().any(|&kind| fits_fragment(def, kind)) {
+            acc.add_macro_input_resolution(ctx, name, def);
+        }
+    });
+    Some(())
+}
+
+/// Whether a name resolving to `def` can start a fragment of the given kind.
+fn fits_fragment(def: ScopeDef, kind: MetaVarKind) -> bool {
+    match kind {
+        MetaVarKind::Path => !matches!(def, ScopeDef::Label(_)),
+        MetaVarKind::Expr | MetaVarKind::Stmt | MetaVarKind::Tt => !matches!(
+            def,
+            ScopeDef::Label(_) | ScopeDef::GenericParam(hir::GenericParam::LifetimeParam(_))
+        ),
+        MetaVarKind::Ty => matches!(
+            def,
+            ScopeDef::ModuleDef(
+                ModuleDef::Module(_)
+                    | ModuleDef::Adt(_)
+                    | ModuleDef::Trait(_)
+                    | ModuleDef::TraitAlias(_)
+                    | ModuleDef::TypeAlias(_)
+                    | ModuleDef::BuiltinType(_)
+                    | ModuleDef::Macro(_)
+            ) | ScopeDef::GenericParam(hir::GenericParam::TypeParam(_))
+                | ScopeDef::AdtSelfType(_)
+                | ScopeDef::ImplSelfType(_)
+        ),
+        MetaVarKind::Pat | MetaVarKind::PatParam => matches!(
+            def,
+            ScopeDef::ModuleDef(
+                ModuleDef::Module(_)
+                    | ModuleDef::Adt(_)
+                    | ModuleDef::Variant(_)
+                    | ModuleDef::Const(_)
+                    | ModuleDef::TypeAlias(_)
+                    | ModuleDef::Macro(_)
+            ) | ScopeDef::GenericParam(hir::GenericParam::ConstParam(_))
+                | ScopeDef::AdtSelfType(_)
+                | ScopeDef::ImplSelfType(_)
+        ),
+        MetaVarKind::Ident
+        | MetaVarKind::Lifetime
+        | MetaVarKind::Literal
+        | MetaVarKind::Block
+        | MetaVarKind::Meta
+        | MetaVarKind::Item
+        | MetaVarKind::Vis => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::tests::{check_edit, completion_list};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_matcher_keywords() {
+        check(
+            r#"
+macro_rules! m {
+    (mode fast) => {};
+    (mode slow) => {};
+    (level $e:expr) => {};
+}
+fn f() {
+    m!($0);
+}
+"#,
+            expect![[r#"
+                kw level
+                kw mode
+            "#]],
+        );
+        check(
+            r#"
+macro_rules! m {
+    (mode fast) => {};
+    (mode slow) => {};
+    (level $e:expr) => {};
+}
+fn f() {
+    m!(mode $0);
+}
+"#,
+            expect![[r#"
+                kw fast
+                kw slow
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_keywords_after_fragments() {
+        check(
+            r#"
+macro_rules! m {
+    ($a:ident as $t:ty) => {};
+    ($a:ident with $e:expr) => {};
+}
+fn f() {
+    m!(foo $0);
+}
+"#,
+            expect![[r#"
+                kw as
+                kw with
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_keywords_in_repetitions() {
+        check(
+            r#"
+macro_rules! m {
+    ($(flag $f:ident),* ; done) => {};
+}
+fn f() {
+    m!(flag a, flag b, $0);
+}
+"#,
+            expect![[r#"
+                kw flag
+            "#]],
+        );
+        check(
+            r#"
+macro_rules! m {
+    ($(flag $f:ident),* ; done) => {};
+}
+fn f() {
+    m!(flag a ; $0);
+}
+"#,
+            expect![[r#"
+                kw done
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_type_fragment_of_unmatched_input() {
+        check(
+            r#"
+macro_rules! m {
+    ($a:ident as $t:ty) => {};
+}
+struct Foo;
+const C: u32 = 0;
+fn f() {
+    m!(x as $0 +);
+}
+"#,
+            expect![[r#"
+                ma m
+                st Foo
+                bt u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_expr_fragment_of_unmatched_input() {
+        check(
+            r#"
+macro_rules! m {
+    (run $e:expr; then $t:ty) => {};
+}
+struct Foo;
+fn g() {}
+fn f() {
+    m!(run Fo$0);
+}
+"#,
+            expect![[r#"
+                fn f
+                fn g
+                ma m
+                st Foo
+                bt u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn inserts_matcher_keyword() {
+        check_edit(
+            "slow",
+            r#"
+macro_rules! m {
+    (mode fast) => {};
+    (mode slow) => {};
+}
+fn f() {
+    m!(mode sl$0);
+}
+"#,
+            r#"
+macro_rules! m {
+    (mode fast) => {};
+    (mode slow) => {};
+}
+fn f() {
+    m!(mode slow);
+}
+"#,
+        );
+    }
+}
@@ -367,6 +367,13 @@ pub(super) enum CompletionAnalysis {
         /// fake token
         expanded: Option<ast::String>,
     },
+    /// The doc comment the cursor is currently inside
+    DocComment {
+        original: ast::Comment,
+    },
+    /// Set if we are currently completing in the input of a macro call that couldn't be expanded
+    /// with the cursor mapped into the expansion, see `CompletionContext::macro_input`
+    UnexpandedMacroInput,
     /// Set if we are currently completing in an unexpanded attribute, this usually implies a builtin attribute like `allow($0)`
     UnexpandedAttrTT {
         colon_prefix: bool,
@@ -375,6 +382,17 @@ pub(super) enum CompletionAnalysis {
     },
 }
 
+/// The input of a function-like macro call we are completing in.
+#[derive(Debug)]
+pub(crate) struct MacroInputCtx {
+    /// The macro call, in the original file or in the expansion of an outer macro call
+    pub(crate) call: ast::MacroCall,
+    /// The token tree of `call` with the completion marker inserted
+    pub(crate) fake_token_tree: ast::TokenTree,
+    /// The offset of the completion marker in the file of `fake_token_tree`
+    pub(crate) fake_offset: TextSize,
+}
+
 /// Information about the field or method access we are completing.
 #[derive(Debug)]
 pub(super) struct DotAccess {
@@ -438,6 +456,9 @@ pub(crate) struct CompletionContext<'a> {
     ///   - mod bar
     /// Here depth will be 2
     pub(super) depth_from_crate_root: usize,
+
+    /// The input of the innermost function-like macro call the cursor is in, if any.
+    pub(super) macro_input: Option<MacroInputCtx>,
 }
 
 impl CompletionContext<'_> {
@@ -680,6 +701,7 @@ impl<'a> CompletionContext<'a> {
             qualifier_ctx,
             token,
             offset,
+            macro_input,
         } = expand_and_analyze(
             &sema,
             original_file.syntax().clone(),
@@ -724,6 +746,7 @@ impl<'a> CompletionContext<'a> {
             qualifier_ctx,
             locals,
             depth_from_crate_root,
+            macro_input,
         };
         Some((ctx, analysis))
     }
@@ -12,9 +12,9 @@ use syntax::{
 
 use crate::context::{
     AttrCtx, CompletionAnalysis, DotAccess, DotAccessKind, ExprCtx, ItemListKind, LifetimeContext,
-    LifetimeKind, NameContext, NameKind, NameRefContext, NameRefKind, ParamContext, ParamKind,
-    PathCompletionCtx, PathKind, PatternContext, PatternRefutability, Qualified, QualifierCtx,
-    TypeAscriptionTarget, TypeLocation, COMPLETION_MARKER,
+    LifetimeKind, MacroInputCtx, NameContext, NameKind, NameRefContext, NameRefKind, ParamContext,
+    ParamKind, PathCompletionCtx, PathKind, PatternContext, PatternRefutability, Qualified,
+    QualifierCtx, TypeAscriptionTarget, TypeLocation, COMPLETION_MARKER,
 };
 
 struct ExpansionResult {
@@ -23,6 +23,7 @@ struct ExpansionResult {
     offset: TextSize,
     fake_ident_token: SyntaxToken,
     derive_ctx: Option<(SyntaxNode, SyntaxNode, TextSize, ast::Attr)>,
+    macro_input: Option<MacroInputCtx>,
 }
 
 pub(super) struct AnalysisResult {
@@ -32,6 +33,7 @@ pub(super) struct AnalysisResult {
     /// the original token of the expanded file
     pub(super) token: SyntaxToken,
     pub(super) offset: TextSize,
+    pub(super) macro_input: Option<MacroInputCtx>,
 }
 
 pub(super) fn expand_and_analyze(
@@ -49,15 +51,16 @@ pub(super) fn expand_and_analyze(
     // make the offset point to the start of the original token, as that is what the
     // intermediate offsets calculated in expansion always points to
     let offset = offset - relative_offset;
-    let expansion =
+    let mut expansion =
         expand(sema, original_file, speculative_file, offset, fake_ident_token, relative_offset);
+    let macro_input = expansion.macro_input.take();
 
     // add the relative offset back, so that left_biased finds the proper token
     let offset = expansion.offset + relative_offset;
     let token = expansion.original_file.token_at_offset(offset).left_biased()?;
 
     analyze(sema, expansion, original_token, &token).map(|(analysis, expected, qualifier_ctx)| {
-        AnalysisResult { analysis, expected, qualifier_ctx, token, offset }
+        AnalysisResult { analysis, expected, qualifier_ctx, token, offset, macro_input }
     })
 }
 
@@ -74,6 +77,7 @@ fn expand(
 ) -> ExpansionResult {
     let _p = profile::span("CompletionContext::expand");
     let mut derive_ctx = None;
+    let mut macro_input = None;
 
     'expansion: loop {
         let parent_item =
@@ -168,6 +172,11 @@ fn expand(
                 Some(tt) => tt,
                 None => break 'expansion,
             };
+            macro_input = Some(MacroInputCtx {
+                call: actual_macro_call.clone(),
+                fake_token_tree: speculative_args.clone(),
+                fake_offset: offset,
+            });
 
             match (
                 sema.expand(&actual_macro_call),
@@ -200,7 +209,14 @@ fn expand(
         // none of our states have changed so stop the loop
         break 'expansion;
     }
-    ExpansionResult { original_file, speculative_file, offset, fake_ident_token, derive_ctx }
+    ExpansionResult {
+        original_file,
+        speculative_file,
+        offset,
+        fake_ident_token,
+        derive_ctx,
+        macro_input,
+    }
 }
 
 /// Fill the completion context, this is what does semantic reasoning about the surrounding context
@@ -212,8 +228,14 @@ fn analyze(
     self_token: &SyntaxToken,
 ) -> Option<(CompletionAnalysis, (Option<Type>, Option<ast::NameOrNameRef>), QualifierCtx)> {
     let _p = profile::span("CompletionContext::analyze");
-    let ExpansionResult { original_file, speculative_file, offset, fake_ident_token, derive_ctx } =
-        expansion_result;
+    let ExpansionResult {
+        original_file,
+        speculative_file,
+        offset,
+        fake_ident_token,
+        derive_ctx,
+        macro_input: _,
+    } = expansion_result;
 
     // Overwrite the path kind for derives
     if let Some((original_file, file_with_fake_ident, offset, origin_attr)) = derive_ctx {
@@ -244,6 +266,10 @@ fn analyze(
     let Some(name_like) = find_node_at_offset(&speculative_file, offset) else {
         let analysis = if let Some(original) = ast::String::cast(original_token.clone()) {
             CompletionAnalysis::String { original, expanded: ast::String::cast(self_token.clone()) }
+        } else if let Some(original) =
+            ast::Comment::cast(original_token.clone()).filter(|it| it.is_doc())
+        {
+            CompletionAnalysis::DocComment { original }
         } else {
             // Fix up trailing whitespace problem
             // #[attr(foo = $0
@@ -262,6 +288,13 @@ fn analyze(
                     colon_prefix,
                     extern_crate: p.ancestors().find_map(ast::ExternCrate::cast),
                 }
+            } else if fake_ident_token
+                .parent()
+                .map_or(false, |it| it.kind() == SyntaxKind::TOKEN_TREE)
+                && fake_ident_token.parent_ancestors().any(|it| it.kind() == SyntaxKind::MACRO_CALL)
+            {
+                // m!(foo $0
+                CompletionAnalysis::UnexpandedMacroInput
             } else {
                 return None;
             }
@@ -207,6 +207,10 @@ pub fn completions(
                 completions::extern_abi::complete_extern_abi(acc, ctx, expanded);
                 completions::format_string::format_string(acc, ctx, original, expanded);
                 completions::env_vars::complete_cargo_env_vars(acc, ctx, expanded);
+                completions::attribute::complete_cfg_string(acc, ctx, original);
+            }
+            CompletionAnalysis::DocComment { original } => {
+                completions::doc_link::complete_doc_link(acc, ctx, original);
             }
             CompletionAnalysis::UnexpandedAttrTT {
                 colon_prefix,
@@ -221,7 +225,13 @@ pub fn completions(
                     extern_crate.as_ref(),
                 );
             }
-            CompletionAnalysis::UnexpandedAttrTT { .. } | CompletionAnalysis::String { .. } => (),
+            CompletionAnalysis::UnexpandedAttrTT { .. }
+            | CompletionAnalysis::String { .. }
+            | CompletionAnalysis::UnexpandedMacroInput => (),
+        }
+        if let Some(macro_input) = &ctx.macro_input {
+            let is_unexpanded = matches!(analysis, CompletionAnalysis::UnexpandedMacroInput);
+            completions::macro_input::complete_macro_input(acc, ctx, macro_input, is_unexpanded);
         }
     }
 
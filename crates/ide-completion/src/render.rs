@@ -272,6 +272,23 @@ pub(crate) fn render_resolution_with_import_pat(
     Some(render_resolution_pat(ctx, pattern_ctx, local_name, Some(import_edit), resolution))
 }
 
+/// Renders a resolution as just its name, for positions without a path to complete.
+pub(crate) fn render_resolution_simple(
+    ctx: RenderContext<'_>,
+    source_range: TextRange,
+    local_name: hir::Name,
+    resolution: ScopeDef,
+) -> Builder {
+    let mut item = CompletionItem::new(
+        res_to_kind(resolution),
+        source_range,
+        local_name.unescaped().to_smol_str(),
+    );
+    item.set_documentation(scope_def_docs(ctx.db(), resolution))
+        .set_deprecated(scope_def_is_deprecated(&ctx, resolution));
+    item
+}
+
 fn scope_def_to_name(
     resolution: ScopeDef,
     ctx: &RenderContext<'_>,
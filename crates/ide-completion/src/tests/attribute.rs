@@ -673,6 +673,93 @@ mod cfg {
             "#]],
         );
     }
+
+    #[test]
+    fn cfg_feature() {
+        check(
+            r#"
+//- /main.rs cfg:feature=std,feature=serde
+#[cfg(feature = $0)]
+"#,
+            expect![[r#"
+                ba serde
+                ba std
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs cfg:feature=std,feature=serde
+#[cfg(feature = "s$0")]
+"#,
+            expect![[r#"
+                ba serde
+                ba std
+            "#]],
+        );
+    }
+
+    #[test]
+    fn cfg_target_os_includes_configured_target() {
+        check(
+            r#"
+//- /main.rs cfg:target_os=wasi
+#[cfg(all(unix, target_os = "$0"))]
+"#,
+            expect![[r#"
+                ba cuda
+                ba dragonfly
+                ba emscripten
+                ba freebsd
+                ba fuchsia
+                ba haiku
+                ba hermit
+                ba illumos
+                ba l4re
+                ba linux
+                ba netbsd
+                ba none
+                ba openbsd
+                ba psp
+                ba redox
+                ba solaris
+                ba uefi
+                ba unknown
+                ba vxworks
+                ba wasi
+                ba windows
+            "#]],
+        );
+    }
+
+    #[test]
+    fn cfg_attr_predicate() {
+        check(
+            r#"
+//- /main.rs cfg:test,feature=std
+#[cfg_attr($0)]
+"#,
+            expect![[r#"
+                ba feature
+                ba test
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs cfg:test,feature=std
+#[cfg_attr(feature = "$0", derive(Debug))]
+"#,
+            expect![[r#"
+                ba std
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs cfg:test,feature=std
+#[cfg_attr(test, allow(dead_code), doc = "$0")]
+"#,
+            expect![[r#""#]],
+        );
+    }
 }
 
 mod derive {
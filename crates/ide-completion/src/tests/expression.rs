@@ -521,7 +521,12 @@ fn quux(x: i32) {
     m!(x$0
 }
 "#,
-        expect![[r#""#]],
+        expect![[r#"
+            fn quux
+            lc x
+            ma m
+            bt u32
+        "#]],
     );
 }
 
@@ -0,0 +1,1042 @@
+//! Recognizes manual impls of the builtin derivable traits that are equivalent to what
+//! `#[derive]` would generate, so they can be replaced by the attribute.
+//!
+//! For enums, only `Clone`, `Debug`, `Default` and `Eq` are recognized. The derived comparisons
+//! and hashes of enums go through the discriminant, which manual impls spell out in too many ways
+//! to match reliably.
+
+use hir::{HasSource, PathResolution, Semantics, StructKind};
+use syntax::{
+    ast::{
+        self, edit::IndentLevel, HasAttrs, HasGenericParams, HasName, HasTypeBounds, HasVisibility,
+    },
+    AstNode, SyntaxKind,
+};
+
+use crate::{
+    base_db::FileId, famous_defs::FamousDefs, source_change::SourceChangeBuilder, RootDatabase,
+};
+
+const DERIVES: &[&str] =
+    &["Clone", "Debug", "Default", "Eq", "Hash", "Ord", "PartialEq", "PartialOrd"];
+
+/// A manual trait impl that can be replaced by a `#[derive]` on its struct or enum.
+#[derive(Debug)]
+pub struct DerivableImpl {
+    /// The name of the derive macro.
+    pub derive: &'static str,
+    adt: ast::Adt,
+    adt_file: FileId,
+    /// The variant a derived `Default` of an enum returns, which gets a `#[default]` attribute.
+    default_variant: Option<ast::Variant>,
+}
+
+impl DerivableImpl {
+    /// Removes `impl_` from `impl_file` and adds the trait to the derives of the type.
+    pub fn replace_with_derive(
+        &self,
+        builder: &mut SourceChangeBuilder,
+        impl_file: FileId,
+        impl_: &ast::Impl,
+    ) {
+        builder.edit_file(impl_file);
+        let mut range = impl_.syntax().text_range();
+        if let Some(ws) = impl_.syntax().prev_sibling_or_token() {
+            if ws.kind() == SyntaxKind::WHITESPACE {
+                range = range.cover(ws.text_range());
+            }
+        }
+        builder.delete(range);
+
+        builder.edit_file(self.adt_file);
+        if let Some(variant) = &self.default_variant {
+            let indent = IndentLevel::from_node(variant.syntax());
+            builder.insert(variant.syntax().text_range().start(), format!("#[default]\n{indent}"));
+        }
+        let derive_attr = self
+            .adt
+            .attrs()
+            .find(|attr| attr.simple_name().as_deref() == Some("derive"))
+            .and_then(|attr| attr.token_tree());
+        match derive_attr.as_ref().and_then(|tt| Some((tt, tt.right_delimiter_token()?))) {
+            Some((tt, r_paren)) => {
+                let is_empty = tt.syntax().children_with_tokens().count() <= 2;
+                let text =
+                    if is_empty { self.derive.to_owned() } else { format!(", {}", self.derive) };
+                builder.insert(r_paren.text_range().start(), text);
+            }
+            None => {
+                let keyword = match &self.adt {
+                    ast::Adt::Struct(it) => it.struct_token(),
+                    ast::Adt::Enum(it) => it.enum_token(),
+                    ast::Adt::Union(it) => it.union_token(),
+                };
+                let start = match self.adt.visibility() {
+                    Some(vis) => vis.syntax().text_range().start(),
+                    None => match keyword {
+                        Some(it) => it.text_range().start(),
+                        None => return,
+                    },
+                };
+                let indent = IndentLevel::from_node(self.adt.syntax());
+                builder.insert(start, format!("#[derive({})]\n{indent}", self.derive));
+            }
+        }
+    }
+}
+
+/// Checks whether `impl_` is a manual impl of a builtin derivable trait for a local struct or enum
+/// that is equivalent to the impl `#[derive]` would generate.
+pub fn derivable_impl(
+    sema: &Semantics<'_, RootDatabase>,
+    impl_: &ast::Impl,
+) -> Option<DerivableImpl> {
+    let ast::Type::PathType(trait_ty) = impl_.trait_()? else { return None };
+    let trait_name = trait_ty.path()?.segment()?.name_ref()?;
+    let derive = *DERIVES.iter().find(|&&it| trait_name.text() == it)?;
+    if impl_.excl_token().is_some()
+        || impl_.unsafe_token().is_some()
+        || impl_.default_token().is_some()
+        || impl_.attrs().next().is_some()
+    {
+        return None;
+    }
+
+    let db = sema.db;
+    let hir_impl = sema.to_def(impl_)?;
+    let krate = hir_impl.module(db).krate();
+    let famous_defs = FamousDefs(sema, krate);
+    let expected = match derive {
+        "Clone" => famous_defs.core_clone_Clone(),
+        "Debug" => famous_defs.core_fmt_Debug(),
+        "Default" => famous_defs.core_default_Default(),
+        "Eq" => famous_defs.core_cmp_Eq(),
+        "Hash" => famous_defs.core_hash_Hash(),
+        "Ord" => famous_defs.core_cmp_Ord(),
+        "PartialEq" => famous_defs.core_cmp_PartialEq(),
+        "PartialOrd" => famous_defs.core_cmp_PartialOrd(),
+        _ => None,
+    };
+    if hir_impl.trait_(db)? != expected? {
+        return None;
+    }
+    let adt = hir_impl.self_ty(db).as_adt()?;
+    if adt.module(db).krate() != krate {
+        return None;
+    }
+    let (adt_ast, adt_file) = match adt {
+        hir::Adt::Struct(it) => {
+            let source = it.source(db)?;
+            (ast::Adt::Struct(source.value), source.file_id.file_id()?)
+        }
+        hir::Adt::Enum(it) => {
+            let source = it.source(db)?;
+            (ast::Adt::Enum(source.value), source.file_id.file_id()?)
+        }
+        hir::Adt::Union(_) => return None,
+    };
+    if !generics_match(impl_, &adt_ast, derive) {
+        return None;
+    }
+
+    let name = adt_ast.name()?.to_string();
+    let items: Vec<_> = impl_.assoc_item_list()?.assoc_items().collect();
+    let mut default_variant = None;
+    let matches = match (adt, &adt_ast) {
+        (hir::Adt::Struct(strukt), ast::Adt::Struct(strukt_ast)) => {
+            let fields = fields(sema, strukt.fields(db), strukt.kind(db), strukt_ast.field_list())?;
+            let cx = Ctx { sema, famous_defs, name, fields };
+            match (derive, items.as_slice()) {
+                ("Eq", []) => cx.fields_impl_eq(cx.fields.iter()),
+                (_, [ast::AssocItem::Fn(func)]) => match derive {
+                    "Clone" => cx.is_derived_clone(func),
+                    "Debug" => cx.is_derived_debug(func),
+                    "Default" => cx.is_derived_default(func),
+                    "Hash" => cx.is_derived_hash(func),
+                    "PartialEq" => cx.is_derived_eq(func),
+                    "PartialOrd" => cx.is_derived_cmp(func, "partial_cmp"),
+                    "Ord" => cx.is_derived_cmp(func, "cmp"),
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        (hir::Adt::Enum(enum_), ast::Adt::Enum(enum_ast)) => {
+            let variants = variants(sema, enum_, enum_ast)?;
+            let cx = Ctx { sema, famous_defs, name, fields: Fields::Unit };
+            let cx = EnumCtx { cx, variants };
+            match (derive, items.as_slice()) {
+                ("Eq", []) => {
+                    cx.cx.fields_impl_eq(cx.variants.iter().flat_map(|it| it.fields.iter()))
+                }
+                (_, [ast::AssocItem::Fn(func)]) => match derive {
+                    "Clone" => cx.is_derived_clone(func),
+                    "Debug" => cx.is_derived_debug(func),
+                    "Default" => {
+                        default_variant = cx.derived_default(func);
+                        default_variant.is_some()
+                    }
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+    matches.then_some(DerivableImpl { derive, adt: adt_ast, adt_file, default_variant })
+}
+
+/// The derive adds a bound on the trait to each type parameter, and nothing else.
+fn generics_match(impl_: &ast::Impl, adt: &ast::Adt, derive: &str) -> bool {
+    if impl_.where_clause().is_some() || adt.where_clause().is_some() {
+        return false;
+    }
+    let params = |it: Option<ast::GenericParamList>| {
+        it.map_or_else(Vec::new, |it| it.generic_params().collect::<Vec<_>>())
+    };
+    let struct_params = params(adt.generic_param_list());
+    let impl_params = params(impl_.generic_param_list());
+    if struct_params.len() != impl_params.len() {
+        return false;
+    }
+    let mut names = Vec::new();
+    for (struct_param, impl_param) in struct_params.iter().zip(&impl_params) {
+        let name = match (struct_param, impl_param) {
+            (ast::GenericParam::LifetimeParam(s), ast::GenericParam::LifetimeParam(i))
+                if s.type_bound_list().is_none() && i.type_bound_list().is_none() =>
+            {
+                i.lifetime().map(|it| it.to_string())
+            }
+            (ast::GenericParam::TypeParam(s), ast::GenericParam::TypeParam(i))
+                if s.type_bound_list().is_none() && s.default_type().is_none() =>
+            {
+                let mut bounds = i.type_bound_list().into_iter().flat_map(|it| it.bounds());
+                let is_trait_bound = |bound: Option<ast::TypeBound>| {
+                    matches!(bound.and_then(|it| it.ty()), Some(ast::Type::PathType(ty))
+                        if ty.path().and_then(|it| it.segment()?.name_ref())
+                            .map_or(false, |it| it.text() == derive))
+                };
+                if !is_trait_bound(bounds.next()) || bounds.next().is_some() {
+                    return false;
+                }
+                i.name().map(|it| it.to_string())
+            }
+            (ast::GenericParam::ConstParam(_), ast::GenericParam::ConstParam(i)) => {
+                i.name().map(|it| it.to_string())
+            }
+            _ => None,
+        };
+        match name {
+            Some(name) => names.push(name),
+            None => return false,
+        }
+    }
+
+    let Some(ast::Type::PathType(self_ty)) = impl_.self_ty() else { return false };
+    let Some(segment) = self_ty.path().and_then(|it| it.segment()) else { return false };
+    let args: Vec<String> = segment
+        .generic_arg_list()
+        .map_or_else(Vec::new, |it| it.generic_args().map(|it| it.to_string()).collect());
+    args == names
+}
+
+struct Field {
+    /// The field name, or its index for tuple fields.
+    name: String,
+    ty: hir::Type,
+    source_ty: Option<ast::Type>,
+}
+
+enum Fields {
+    Unit,
+    Tuple(Vec<Field>),
+    Record(Vec<Field>),
+}
+
+impl Fields {
+    fn iter(&self) -> std::slice::Iter<'_, Field> {
+        match self {
+            Fields::Unit => [].iter(),
+            Fields::Tuple(it) | Fields::Record(it) => it.iter(),
+        }
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.iter().map(|it| it.name.as_str()).collect()
+    }
+}
+
+struct Variant {
+    name: String,
+    fields: Fields,
+    source: ast::Variant,
+}
+
+fn variants(
+    sema: &Semantics<'_, RootDatabase>,
+    enum_: hir::Enum,
+    enum_ast: &ast::Enum,
+) -> Option<Vec<Variant>> {
+    let db = sema.db;
+    let hir_variants = enum_.variants(db);
+    let variants: Vec<_> = enum_ast.variant_list()?.variants().collect();
+    // Variants with attributes might be `cfg`-ed out.
+    if hir_variants.is_empty()
+        || variants.len() != hir_variants.len()
+        || variants.iter().any(|it| it.attrs().next().is_some())
+    {
+        return None;
+    }
+    variants
+        .into_iter()
+        .zip(hir_variants)
+        .map(|(source, variant)| {
+            let fields = fields(sema, variant.fields(db), variant.kind(db), source.field_list())?;
+            Some(Variant { name: source.name()?.to_string(), fields, source })
+        })
+        .collect()
+}
+
+fn fields(
+    sema: &Semantics<'_, RootDatabase>,
+    hir_fields: Vec<hir::Field>,
+    kind: StructKind,
+    field_list: Option<ast::FieldList>,
+) -> Option<Fields> {
+    let db = sema.db;
+    let (syntax_fields, source_tys): (Vec<_>, Vec<_>) = match field_list {
+        None => (Vec::new(), Vec::new()),
+        Some(ast::FieldList::RecordFieldList(it)) => it
+            .fields()
+            .map(|it| (it.name().map(|it| it.to_string()), (it.ty(), it.attrs().next().is_some())))
+            .unzip(),
+        Some(ast::FieldList::TupleFieldList(it)) => it
+            .fields()
+            .enumerate()
+            .map(|(idx, it)| (Some(idx.to_string()), (it.ty(), it.attrs().next().is_some())))
+            .unzip(),
+    };
+    // Fields with attributes might be `cfg`-ed out.
+    if source_tys.iter().any(|(_, has_attrs)| *has_attrs) || syntax_fields.len() != hir_fields.len()
+    {
+        return None;
+    }
+    let fields = syntax_fields
+        .into_iter()
+        .zip(source_tys)
+        .zip(hir_fields)
+        .map(|((name, (source_ty, _)), field)| {
+            Some(Field { name: name?, ty: field.ty(db), source_ty })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(match kind {
+        StructKind::Unit => Fields::Unit,
+        StructKind::Tuple => Fields::Tuple(fields),
+        StructKind::Record => Fields::Record(fields),
+    })
+}
+
+struct Ctx<'a, 'db> {
+    sema: &'a Semantics<'db, RootDatabase>,
+    famous_defs: FamousDefs<'a, 'db>,
+    /// The name of the struct or enum.
+    name: String,
+    fields: Fields,
+}
+
+impl Ctx<'_, '_> {
+    /// `Self { a: self.a.clone() }`
+    fn is_derived_clone(&self, func: &ast::Fn) -> bool {
+        let Some(expr) = fn_named(func, "clone").and_then(tail_only) else { return false };
+        self.is_constructor(&expr, |field, value| {
+            let is_clone_call = match value {
+                ast::Expr::MethodCallExpr(call) => {
+                    method_call(call, "clone", 0)
+                        && self_field(call.receiver(), "self").as_deref() == Some(&field.name)
+                }
+                ast::Expr::CallExpr(call) => {
+                    call.expr().map_or(false, |it| it.syntax().text() == "Clone::clone")
+                        && matches!(args(call).as_slice(), [arg]
+                            if ref_field(Some(arg.clone()), "self").as_deref() == Some(&field.name))
+                }
+                _ => false,
+            };
+            is_clone_call
+                || (self_field(Some(value.clone()), "self").as_deref() == Some(&field.name)
+                    && field.ty.is_copy(self.sema.db))
+        })
+    }
+
+    /// `Self { a: Default::default() }`
+    fn is_derived_default(&self, func: &ast::Fn) -> bool {
+        let Some(func) = fn_named(func, "default") else { return false };
+        if func.param_list().map_or(true, |it| it.params().next().is_some()) {
+            return false;
+        }
+        let Some(expr) = tail_only(func) else { return false };
+        self.is_constructor(&expr, |field, value| self.is_default_value(field, value))
+    }
+
+    fn is_default_value(&self, field: &Field, value: &ast::Expr) -> bool {
+        match value {
+            ast::Expr::CallExpr(call) if args(call).is_empty() => {
+                let Some(ast::Expr::PathExpr(callee)) = call.expr() else { return false };
+                let Some(path) = callee.path() else { return false };
+                let Some(qualifier) = path.qualifier() else { return false };
+                let method = path.segment().and_then(|it| it.name_ref());
+                let resolves_to_field_ty = || match self.sema.resolve_path(&qualifier) {
+                    Some(PathResolution::Def(hir::ModuleDef::Adt(adt))) => {
+                        field.ty.as_adt() == Some(adt)
+                    }
+                    _ => false,
+                };
+                match method.as_ref().map(|it| it.text()) {
+                    Some(name) if name == "default" => {
+                        qualifier.syntax().text() == "Default"
+                            || field
+                                .source_ty
+                                .as_ref()
+                                .map_or(false, |ty| ty.syntax().text() == qualifier.syntax().text())
+                            || resolves_to_field_ty()
+                    }
+                    Some(name) if name == "new" => {
+                        matches!(qualifier.syntax().text().to_string().as_str(), "String" | "Vec")
+                            && resolves_to_field_ty()
+                    }
+                    _ => false,
+                }
+            }
+            ast::Expr::Literal(lit) => match lit.kind() {
+                ast::LiteralKind::IntNumber(it) => {
+                    it.value() == Some(0) && field.ty.is_int_or_uint()
+                }
+                ast::LiteralKind::FloatNumber(it) => it.value() == Some(0.0) && field.ty.is_float(),
+                ast::LiteralKind::Bool(it) => !it && field.ty.is_bool(),
+                _ => false,
+            },
+            ast::Expr::PathExpr(path) => {
+                path.syntax().text() == "None"
+                    && field.ty.as_adt().is_some()
+                    && field.ty.as_adt() == self.famous_defs.core_option_Option().map(Into::into)
+            }
+            _ => false,
+        }
+    }
+
+    /// `f.debug_struct("S").field("a", &self.a).finish()`
+    fn is_derived_debug(&self, func: &ast::Fn) -> bool {
+        let Some(formatter) = param_name(func) else { return false };
+        let Some(expr) = fn_named(func, "fmt").and_then(tail_only) else { return false };
+        is_debug_expr(expr, &formatter, &self.name, &self.fields, |it| {
+            ref_field(Some(it.clone()), "self")
+        })
+    }
+
+    /// `self.a.hash(state);`
+    fn is_derived_hash(&self, func: &ast::Fn) -> bool {
+        let Some(state) = param_name(func) else { return false };
+        let Some(stmt_list) = fn_named(func, "hash").and_then(|it| it.body()?.stmt_list()) else {
+            return false;
+        };
+        if stmt_list.tail_expr().is_some() {
+            return false;
+        }
+        let mut hashed = Vec::new();
+        for stmt in stmt_list.statements() {
+            let ast::Stmt::ExprStmt(stmt) = stmt else { return false };
+            let field = match stmt.expr() {
+                Some(ast::Expr::MethodCallExpr(call)) if method_call(&call, "hash", 1) => {
+                    if !is_path(args(&call).into_iter().next(), &state) {
+                        return false;
+                    }
+                    self_field(call.receiver(), "self")
+                }
+                Some(ast::Expr::CallExpr(call))
+                    if call.expr().map_or(false, |it| it.syntax().text() == "Hash::hash") =>
+                {
+                    match args(&call).as_slice() {
+                        [value, arg] if is_path(Some(arg.clone()), &state) => {
+                            ref_field(Some(value.clone()), "self")
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match field {
+                Some(field) => hashed.push(field),
+                None => return false,
+            }
+        }
+        hashed == self.fields.names()
+    }
+
+    /// `self.a == other.a && self.b == other.b`
+    fn is_derived_eq(&self, func: &ast::Fn) -> bool {
+        let Some(other) = param_name(func) else { return false };
+        let Some(expr) = fn_named(func, "eq").and_then(tail_only) else { return false };
+        if let Fields::Unit = self.fields {
+            return matches!(&expr, ast::Expr::Literal(lit)
+                if matches!(lit.kind(), ast::LiteralKind::Bool(true)));
+        }
+        let mut compared = Vec::new();
+        for conjunct in conjuncts(expr) {
+            let field = match conjunct {
+                ast::Expr::BinExpr(bin)
+                    if bin.op_kind()
+                        == Some(ast::BinaryOp::CmpOp(ast::CmpOp::Eq { negated: false })) =>
+                {
+                    let lhs = self_field(bin.lhs(), "self");
+                    (lhs.is_some() && lhs == self_field(bin.rhs(), &other)).then_some(lhs).flatten()
+                }
+                ast::Expr::MethodCallExpr(call) if method_call(&call, "eq", 1) => {
+                    let lhs = self_field(call.receiver(), "self");
+                    let rhs = ref_field(args(&call).into_iter().next(), &other);
+                    (lhs.is_some() && lhs == rhs).then_some(lhs).flatten()
+                }
+                _ => None,
+            };
+            match field {
+                Some(field) => compared.push(field),
+                None => return false,
+            }
+        }
+        let mut expected = self.fields.names();
+        expected.sort_unstable();
+        compared.sort_unstable();
+        compared == expected
+    }
+
+    /// `self.a.cmp(&other.a).then(self.b.cmp(&other.b))`, the tuple comparison
+    /// `(self.a, self.b).cmp(&(other.a, other.b))`, or `match` statements returning early when
+    /// the fields compare unequal.
+    fn is_derived_cmp(&self, func: &ast::Fn, method: &str) -> bool {
+        let Some(other) = param_name(func) else { return false };
+        let Some(stmt_list) = fn_named(func, method).and_then(|it| it.body()?.stmt_list()) else {
+            return false;
+        };
+        let Some(tail) = stmt_list.tail_expr() else { return false };
+        let is_partial = method == "partial_cmp";
+
+        if let Fields::Unit = self.fields {
+            let tail = if is_partial {
+                match some_arg(&tail) {
+                    Some(it) => it,
+                    None => return false,
+                }
+            } else {
+                tail
+            };
+            return stmt_list.statements().next().is_none()
+                && matches!(&tail, ast::Expr::PathExpr(path) if is_equal_path(path.path()));
+        }
+
+        let mut compared = Vec::new();
+        for stmt in stmt_list.statements() {
+            let Some(ast::Expr::MatchExpr(match_expr)) = (match stmt {
+                ast::Stmt::ExprStmt(it) => it.expr(),
+                _ => None,
+            }) else {
+                return false;
+            };
+            let Some(field) = cmp_call(match_expr.expr(), method, &other) else { return false };
+            if !is_early_return(&match_expr, is_partial) {
+                return false;
+            }
+            compared.push(field);
+        }
+
+        match &tail {
+            ast::Expr::MethodCallExpr(call)
+                if method_call(call, method, 1)
+                    && matches!(call.receiver(), Some(ast::Expr::TupleExpr(_))) =>
+            {
+                if !compared.is_empty() {
+                    return false;
+                }
+                let Some(ast::Expr::TupleExpr(lhs)) = call.receiver() else { return false };
+                let rhs = match args(call).into_iter().next() {
+                    Some(ast::Expr::RefExpr(it)) => it.expr().map(strip_parens),
+                    _ => None,
+                };
+                let Some(ast::Expr::TupleExpr(rhs)) = rhs else { return false };
+                let field_of = |expr: ast::Expr, receiver: &str| match expr {
+                    ast::Expr::RefExpr(it) => self_field(it.expr(), receiver),
+                    it => self_field(Some(it), receiver),
+                };
+                let lhs: Vec<_> = lhs.fields().map(|it| field_of(it, "self")).collect();
+                let rhs: Vec<_> = rhs.fields().map(|it| field_of(it, &other)).collect();
+                if lhs != rhs || lhs.iter().any(Option::is_none) {
+                    return false;
+                }
+                compared.extend(lhs.into_iter().flatten());
+            }
+            _ => {
+                let mut chain = Vec::new();
+                let mut expr = tail.clone();
+                loop {
+                    match &expr {
+                        ast::Expr::MethodCallExpr(call)
+                            if !is_partial
+                                && (method_call(call, "then", 1)
+                                    || method_call(call, "then_with", 1)) =>
+                        {
+                            let arg = args(call).into_iter().next();
+                            let arg = match arg {
+                                Some(ast::Expr::ClosureExpr(closure))
+                                    if method_call(call, "then_with", 1)
+                                        && closure
+                                            .param_list()
+                                            .map_or(false, |it| it.params().next().is_none()) =>
+                                {
+                                    closure.body()
+                                }
+                                arg if method_call(call, "then", 1) => arg,
+                                _ => None,
+                            };
+                            let Some(field) = cmp_call(arg, method, &other) else { return false };
+                            chain.push(field);
+                            let Some(receiver) = call.receiver() else { return false };
+                            expr = receiver;
+                        }
+                        _ => {
+                            let Some(field) = cmp_call(Some(expr), method, &other) else {
+                                return false;
+                            };
+                            chain.push(field);
+                            break;
+                        }
+                    }
+                }
+                chain.reverse();
+                compared.extend(chain);
+            }
+        }
+        compared == self.fields.names()
+    }
+
+    /// Whether `expr` constructs the struct with each field's value accepted by `value_matches`.
+    fn is_constructor(
+        &self,
+        expr: &ast::Expr,
+        value_matches: impl Fn(&Field, &ast::Expr) -> bool,
+    ) -> bool {
+        let is_self = |path: Option<ast::Path>| {
+            path.map_or(false, |it| {
+                let text = it.syntax().text();
+                text == "Self" || text == self.name.as_str()
+            })
+        };
+        is_constructor(expr, &self.fields, is_self, value_matches)
+    }
+
+    /// Whether all the field types implement `Eq`, which the derive requires.
+    fn fields_impl_eq<'f>(&self, mut fields: impl Iterator<Item = &'f Field>) -> bool {
+        let db = self.sema.db;
+        let Some(eq) = self.famous_defs.core_cmp_Eq() else { return false };
+        fields
+            .all(|field| field.ty.as_type_param(db).is_some() || field.ty.impls_trait(db, eq, &[]))
+    }
+}
+
+struct EnumCtx<'a, 'db> {
+    cx: Ctx<'a, 'db>,
+    variants: Vec<Variant>,
+}
+
+/// An arm of a `match self` over the variants.
+struct Arm<'a> {
+    variant: &'a Variant,
+    /// The names bound to the fields of the variant, by field name.
+    bindings: Vec<(String, String)>,
+    expr: ast::Expr,
+}
+
+impl Arm<'_> {
+    fn binding(&self, field: &Field) -> Option<&str> {
+        self.bindings.iter().find(|(it, _)| *it == field.name).map(|(_, it)| it.as_str())
+    }
+
+    /// The field bound to `binding` or `&binding`.
+    fn field_of(&self, expr: &ast::Expr) -> Option<String> {
+        let expr = match expr {
+            ast::Expr::RefExpr(it) if it.mut_token().is_none() => it.expr()?,
+            it => it.clone(),
+        };
+        let ast::Expr::PathExpr(path) = expr else { return None };
+        let text = path.syntax().text();
+        self.bindings.iter().find(|(_, it)| text == it.as_str()).map(|(it, _)| it.clone())
+    }
+}
+
+impl EnumCtx<'_, '_> {
+    /// `Self::A(a) => Self::A(a.clone())`
+    fn is_derived_clone(&self, func: &ast::Fn) -> bool {
+        let Some(arms) = fn_named(func, "clone").and_then(|it| self.match_arms(it)) else {
+            return false;
+        };
+        arms.iter().all(|arm| {
+            let is_variant = |it| self.is_variant_path(it, arm.variant);
+            is_constructor(&arm.expr, &arm.variant.fields, is_variant, |field, value| {
+                let Some(binding) = arm.binding(field) else { return false };
+                match value {
+                    ast::Expr::MethodCallExpr(call) => {
+                        method_call(call, "clone", 0) && is_path(call.receiver(), binding)
+                    }
+                    ast::Expr::CallExpr(call) => {
+                        call.expr().map_or(false, |it| it.syntax().text() == "Clone::clone")
+                            && matches!(args(call).as_slice(), [arg]
+                                if is_path(Some(arg.clone()), binding))
+                    }
+                    ast::Expr::PrefixExpr(deref) => {
+                        deref.op_kind() == Some(ast::UnaryOp::Deref)
+                            && is_path(deref.expr(), binding)
+                            && field.ty.is_copy(self.cx.sema.db)
+                    }
+                    _ => false,
+                }
+            })
+        })
+    }
+
+    /// `Self::A(a) => f.debug_tuple("A").field(a).finish()`
+    fn is_derived_debug(&self, func: &ast::Fn) -> bool {
+        let Some(formatter) = param_name(func) else { return false };
+        let Some(arms) = fn_named(func, "fmt").and_then(|it| self.match_arms(it)) else {
+            return false;
+        };
+        arms.iter().all(|arm| {
+            let fields = &arm.variant.fields;
+            is_debug_expr(arm.expr.clone(), &formatter, &arm.variant.name, fields, |it| {
+                arm.field_of(it)
+            })
+        })
+    }
+
+    /// `Self::A` of a unit variant, which becomes the `#[default]` one.
+    fn derived_default(&self, func: &ast::Fn) -> Option<ast::Variant> {
+        let func = fn_named(func, "default")?;
+        if func.param_list()?.params().next().is_some() {
+            return None;
+        }
+        let ast::Expr::PathExpr(path) = tail_only(func)? else { return None };
+        let variant = self.variants.iter().find(|it| self.is_variant_path(path.path(), it))?;
+        matches!(variant.fields, Fields::Unit).then(|| variant.source.clone())
+    }
+
+    /// `Self::A` or `Enum::A`.
+    fn is_variant_path(&self, path: Option<ast::Path>, variant: &Variant) -> bool {
+        let Some(path) = path else { return false };
+        let is_self = path.qualifier().map_or(false, |it| {
+            let text = it.syntax().text();
+            text == "Self" || text == self.cx.name.as_str()
+        });
+        is_self
+            && path
+                .segment()
+                .and_then(|it| it.name_ref())
+                .map_or(false, |it| it.text() == variant.name.as_str())
+    }
+
+    /// The arms of the `match self` that is the only expression of `func`, which has to match
+    /// each variant exactly once.
+    fn match_arms(&self, func: &ast::Fn) -> Option<Vec<Arm<'_>>> {
+        let ast::Expr::MatchExpr(match_expr) = tail_only(func)? else { return None };
+        if !is_path(match_expr.expr(), "self") {
+            return None;
+        }
+        let mut arms: Vec<Arm<'_>> = Vec::new();
+        for arm in match_expr.match_arm_list()?.arms() {
+            if arm.guard().is_some() {
+                return None;
+            }
+            let (variant, bindings) = self.variant_pat(arm.pat()?)?;
+            if arms.iter().any(|it| std::ptr::eq(it.variant, variant)) {
+                return None;
+            }
+            arms.push(Arm { variant, bindings, expr: strip_parens(arm.expr()?) });
+        }
+        (arms.len() == self.variants.len()).then_some(arms)
+    }
+
+    /// The variant `pat` matches and the names it binds the fields to, if it binds all of them.
+    fn variant_pat(&self, pat: ast::Pat) -> Option<(&Variant, Vec<(String, String)>)> {
+        let (path, bindings): (_, Vec<_>) = match pat {
+            ast::Pat::PathPat(it) => (it.path(), Vec::new()),
+            ast::Pat::TupleStructPat(it) => {
+                let bindings = it
+                    .fields()
+                    .enumerate()
+                    .map(|(idx, it)| Some((idx.to_string(), binding_name(it)?)))
+                    .collect::<Option<_>>()?;
+                (it.path(), bindings)
+            }
+            ast::Pat::RecordPat(it) => {
+                let list = it.record_pat_field_list()?;
+                if list.rest_pat().is_some() {
+                    return None;
+                }
+                let bindings = list
+                    .fields()
+                    .map(|it| Some((it.field_name()?.to_string(), binding_name(it.pat()?)?)))
+                    .collect::<Option<_>>()?;
+                (it.path(), bindings)
+            }
+            _ => return None,
+        };
+        let variant = self.variants.iter().find(|it| self.is_variant_path(path.clone(), it))?;
+        let mut bound: Vec<_> = bindings.iter().map(|(it, _)| it.as_str()).collect();
+        let mut expected = variant.fields.names();
+        bound.sort_unstable();
+        expected.sort_unstable();
+        (bound == expected).then_some((variant, bindings))
+    }
+}
+
+/// Whether `expr` constructs a struct or variant with the fields, with each field's value accepted
+/// by `value_matches`.
+fn is_constructor(
+    expr: &ast::Expr,
+    fields: &Fields,
+    is_self: impl Fn(Option<ast::Path>) -> bool,
+    value_matches: impl Fn(&Field, &ast::Expr) -> bool,
+) -> bool {
+    match (fields, expr) {
+        (Fields::Unit, ast::Expr::PathExpr(it)) => is_self(it.path()),
+        (Fields::Tuple(fields), ast::Expr::CallExpr(call)) => {
+            let args = args(call);
+            matches!(call.expr(), Some(ast::Expr::PathExpr(it)) if is_self(it.path()))
+                && args.len() == fields.len()
+                && fields.iter().zip(&args).all(|(field, arg)| value_matches(field, arg))
+        }
+        (Fields::Record(fields), ast::Expr::RecordExpr(record)) => {
+            let Some(list) = record.record_expr_field_list() else { return false };
+            if !is_self(record.path()) || list.spread().is_some() {
+                return false;
+            }
+            let record_fields: Vec<_> = list.fields().collect();
+            record_fields.len() == fields.len()
+                && fields.iter().all(|field| {
+                    record_fields.iter().any(|it| {
+                        it.name_ref().map_or(false, |it| it.text() == field.name.as_str())
+                            && it.expr().map_or(false, |value| value_matches(field, &value))
+                    })
+                })
+        }
+        _ => false,
+    }
+}
+
+/// `f.debug_struct("S").field("a", value).finish()`, with `field_of` telling the field a value
+/// refers to.
+fn is_debug_expr(
+    expr: ast::Expr,
+    formatter: &str,
+    name: &str,
+    fields: &Fields,
+    field_of: impl Fn(&ast::Expr) -> Option<String>,
+) -> bool {
+    let ast::Expr::MethodCallExpr(call) = expr else { return false };
+    if let Fields::Unit = fields {
+        return method_call(&call, "write_str", 1)
+            && is_path(call.receiver(), formatter)
+            && args(&call).first().and_then(string_value).as_deref() == Some(name);
+    }
+    if !method_call(&call, "finish", 0) {
+        return false;
+    }
+    let (start, is_record) = match fields {
+        Fields::Record(_) => ("debug_struct", true),
+        _ => ("debug_tuple", false),
+    };
+    let mut found = Vec::new();
+    let mut receiver = call.receiver();
+    while let Some(ast::Expr::MethodCallExpr(call)) = receiver {
+        let args = args(&call);
+        if method_call(&call, start, 1) {
+            if !is_path(call.receiver(), formatter)
+                || args.first().and_then(string_value).as_deref() != Some(name)
+            {
+                return false;
+            }
+            found.reverse();
+            return found == fields.names();
+        }
+        let field = match (is_record, args.as_slice()) {
+            (true, [name, value]) if method_call(&call, "field", 2) => {
+                let name = string_value(name);
+                let value = field_of(value);
+                match (name, value) {
+                    (Some(name), Some(value)) if name == value => value,
+                    _ => return false,
+                }
+            }
+            (false, [value]) if method_call(&call, "field", 1) => match field_of(value) {
+                Some(it) => it,
+                None => return false,
+            },
+            _ => return false,
+        };
+        let Some(field) = fields.names().into_iter().find(|it| *it == field) else {
+            return false;
+        };
+        found.push(field);
+        receiver = call.receiver();
+    }
+    false
+}
+
+/// The name of a plain `x` binding.
+fn binding_name(pat: ast::Pat) -> Option<String> {
+    let ast::Pat::IdentPat(it) = pat else { return None };
+    if it.ref_token().is_some() || it.mut_token().is_some() || it.pat().is_some() {
+        return None;
+    }
+    Some(it.name()?.to_string())
+}
+
+fn fn_named<'a>(func: &'a ast::Fn, name: &str) -> Option<&'a ast::Fn> {
+    (func.name()?.text() == name).then_some(func)
+}
+
+/// The only expression in the body of `func`.
+fn tail_only(func: &ast::Fn) -> Option<ast::Expr> {
+    let stmt_list = func.body()?.stmt_list()?;
+    if stmt_list.statements().next().is_some() {
+        return None;
+    }
+    stmt_list.tail_expr().map(strip_parens)
+}
+
+/// The name of the first parameter after `self`.
+fn param_name(func: &ast::Fn) -> Option<String> {
+    match func.param_list()?.params().next()?.pat()? {
+        ast::Pat::IdentPat(it) => Some(it.name()?.to_string()),
+        _ => None,
+    }
+}
+
+fn strip_parens(expr: ast::Expr) -> ast::Expr {
+    match expr {
+        ast::Expr::ParenExpr(it) => it.expr().map_or(ast::Expr::ParenExpr(it), strip_parens),
+        it => it,
+    }
+}
+
+fn args(call: &impl ast::HasArgList) -> Vec<ast::Expr> {
+    call.arg_list().map_or_else(Vec::new, |it| it.args().collect())
+}
+
+fn method_call(call: &ast::MethodCallExpr, name: &str, arg_count: usize) -> bool {
+    call.name_ref().map_or(false, |it| it.text() == name)
+        && call.generic_arg_list().is_none()
+        && args(call).len() == arg_count
+}
+
+fn is_path(expr: Option<ast::Expr>, name: &str) -> bool {
+    matches!(expr, Some(ast::Expr::PathExpr(it)) if it.syntax().text() == name)
+}
+
+/// The field name of `receiver.field`.
+fn self_field(expr: Option<ast::Expr>, receiver: &str) -> Option<String> {
+    let ast::Expr::FieldExpr(field) = strip_parens(expr?) else { return None };
+    is_path(field.expr(), receiver).then(|| field.name_ref().map(|it| it.text().to_string()))?
+}
+
+/// The field name of `&receiver.field`.
+fn ref_field(expr: Option<ast::Expr>, receiver: &str) -> Option<String> {
+    match expr? {
+        ast::Expr::RefExpr(it) if it.mut_token().is_none() => self_field(it.expr(), receiver),
+        _ => None,
+    }
+}
+
+fn string_value(expr: &ast::Expr) -> Option<String> {
+    let ast::Expr::Literal(lit) = expr else { return None };
+    let ast::LiteralKind::String(string) = lit.kind() else { return None };
+    Some(string.value()?.into_owned())
+}
+
+fn conjuncts(expr: ast::Expr) -> Vec<ast::Expr> {
+    match strip_parens(expr) {
+        ast::Expr::BinExpr(bin)
+            if bin.op_kind() == Some(ast::BinaryOp::LogicOp(ast::LogicOp::And)) =>
+        {
+            let mut res = bin.lhs().map_or_else(Vec::new, conjuncts);
+            res.extend(bin.rhs().map_or_else(Vec::new, conjuncts));
+            res
+        }
+        it => vec![it],
+    }
+}
+
+/// The field compared by `self.field.method(&other.field)` or `Trait::method(&self.field, &other.field)`.
+fn cmp_call(expr: Option<ast::Expr>, method: &str, other: &str) -> Option<String> {
+    let (lhs, rhs) = match strip_parens(expr?) {
+        ast::Expr::MethodCallExpr(call) if method_call(&call, method, 1) => {
+            (self_field(call.receiver(), "self")?, ref_field(args(&call).pop(), other)?)
+        }
+        ast::Expr::CallExpr(call) => {
+            let trait_name = if method == "cmp" { "Ord" } else { "PartialOrd" };
+            if call.expr()?.syntax().text() != format!("{trait_name}::{method}").as_str() {
+                return None;
+            }
+            let [lhs, rhs] = <[_; 2]>::try_from(args(&call)).ok()?;
+            (ref_field(Some(lhs), "self")?, ref_field(Some(rhs), other)?)
+        }
+        _ => return None,
+    };
+    (lhs == rhs).then_some(lhs)
+}
+
+/// `match .. { Ordering::Equal => {} ord => return ord }`, with `Some(Ordering::Equal)` for
+/// `partial_cmp`.
+fn is_early_return(match_expr: &ast::MatchExpr, is_partial: bool) -> bool {
+    let Some(arms) = match_expr.match_arm_list() else { return false };
+    let arms: Vec<_> = arms.arms().collect();
+    let [equal, other] = arms.as_slice() else { return false };
+    if equal.guard().is_some() || other.guard().is_some() {
+        return false;
+    }
+    let equal_pat = match (equal.pat(), is_partial) {
+        (Some(ast::Pat::TupleStructPat(pat)), true)
+            if pat.path().map_or(false, |it| it.syntax().text() == "Some") =>
+        {
+            let fields: Vec<_> = pat.fields().collect();
+            match fields.as_slice() {
+                [ast::Pat::PathPat(it)] => is_equal_path(it.path()),
+                _ => false,
+            }
+        }
+        (Some(ast::Pat::PathPat(it)), false) => is_equal_path(it.path()),
+        _ => false,
+    };
+    let equal_body = match equal.expr() {
+        Some(ast::Expr::BlockExpr(it)) => it
+            .stmt_list()
+            .map_or(false, |it| it.statements().next().is_none() && it.tail_expr().is_none()),
+        Some(ast::Expr::TupleExpr(it)) => it.fields().next().is_none(),
+        _ => false,
+    };
+    let returns_binding = match (other.pat(), other.expr()) {
+        (Some(ast::Pat::IdentPat(pat)), Some(ast::Expr::ReturnExpr(ret))) => {
+            pat.name().map_or(false, |name| is_path(ret.expr(), &name.text()))
+        }
+        _ => false,
+    };
+    equal_pat && equal_body && returns_binding
+}
+
+fn is_equal_path(path: Option<ast::Path>) -> bool {
+    path.and_then(|it| it.segment()?.name_ref()).map_or(false, |it| it.text() == "Equal")
+}
+
+/// `x` of `Some(x)`.
+fn some_arg(expr: &ast::Expr) -> Option<ast::Expr> {
+    let ast::Expr::CallExpr(call) = expr else { return None };
+    if call.expr()?.syntax().text() != "Some" {
+        return None;
+    }
+    let [arg] = <[_; 1]>::try_from(args(call)).ok()?;
+    Some(arg)
+}
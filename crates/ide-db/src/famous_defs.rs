@@ -1,7 +1,7 @@
 //! See [`FamousDefs`].
 
 use base_db::{CrateOrigin, LangCrateOrigin, SourceDatabase};
-use hir::{Crate, Enum, Macro, Module, ScopeDef, Semantics, Trait};
+use hir::{Crate, Enum, Macro, Module, ScopeDef, Semantics, Struct, Trait};
 
 use crate::RootDatabase;
 
@@ -42,6 +42,22 @@ impl FamousDefs<'_, '_> {
         self.find_lang_crate(LangCrateOrigin::ProcMacro)
     }
 
+    pub fn core_clone_Clone(&self) -> Option<Trait> {
+        self.find_trait("core:clone:Clone")
+    }
+
+    pub fn core_cmp_PartialEq(&self) -> Option<Trait> {
+        self.find_trait("core:cmp:PartialEq")
+    }
+
+    pub fn core_cmp_Eq(&self) -> Option<Trait> {
+        self.find_trait("core:cmp:Eq")
+    }
+
+    pub fn core_cmp_PartialOrd(&self) -> Option<Trait> {
+        self.find_trait("core:cmp:PartialOrd")
+    }
+
     pub fn core_cmp_Ord(&self) -> Option<Trait> {
         self.find_trait("core:cmp:Ord")
     }
@@ -106,6 +122,75 @@ impl FamousDefs<'_, '_> {
         self.find_trait("core:marker:Copy")
     }
 
+    pub fn core_hash_Hash(&self) -> Option<Trait> {
+        self.find_trait("core:hash:Hash")
+    }
+
+    pub fn core_fmt_Debug(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:Debug")
+    }
+
+    pub fn core_fmt_Display(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:Display")
+    }
+
+    pub fn core_fmt_LowerHex(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:LowerHex")
+    }
+
+    pub fn core_fmt_UpperHex(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:UpperHex")
+    }
+
+    pub fn core_fmt_Octal(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:Octal")
+    }
+
+    pub fn core_fmt_Binary(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:Binary")
+    }
+
+    pub fn core_fmt_LowerExp(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:LowerExp")
+    }
+
+    pub fn core_fmt_UpperExp(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:UpperExp")
+    }
+
+    pub fn core_fmt_Pointer(&self) -> Option<Trait> {
+        self.find_trait("core:fmt:Pointer")
+    }
+
+    pub fn alloc_boxed_Box(&self) -> Option<Struct> {
+        // Crates usually only depend on `std`, which re-exports `Box`.
+        self.find_struct("alloc:boxed:Box").or_else(|| self.find_struct("std:boxed:Box"))
+    }
+
+    pub fn alloc_vec_Vec(&self) -> Option<Struct> {
+        self.find_struct("alloc:vec:Vec").or_else(|| self.find_struct("std:vec:Vec"))
+    }
+
+    pub fn core_cell_RefCell(&self) -> Option<Struct> {
+        self.find_struct("core:cell:RefCell").or_else(|| self.find_struct("std:cell:RefCell"))
+    }
+
+    pub fn alloc_rc_Rc(&self) -> Option<Struct> {
+        self.find_struct("alloc:rc:Rc").or_else(|| self.find_struct("std:rc:Rc"))
+    }
+
+    pub fn alloc_sync_Arc(&self) -> Option<Struct> {
+        self.find_struct("alloc:sync:Arc").or_else(|| self.find_struct("std:sync:Arc"))
+    }
+
+    pub fn std_sync_Mutex(&self) -> Option<Struct> {
+        self.find_struct("std:sync:Mutex")
+    }
+
+    pub fn alloc_string_String(&self) -> Option<Struct> {
+        self.find_struct("alloc:string:String").or_else(|| self.find_struct("std:string:String"))
+    }
+
     pub fn core_macros_builtin_derive(&self) -> Option<Macro> {
         self.find_macro("core:macros:builtin:derive")
     }
@@ -135,6 +220,13 @@ impl FamousDefs<'_, '_> {
         }
     }
 
+    fn find_struct(&self, path: &str) -> Option<Struct> {
+        match self.find_def(path)? {
+            hir::ScopeDef::ModuleDef(hir::ModuleDef::Adt(hir::Adt::Struct(it))) => Some(it),
+            _ => None,
+        }
+    }
+
     fn find_enum(&self, path: &str) -> Option<Enum> {
         match self.find_def(path)? {
             hir::ScopeDef::ModuleDef(hir::ModuleDef::Adt(hir::Adt::Enum(it))) => Some(it),
@@ -9,6 +9,7 @@ mod apply_change;
 pub mod active_parameter;
 pub mod assists;
 pub mod defs;
+pub mod derivable_impl;
 pub mod famous_defs;
 pub mod helpers;
 pub mod items_locator;
@@ -39,6 +40,8 @@ pub mod syntax_helpers {
     pub mod insert_whitespace_into_node;
     pub mod format_string;
     pub mod format_string_exprs;
+    pub mod intra_doc_links;
+    pub mod lifetime_elision;
 
     pub use parser::LexedStr;
 }
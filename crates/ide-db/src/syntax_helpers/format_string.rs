@@ -1,9 +1,12 @@
 //! Tools to work with format string literals for the `format_args!` family of macros.
+use hir::{DescendPreference, PathResolution, Semantics, Trait, Type};
 use syntax::{
-    ast::{self, IsString},
+    ast::{self, make, HasName, IsString},
     AstNode, AstToken, TextRange, TextSize,
 };
 
+use crate::{famous_defs::FamousDefs, RootDatabase};
+
 // FIXME: This can probably be re-implemented via the HIR?
 pub fn is_format_string(string: &ast::String) -> bool {
     // Check if `string` is a format string argument of a macro invocation.
@@ -296,3 +299,188 @@ pub fn lex_format_specifiers(
         chars.next();
     }
 }
+
+/// The formatting trait a format spec asks for, like `Debug` for `{:?}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatTrait {
+    Display,
+    Debug,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+    LowerExp,
+    UpperExp,
+    Pointer,
+}
+
+impl FormatTrait {
+    /// Determines the trait from the type at the end of a format spec, returns `None` if the
+    /// type is not a valid one.
+    pub fn from_spec(spec: &str) -> Option<FormatTrait> {
+        if spec.ends_with('?') {
+            return Some(FormatTrait::Debug);
+        }
+        let res = match spec.chars().last() {
+            Some('x') => FormatTrait::LowerHex,
+            Some('X') => FormatTrait::UpperHex,
+            Some('o') => FormatTrait::Octal,
+            Some('b') => FormatTrait::Binary,
+            Some('e') => FormatTrait::LowerExp,
+            Some('E') => FormatTrait::UpperExp,
+            Some('p') => FormatTrait::Pointer,
+            Some(c) if c.is_alphabetic() || c == '_' => return None,
+            _ => FormatTrait::Display,
+        };
+        Some(res)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FormatTrait::Display => "Display",
+            FormatTrait::Debug => "Debug",
+            FormatTrait::LowerHex => "LowerHex",
+            FormatTrait::UpperHex => "UpperHex",
+            FormatTrait::Octal => "Octal",
+            FormatTrait::Binary => "Binary",
+            FormatTrait::LowerExp => "LowerExp",
+            FormatTrait::UpperExp => "UpperExp",
+            FormatTrait::Pointer => "Pointer",
+        }
+    }
+
+    pub fn resolve(self, famous_defs: &FamousDefs<'_, '_>) -> Option<Trait> {
+        match self {
+            FormatTrait::Display => famous_defs.core_fmt_Display(),
+            FormatTrait::Debug => famous_defs.core_fmt_Debug(),
+            FormatTrait::LowerHex => famous_defs.core_fmt_LowerHex(),
+            FormatTrait::UpperHex => famous_defs.core_fmt_UpperHex(),
+            FormatTrait::Octal => famous_defs.core_fmt_Octal(),
+            FormatTrait::Binary => famous_defs.core_fmt_Binary(),
+            FormatTrait::LowerExp => famous_defs.core_fmt_LowerExp(),
+            FormatTrait::UpperExp => famous_defs.core_fmt_UpperExp(),
+            FormatTrait::Pointer => famous_defs.core_fmt_Pointer(),
+        }
+    }
+
+    /// Whether `ty` implements the trait. Returns `None` if this can't be determined.
+    pub fn is_implemented_by(self, famous_defs: &FamousDefs<'_, '_>, ty: &Type) -> Option<bool> {
+        if ty.is_unknown() {
+            return None;
+        }
+        let trait_ = self.resolve(famous_defs)?;
+        // `core` forwards all formatting traits but `Pointer` through references.
+        let ty = match self {
+            FormatTrait::Pointer => ty.clone(),
+            _ => ty.strip_references(),
+        };
+        Some(ty.impls_trait(famous_defs.0.db, trait_, &[]))
+    }
+}
+
+/// The argument a placeholder refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatArgRef {
+    Index(usize),
+    Name(String),
+}
+
+/// A `{...}` placeholder of a format string.
+#[derive(Debug)]
+pub struct FormatPlaceholder {
+    /// The range of the placeholder including its braces. For a placeholder that isn't closed
+    /// this extends to the end of the text.
+    pub range: TextRange,
+    pub argument: FormatArgRef,
+    /// The range of the format spec following the `:`.
+    pub spec: Option<TextRange>,
+}
+
+/// Collects the placeholders in the contents of a format string, ranges are relative to the
+/// start of `text`.
+pub fn format_placeholders(text: &str) -> Vec<FormatPlaceholder> {
+    let mut res = Vec::new();
+    let mut next_index = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek().map(|&(_, it)| it) == Some(c) => {
+                // escaped brace
+                chars.next();
+            }
+            '{' => {
+                let end = chars.by_ref().find(|&(_, it)| it == '}').map(|(idx, _)| idx);
+                let inner = &text[start + 1..end.unwrap_or(text.len())];
+                let (arg, spec) = match inner.split_once(':') {
+                    Some((arg, spec)) => {
+                        let spec_start = start + 1 + arg.len() + 1;
+                        (arg.trim(), Some((spec, spec_start)))
+                    }
+                    None => (inner.trim(), None),
+                };
+                let argument = if arg.is_empty() {
+                    // `{:.*}` takes its precision from the next positional argument first
+                    if spec.map_or(false, |(spec, _)| spec.contains(".*")) {
+                        next_index += 1;
+                    }
+                    next_index += 1;
+                    FormatArgRef::Index(next_index - 1)
+                } else if let Ok(idx) = arg.parse() {
+                    FormatArgRef::Index(idx)
+                } else {
+                    FormatArgRef::Name(arg.to_owned())
+                };
+                let range = start..end.map_or(text.len(), |it| it + 1);
+                res.push(FormatPlaceholder {
+                    range: text_range(range.start, range.end),
+                    argument,
+                    spec: spec.map(|(spec, start)| text_range(start, start + spec.len())),
+                });
+            }
+            _ => (),
+        }
+    }
+    res
+}
+
+/// Resolves the type of the argument of the `format_args!` call `string` is the template of.
+/// `string` is the string as written by the user, possibly inside of a macro call.
+pub fn format_argument_ty(
+    sema: &Semantics<'_, RootDatabase>,
+    string: &ast::String,
+    argument: &FormatArgRef,
+) -> Option<Type> {
+    let format_args = sema
+        .descend_into_macros(DescendPreference::SameText, string.syntax().clone())
+        .into_iter()
+        .filter_map(ast::String::cast)
+        .find(is_format_string)?
+        .syntax()
+        .parent()?
+        .parent()
+        .and_then(ast::FormatArgsExpr::cast)?;
+    let mut args = format_args.args();
+    let arg = match argument {
+        FormatArgRef::Index(idx) => args.nth(*idx)?,
+        FormatArgRef::Name(name) => {
+            match args.find(|it| it.name().map_or(false, |it| it.text() == name.as_str())) {
+                Some(arg) => arg,
+                // an implicitly captured variable
+                None => {
+                    let scope = sema.scope(format_args.syntax())?;
+                    return match scope.speculative_resolve(&make::ext::ident_path(name))? {
+                        PathResolution::Local(local) => Some(local.ty(sema.db)),
+                        PathResolution::Def(hir::ModuleDef::Const(it)) => Some(it.ty(sema.db)),
+                        PathResolution::Def(hir::ModuleDef::Static(it)) => Some(it.ty(sema.db)),
+                        _ => None,
+                    };
+                }
+            }
+        }
+    };
+    Some(sema.type_of_expr(&arg.expr()?)?.original)
+}
+
+fn text_range(start: usize, end: usize) -> TextRange {
+    TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32))
+}
@@ -1,6 +1,8 @@
 //! Tools to work with expressions present in format string literals for the `format_args!` family of macros.
 //! Primarily meant for assists and completions.
 
+use syntax::{TextRange, TextSize};
+
 /// Enum for representing extracted format string args.
 /// Can either be extracted expressions (which includes identifiers),
 /// or placeholders `{}`.
@@ -197,6 +199,98 @@ pub fn parse_format_exprs(input: &str) -> Result<(String, Vec<Arg>), ()> {
     Ok((output, extracted_expressions))
 }
 
+/// A placeholder of a format string which doesn't contain an expression, like `{}`, `{0:?}` or
+/// `{name}`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    /// The range of the placeholder in the format string, braces included.
+    pub range: TextRange,
+    pub arg: PlaceholderArg,
+    /// The format spec following the `:`.
+    pub spec: Option<String>,
+}
+
+/// The argument a [`Placeholder`] refers to.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlaceholderArg {
+    /// `{}`, the positional argument after the one of the previous `{}`.
+    Next,
+    Index(usize),
+    Name(String),
+}
+
+/**
+ Parser for the placeholders of a format string, which fails if the string is malformed or if
+ a placeholder contains an expression other than a name.
+
+ `input` is the text of a string literal between its quotes, escape sequences are skipped.
+ ```rust
+ # use ide_db::syntax_helpers::format_string_exprs::*;
+ let placeholders = parse_placeholders("{} {{}} {0:?} {name}").unwrap();
+ assert_eq!(placeholders[1].arg, PlaceholderArg::Index(0));
+ assert_eq!(placeholders[1].spec.as_deref(), Some("?"));
+ assert!(parse_placeholders("{x + 1}").is_err());
+ ```
+*/
+pub fn parse_placeholders(input: &str) -> Result<Vec<Placeholder>, ()> {
+    let mut placeholders = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, chr)) = chars.next() {
+        match chr {
+            '\\' => {
+                // `\u{7b}` isn't a placeholder.
+                if let Some((_, 'u')) = chars.next() {
+                    for (_, chr) in chars.by_ref() {
+                        if chr == '}' {
+                            break;
+                        }
+                    }
+                }
+            }
+            '{' if chars.next_if(|&(_, chr)| chr == '{').is_some() => (),
+            '}' if chars.next_if(|&(_, chr)| chr == '}').is_some() => (),
+            '}' => return Err(()),
+            '{' => {
+                let mut inner = String::new();
+                let end = loop {
+                    match chars.next() {
+                        Some((idx, '}')) => break idx + 1,
+                        Some((_, '{')) | None => return Err(()),
+                        Some((_, chr)) => inner.push(chr),
+                    }
+                };
+                let (arg, spec) = match inner.split_once(':') {
+                    Some((arg, spec)) => (arg.trim(), Some(spec.to_owned())),
+                    None => (inner.trim(), None),
+                };
+                let arg = if arg.is_empty() {
+                    PlaceholderArg::Next
+                } else if let Ok(idx) = arg.parse() {
+                    PlaceholderArg::Index(idx)
+                } else if is_name(arg) {
+                    PlaceholderArg::Name(arg.to_owned())
+                } else {
+                    return Err(());
+                };
+                let range = TextRange::new(
+                    TextSize::try_from(start).unwrap(),
+                    TextSize::try_from(end).unwrap(),
+                );
+                placeholders.push(Placeholder { range, arg, spec });
+            }
+            _ => (),
+        }
+    }
+    Ok(placeholders)
+}
+
+fn is_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().map_or(false, |it| it.is_alphabetic() || it == '_')
+        && chars.all(|it| it.is_alphanumeric() || it == '_')
+        && text != "_"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -268,4 +362,35 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn placeholders() {
+        let check = |input: &str, expect: Expect| {
+            let repr = match parse_placeholders(input) {
+                Ok(placeholders) => placeholders
+                    .iter()
+                    .map(|it| {
+                        let arg = match &it.arg {
+                            PlaceholderArg::Next => "next".to_owned(),
+                            PlaceholderArg::Index(idx) => idx.to_string(),
+                            PlaceholderArg::Name(name) => name.clone(),
+                        };
+                        format!("{:?} {arg} {:?}", it.range, it.spec)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Err(()) => "-".to_owned(),
+            };
+            expect.assert_eq(&repr);
+        };
+
+        check("no placeholders {{}}", expect![[""]]);
+        check("{} {:?}", expect![["0..2 next None, 3..7 next Some(\"?\")"]]);
+        check("{1:>width$} {name}", expect![["0..11 1 Some(\">width$\"), 12..18 name None"]]);
+        check(r"\u{7b} {x}", expect![["7..10 x None"]]);
+        check("{x + 1}", expect![["-"]]);
+        check("{r#raw}", expect![["-"]]);
+        check("{malformed", expect![["-"]]);
+        check("malformed}", expect![["-"]]);
+    }
 }
@@ -0,0 +1,95 @@
+//! The lifetime elision rules for function signatures, as far as they can be applied on syntax.
+//!
+//! Only references are considered, lifetimes hidden in paths like `Foo` for `Foo<'a>` need type
+//! information.
+use syntax::{
+    ast::{self, HasName},
+    SyntaxToken,
+};
+
+use crate::syntax_helpers::node_ext::walk_ty;
+
+/// A reference in the parameters of a function, which gets a lifetime of its own if it doesn't
+/// name one.
+#[derive(Debug, Clone)]
+pub struct InputLifetime {
+    /// The name of the parameter, if it's a plain binding.
+    pub param_name: Option<ast::Name>,
+    pub amp_token: Option<SyntaxToken>,
+    pub lifetime: Option<ast::Lifetime>,
+    pub is_self: bool,
+}
+
+impl InputLifetime {
+    /// Whether the lifetime is left to elision, either omitted or `'_`.
+    pub fn is_elided(&self) -> bool {
+        match &self.lifetime {
+            Some(lt) => lt.text() == "'_",
+            None => true,
+        }
+    }
+}
+
+/// The references in `param_list`, in order and starting with `&self`.
+pub fn input_lifetimes(param_list: &ast::ParamList) -> Vec<InputLifetime> {
+    let mut acc = Vec::new();
+    if let Some(self_param) = param_list.self_param().filter(|it| it.amp_token().is_some()) {
+        acc.push(InputLifetime {
+            param_name: None,
+            amp_token: self_param.amp_token(),
+            lifetime: self_param.lifetime(),
+            is_self: true,
+        });
+    }
+    for (pat, ty) in param_list.params().filter_map(|it| Some((it.pat(), it.ty()?))) {
+        let param_name = match &pat {
+            Some(ast::Pat::IdentPat(it)) => it.name(),
+            _ => None,
+        };
+        // FIXME: check path types
+        walk_ty(&ty, &mut |ty| match ty {
+            ast::Type::RefType(r) => {
+                acc.push(InputLifetime {
+                    param_name: param_name.clone(),
+                    amp_token: r.amp_token(),
+                    lifetime: r.lifetime(),
+                    is_self: false,
+                });
+                false
+            }
+            ast::Type::FnPtrType(_) => true,
+            ast::Type::PathType(t) => {
+                t.path().and_then(|it| it.segment()).and_then(|it| it.param_list()).is_some()
+            }
+            _ => false,
+        });
+    }
+    acc
+}
+
+/// The input whose lifetime the elided lifetimes in the return type get, which is `&self` or
+/// the only input reference.
+pub fn output_lifetime_source(inputs: &[InputLifetime]) -> Option<&InputLifetime> {
+    match inputs {
+        [first, ..] if first.is_self || inputs.len() == 1 => Some(first),
+        _ => None,
+    }
+}
+
+/// The references in a return type, which take their lifetime from the inputs if they don't
+/// name one.
+pub fn output_refs(ret_ty: &ast::Type) -> Vec<ast::RefType> {
+    let mut acc = Vec::new();
+    walk_ty(ret_ty, &mut |ty| match ty {
+        ast::Type::RefType(ty) => {
+            acc.push(ty);
+            false
+        }
+        ast::Type::FnPtrType(_) => true,
+        ast::Type::PathType(t) => {
+            t.path().and_then(|it| it.segment()).and_then(|it| it.param_list()).is_some()
+        }
+        _ => false,
+    });
+    acc
+}
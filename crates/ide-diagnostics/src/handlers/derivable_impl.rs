@@ -0,0 +1,152 @@
+use hir::Semantics;
+use ide_db::{
+    base_db::{FileId, FileRange},
+    derivable_impl::derivable_impl as find_derivable_impl,
+    source_change::SourceChangeBuilder,
+    RootDatabase,
+};
+use syntax::{ast, AstNode, SyntaxNode, TextRange};
+
+use crate::{fix, Diagnostic, DiagnosticCode, Severity};
+
+// Diagnostic: derivable-impl
+//
+// This diagnostic is triggered if a manual impl of `Clone`, `Debug`, `Default`, `Eq`, `Hash`,
+// `Ord`, `PartialEq` or `PartialOrd` for a struct, or of `Clone`, `Debug`, `Default` or `Eq` for
+// an enum, does exactly what `#[derive]` would generate.
+pub(crate) fn derivable_impl(
+    sema: &Semantics<'_, RootDatabase>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<()> {
+    let impl_ = ast::Impl::cast(node.clone())?;
+    let derivable = find_derivable_impl(sema, &impl_)?;
+    let range = TextRange::new(
+        impl_.trait_()?.syntax().text_range().start(),
+        impl_.self_ty()?.syntax().text_range().end(),
+    );
+
+    let mut builder = SourceChangeBuilder::new(file_id);
+    derivable.replace_with_derive(&mut builder, file_id, &impl_);
+    acc.push(
+        Diagnostic::new(
+            DiagnosticCode::Ra("derivable-impl", Severity::WeakWarning),
+            format!("`{}` impl can be derived", derivable.derive),
+            FileRange { file_id, range },
+        )
+        .with_fixes(Some(vec![fix(
+            "replace_manual_impl_with_derive",
+            &format!("Replace with `#[derive({})]`", derivable.derive),
+            builder.finish(),
+            range,
+        )])),
+    );
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn derivable_clone() {
+        check_diagnostics(
+            r#"
+//- minicore: clone, derive, builtin_impls
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+impl Clone for Point {
+   //^^^^^^^^^^^^^^^ 💡 weak: `Clone` impl can be derived
+    fn clone(&self) -> Self {
+        Point { y: self.y.clone(), x: self.x.clone() }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_derivable_when_fields_differ() {
+        check_diagnostics(
+            r#"
+//- minicore: eq, derive
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Self) -> bool {
+        self.x == other.x
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_with_derive() {
+        check_fix(
+            r#"
+//- minicore: fmt, derive, clone
+struct Id;
+impl core::fmt::Debug for Id {
+    fn fmt(&self, _: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Wrapper(Id);
+
+impl core::fmt::Debug for Wrapper$0 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Wrapper").field(&self.0).finish()
+    }
+}
+"#,
+            r#"
+struct Id;
+impl core::fmt::Debug for Id {
+    fn fmt(&self, _: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Wrapper(Id);
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_enum_default_with_derive() {
+        check_fix(
+            r#"
+//- minicore: default, derive
+enum Level {
+    Low,
+    High,
+}
+
+impl Default for Level$0 {
+    fn default() -> Self {
+        Self::Low
+    }
+}
+"#,
+            r#"
+#[derive(Default)]
+enum Level {
+    #[default]
+    Low,
+    High,
+}
+"#,
+        );
+    }
+}
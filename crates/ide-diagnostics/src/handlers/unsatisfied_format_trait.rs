@@ -0,0 +1,117 @@
+//! Diagnostic emitted for placeholders of format strings whose argument doesn't implement the
+//! formatting trait the placeholder asks for.
+
+use hir::{HirDisplay, Semantics};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    famous_defs::FamousDefs,
+    syntax_helpers::format_string::{format_argument_ty, format_placeholders, FormatTrait},
+    RootDatabase,
+};
+use syntax::{
+    ast::{self, IsString},
+    AstNode, AstToken, SyntaxNode,
+};
+
+use crate::{Diagnostic, DiagnosticCode, Severity};
+
+// Diagnostic: unsatisfied-format-trait
+//
+// This diagnostic is triggered if a placeholder of a format string asks for a formatting trait
+// that its argument does not implement, like `{:x}` for a `&str`.
+pub(crate) fn unsatisfied_format_trait(
+    sema: &Semantics<'_, RootDatabase>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<()> {
+    let macro_call = ast::MacroCall::cast(node.clone())?;
+    let token_tree = macro_call.token_tree()?;
+    let krate = sema.scope(macro_call.syntax())?.krate();
+    let famous_defs = FamousDefs(sema, krate);
+    let strings = token_tree
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter_map(ast::String::cast);
+    for string in strings {
+        let (Some(contents_range), Some(text)) =
+            (string.text_range_between_quotes(), string.value())
+        else {
+            continue;
+        };
+        // Escapes change the offsets, so only raw-equivalent strings are checked.
+        if text.len() != usize::from(contents_range.len()) {
+            continue;
+        }
+        // `format_argument_ty` only resolves arguments of strings that end up as format strings.
+        for placeholder in format_placeholders(&text) {
+            let spec = placeholder.spec.map_or("", |it| &text[it]);
+            let Some(format_trait) = FormatTrait::from_spec(spec) else { continue };
+            let Some(ty) = format_argument_ty(sema, &string, &placeholder.argument) else {
+                continue;
+            };
+            if format_trait.is_implemented_by(&famous_defs, &ty) != Some(false) {
+                continue;
+            }
+            acc.push(
+                Diagnostic::new(
+                    DiagnosticCode::Ra("unsatisfied-format-trait", Severity::Error),
+                    format!(
+                        "`{}` doesn't implement `{}`",
+                        ty.display(sema.db),
+                        format_trait.name()
+                    ),
+                    FileRange { file_id, range: placeholder.range + contents_range.start() },
+                )
+                .experimental(),
+            );
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn unimplemented_format_traits() {
+        check_diagnostics(
+            r#"
+//- minicore: fmt, builtin_impls
+struct S;
+
+fn main() {
+    let s = S;
+    format_args!("{} {:?}", 0, s);
+                   //^^^^ error: `S` doesn't implement `Debug`
+    format_args!("{:x}", "str");
+                //^^^^ error: `&str` doesn't implement `LowerHex`
+    format_args!("{s}");
+                //^^^ error: `S` doesn't implement `Display`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn implemented_format_traits() {
+        check_diagnostics(
+            r#"
+//- minicore: fmt, builtin_impls
+struct S;
+impl core::fmt::Display for S {
+    fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        loop {}
+    }
+}
+
+fn main() {
+    let s = S;
+    format_args!("{} {:>8} {:#x} {2:X}", s, &s, 10, 11);
+}
+"#,
+        );
+    }
+}
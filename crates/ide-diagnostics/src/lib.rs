@@ -59,11 +59,13 @@ mod handlers {
     pub(crate) mod unresolved_macro_call;
     pub(crate) mod unresolved_module;
     pub(crate) mod unresolved_proc_macro;
+    pub(crate) mod unsatisfied_format_trait;
     pub(crate) mod undeclared_label;
     pub(crate) mod unreachable_label;
     pub(crate) mod unused_variables;
 
     // The handlers below are unusual, the implement the diagnostics as well.
+    pub(crate) mod derivable_impl;
     pub(crate) mod field_shorthand;
     pub(crate) mod useless_braces;
     pub(crate) mod unlinked_file;
@@ -313,6 +315,10 @@ pub fn diagnostics(
         handlers::useless_braces::useless_braces(&mut res, file_id, &node);
         handlers::field_shorthand::field_shorthand(&mut res, file_id, &node);
         handlers::json_is_not_rust::json_in_items(&sema, &mut res, file_id, &node, config);
+        handlers::derivable_impl::derivable_impl(&sema, &mut res, file_id, &node);
+        handlers::unsatisfied_format_trait::unsatisfied_format_trait(
+            &sema, &mut res, file_id, &node,
+        );
     }
 
     let module = sema.to_module_def(file_id);
@@ -345,6 +345,15 @@ impl Match {
     pub fn matched_text(&self) -> String {
         self.matched_node.text().to_string()
     }
+
+    pub fn range(&self) -> FileRange {
+        self.range
+    }
+
+    /// The range of the code the placeholder `$name` matched, if it's in the pattern.
+    pub fn placeholder_range(&self, name: &str) -> Option<FileRange> {
+        self.placeholder_values.get(&parsing::Var(name.to_owned())).map(|it| it.range)
+    }
 }
 
 impl std::error::Error for SsrError {}
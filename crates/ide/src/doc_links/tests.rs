@@ -5,14 +5,14 @@ use hir::Semantics;
 use ide_db::{
     base_db::{FilePosition, FileRange},
     defs::Definition,
-    documentation::{Documentation, HasDocs},
+    documentation::{resolve_doc_path_for_def, Documentation, HasDocs},
     RootDatabase,
 };
 use itertools::Itertools;
 use syntax::{ast, match_ast, AstNode, SyntaxNode};
 
 use crate::{
-    doc_links::{extract_definitions_from_docs, resolve_doc_path_for_def, rewrite_links},
+    doc_links::{extract_definitions_from_docs, rewrite_links},
     fixture, TryToNav,
 };
 
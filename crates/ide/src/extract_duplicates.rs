@@ -0,0 +1,790 @@
+//! This module provides an assist extracting an expression and the code duplicating it elsewhere
+//! in the crate into a function. The duplicates are found with the SSR matcher, which is why this
+//! lives here rather than in ide_assists, like the SSR assist. The assist is only offered if the
+//! current file contains a duplicate, the rest of the crate is searched when it's resolved.
+
+use hir::{HirDisplay, ModuleDef, PathResolution, Semantics};
+use ide_assists::{Assist, AssistConfig, AssistId, AssistKind, AssistResolveStrategy};
+use ide_db::{
+    base_db::{FilePosition, FileRange},
+    label::Label,
+    source_change::SourceChangeBuilder,
+    FxHashMap, RootDatabase,
+};
+use ide_ssr::{MatchFinder, SsrPattern};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel},
+    AstNode, SyntaxKind, TextRange, TextSize,
+};
+
+pub(crate) fn extract_duplicates(
+    db: &RootDatabase,
+    config: &AssistConfig,
+    resolve: &AssistResolveStrategy,
+    frange: FileRange,
+) -> Option<Assist> {
+    let id = AssistId("extract_duplicates", AssistKind::RefactorExtract);
+    if let Some(allowed) = &config.allowed {
+        if !allowed.iter().any(|kind| kind.contains(id.1)) {
+            return None;
+        }
+    }
+    if frange.range.is_empty() {
+        return None;
+    }
+    let sema = Semantics::new(db);
+    let expr = selected_expr(&sema, frange)?;
+    let module = sema.scope(expr.syntax())?.module();
+    let fragment = Fragment::new(&sema, &expr, module)?;
+
+    // Searching the whole crate is too slow to do for every selection, so only the current file is
+    // searched until the assist is resolved.
+    let should_resolve = resolve.should_resolve(&id);
+    let restrict_ranges = if should_resolve {
+        vec![]
+    } else {
+        let file_range = sema.parse(frange.file_id).syntax().text_range();
+        vec![FileRange { file_id: frange.file_id, range: file_range }]
+    };
+    let mut finder = MatchFinder::in_context(
+        db,
+        FilePosition { file_id: frange.file_id, offset: frange.range.start() },
+        restrict_ranges,
+    )
+    .ok()?;
+    finder.add_search_pattern(fragment.pattern.parse::<SsrPattern>().ok()?).ok()?;
+    let mut calls = Vec::new();
+    for m in finder.matches().matches {
+        let range = m.range();
+        if sema.to_module_def(range.file_id).map(|it| it.krate()) != Some(module.krate()) {
+            continue;
+        }
+        let Some(args) = fragment.args(&sema, |var| m.placeholder_range(var), range) else {
+            continue;
+        };
+        calls.push((range, args));
+    }
+    // The selection has to be one of the matches, along with at least one duplicate.
+    let selection = FileRange { file_id: frange.file_id, range: expr.syntax().text_range() };
+    if calls.len() < 2 || !calls.iter().any(|(range, _)| *range == selection) {
+        return None;
+    }
+
+    // Literals that are the same in all duplicates stay in the function.
+    let inlined: Vec<bool> = fragment
+        .params
+        .iter()
+        .enumerate()
+        .map(|(idx, param)| {
+            param.is_literal && calls.iter().all(|(_, args)| args[idx] == param.text)
+        })
+        .collect();
+
+    // Duplicates in other modules call the function through the path to its module, and without
+    // one the function can't be reached from there.
+    let mut needs_pub = false;
+    let mut call_texts = Vec::new();
+    for (range, args) in calls {
+        let call_module = sema
+            .find_node_at_offset_with_descend::<ast::Expr>(
+                sema.parse(range.file_id).syntax(),
+                range.range.start(),
+            )
+            .and_then(|it| sema.scope(it.syntax()))
+            .map(|it| it.module());
+        let mut text = String::new();
+        if let Some(call_module) = call_module.filter(|it| *it != module) {
+            needs_pub = true;
+            let module_path = call_module.find_use_path(
+                db,
+                ModuleDef::Module(module),
+                config.prefer_no_std,
+                config.prefer_prelude,
+            )?;
+            format_to!(text, "{}::", module_path.display(db));
+        }
+        let args = args.iter().zip(&inlined).filter(|(_, inlined)| !**inlined);
+        let args = args.map(|(arg, _)| arg).join(", ");
+        format_to!(text, "fun_name({args})");
+        call_texts.push((range, text));
+    }
+
+    let source_change = should_resolve.then(|| {
+        let mut builder = SourceChangeBuilder::new(frange.file_id);
+        call_texts.sort_by_key(|(range, _)| range.file_id);
+        for (file_id, calls) in &call_texts.into_iter().group_by(|(range, _)| range.file_id) {
+            builder.edit_file(file_id);
+            for (range, text) in calls {
+                builder.replace(range.range, text);
+            }
+        }
+
+        let item = containing_item(&expr);
+        let offset = item.as_ref().map_or(TextSize::from(0), |it| it.syntax().text_range().end());
+        let indent = item.as_ref().map_or(IndentLevel(0), |it| IndentLevel::from_node(it.syntax()));
+        let vis = if needs_pub { "pub(crate) " } else { "" };
+        let mut text = format!(
+            "\n\n{indent}{vis}fn fun_name({})",
+            fragment
+                .params
+                .iter()
+                .zip(&inlined)
+                .filter(|(_, inlined)| !**inlined)
+                .map(|(param, _)| format!("{}: {}", param.name, param.ty))
+                .join(", ")
+        );
+        if let Some(ret_ty) = &fragment.ret_ty {
+            format_to!(text, " -> {ret_ty}");
+        }
+        let from = IndentLevel::from_node(expr.syntax());
+        let body = fragment.render_body(&inlined);
+        if matches!(expr, ast::Expr::BlockExpr(_)) {
+            format_to!(text, " {}", reindent(&body, from, indent));
+        } else {
+            let body = reindent(&body, from, indent + 1);
+            format_to!(text, " {{\n{}{body}\n{indent}}}", indent + 1);
+        }
+        builder.edit_file(frange.file_id);
+        match config.snippet_cap {
+            Some(cap) => {
+                let text = escape_snippet(&text).replacen("fn fun_name(", "fn $0fun_name(", 1);
+                builder.insert_snippet(cap, offset, text)
+            }
+            None => builder.insert(offset, text),
+        }
+        builder.finish()
+    });
+
+    Some(Assist {
+        id,
+        label: Label::new("Extract duplicates into function".to_owned()),
+        group: None,
+        target: frange.range,
+        source_change,
+        trigger_signature_help: false,
+    })
+}
+
+/// The expression the selection covers exactly, ignoring surrounding whitespace.
+fn selected_expr(sema: &Semantics<'_, RootDatabase>, frange: FileRange) -> Option<ast::Expr> {
+    let file = sema.parse(frange.file_id);
+    let text = file.syntax().text().to_string();
+    let selected = &text[frange.range];
+    let start = frange.range.start()
+        + TextSize::of(&selected[..selected.len() - selected.trim_start().len()]);
+    let range = TextRange::at(start, TextSize::of(selected.trim()));
+    let expr = file
+        .syntax()
+        .covering_element(range)
+        .ancestors()
+        .take_while(|it| it.text_range() == range)
+        .filter_map(ast::Expr::cast)
+        .last()?;
+    // There is nothing to extract from a lone variable or literal.
+    match expr {
+        ast::Expr::PathExpr(_) | ast::Expr::Literal(_) => None,
+        _ => Some(expr),
+    }
+}
+
+/// The item the new function goes after.
+fn containing_item(expr: &ast::Expr) -> Option<ast::Item> {
+    expr.syntax().ancestors().filter_map(ast::Item::cast).find(|item| {
+        item.syntax().parent().map_or(false, |parent| {
+            ast::SourceFile::can_cast(parent.kind())
+                || (ast::ItemList::can_cast(parent.kind())
+                    && parent.parent().map_or(false, |it| ast::Module::can_cast(it.kind())))
+        })
+    })
+}
+
+fn escape_snippet(text: &str) -> String {
+    let mut buf = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '$' | '}') {
+            buf.push('\\');
+        }
+        buf.push(c);
+    }
+    buf
+}
+
+fn reindent(text: &str, from: IndentLevel, to: IndentLevel) -> String {
+    let from = from.to_string();
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| match line.strip_prefix(&from) {
+            Some(line) if idx > 0 && !line.is_empty() => format!("{to}{line}"),
+            _ => line.to_owned(),
+        })
+        .join("\n")
+}
+
+/// How the value of a parameter is passed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Pass {
+    Value,
+    Ref,
+    RefMut,
+}
+
+/// A parameter of the new function, taking the parts of the fragment that differ between the
+/// duplicates.
+struct Param {
+    name: String,
+    /// The placeholders in the search pattern, one per use of the parameter in the fragment.
+    vars: Vec<String>,
+    /// The code the parameter replaces in the selection.
+    text: String,
+    /// Whether the parameter is a literal rather than a variable.
+    is_literal: bool,
+    pass: Pass,
+    /// The type of the placeholders.
+    ty: String,
+}
+
+/// The selected expression, turned into a search pattern and a function body.
+struct Fragment {
+    pattern: String,
+    /// The body of the function, as the text before each use of a parameter and the text after
+    /// the last one.
+    body: (Vec<(String, usize)>, String),
+    params: Vec<Param>,
+    ret_ty: Option<String>,
+    /// The type of the whole fragment.
+    ty: String,
+}
+
+impl Fragment {
+    fn new(
+        sema: &Semantics<'_, RootDatabase>,
+        expr: &ast::Expr,
+        module: hir::Module,
+    ) -> Option<Fragment> {
+        let db = sema.db;
+        let range = expr.syntax().text_range();
+        let display = |ty: &hir::Type| {
+            if ty.contains_unknown() || !ty.generic_params(db).is_empty() {
+                return None;
+            }
+            ty.display_source_code(db, module.into(), true).ok()
+        };
+
+        // The parts of the fragment becoming parameters, with the local they refer to if any.
+        let mut parts: Vec<(TextRange, Option<hir::Local>, Pass, String)> = Vec::new();
+        let mut nodes = expr.syntax().preorder();
+        while let Some(event) = nodes.next() {
+            let syntax::WalkEvent::Enter(node) = event else { continue };
+            if matches!(
+                node.kind(),
+                SyntaxKind::RETURN_EXPR
+                    | SyntaxKind::TRY_EXPR
+                    | SyntaxKind::AWAIT_EXPR
+                    | SyntaxKind::BREAK_EXPR
+                    | SyntaxKind::CONTINUE_EXPR
+                    | SyntaxKind::YIELD_EXPR
+            ) {
+                // The control flow would leave the new function instead.
+                return None;
+            }
+            if node
+                .children_with_tokens()
+                .any(|it| matches!(it.kind(), SyntaxKind::SELF_KW | SyntaxKind::SELF_TYPE_KW))
+            {
+                return None;
+            }
+            if let Some(literal) = ast::Literal::cast(node.clone()) {
+                let ty = display(&sema.type_of_expr(&literal.clone().into())?.original)?;
+                parts.push((literal.syntax().text_range(), None, Pass::Value, ty));
+                nodes.skip_subtree();
+                continue;
+            }
+            let Some(path_expr) = ast::PathExpr::cast(node.clone()) else { continue };
+            nodes.skip_subtree();
+            let Some(PathResolution::Local(local)) = sema.resolve_path(&path_expr.path()?) else {
+                continue;
+            };
+            if range.contains_range(local.primary_source(db).syntax().text_range()) {
+                continue;
+            }
+            if is_assigned(&path_expr, range) {
+                return None;
+            }
+            let local_ty = local.ty(db);
+            let parent = path_expr.syntax().parent()?;
+            let (part, pass, ty) = if let Some(ref_expr) = ast::RefExpr::cast(parent.clone()) {
+                let ty = display(&sema.type_of_expr(&ref_expr.clone().into())?.original)?;
+                (ref_expr.syntax().text_range(), Pass::Value, ty)
+            } else if local_ty.is_copy(db) || local_ty.is_reference() {
+                (path_expr.syntax().text_range(), Pass::Value, display(&local_ty)?)
+            } else {
+                let pass = if let Some(call) = ast::MethodCallExpr::cast(parent.clone()) {
+                    match sema.resolve_method_call(&call)?.self_param(db)?.access(db) {
+                        hir::Access::Shared => Pass::Ref,
+                        hir::Access::Exclusive => Pass::RefMut,
+                        hir::Access::Owned => Pass::Value,
+                    }
+                } else if ast::FieldExpr::can_cast(parent.kind()) {
+                    Pass::Ref
+                } else {
+                    Pass::Value
+                };
+                let ty = display(&local_ty)?;
+                let ty = match pass {
+                    Pass::Value => ty,
+                    Pass::Ref => format!("&{ty}"),
+                    Pass::RefMut => format!("&mut {ty}"),
+                };
+                (path_expr.syntax().text_range(), pass, ty)
+            };
+            parts.push((part, Some(local), pass, ty));
+        }
+
+        // Uses of the same variable in the same way share a parameter.
+        let mut params: Vec<Param> = Vec::new();
+        let mut local_params: FxHashMap<(hir::Local, Pass, String), usize> = FxHashMap::default();
+        let mut part_params = Vec::new();
+        for (idx, (part, local, pass, ty)) in parts.iter().enumerate() {
+            let var = format!("p{idx}");
+            let key = local.map(|local| (local, *pass, expr_text(expr, *part)));
+            let param_idx = match key.as_ref().and_then(|key| local_params.get(key)) {
+                Some(&param_idx) => param_idx,
+                None => {
+                    let base = match local {
+                        Some(local) => local.name(db).display(db).to_string(),
+                        None => format!("arg{}", params.iter().filter(|it| it.is_literal).count()),
+                    };
+                    let name = (0..)
+                        .map(|n| if n == 0 { base.clone() } else { format!("{base}{n}") })
+                        .find(|name| params.iter().all(|it| it.name != *name))?;
+                    params.push(Param {
+                        name,
+                        vars: Vec::new(),
+                        text: expr_text(expr, *part),
+                        is_literal: local.is_none(),
+                        pass: *pass,
+                        ty: ty.clone(),
+                    });
+                    if let Some(key) = key {
+                        local_params.insert(key, params.len() - 1);
+                    }
+                    params.len() - 1
+                }
+            };
+            params[param_idx].vars.push(var.clone());
+            part_params.push((*part, var, param_idx));
+        }
+
+        let mut pattern = String::new();
+        let mut body = Vec::new();
+        let mut offset = range.start();
+        for (part, var, param_idx) in &part_params {
+            let before = expr_text(expr, TextRange::new(offset, part.start()));
+            pattern.push_str(&before);
+            if params[*param_idx].is_literal {
+                format_to!(pattern, "${{{var}:kind(literal)}}");
+            } else {
+                format_to!(pattern, "${var}");
+            }
+            body.push((before, *param_idx));
+            offset = part.end();
+        }
+        let rest = expr_text(expr, TextRange::new(offset, range.end()));
+        pattern.push_str(&rest);
+
+        let ty = sema.type_of_expr(expr)?.original;
+        let ret_ty = if ty.is_unit() { None } else { Some(display(&ty)?) };
+        Some(Fragment { pattern, body: (body, rest), params, ret_ty, ty: display(&ty)? })
+    }
+
+    /// The body of the function, with the parameters that are `inlined` replaced by their code in
+    /// the selection.
+    fn render_body(&self, inlined: &[bool]) -> String {
+        let (parts, rest) = &self.body;
+        let mut buf = String::new();
+        for (before, param_idx) in parts {
+            let param = &self.params[*param_idx];
+            buf.push_str(before);
+            buf.push_str(if inlined[*param_idx] { &param.text } else { &param.name });
+        }
+        buf.push_str(rest);
+        buf
+    }
+
+    /// The arguments for the duplicate at `range`, given the ranges the placeholders matched.
+    fn args(
+        &self,
+        sema: &Semantics<'_, RootDatabase>,
+        placeholder_range: impl Fn(&str) -> Option<FileRange>,
+        range: FileRange,
+    ) -> Option<Vec<String>> {
+        let db = sema.db;
+        let module = sema.to_module_def(range.file_id)?;
+        let file = sema.parse(range.file_id);
+        let expr_at = |range: TextRange| {
+            file.syntax()
+                .covering_element(range)
+                .ancestors()
+                .take_while(|it| it.text_range() == range)
+                .filter_map(ast::Expr::cast)
+                .last()
+        };
+        let display = |expr: &ast::Expr| {
+            let ty = sema.type_of_expr(expr)?.original;
+            ty.display_source_code(db, module.into(), true).ok()
+        };
+        if display(&expr_at(range.range)?)? != self.ty {
+            return None;
+        }
+        self.params
+            .iter()
+            .map(|param| {
+                let texts = param
+                    .vars
+                    .iter()
+                    .map(|var| {
+                        let range = placeholder_range(var)?;
+                        Some(file.syntax().text().slice(range.range).to_string())
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                // Repeated uses of a variable have to be the same variable in the duplicate.
+                if !texts.iter().all_equal() {
+                    return None;
+                }
+                let arg = expr_at(placeholder_range(&param.vars[0])?.range)?;
+                if !param.is_literal && !is_variable_or_constant(&arg) {
+                    return None;
+                }
+                let ty = display(&arg)?;
+                let (prefix, expected) = match param.pass {
+                    Pass::Value => ("", param.ty.as_str()),
+                    Pass::Ref => ("&", param.ty.trim_start_matches('&')),
+                    Pass::RefMut => ("&mut ", param.ty.trim_start_matches("&mut ")),
+                };
+                (ty == expected).then(|| format!("{prefix}{}", texts[0]))
+            })
+            .collect()
+    }
+}
+
+fn expr_text(expr: &ast::Expr, range: TextRange) -> String {
+    let start = expr.syntax().text_range().start();
+    expr.syntax().text().slice(range - start).to_string()
+}
+
+/// Whether `expr` is a variable, constant or literal, or a reference to one, so evaluating it
+/// before the rest of the duplicate doesn't change what the duplicate does.
+fn is_variable_or_constant(expr: &ast::Expr) -> bool {
+    let expr = match expr {
+        ast::Expr::RefExpr(it) => it.expr(),
+        _ => Some(expr.clone()),
+    };
+    matches!(expr, Some(ast::Expr::PathExpr(_) | ast::Expr::Literal(_)))
+}
+
+/// Whether the variable `path_expr` refers to, or a field of it, is assigned to in `range`.
+fn is_assigned(path_expr: &ast::PathExpr, range: TextRange) -> bool {
+    let mut node = path_expr.syntax().clone();
+    while let Some(parent) = node.parent().filter(|it| range.contains_range(it.text_range())) {
+        if let Some(bin_expr) = ast::BinExpr::cast(parent.clone()) {
+            return matches!(bin_expr.op_kind(), Some(ast::BinaryOp::Assignment { .. }))
+                && bin_expr.lhs().map_or(false, |it| it.syntax() == &node);
+        }
+        if !ast::FieldExpr::can_cast(parent.kind()) && !ast::IndexExpr::can_cast(parent.kind()) {
+            return false;
+        }
+        node = parent;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::PrefixKind;
+    use ide_assists::{AssistConfig, AssistResolveStrategy};
+    use ide_db::{
+        base_db::{salsa::Durability, FileRange, SourceDatabaseExt},
+        imports::insert_use::{ImportGranularity, InsertUseConfig},
+        symbol_index::SymbolsDatabase,
+        FxHashSet, RootDatabase, SnippetCap,
+    };
+    use itertools::Itertools;
+    use stdx::format_to;
+    use test_fixture::WithFixture;
+    use triomphe::Arc;
+
+    use super::extract_duplicates;
+
+    const CONFIG: AssistConfig = AssistConfig {
+        snippet_cap: None,
+        allowed: None,
+        insert_use: InsertUseConfig {
+            granularity: ImportGranularity::Crate,
+            prefix_kind: PrefixKind::Plain,
+            enforce_granularity: true,
+            group: true,
+            skip_glob_imports: true,
+        },
+        prefer_no_std: false,
+        prefer_prelude: true,
+        assist_emit_must_use: false,
+        encapsulate_field_skip_impl: true,
+    };
+
+    fn assist(
+        config: &AssistConfig,
+        resolve: &AssistResolveStrategy,
+        ra_fixture: &str,
+    ) -> (RootDatabase, Option<ide_assists::Assist>) {
+        let (mut db, file_id, range_or_offset) = RootDatabase::with_range_or_offset(ra_fixture);
+        let mut local_roots = FxHashSet::default();
+        local_roots.insert(test_fixture::WORKSPACE);
+        db.set_local_roots_with_durability(Arc::new(local_roots), Durability::HIGH);
+        let frange = FileRange { file_id, range: range_or_offset.into() };
+        let assist = extract_duplicates(&db, config, resolve, frange);
+        (db, assist)
+    }
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        check_with_config(&CONFIG, ra_fixture, expect)
+    }
+
+    fn check_with_config(config: &AssistConfig, ra_fixture: &str, expect: Expect) {
+        let (db, assist) = assist(config, &AssistResolveStrategy::All, ra_fixture);
+        let assist = assist.expect("assist is not applicable");
+        let mut buf = format!("{}\n", assist.label);
+        let source_change = assist.source_change.unwrap();
+        for (file_id, (edit, _)) in
+            source_change.source_file_edits.into_iter().sorted_by_key(|(file_id, _)| *file_id)
+        {
+            let mut text = db.file_text(file_id).to_string();
+            edit.apply(&mut text);
+            let path =
+                db.source_root(db.file_source_root(file_id)).path_for_file(&file_id).cloned();
+            format_to!(buf, "//- {}\n{text}", path.map_or(String::new(), |it| it.to_string()));
+        }
+        expect.assert_eq(&buf);
+    }
+
+    fn check_not_applicable(ra_fixture: &str) {
+        assert!(assist(&CONFIG, &AssistResolveStrategy::All, ra_fixture).1.is_none());
+    }
+
+    #[test]
+    fn extracts_duplicates_across_files() {
+        check(
+            r#"
+//- /main.rs
+mod geometry;
+
+struct Point { x: i32, y: i32 }
+
+fn norm(p: &Point, scale: i32) -> i32 {
+    $0(p.x * p.x + p.y * p.y) * scale + 1$0
+}
+
+fn total(a: &Point, b: &Point) -> i32 {
+    let first = (a.x * a.x + a.y * a.y) * 2 + 1;
+    let second = (b.x * b.x + b.y * b.y) * 3 + 1;
+    // Different variables for the repeated `p`, so not a duplicate.
+    let mixed = (a.x * b.x + a.y * a.y) * 3 + 1;
+    first + second + mixed
+}
+//- /geometry.rs
+use crate::Point;
+
+fn origin_norm(origin: &Point) -> i32 {
+    (origin.x * origin.x + origin.y * origin.y) * 4 + 1
+}
+"#,
+            expect![[r#"
+                Extract duplicates into function
+                //- /main.rs
+                mod geometry;
+
+                struct Point { x: i32, y: i32 }
+
+                fn norm(p: &Point, scale: i32) -> i32 {
+                    fun_name(p, scale)
+                }
+
+                pub(crate) fn fun_name(p: &Point, scale: i32) -> i32 {
+                    (p.x * p.x + p.y * p.y) * scale + 1
+                }
+
+                fn total(a: &Point, b: &Point) -> i32 {
+                    let first = fun_name(a, 2);
+                    let second = fun_name(b, 3);
+                    // Different variables for the repeated `p`, so not a duplicate.
+                    let mixed = (a.x * b.x + a.y * a.y) * 3 + 1;
+                    first + second + mixed
+                }
+                //- /geometry.rs
+                use crate::Point;
+
+                fn origin_norm(origin: &Point) -> i32 {
+                    crate::fun_name(origin, 4)
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn extracts_block_with_method_calls() {
+        check(
+            r#"
+struct Log { lines: u32 }
+impl Log {
+    fn push(&mut self, n: u32) { self.lines += n; }
+}
+
+fn first(log: &mut Log) {
+    $0{
+        log.push(1);
+        log.push(2);
+    }$0
+}
+
+fn second(other: &mut Log) {
+    {
+        other.push(1);
+        other.push(2);
+    }
+}
+"#,
+            expect![[r#"
+                Extract duplicates into function
+                //- /main.rs
+                struct Log { lines: u32 }
+                impl Log {
+                    fn push(&mut self, n: u32) { self.lines += n; }
+                }
+
+                fn first(log: &mut Log) {
+                    fun_name(log)
+                }
+
+                fn fun_name(log: &mut Log) {
+                    log.push(1);
+                    log.push(2);
+                }
+
+                fn second(other: &mut Log) {
+                    fun_name(other)
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn only_searches_current_file_until_resolved() {
+        let fixture = r#"
+//- /main.rs
+mod other;
+fn f(a: i32) -> i32 {
+    $0a * 2 + 1$0
+}
+//- /other.rs
+fn g(b: i32) -> i32 {
+    b * 2 + 1
+}
+"#;
+        assert!(assist(&CONFIG, &AssistResolveStrategy::None, fixture).1.is_none());
+        assert!(assist(&CONFIG, &AssistResolveStrategy::All, fixture).1.is_some());
+    }
+
+    #[test]
+    fn escapes_snippet_syntax_in_body() {
+        check_with_config(
+            &AssistConfig { snippet_cap: SnippetCap::new(true), ..CONFIG },
+            r#"
+struct Log { lines: u32 }
+impl Log {
+    fn push(&mut self, s: &str) {}
+}
+
+fn first(log: &mut Log) {
+    $0{
+        log.push("${x}");
+    }$0
+}
+
+fn second(other: &mut Log) {
+    {
+        other.push("${x}");
+    }
+}
+"#,
+            expect![[r#"
+                Extract duplicates into function
+                //- /main.rs
+                struct Log { lines: u32 }
+                impl Log {
+                    fn push(&mut self, s: &str) {}
+                }
+
+                fn first(log: &mut Log) {
+                    fun_name(log)
+                }
+
+                fn $0fun_name(log: &mut Log) {
+                    log.push("\${x\}");
+                \}
+
+                fn second(other: &mut Log) {
+                    fun_name(other)
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn not_applicable_if_a_duplicate_cant_reach_the_module() {
+        check_not_applicable(
+            r#"
+mod outer {
+    mod inner {
+        fn f(a: i32) -> i32 {
+            $0a * 2 + 1$0
+        }
+    }
+}
+
+fn g(b: i32) -> i32 {
+    b * 2 + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_duplicates_or_with_control_flow() {
+        check_not_applicable(
+            r#"
+fn f(a: i32) -> i32 {
+    $0a * 2$0
+}
+"#,
+        );
+        check_not_applicable(
+            r#"
+fn f(a: Option<i32>) -> Option<i32> {
+    let x = $0a? + 1$0;
+    let y = a? + 1;
+    Some(x + y)
+}
+"#,
+        );
+        check_not_applicable(
+            r#"
+fn f(mut a: i32) {
+    $0a += 1$0;
+    a += 1;
+}
+"#,
+        );
+    }
+}
@@ -2,7 +2,10 @@
 //! ```no_run
 //! fn example/* <'0> */(a: &/* '0 */()) {}
 //! ```
-use ide_db::{syntax_helpers::node_ext::walk_ty, FxHashMap};
+use ide_db::{
+    syntax_helpers::lifetime_elision::{input_lifetimes, output_lifetime_source, output_refs},
+    FxHashMap,
+};
 use itertools::Itertools;
 use syntax::SmolStr;
 use syntax::{
@@ -37,44 +40,7 @@ pub(super) fn hints(
     let ret_type = func.ret_type();
     let self_param = param_list.self_param().filter(|it| it.amp_token().is_some());
 
-    let is_elided = |lt: &Option<ast::Lifetime>| match lt {
-        Some(lt) => matches!(lt.text().as_str(), "'_"),
-        None => true,
-    };
-
-    let potential_lt_refs = {
-        let mut acc: Vec<_> = vec![];
-        if let Some(self_param) = &self_param {
-            let lifetime = self_param.lifetime();
-            let is_elided = is_elided(&lifetime);
-            acc.push((None, self_param.amp_token(), lifetime, is_elided));
-        }
-        param_list.params().filter_map(|it| Some((it.pat(), it.ty()?))).for_each(|(pat, ty)| {
-            // FIXME: check path types
-            walk_ty(&ty, &mut |ty| match ty {
-                ast::Type::RefType(r) => {
-                    let lifetime = r.lifetime();
-                    let is_elided = is_elided(&lifetime);
-                    acc.push((
-                        pat.as_ref().and_then(|it| match it {
-                            ast::Pat::IdentPat(p) => p.name(),
-                            _ => None,
-                        }),
-                        r.amp_token(),
-                        lifetime,
-                        is_elided,
-                    ));
-                    false
-                }
-                ast::Type::FnPtrType(_) => true,
-                ast::Type::PathType(t) => {
-                    t.path().and_then(|it| it.segment()).and_then(|it| it.param_list()).is_some()
-                }
-                _ => false,
-            })
-        });
-        acc
-    };
+    let potential_lt_refs = input_lifetimes(&param_list);
 
     // allocate names
     let mut gen_idx_name = {
@@ -97,7 +63,7 @@ pub(super) fn hints(
             false => Default::default(),
         };
     {
-        let mut potential_lt_refs = potential_lt_refs.iter().filter(|&&(.., is_elided)| is_elided);
+        let mut potential_lt_refs = potential_lt_refs.iter().filter(|it| it.is_elided());
         if let Some(_) = &self_param {
             if let Some(_) = potential_lt_refs.next() {
                 allocated_lifetimes.push(if config.param_names_for_lifetime_elision_hints {
@@ -108,8 +74,8 @@ pub(super) fn hints(
                 });
             }
         }
-        potential_lt_refs.for_each(|(name, ..)| {
-            let name = match name {
+        potential_lt_refs.for_each(|it| {
+            let name = match &it.param_name {
                 Some(it) if config.param_names_for_lifetime_elision_hints => {
                     if let Some(c) = used_names.get_mut(it.text().as_str()) {
                         *c += 1;
@@ -126,19 +92,15 @@ pub(super) fn hints(
     }
 
     // fetch output lifetime if elision rule applies
-    let output = match potential_lt_refs.as_slice() {
-        [(_, _, lifetime, _), ..] if self_param.is_some() || potential_lt_refs.len() == 1 => {
-            match lifetime {
-                Some(lt) => match lt.text().as_str() {
-                    "'_" => allocated_lifetimes.get(0).cloned(),
-                    "'static" => None,
-                    name => Some(name.into()),
-                },
-                None => allocated_lifetimes.get(0).cloned(),
-            }
-        }
-        [..] => None,
-    };
+    let output =
+        output_lifetime_source(&potential_lt_refs).and_then(|source| match &source.lifetime {
+            Some(lt) => match lt.text().as_str() {
+                "'_" => allocated_lifetimes.get(0).cloned(),
+                "'static" => None,
+                name => Some(name.into()),
+            },
+            None => allocated_lifetimes.get(0).cloned(),
+        });
 
     if allocated_lifetimes.is_empty() && output.is_none() {
         return None;
@@ -149,20 +111,12 @@ pub(super) fn hints(
     let mut is_trivial = true;
     if let (Some(output_lt), Some(r)) = (&output, ret_type) {
         if let Some(ty) = r.ty() {
-            walk_ty(&ty, &mut |ty| match ty {
-                ast::Type::RefType(ty) if ty.lifetime().is_none() => {
-                    if let Some(amp) = ty.amp_token() {
-                        is_trivial = false;
-                        acc.push(mk_lt_hint(amp, output_lt.to_string()));
-                    }
-                    false
+            for ty in output_refs(&ty).into_iter().filter(|it| it.lifetime().is_none()) {
+                if let Some(amp) = ty.amp_token() {
+                    is_trivial = false;
+                    acc.push(mk_lt_hint(amp, output_lt.to_string()));
                 }
-                ast::Type::FnPtrType(_) => true,
-                ast::Type::PathType(t) => {
-                    t.path().and_then(|it| it.segment()).and_then(|it| it.param_list()).is_some()
-                }
-                _ => false,
-            })
+            }
         }
     }
 
@@ -171,9 +125,9 @@ pub(super) fn hints(
     }
 
     let mut a = allocated_lifetimes.iter();
-    for (_, amp_token, _, is_elided) in potential_lt_refs {
-        if is_elided {
-            let t = amp_token?;
+    for input in potential_lt_refs {
+        if input.is_elided() {
+            let t = input.amp_token?;
             let lt = a.next()?;
             acc.push(mk_lt_hint(t, lt.to_string()));
         }
@@ -30,6 +30,7 @@ mod signature_help;
 mod doc_links;
 mod highlight_related;
 mod expand_macro;
+mod extract_duplicates;
 mod extend_selection;
 mod file_structure;
 mod folding_ranges;
@@ -664,10 +665,13 @@ impl Analysis {
                 Vec::new()
             };
             let ssr_assists = ssr::ssr_assists(db, &resolve, frange);
+            let extract_duplicates_assist =
+                extract_duplicates::extract_duplicates(db, assist_config, &resolve, frange);
             let assists = ide_assists::assists(db, assist_config, resolve, frange);
 
             let mut res = diagnostic_assists;
             res.extend(ssr_assists.into_iter());
+            res.extend(extract_duplicates_assist);
             res.extend(assists.into_iter());
 
             res
@@ -2,6 +2,7 @@
 //! `tt::TokenTree` representing an argument of macro invocation, and produces a
 //! `tt::TokenTree` for the result of the expansion.
 
+pub(crate) mod hints;
 mod matcher;
 mod transcriber;
 
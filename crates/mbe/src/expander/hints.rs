@@ -0,0 +1,199 @@
+//! Computes what the rules of a macro expect at some position of a call's input. Unlike matching,
+//! this looks at every way a rule could match the input up to that position and ignores
+//! everything after it, so it also works for incomplete input that matches no rule (yet).
+
+use tt::Span;
+
+use crate::{
+    expander::matcher::match_meta_var,
+    parser::{MetaTemplate, MetaVarKind, Op, RepeatKind, Separator},
+    tt_iter::TtIter,
+    ExpectedToken,
+};
+
+pub(crate) fn expected_tokens<S: Span>(
+    rules: &[crate::Rule<S>],
+    input: &tt::Subtree<S>,
+    is_cursor: &dyn Fn(&tt::Ident<S>) -> bool,
+    is_2021: bool,
+) -> Vec<ExpectedToken> {
+    let mut ctx =
+        Ctx { is_cursor, is_2021, delim_span: input.delimiter.delim_span(), res: Vec::new() };
+    for rule in rules.iter() {
+        ctx.match_template(&rule.lhs, vec![TtIter::new(input)]);
+    }
+    ctx.res
+}
+
+struct Ctx<'a, S> {
+    is_cursor: &'a dyn Fn(&tt::Ident<S>) -> bool,
+    is_2021: bool,
+    delim_span: tt::DelimSpan<S>,
+    res: Vec<ExpectedToken>,
+}
+
+impl<S: Span> Ctx<'_, S> {
+    /// Matches `template` against the start of each input, returning the rest of the inputs for
+    /// all the ways it matched. Inputs that reach the cursor stop there, recording what the
+    /// template expects at it.
+    fn match_template<'t>(
+        &mut self,
+        template: &MetaTemplate<S>,
+        mut inputs: Vec<TtIter<'t, S>>,
+    ) -> Vec<TtIter<'t, S>> {
+        for op in template.iter() {
+            let mut next = Vec::new();
+            for input in inputs {
+                next.extend(self.match_op(op, input));
+            }
+            // All inputs are suffixes of the same token trees, so this drops duplicates.
+            next.sort_by_key(|it| it.len());
+            next.dedup_by_key(|it| it.len());
+            inputs = next;
+        }
+        inputs
+    }
+
+    fn match_op<'t>(&mut self, op: &Op<S>, mut input: TtIter<'t, S>) -> Vec<TtIter<'t, S>> {
+        if self.is_at_cursor(&input) {
+            self.expect_first(op);
+            // Optional repetitions may also match nothing, leaving the cursor to what follows.
+            return match op {
+                Op::Repeat { kind: RepeatKind::ZeroOrMore | RepeatKind::ZeroOrOne, .. } => {
+                    vec![input]
+                }
+                _ => vec![],
+            };
+        }
+        match op {
+            Op::Var { kind, .. } => {
+                let kind = kind.unwrap_or(MetaVarKind::Tt);
+                let before = input.clone();
+                let res = match_meta_var(kind, &mut input, self.is_2021, self.delim_span);
+                let consumed = &before.inner.as_slice()[..before.len() - input.len()];
+                // Partial input often doesn't parse as the fragment, so a failed match that runs
+                // into the cursor still tells us the cursor is inside of it.
+                if self.contains_cursor(consumed)
+                    || (res.err.is_some() && self.contains_cursor(input.inner.as_slice()))
+                {
+                    self.push(ExpectedToken::Fragment(kind));
+                    return vec![];
+                }
+                match res.err {
+                    Some(_) => vec![],
+                    None => vec![input],
+                }
+            }
+            Op::Ident(lhs) => match input.expect_leaf() {
+                Ok(tt::Leaf::Ident(it)) if it.text == lhs.text => vec![input],
+                _ => vec![],
+            },
+            Op::Literal(lhs) => match input.expect_leaf() {
+                Ok(tt::Leaf::Literal(it)) if it.text == lhs.text => vec![input],
+                _ => vec![],
+            },
+            Op::Punct(lhs) => match input.expect_glued_punct() {
+                Ok(rhs) if lhs.iter().map(|it| it.char).eq(rhs.iter().map(|it| it.char)) => {
+                    vec![input]
+                }
+                _ => vec![],
+            },
+            Op::Subtree { tokens, delimiter } => match input.expect_subtree() {
+                Ok(subtree) if subtree.delimiter.kind == delimiter.kind => {
+                    let rest = self.match_template(tokens, vec![TtIter::new(subtree)]);
+                    if rest.iter().any(|it| it.len() == 0) {
+                        vec![input]
+                    } else {
+                        vec![]
+                    }
+                }
+                _ => vec![],
+            },
+            Op::Repeat { tokens, kind, separator } => {
+                self.match_repeat(tokens, *kind, separator.as_ref(), input)
+            }
+            Op::Ignore { .. } | Op::Index { .. } | Op::Count { .. } | Op::Length { .. } => {
+                vec![]
+            }
+        }
+    }
+
+    fn match_repeat<'t>(
+        &mut self,
+        tokens: &MetaTemplate<S>,
+        kind: RepeatKind,
+        separator: Option<&Separator<S>>,
+        input: TtIter<'t, S>,
+    ) -> Vec<TtIter<'t, S>> {
+        let mut res = Vec::new();
+        if kind != RepeatKind::OneOrMore {
+            res.push(input.clone());
+        }
+        let mut inputs = vec![input];
+        while !inputs.is_empty() {
+            let min_len = inputs.iter().map(|it| it.len()).min().unwrap_or(0);
+            let mut matched = self.match_template(tokens, inputs);
+            // Repetitions that consume nothing would loop forever.
+            matched.retain(|it| it.len() < min_len);
+            res.extend(matched.iter().cloned());
+            if kind == RepeatKind::ZeroOrOne {
+                break;
+            }
+            inputs = match separator {
+                None => matched,
+                Some(separator) => matched
+                    .into_iter()
+                    .filter_map(|mut it| eat_separator(separator, &mut it).then_some(it))
+                    .collect(),
+            };
+        }
+        res
+    }
+
+    fn expect_first(&mut self, op: &Op<S>) {
+        match op {
+            Op::Var { kind, .. } => {
+                self.push(ExpectedToken::Fragment(kind.unwrap_or(MetaVarKind::Tt)))
+            }
+            Op::Ident(it) => self.push(ExpectedToken::Ident(it.text.clone())),
+            Op::Repeat { tokens, .. } => {
+                if let Some(op) = tokens.iter().next() {
+                    self.expect_first(op);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn push(&mut self, expected: ExpectedToken) {
+        if !self.res.contains(&expected) {
+            self.res.push(expected);
+        }
+    }
+
+    fn is_at_cursor(&self, input: &TtIter<'_, S>) -> bool {
+        matches!(input.peek_n(0), Some(tt::TokenTree::Leaf(tt::Leaf::Ident(it))) if (self.is_cursor)(it))
+    }
+
+    fn contains_cursor(&self, tts: &[tt::TokenTree<S>]) -> bool {
+        tts.iter().any(|tt| match tt {
+            tt::TokenTree::Leaf(tt::Leaf::Ident(it)) => (self.is_cursor)(it),
+            tt::TokenTree::Leaf(_) => false,
+            tt::TokenTree::Subtree(it) => self.contains_cursor(&it.token_trees),
+        })
+    }
+}
+
+fn eat_separator<S: Span>(separator: &Separator<S>, input: &mut TtIter<'_, S>) -> bool {
+    match separator {
+        Separator::Ident(lhs) => {
+            matches!(input.expect_leaf(), Ok(tt::Leaf::Ident(it)) if it.text == lhs.text)
+        }
+        Separator::Literal(lhs) => {
+            matches!(input.expect_leaf(), Ok(tt::Leaf::Literal(it)) if it.text == lhs.text)
+        }
+        Separator::Puncts(lhs) => input
+            .expect_glued_punct()
+            .is_ok_and(|rhs| lhs.iter().map(|it| it.char).eq(rhs.iter().map(|it| it.char))),
+    }
+}
@@ -4,7 +4,8 @@
 //! `TokenTree`s as well!
 //!
 //! The tests for this functionality live in another crate:
-//! `hir_def::macro_expansion_tests::mbe`.
+//! `hir_def::macro_expansion_tests::mbe`, except for the tests of the hints for incomplete macro
+//! input, which are in `tests`.
 
 #![warn(rust_2018_idioms, unused_lifetimes)]
 
@@ -16,14 +17,17 @@ mod to_parser_input;
 
 #[cfg(test)]
 mod benchmark;
+#[cfg(test)]
+mod tests;
 
 use stdx::impl_from;
+use syntax::SmolStr;
 use tt::Span;
 
 use std::fmt;
 
 use crate::{
-    parser::{MetaTemplate, MetaVarKind, Op},
+    parser::{MetaTemplate, Op},
     tt_iter::TtIter,
 };
 
@@ -39,6 +43,8 @@ pub use crate::syntax_bridge::{
 
 pub use crate::syntax_bridge::dummy_test_span_utils::*;
 
+pub use crate::parser::MetaVarKind;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ParseError {
     UnexpectedToken(Box<str>),
@@ -256,6 +262,27 @@ impl<S: Span> DeclarativeMacro<S> {
     ) -> ExpandResult<tt::Subtree<S>> {
         expander::expand_rules(&self.rules, &tt, marker, self.is_2021, new_meta_vars, call_site)
     }
+
+    /// Computes what the rules of this macro expect at the position of the identifier in `tt`
+    /// that `is_cursor` picks out. Tokens after that position are ignored, so this also works
+    /// for incomplete input that doesn't match any rule.
+    pub fn expected_tokens_at(
+        &self,
+        tt: &tt::Subtree<S>,
+        is_cursor: impl Fn(&tt::Ident<S>) -> bool,
+    ) -> Vec<ExpectedToken> {
+        expander::hints::expected_tokens(&self.rules, tt, &is_cursor, self.is_2021)
+    }
+}
+
+/// Something a macro's matchers expect at some position of its input, see
+/// [`DeclarativeMacro::expected_tokens_at`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpectedToken {
+    /// A fragment, like an expression for `$e:expr`.
+    Fragment(MetaVarKind),
+    /// An identifier or keyword that is part of the matcher, like `as` in `$a:ident as $b:ty`.
+    Ident(SmolStr),
 }
 
 impl<S: Span> Rule<S> {
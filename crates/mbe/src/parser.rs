@@ -95,8 +95,9 @@ pub(crate) enum RepeatKind {
     ZeroOrOne,
 }
 
+/// The fragment specifier of a metavariable, like `expr` in `$e:expr`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub(crate) enum MetaVarKind {
+pub enum MetaVarKind {
     Path,
     Ty,
     Pat,
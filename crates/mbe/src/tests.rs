@@ -0,0 +1,100 @@
+//! Tests for the hints computed by [`DeclarativeMacro::expected_tokens_at`].
+
+use syntax::{ast, AstNode};
+
+use crate::{
+    syntax_node_to_token_tree, DeclarativeMacro, DummyTestSpanMap, ExpectedToken, MetaVarKind,
+    DUMMY,
+};
+
+/// Checks what the rules of `macro_rules` expect in the call `input` at the identifier `$0`.
+fn check(macro_rules: &str, input: &str, expected: &[ExpectedToken]) {
+    let token_tree = |text: &str| {
+        let source_file = ast::SourceFile::parse(text).ok().unwrap();
+        let token_tree = source_file.syntax().descendants().find_map(ast::TokenTree::cast).unwrap();
+        syntax_node_to_token_tree(token_tree.syntax(), DummyTestSpanMap, DUMMY)
+    };
+    let mac = DeclarativeMacro::parse_macro_rules(&token_tree(macro_rules), true, true);
+    assert!(mac.err().is_none(), "{:?}", mac.err());
+    let call = token_tree(&format!("m!{};", input.replace("$0", "__cursor")));
+    let res = mac.expected_tokens_at(&call, |ident| ident.text == "__cursor");
+    assert_eq!(res, expected);
+}
+
+fn fragment(kind: MetaVarKind) -> ExpectedToken {
+    ExpectedToken::Fragment(kind)
+}
+
+#[test]
+fn fragment_of_partial_input() {
+    let macro_rules = r#"
+macro_rules! m {
+    ($e:expr; $t:ty) => {};
+}
+"#;
+    check(macro_rules, "($0)", &[fragment(MetaVarKind::Expr)]);
+    // The expression doesn't parse, but the cursor is still inside of it.
+    check(macro_rules, "(1 + $0)", &[fragment(MetaVarKind::Expr)]);
+    check(macro_rules, "(foo(1, $0))", &[fragment(MetaVarKind::Expr)]);
+    // Tokens after the cursor don't have to match.
+    check(macro_rules, "(1; $0 + +)", &[fragment(MetaVarKind::Ty)]);
+}
+
+#[test]
+fn keywords_and_fragments_of_all_rules() {
+    let macro_rules = r#"
+macro_rules! m {
+    ($a:ident as $b:ty) => {};
+    ($a:ident in $b:expr) => {};
+    (const $a:ident) => {};
+}
+"#;
+    check(
+        macro_rules,
+        "($0)",
+        &[fragment(MetaVarKind::Ident), ExpectedToken::Ident("const".into())],
+    );
+    check(
+        macro_rules,
+        "(x $0)",
+        &[ExpectedToken::Ident("as".into()), ExpectedToken::Ident("in".into())],
+    );
+    check(macro_rules, "(x as $0)", &[fragment(MetaVarKind::Ty)]);
+    check(macro_rules, "(x in a.$0)", &[fragment(MetaVarKind::Expr)]);
+}
+
+#[test]
+fn fragments_in_repetitions() {
+    let macro_rules = r#"
+macro_rules! m {
+    ($($x:ident)* end) => {};
+}
+"#;
+    check(macro_rules, "($0)", &[fragment(MetaVarKind::Ident), ExpectedToken::Ident("end".into())]);
+    check(
+        macro_rules,
+        "(a b $0)",
+        &[fragment(MetaVarKind::Ident), ExpectedToken::Ident("end".into())],
+    );
+
+    let macro_rules = r#"
+macro_rules! m {
+    ($($x:expr),+ ; $n:ident) => {};
+}
+"#;
+    check(macro_rules, "($0)", &[fragment(MetaVarKind::Expr)]);
+    check(macro_rules, "(1, 2 + 3, $0)", &[fragment(MetaVarKind::Expr)]);
+    check(macro_rules, "(1, 2; $0)", &[fragment(MetaVarKind::Ident)]);
+}
+
+#[test]
+fn fragments_in_subtrees() {
+    let macro_rules = r#"
+macro_rules! m {
+    ([$($k:literal => $v:expr),*] $p:pat) => {};
+}
+"#;
+    check(macro_rules, "([$0])", &[fragment(MetaVarKind::Literal)]);
+    check(macro_rules, "([1 => $0])", &[fragment(MetaVarKind::Expr)]);
+    check(macro_rules, "([1 => a, 2 => b] $0)", &[fragment(MetaVarKind::Pat)]);
+}
@@ -0,0 +1,972 @@
+//! Editing support for `Cargo.toml` manifests.
+//!
+//! Manifests are not part of the analysis, so this works directly on the text of the manifest
+//! together with the `cargo metadata` we have already loaded for the workspace. It completes
+//! table names, well-known keys, dependency and feature names, and reports unknown features and
+//! `path` dependencies which do not point to a package of the workspace.
+//!
+//! We don't depend on a TOML parser, so the manifest is scanned line by line. This covers the
+//! way manifests are written in practice: every key-value pair starts on its own line, and only
+//! arrays may continue over several lines.
+
+use std::{ops::Range, path::PathBuf};
+
+use ide::{FileId, TextRange, TextSize};
+use project_model::{ManifestPath, ProjectWorkspace};
+use rustc_hash::{FxHashMap, FxHashSet};
+use vfs::{AbsPath, AbsPathBuf};
+
+use crate::global_state::GlobalStateSnapshot;
+
+const TABLES: &[&str] = &[
+    "badges",
+    "build-dependencies",
+    "dependencies",
+    "dev-dependencies",
+    "features",
+    "lib",
+    "lints",
+    "package",
+    "patch.crates-io",
+    "profile.bench",
+    "profile.dev",
+    "profile.release",
+    "profile.test",
+    "workspace",
+    "workspace.dependencies",
+    "workspace.lints",
+    "workspace.package",
+];
+
+const ARRAY_TABLES: &[&str] = &["bench", "bin", "example", "test"];
+
+const PACKAGE_KEYS: &[&str] = &[
+    "authors",
+    "autobenches",
+    "autobins",
+    "autoexamples",
+    "autotests",
+    "build",
+    "categories",
+    "default-run",
+    "description",
+    "documentation",
+    "edition",
+    "exclude",
+    "homepage",
+    "include",
+    "keywords",
+    "license",
+    "license-file",
+    "links",
+    "metadata",
+    "name",
+    "publish",
+    "readme",
+    "repository",
+    "resolver",
+    "rust-version",
+    "version",
+    "workspace",
+];
+
+const TARGET_KEYS: &[&str] = &[
+    "bench",
+    "crate-type",
+    "doc",
+    "doctest",
+    "edition",
+    "harness",
+    "name",
+    "path",
+    "proc-macro",
+    "required-features",
+    "test",
+];
+
+const DEPENDENCY_KEYS: &[&str] = &[
+    "branch",
+    "default-features",
+    "features",
+    "git",
+    "optional",
+    "package",
+    "path",
+    "registry",
+    "rev",
+    "tag",
+    "version",
+    "workspace",
+];
+
+const WORKSPACE_KEYS: &[&str] = &[
+    "default-members",
+    "dependencies",
+    "exclude",
+    "lints",
+    "members",
+    "metadata",
+    "package",
+    "resolver",
+];
+
+const PROFILE_KEYS: &[&str] = &[
+    "codegen-units",
+    "debug",
+    "debug-assertions",
+    "incremental",
+    "inherits",
+    "lto",
+    "opt-level",
+    "overflow-checks",
+    "panic",
+    "rpath",
+    "split-debuginfo",
+    "strip",
+];
+
+/// Returns the manifest path if `file_id` is a `Cargo.toml`.
+pub(crate) fn manifest_for_file(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+) -> Option<ManifestPath> {
+    let path = snap.file_id_to_file_path(file_id);
+    if path.name_and_extension()? != ("Cargo", Some("toml")) {
+        return None;
+    }
+    ManifestPath::try_from(path.as_path()?.to_path_buf()).ok()
+}
+
+/// What the project model knows about the package of a manifest.
+#[derive(Debug, Default)]
+pub(crate) struct ManifestInfo {
+    /// The features of the package's resolved dependencies, keyed by their (crate) name.
+    pub(crate) dependency_features: FxHashMap<String, FxHashSet<String>>,
+    /// Names of all packages in the resolved dependency graphs, that is those in the lockfiles.
+    pub(crate) known_packages: FxHashSet<String>,
+    /// The manifests of all packages in the resolved dependency graphs.
+    pub(crate) package_manifests: FxHashSet<AbsPathBuf>,
+    /// The `.cache` directories of the registry indices in `$CARGO_HOME`, only collected for
+    /// completions.
+    pub(crate) registry_caches: Vec<PathBuf>,
+}
+
+impl ManifestInfo {
+    pub(crate) fn new(workspaces: &[ProjectWorkspace], manifest: &ManifestPath) -> ManifestInfo {
+        let mut res = ManifestInfo::default();
+        for ws in workspaces {
+            let ProjectWorkspace::Cargo { cargo, .. } = ws else { continue };
+            res.known_packages.extend(cargo.packages().map(|pkg| cargo[pkg].name.clone()));
+            res.package_manifests
+                .extend(cargo.packages().map(|pkg| cargo[pkg].manifest.to_path_buf()));
+            let Some(pkg) = cargo.packages().find(|&pkg| cargo[pkg].manifest == *manifest) else {
+                continue;
+            };
+            for dep in &cargo[pkg].dependencies {
+                let features = cargo[dep.pkg].features.keys().cloned();
+                res.dependency_features.entry(dep.name.clone()).or_default().extend(features);
+            }
+        }
+        res
+    }
+
+    /// Looks up the registry index caches, which are used to complete package names.
+    pub(crate) fn with_registry_caches(mut self) -> ManifestInfo {
+        if let Some(entries) =
+            toolchain::cargo_home().and_then(|it| it.join("registry/index").read_dir().ok())
+        {
+            self.registry_caches
+                .extend(entries.filter_map(|it| Some(it.ok()?.path().join(".cache"))));
+        }
+        self
+    }
+
+    fn features_of(&self, dependency: &str) -> Option<&FxHashSet<String>> {
+        self.dependency_features.get(&dependency.replace('-', "_"))
+    }
+
+    /// Names of the packages in the registry index caches starting with `prefix`.
+    fn registry_packages(&self, prefix: &str) -> Vec<String> {
+        // The caches mirror the layout of the index, which puts names with four or more
+        // characters into `ab/cd/abcd...`. Shorter prefixes would need a walk of the whole cache.
+        let prefix = prefix.to_ascii_lowercase();
+        if prefix.len() < 4 || !prefix.is_ascii() {
+            return Vec::new();
+        }
+        let dir = PathBuf::from(&prefix[..2]).join(&prefix[2..4]);
+        self.registry_caches
+            .iter()
+            .filter_map(|cache| cache.join(&dir).read_dir().ok())
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with(&prefix))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompletionKind {
+    Table,
+    Key,
+    Dependency,
+    Feature,
+}
+
+#[derive(Debug)]
+pub(crate) struct CompletionItem {
+    pub(crate) label: String,
+    pub(crate) kind: CompletionKind,
+    /// The range the label replaces.
+    pub(crate) range: TextRange,
+}
+
+pub(crate) fn completions(
+    text: &str,
+    offset: TextSize,
+    info: &ManifestInfo,
+) -> Vec<CompletionItem> {
+    let offset = usize::from(offset);
+    let manifest = Manifest::parse(text);
+    let mut res = match manifest.entries.iter().find(|it| contains(&it.value_range, offset)) {
+        Some(entry) => manifest.value_completions(entry, offset, info),
+        None => manifest.line_completions(offset, info),
+    };
+    res.sort_by(|a, b| a.label.cmp(&b.label));
+    res.dedup_by(|a, b| a.label == b.label);
+    res
+}
+
+#[derive(Debug)]
+pub(crate) struct ManifestDiagnostic {
+    pub(crate) range: TextRange,
+    pub(crate) code: &'static str,
+    pub(crate) message: String,
+}
+
+pub(crate) fn diagnostics(
+    text: &str,
+    manifest_dir: &AbsPath,
+    info: &ManifestInfo,
+) -> Vec<ManifestDiagnostic> {
+    let manifest = Manifest::parse(text);
+    let dependencies = manifest.dependencies();
+    let declared_features = manifest.declared_features().collect::<FxHashSet<_>>();
+    let is_optional_dependency =
+        |name: &str| dependencies.iter().any(|it| it.optional && it.name == name);
+
+    let mut res = Vec::new();
+    let mut add = |lit: &StringLit<'_>, code, message| {
+        res.push(ManifestDiagnostic { range: text_range(&lit.range), code, message })
+    };
+
+    let feature_entries =
+        manifest.entries.iter().filter(|it| manifest.table_kind(it.table) == TableKind::Features);
+    for lit in feature_entries.flat_map(string_literals) {
+        let message = if let Some(name) = lit.text.strip_prefix("dep:") {
+            (!is_optional_dependency(name))
+                .then(|| format!("`{name}` is not an optional dependency"))
+        } else if let Some((dep, feature)) = lit.text.split_once('/') {
+            let dep = dep.trim_end_matches('?');
+            if dependencies.iter().any(|it| it.name == dep) {
+                unknown_dependency_feature(info, dep, feature)
+            } else {
+                Some(format!("unknown dependency `{dep}`"))
+            }
+        } else {
+            let is_known = declared_features.contains(lit.text) || is_optional_dependency(lit.text);
+            (!is_known).then(|| format!("unknown feature `{}`", lit.text))
+        };
+        if let Some(message) = message {
+            add(&lit, "unknown-feature", message);
+        }
+    }
+
+    for dep in &dependencies {
+        for lit in &dep.features {
+            if let Some(message) = unknown_dependency_feature(info, dep.name, lit.text) {
+                add(lit, "unknown-feature", message);
+            }
+        }
+        // The dependency graph contains every package a path dependency points to, as long as it
+        // could be resolved. Without a graph, there's nothing to check against.
+        if let Some(path) = dep.path.as_ref().filter(|_| !info.package_manifests.is_empty()) {
+            let manifest = manifest_dir.join(path.text).join("Cargo.toml").normalize();
+            if !info.package_manifests.contains(&manifest) {
+                let message = format!("no package found at `{}`", path.text);
+                add(path, "invalid-path-dependency", message);
+            }
+        }
+    }
+    res.sort_by_key(|it| it.range.start());
+    res
+}
+
+fn unknown_dependency_feature(info: &ManifestInfo, dep: &str, feature: &str) -> Option<String> {
+    // Without metadata for the dependency (it might be inactive or not resolved yet) we can't
+    // tell, so only report features we know don't exist.
+    let features = info.features_of(dep)?;
+    (!features.contains(feature)).then(|| format!("dependency `{dep}` has no feature `{feature}`"))
+}
+
+/// A table header, `[name]` or `[[name]]`.
+#[derive(Debug)]
+struct Table<'a> {
+    name: &'a str,
+    name_range: Range<usize>,
+}
+
+/// A `key = value` pair.
+#[derive(Debug, Clone)]
+struct Entry<'a> {
+    /// Index of the table the entry belongs to, `None` for the root table.
+    table: Option<usize>,
+    key: &'a str,
+    value: &'a str,
+    value_range: Range<usize>,
+}
+
+/// The contents of a string literal.
+#[derive(Debug)]
+struct StringLit<'a> {
+    text: &'a str,
+    range: Range<usize>,
+}
+
+/// A dependency declared in the manifest, either inline or as a `[dependencies.name]` table.
+#[derive(Debug)]
+struct Dependency<'a> {
+    name: &'a str,
+    optional: bool,
+    features: Vec<StringLit<'a>>,
+    path: Option<StringLit<'a>>,
+}
+
+impl<'a> Dependency<'a> {
+    fn new(name: &'a str, entries: &[Entry<'a>]) -> Dependency<'a> {
+        let value = |key| entries.iter().find(|it| it.key == key);
+        Dependency {
+            name,
+            optional: value("optional").map_or(false, |it| it.value == "true"),
+            features: value("features").map(string_literals).unwrap_or_default(),
+            path: value("path").and_then(|it| string_literals(it).into_iter().next()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableKind<'a> {
+    Package,
+    Target,
+    /// A table listing dependencies, like `[dependencies]` or `[target.'cfg(unix)'.dependencies]`.
+    Dependencies,
+    /// A table describing a single dependency, like `[dependencies.serde]`.
+    Dependency(&'a str),
+    Features,
+    Workspace,
+    Profile,
+    Other,
+}
+
+struct Manifest<'a> {
+    text: &'a str,
+    tables: Vec<Table<'a>>,
+    entries: Vec<Entry<'a>>,
+}
+
+impl<'a> Manifest<'a> {
+    fn parse(text: &'a str) -> Manifest<'a> {
+        let mut tables = Vec::new();
+        let mut entries: Vec<Entry<'a>> = Vec::new();
+        // Nesting depth of the value of the last entry, if it continues on the next line.
+        let mut depth = 0;
+        let mut line_start = 0;
+        for line in text.split_inclusive('\n') {
+            let start = line_start;
+            line_start += line.len();
+            let content = strip_comment(line).trim_end();
+
+            if depth > 0 {
+                depth += nesting_depth(content);
+                if let Some(entry) = entries.last_mut() {
+                    let end = (start + content.len()).max(entry.value_range.end);
+                    entry.value_range.end = end;
+                    entry.value = &text[entry.value_range.clone()];
+                }
+                continue;
+            }
+
+            let trimmed = content.trim_start();
+            let indent = content.len() - trimmed.len();
+            if let Some(header) = trimmed.strip_prefix('[') {
+                let header = header.strip_prefix('[').unwrap_or(header);
+                let name_start = start + content.len() - header.len();
+                let name = header.trim_end_matches(']');
+                let leading = name.len() - name.trim_start().len();
+                let name = name.trim();
+                let name_start = name_start + leading;
+                tables.push(Table { name, name_range: name_start..name_start + name.len() });
+            } else if let Some(entry) =
+                split_entry(tables.len().checked_sub(1), trimmed, start + indent)
+            {
+                depth = nesting_depth(entry.value).max(0);
+                entries.push(entry);
+            }
+        }
+        Manifest { text, tables, entries }
+    }
+
+    fn table_kind(&self, table: Option<usize>) -> TableKind<'a> {
+        table.map_or(TableKind::Other, |idx| table_kind(self.tables[idx].name))
+    }
+
+    fn declared_features(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.entries
+            .iter()
+            .filter(|it| self.table_kind(it.table) == TableKind::Features)
+            .map(|it| it.key)
+    }
+
+    fn dependencies(&self) -> Vec<Dependency<'a>> {
+        let mut res = Vec::new();
+        for entry in &self.entries {
+            if self.table_kind(entry.table) == TableKind::Dependencies {
+                res.push(Dependency::new(entry.key, &inline_table_entries(entry)));
+            }
+        }
+        for idx in 0..self.tables.len() {
+            if let TableKind::Dependency(name) = self.table_kind(Some(idx)) {
+                let entries = self
+                    .entries
+                    .iter()
+                    .filter(|it| it.table == Some(idx))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                res.push(Dependency::new(name, &entries));
+            }
+        }
+        res
+    }
+
+    /// Completions for a table header or key on the line of the cursor.
+    fn line_completions(&self, offset: usize, info: &ManifestInfo) -> Vec<CompletionItem> {
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |it| it + 1);
+        let typed = self.text[line_start..offset].trim_start();
+
+        if let Some(header) = typed.strip_prefix('[') {
+            let (names, header) = match header.strip_prefix('[') {
+                Some(header) => (ARRAY_TABLES, header),
+                None => (TABLES, header),
+            };
+            let range = offset - header.len()..offset;
+            return items(names.iter().map(|&it| it.to_owned()), CompletionKind::Table, &range);
+        }
+
+        if !typed.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_')) {
+            return Vec::new();
+        }
+        let range = offset - typed.len()..offset;
+        let table = self.tables.iter().rposition(|it| it.name_range.end <= offset);
+        let existing = self
+            .entries
+            .iter()
+            .filter(|it| it.table == table)
+            .map(|it| it.key)
+            .collect::<FxHashSet<_>>();
+        let (keys, kind) = match self.table_kind(table) {
+            TableKind::Dependencies => {
+                let names = info
+                    .known_packages
+                    .iter()
+                    .cloned()
+                    .chain(info.registry_packages(typed))
+                    .filter(|it| !existing.contains(it.as_str()));
+                return items(names, CompletionKind::Dependency, &range);
+            }
+            TableKind::Package => (PACKAGE_KEYS, CompletionKind::Key),
+            TableKind::Target => (TARGET_KEYS, CompletionKind::Key),
+            TableKind::Dependency(_) => (DEPENDENCY_KEYS, CompletionKind::Key),
+            TableKind::Workspace => (WORKSPACE_KEYS, CompletionKind::Key),
+            TableKind::Profile => (PROFILE_KEYS, CompletionKind::Key),
+            TableKind::Features | TableKind::Other => return Vec::new(),
+        };
+        let keys = keys.iter().filter(|it| !existing.contains(*it)).map(|&it| it.to_owned());
+        items(keys, kind, &range)
+    }
+
+    /// Completions for the string in the value of `entry` that contains the cursor.
+    fn value_completions(
+        &self,
+        entry: &Entry<'a>,
+        offset: usize,
+        info: &ManifestInfo,
+    ) -> Vec<CompletionItem> {
+        // Look through inline tables, as in `serde = { version = "1", features = ["$0"] }`.
+        let inline = inline_table_entries(entry);
+        let (dependency, entry) = match inline.iter().find(|it| contains(&it.value_range, offset)) {
+            Some(inner) => (Some(entry.key), inner),
+            None => (None, entry),
+        };
+        let Some(lit) = string_literals(entry).into_iter().find(|it| contains(&it.range, offset))
+        else {
+            return Vec::new();
+        };
+        let range = lit.range.start..offset;
+        let typed = &self.text[range.clone()];
+
+        let names = match (self.table_kind(entry.table), dependency) {
+            (TableKind::Features, None) => self.feature_values(typed, info),
+            (TableKind::Dependencies, Some(dep)) | (TableKind::Dependency(dep), None)
+                if entry.key == "features" =>
+            {
+                info.features_of(dep).into_iter().flatten().cloned().collect()
+            }
+            _ => return Vec::new(),
+        };
+        items(names.into_iter(), CompletionKind::Feature, &range)
+    }
+
+    /// The values a feature can enable: other features, optional dependencies and features of
+    /// dependencies.
+    fn feature_values(&self, typed: &str, info: &ManifestInfo) -> Vec<String> {
+        if let Some((dep, _)) = typed.split_once('/') {
+            let features = info.features_of(dep.trim_end_matches('?')).into_iter().flatten();
+            return features.map(|feature| format!("{dep}/{feature}")).collect();
+        }
+        let optional_deps = self
+            .dependencies()
+            .into_iter()
+            .filter(|it| it.optional)
+            .map(|it| format!("dep:{}", it.name));
+        self.declared_features().map(ToOwned::to_owned).chain(optional_deps).collect()
+    }
+}
+
+fn table_kind(name: &str) -> TableKind<'_> {
+    let is_deps = |it: &str| {
+        matches!(
+            it,
+            "dependencies"
+                | "dev-dependencies"
+                | "dev_dependencies"
+                | "build-dependencies"
+                | "build_dependencies"
+        )
+    };
+    match dotted_segments(name).as_slice() {
+        ["package"] | ["workspace", "package"] => TableKind::Package,
+        ["lib" | "bin" | "example" | "test" | "bench"] => TableKind::Target,
+        ["features"] => TableKind::Features,
+        ["workspace"] => TableKind::Workspace,
+        ["profile", _] => TableKind::Profile,
+        [deps] | ["workspace", deps] | ["target", _, deps] if is_deps(deps) => {
+            TableKind::Dependencies
+        }
+        [deps, name] | ["workspace", deps, name] | ["target", _, deps, name] if is_deps(deps) => {
+            TableKind::Dependency(name)
+        }
+        _ => TableKind::Other,
+    }
+}
+
+/// Splits a dotted key like `target.'cfg(unix)'.dependencies` into its unquoted segments.
+fn dotted_segments(name: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    for (idx, c) in unquoted_chars(name) {
+        if c == '.' {
+            res.push(&name[start..idx]);
+            start = idx + 1;
+        }
+    }
+    res.push(&name[start..]);
+    res.into_iter().map(|it| it.trim().trim_matches(|c| c == '"' || c == '\'')).collect()
+}
+
+/// Splits `key = value` into an entry, `offset` being the position of `text` in the manifest.
+fn split_entry(table: Option<usize>, text: &str, offset: usize) -> Option<Entry<'_>> {
+    let (key, value) = text.split_once('=')?;
+    let value_start = offset + key.len() + 1 + (value.len() - value.trim_start().len());
+    let value = value.trim();
+    Some(Entry {
+        table,
+        key: key.trim(),
+        value,
+        value_range: value_start..value_start + value.len(),
+    })
+}
+
+/// The key-value pairs of an inline table value, `{ key = value, ... }`.
+fn inline_table_entries<'a>(entry: &Entry<'a>) -> Vec<Entry<'a>> {
+    let Some(inner) = entry.value.strip_prefix('{') else { return Vec::new() };
+    let offset = entry.value_range.start + 1;
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in unquoted_chars(inner) {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            ',' | '}' if depth == 0 => {
+                res.extend(split_entry(entry.table, &inner[start..idx], offset + start));
+                start = idx + 1;
+                if c == '}' {
+                    return res;
+                }
+            }
+            '}' => depth -= 1,
+            _ => (),
+        }
+    }
+    // The table isn't closed yet, which happens while typing.
+    res.extend(split_entry(entry.table, &inner[start..], offset + start));
+    res
+}
+
+/// The string literals in the value of `entry`.
+fn string_literals<'a>(entry: &Entry<'a>) -> Vec<StringLit<'a>> {
+    let value = entry.value;
+    let offset = entry.value_range.start;
+    let mut res = Vec::new();
+    let mut chars = value.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '#' => {
+                chars.by_ref().find(|&(_, it)| it == '\n');
+            }
+            '"' | '\'' => {
+                let start = idx + 1;
+                let end = chars
+                    .by_ref()
+                    .find(|&(_, it)| it == c || it == '\n')
+                    .map_or(value.len(), |(it, _)| it);
+                res.push(StringLit {
+                    text: &value[start..end],
+                    range: offset + start..offset + end,
+                });
+            }
+            _ => (),
+        }
+    }
+    res
+}
+
+/// The characters of `text` which are not part of a string literal.
+fn unquoted_chars(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote = None;
+    text.char_indices().filter(move |&(_, c)| match quote {
+        Some(q) => {
+            if c == q {
+                quote = None;
+            }
+            false
+        }
+        None if c == '"' || c == '\'' => {
+            quote = Some(c);
+            false
+        }
+        None => true,
+    })
+}
+
+fn strip_comment(line: &str) -> &str {
+    match unquoted_chars(line).find(|&(_, c)| c == '#') {
+        Some((idx, _)) => &line[..idx],
+        None => line,
+    }
+}
+
+/// How many more arrays and inline tables `text` opens than it closes.
+fn nesting_depth(text: &str) -> i32 {
+    unquoted_chars(text)
+        .map(|(_, c)| match c {
+            '[' | '{' => 1,
+            ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn contains(range: &Range<usize>, offset: usize) -> bool {
+    range.start <= offset && offset <= range.end
+}
+
+fn text_range(range: &Range<usize>) -> TextRange {
+    TextRange::new(TextSize::from(range.start as u32), TextSize::from(range.end as u32))
+}
+
+fn items(
+    labels: impl Iterator<Item = String>,
+    kind: CompletionKind,
+    range: &Range<usize>,
+) -> Vec<CompletionItem> {
+    labels.map(|label| CompletionItem { label, kind, range: text_range(range) }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use test_utils::extract_offset;
+
+    use super::*;
+
+    fn info() -> ManifestInfo {
+        let features = |it: &[&str]| it.iter().map(|&it| it.to_owned()).collect();
+        ManifestInfo {
+            dependency_features: FxHashMap::from_iter([
+                ("serde".to_owned(), features(&["default", "derive", "std"])),
+                ("serde_json".to_owned(), features(&["default", "std", "preserve_order"])),
+            ]),
+            known_packages: ["serde", "serde_json", "itertools"]
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect(),
+            package_manifests: [manifest_dir().parent().unwrap().join("ide/Cargo.toml")]
+                .into_iter()
+                .collect(),
+            registry_caches: Vec::new(),
+        }
+    }
+
+    fn check_completions(ra_fixture: &str, expect: Expect) {
+        let (offset, text) = extract_offset(ra_fixture);
+        let actual = completions(&text, offset, &info())
+            .into_iter()
+            .map(|it| format!("{:?} {} {:?}\n", it.kind, it.label, it.range))
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    fn manifest_dir() -> AbsPathBuf {
+        AbsPathBuf::assert(env!("CARGO_MANIFEST_DIR").into())
+    }
+
+    fn check_diagnostics(text: &str, expect: Expect) {
+        let actual = diagnostics(text, &manifest_dir(), &info())
+            .into_iter()
+            .map(|it| format!("{:?} {}: {}\n", it.range, it.code, it.message))
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_table_names() {
+        check_completions(
+            r#"
+[package]
+name = "foo"
+
+[dev-$0
+"#,
+            expect![[r#"
+                Table badges 26..30
+                Table build-dependencies 26..30
+                Table dependencies 26..30
+                Table dev-dependencies 26..30
+                Table features 26..30
+                Table lib 26..30
+                Table lints 26..30
+                Table package 26..30
+                Table patch.crates-io 26..30
+                Table profile.bench 26..30
+                Table profile.dev 26..30
+                Table profile.release 26..30
+                Table profile.test 26..30
+                Table workspace 26..30
+                Table workspace.dependencies 26..30
+                Table workspace.lints 26..30
+                Table workspace.package 26..30
+            "#]],
+        );
+        check_completions(
+            r#"
+[[$0
+"#,
+            expect![[r#"
+                Table bench 3..3
+                Table bin 3..3
+                Table example 3..3
+                Table test 3..3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_missing_keys() {
+        check_completions(
+            r#"
+[package]
+name = "foo"
+version = "0.1.0"
+ed$0
+
+[dependencies]
+"#,
+            expect![[r#"
+                Key authors 42..44
+                Key autobenches 42..44
+                Key autobins 42..44
+                Key autoexamples 42..44
+                Key autotests 42..44
+                Key build 42..44
+                Key categories 42..44
+                Key default-run 42..44
+                Key description 42..44
+                Key documentation 42..44
+                Key edition 42..44
+                Key exclude 42..44
+                Key homepage 42..44
+                Key include 42..44
+                Key keywords 42..44
+                Key license 42..44
+                Key license-file 42..44
+                Key links 42..44
+                Key metadata 42..44
+                Key publish 42..44
+                Key readme 42..44
+                Key repository 42..44
+                Key resolver 42..44
+                Key rust-version 42..44
+                Key workspace 42..44
+            "#]],
+        );
+        check_completions(
+            r#"
+[target.'cfg(unix)'.dependencies.serde]
+version = "1.0"
+$0
+"#,
+            expect![[r#"
+                Key branch 57..57
+                Key default-features 57..57
+                Key features 57..57
+                Key git 57..57
+                Key optional 57..57
+                Key package 57..57
+                Key path 57..57
+                Key registry 57..57
+                Key rev 57..57
+                Key tag 57..57
+                Key workspace 57..57
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_dependency_names() {
+        check_completions(
+            r#"
+[dependencies]
+serde = "1.0"
+se$0
+"#,
+            expect![[r#"
+                Dependency itertools 30..32
+                Dependency serde_json 30..32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_dependency_features() {
+        check_completions(
+            r#"
+[dependencies]
+serde = { version = "1.0", features = ["derive", "$0"] }
+"#,
+            expect![[r#"
+                Feature default 66..66
+                Feature derive 66..66
+                Feature std 66..66
+            "#]],
+        );
+        check_completions(
+            r#"
+[dependencies.serde-json]
+features = [
+    "st$0",
+]
+"#,
+            expect![[r#"
+                Feature default 45..47
+                Feature preserve_order 45..47
+                Feature std 45..47
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_feature_values() {
+        check_completions(
+            r#"
+[dependencies]
+serde = "1.0"
+itertools = { version = "0.12", optional = true }
+
+[features]
+default = ["$0"]
+std = []
+"#,
+            expect![[r#"
+                Feature default 104..104
+                Feature dep:itertools 104..104
+                Feature std 104..104
+            "#]],
+        );
+        check_completions(
+            r#"
+[features]
+std = ["serde?/$0"]
+"#,
+            expect![[r#"
+                Feature serde?/default 20..27
+                Feature serde?/derive 20..27
+                Feature serde?/std 20..27
+            "#]],
+        );
+    }
+
+    #[test]
+    fn reports_unknown_features() {
+        check_diagnostics(
+            r#"
+[dependencies]
+serde = { version = "1.0", features = ["derive", "alloc"] }
+itertools = { version = "0.12", optional = true }
+log = "0.4"
+
+[features]
+default = ["std", "itertools", "dep:itertools", "dep:log", "serde/rc", "nope", "libc/std"]
+std = [
+    "serde/std", # comment with "quotes"
+    "more",
+]
+"#,
+            expect![[r#"
+                66..71 unknown-feature: dependency `serde` has no feature `alloc`
+                199..206 unknown-feature: `log` is not an optional dependency
+                210..218 unknown-feature: dependency `serde` has no feature `rc`
+                222..226 unknown-feature: unknown feature `nope`
+                230..238 unknown-feature: unknown dependency `libc`
+                295..299 unknown-feature: unknown feature `more`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn reports_invalid_path_dependencies() {
+        check_diagnostics(
+            r#"
+[dependencies]
+ide = { path = "../ide" }
+missing = { path = "../does-not-exist" }
+
+[dev-dependencies.also-missing]
+path = "../does-not-exist-either"
+"#,
+            expect![[r#"
+                62..79 invalid-path-dependency: no package found at `../does-not-exist`
+                124..148 invalid-path-dependency: no package found at `../does-not-exist-either`
+            "#]],
+        );
+    }
+}
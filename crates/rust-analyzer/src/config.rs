@@ -64,6 +64,9 @@ config_data! {
         /// Whether to insert #[must_use] when generating `as_` methods
         /// for enum variants.
         assist_emitMustUse: bool               = "false",
+        /// Whether the encapsulate field assist leaves accesses to the field inside of the
+        /// struct's impl blocks alone.
+        assist_encapsulateField_skipImpl: bool = "true",
         /// Placeholder expression to use for missing expressions in assists.
         assist_expressionFillDefault: ExprFillDefaultDef              = "\"todo\"",
 
@@ -1543,6 +1546,7 @@ impl Config {
             prefer_no_std: self.data.imports_preferNoStd,
             prefer_prelude: self.data.imports_preferPrelude,
             assist_emit_must_use: self.data.assist_emitMustUse,
+            encapsulate_field_skip_impl: self.data.assist_encapsulateField_skipImpl,
         }
     }
 
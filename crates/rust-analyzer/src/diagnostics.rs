@@ -10,7 +10,11 @@ use nohash_hasher::{IntMap, IntSet};
 use rustc_hash::FxHashSet;
 use triomphe::Arc;
 
-use crate::{global_state::GlobalStateSnapshot, lsp, lsp_ext};
+use crate::{
+    cargo_toml::{self, ManifestInfo},
+    global_state::GlobalStateSnapshot,
+    lsp, lsp_ext,
+};
 
 pub(crate) type CheckFixes = Arc<IntMap<usize, IntMap<FileId, Vec<Fix>>>>;
 
@@ -146,6 +150,19 @@ pub(crate) fn fetch_native_diagnostics(
             data: None,
         };
 
+    let convert_manifest_diagnostic =
+        |line_index: &crate::line_index::LineIndex, d: cargo_toml::ManifestDiagnostic| {
+            lsp_types::Diagnostic {
+                range: lsp::to_proto::range(line_index, d.range),
+                // The manifest is only scanned heuristically, so these are not reported as errors.
+                severity: Some(lsp_types::DiagnosticSeverity::WARNING),
+                code: Some(lsp_types::NumberOrString::String(d.code.to_owned())),
+                source: Some("rust-analyzer".to_string()),
+                message: d.message,
+                ..Default::default()
+            }
+        };
+
     // the diagnostics produced may point to different files not requested by the concrete request,
     // put those into here and filter later
     let mut odd_ones = Vec::new();
@@ -154,6 +171,15 @@ pub(crate) fn fetch_native_diagnostics(
         .copied()
         .filter_map(|file_id| {
             let line_index = snapshot.file_line_index(file_id).ok()?;
+            if let Some(manifest) = cargo_toml::manifest_for_file(&snapshot, file_id) {
+                let text = snapshot.analysis.file_text(file_id).ok()?;
+                let info = ManifestInfo::new(&snapshot.workspaces, &manifest);
+                let diagnostics = cargo_toml::diagnostics(&text, manifest.parent(), &info)
+                    .into_iter()
+                    .map(|d| convert_manifest_diagnostic(&line_index, d))
+                    .collect();
+                return Some((file_id, diagnostics));
+            }
             let diagnostics = snapshot
                 .analysis
                 .diagnostics(
@@ -35,6 +35,7 @@ use vfs::{AbsPath, AbsPathBuf, FileId, VfsPath};
 
 use crate::{
     cargo_target_spec::CargoTargetSpec,
+    cargo_toml::{self, ManifestInfo},
     config::{Config, RustfmtConfig, WorkspaceSymbolConfig},
     diff::diff,
     global_state::{GlobalState, GlobalStateSnapshot},
@@ -851,6 +852,17 @@ pub(crate) fn handle_completion(
     let _p = profile::span("handle_completion");
     let text_document_position = params.text_document_position.clone();
     let position = from_proto::file_position(&snap, params.text_document_position)?;
+    if let Some(manifest) = cargo_toml::manifest_for_file(&snap, position.file_id) {
+        let text = snap.analysis.file_text(position.file_id)?;
+        let info = ManifestInfo::new(&snap.workspaces, &manifest).with_registry_caches();
+        let line_index = snap.file_line_index(position.file_id)?;
+        let items = cargo_toml::completions(&text, position.offset, &info)
+            .into_iter()
+            .map(|item| to_proto::manifest_completion_item(&line_index, item))
+            .collect();
+        let completion_list = lsp_types::CompletionList { is_incomplete: true, items };
+        return Ok(Some(completion_list.into()));
+    }
     let completion_trigger_character =
         params.context.and_then(|ctx| ctx.trigger_character).and_then(|s| s.chars().next());
 
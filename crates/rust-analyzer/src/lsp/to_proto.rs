@@ -20,6 +20,7 @@ use vfs::AbsPath;
 
 use crate::{
     cargo_target_spec::CargoTargetSpec,
+    cargo_toml,
     config::{CallInfoConfig, Config},
     global_state::GlobalStateSnapshot,
     line_index::{LineEndings, LineIndex, PositionEncoding},
@@ -234,6 +235,26 @@ pub(crate) fn completion_items(
     res
 }
 
+pub(crate) fn manifest_completion_item(
+    line_index: &LineIndex,
+    item: cargo_toml::CompletionItem,
+) -> lsp_types::CompletionItem {
+    let kind = match item.kind {
+        cargo_toml::CompletionKind::Table => lsp_types::CompletionItemKind::MODULE,
+        cargo_toml::CompletionKind::Key => lsp_types::CompletionItemKind::PROPERTY,
+        cargo_toml::CompletionKind::Dependency => lsp_types::CompletionItemKind::REFERENCE,
+        cargo_toml::CompletionKind::Feature => lsp_types::CompletionItemKind::VALUE,
+    };
+    let text_edit =
+        lsp_types::TextEdit { range: range(line_index, item.range), new_text: item.label };
+    lsp_types::CompletionItem {
+        label: text_edit.new_text.clone(),
+        kind: Some(kind),
+        text_edit: Some(text_edit.into()),
+        ..Default::default()
+    }
+}
+
 fn completion_item(
     acc: &mut Vec<lsp_types::CompletionItem>,
     config: &Config,
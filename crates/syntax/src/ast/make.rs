@@ -847,6 +847,10 @@ pub fn param(pat: ast::Pat, ty: ast::Type) -> ast::Param {
     ast_from_text(&format!("fn f({pat}: {ty}) {{ }}"))
 }
 
+pub fn untyped_param(pat: ast::Pat) -> ast::Param {
+    ast_from_text(&format!("fn f() {{ |{pat}| () }}"))
+}
+
 pub fn self_param() -> ast::SelfParam {
     ast_from_text("fn f(&self) { }")
 }
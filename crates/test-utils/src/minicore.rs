@@ -908,6 +908,27 @@ pub mod fmt {
     pub trait Display {
         fn fmt(&self, f: &mut Formatter<'_>) -> Result;
     }
+    pub trait LowerHex {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+    pub trait UpperHex {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+    pub trait Octal {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+    pub trait Binary {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+    pub trait LowerExp {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+    pub trait UpperExp {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+    pub trait Pointer {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
 
     mod rt {
 
@@ -926,6 +947,34 @@ pub mod fmt {
                 use crate::mem::transmute;
                 unsafe { Argument { formatter: transmute(f), value: transmute(x) } }
             }
+
+            pub fn new_display<'b, T: super::Display>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_debug<'b, T: super::Debug>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_lower_hex<'b, T: super::LowerHex>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_upper_hex<'b, T: super::UpperHex>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_octal<'b, T: super::Octal>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_binary<'b, T: super::Binary>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_lower_exp<'b, T: super::LowerExp>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_upper_exp<'b, T: super::UpperExp>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
+            pub fn new_pointer<'b, T: super::Pointer>(_x: &'b T) -> Argument<'b> {
+                loop {}
+            }
         }
 
         #[lang = "format_alignment"]
@@ -1021,6 +1070,81 @@ pub mod fmt {
         bool char
     }
 
+    macro_rules! impl_fmt {
+        ($tr:ident for $($t:ty)*) => {
+            $(
+                impl const $tr for $t {
+                    fn fmt(&self, _f: &mut Formatter<'_>) -> Result {
+                        Ok(())
+                    }
+                }
+            )*
+        }
+    }
+
+    impl_fmt! { Display for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+        f32 f64
+        bool char str
+    }
+
+    impl_fmt! { LowerHex for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+    }
+
+    impl_fmt! { UpperHex for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+    }
+
+    impl_fmt! { Octal for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+    }
+
+    impl_fmt! { Binary for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+    }
+
+    impl_fmt! { LowerExp for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+        f32 f64
+    }
+
+    impl_fmt! { UpperExp for
+        usize u8 u16 u32 u64 u128
+        isize i8 i16 i32 i64 i128
+        f32 f64
+    }
+
+    impl<T: ?Sized> Pointer for *const T {
+        fn fmt(&self, _f: &mut Formatter<'_>) -> Result {
+            Ok(())
+        }
+    }
+
+    impl<T: ?Sized> Pointer for *mut T {
+        fn fmt(&self, _f: &mut Formatter<'_>) -> Result {
+            Ok(())
+        }
+    }
+
+    impl<T: ?Sized> Pointer for &T {
+        fn fmt(&self, _f: &mut Formatter<'_>) -> Result {
+            Ok(())
+        }
+    }
+
+    impl<T: ?Sized> Pointer for &mut T {
+        fn fmt(&self, _f: &mut Formatter<'_>) -> Result {
+            Ok(())
+        }
+    }
+
     impl<T: Debug> Debug for [T] {
         fn fmt(&self, _f: &mut Formatter<'_>) -> Result {
             Ok(())
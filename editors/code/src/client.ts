@@ -79,7 +79,11 @@ export async function createClient(
     unlinkedFiles: vscode.Uri[],
 ): Promise<lc.LanguageClient> {
     const clientOptions: lc.LanguageClientOptions = {
-        documentSelector: [{ scheme: "file", language: "rust" }],
+        documentSelector: [
+            { scheme: "file", language: "rust" },
+            // Manifests get completions and diagnostics for features and dependencies.
+            { scheme: "file", pattern: "**/Cargo.toml" },
+        ],
         initializationOptions,
         diagnosticCollectionName: "rustc",
         traceOutputChannel,